@@ -1,7 +1,7 @@
 use std::{
     ffi::OsString,
     fs::File,
-    io::{Read, Write, BufRead},
+    io::{BufRead, Read, Write},
     path::{Path, PathBuf},
     process::{Child, Command, Output},
     str::FromStr,
@@ -322,6 +322,7 @@ impl Drop for BitcoindInstance {
 fn net_conf_line(net: Net) -> &'static str {
     match net {
         Net::Mainnet => "",
+        Net::Testnet => "testnet=1",
         Net::Regtest => "regtest=1",
     }
 }
@@ -329,6 +330,7 @@ fn net_conf_line(net: Net) -> &'static str {
 fn net_conf_section_header(net: Net) -> &'static str {
     match net {
         Net::Mainnet => "",
+        Net::Testnet => "[test]",
         Net::Regtest => "[regtest]",
     }
 }
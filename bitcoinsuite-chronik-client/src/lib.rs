@@ -3,7 +3,7 @@ pub mod proto {
     include!(concat!(env!("OUT_DIR"), "/chronik.rs"));
 }
 
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
 use bitcoinsuite_core::{Bytes, Sha256d};
 use bitcoinsuite_error::{ErrorMeta, Result, WrapErr};
@@ -15,6 +15,26 @@ pub struct ChronikClient {
     http_url: String,
     ws_url: String,
     client: reqwest::Client,
+    max_response_size: Option<usize>,
+}
+
+/// Connection settings for [`ChronikClient::connect`].
+///
+/// The defaults work well for public XEC Chronik instances.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectOpts {
+    /// Deadline for establishing a connection, 10s by default.
+    pub connect_timeout: Option<Duration>,
+    /// Deadline for a whole request including the response body, 60s by default.
+    pub request_timeout: Option<Duration>,
+    /// Interval of TCP keepalive probes, 30s by default. Without keepalives, idle connections
+    /// are silently dropped by many NATs.
+    pub tcp_keepalive: Option<Duration>,
+    /// Largest accepted response body in bytes, 64 MiB by default. Blocks with many txs can be
+    /// large.
+    pub max_response_size: Option<usize>,
+    /// Reject `http://` URLs, i.e. require TLS, `false` by default.
+    pub https_only: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -55,6 +75,18 @@ pub enum ChronikClientError {
     #[error("HTTP request error")]
     HttpRequestError,
 
+    #[critical()]
+    #[error("Failed building HTTP client")]
+    HttpClientBuildError,
+
+    #[critical()]
+    #[error("`url` must start with 'https://' if `https_only` is set, got: {0}")]
+    HttpsRequired(String),
+
+    #[critical()]
+    #[error("Response too large: {size} bytes, but at most {max_size} bytes allowed")]
+    ResponseTooLarge { size: usize, max_size: usize },
+
     #[critical()]
     #[error("Unexpected text message: {0}")]
     UnexpectedWsTextMessage(String),
@@ -76,6 +108,32 @@ use self::ChronikClientError::*;
 
 impl ChronikClient {
     pub fn new(url: String) -> Result<Self> {
+        Self::with_client(url, reqwest::Client::new(), None)
+    }
+
+    /// Connect to the Chronik instance at `url` with the given connection settings.
+    pub fn connect(url: String, opts: ConnectOpts) -> Result<Self> {
+        if opts.https_only && !url.starts_with("https://") {
+            return Err(HttpsRequired(url).into());
+        }
+        let mut builder = reqwest::Client::builder()
+            .tcp_keepalive(opts.tcp_keepalive)
+            .https_only(opts.https_only);
+        if let Some(connect_timeout) = opts.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(request_timeout) = opts.request_timeout {
+            builder = builder.timeout(request_timeout);
+        }
+        let client = builder.build().wrap_err(HttpClientBuildError)?;
+        Self::with_client(url, client, opts.max_response_size)
+    }
+
+    fn with_client(
+        url: String,
+        client: reqwest::Client,
+        max_response_size: Option<usize>,
+    ) -> Result<Self> {
         if url.ends_with('/') {
             return Err(CannotHaveTrailingSlashInUrl(url).into());
         }
@@ -90,7 +148,8 @@ impl ChronikClient {
         Ok(ChronikClient {
             http_url: url,
             ws_url,
-            client: reqwest::Client::new(),
+            client,
+            max_response_size,
         })
     }
 
@@ -197,7 +256,7 @@ impl ChronikClient {
             .send()
             .await
             .wrap_err(HttpRequestError)?;
-        Self::_handle_response(response).await
+        self._handle_response(response).await
     }
 
     async fn _get<MResponse: prost::Message + Default>(
@@ -211,16 +270,20 @@ impl ChronikClient {
             .send()
             .await
             .wrap_err(HttpRequestError)?;
-        Self::_handle_response(response).await
+        self._handle_response(response).await
     }
 
     async fn _handle_response<MResponse: prost::Message + Default>(
+        &self,
         response: reqwest::Response,
     ) -> Result<MResponse> {
         use prost::Message as _;
+        if let (Some(max_size), Some(size)) = (self.max_response_size, response.content_length()) {
+            Self::_check_response_size(size as usize, max_size)?;
+        }
         let status_code = response.status();
         if status_code != StatusCode::OK {
-            let data = response.bytes().await?;
+            let data = self._read_body(response).await?;
             let error = proto::Error::decode(data.as_ref())
                 .wrap_err_with(|| InvalidProtobuf(hex::encode(&data)))?;
             return Err(ChronikError {
@@ -230,11 +293,43 @@ impl ChronikClient {
             }
             .into());
         }
-        let bytes = response.bytes().await.wrap_err(HttpRequestError)?;
+        let bytes = self._read_body(response).await?;
         let response = MResponse::decode(bytes.as_ref())
             .wrap_err_with(|| InvalidProtobuf(hex::encode(&bytes)))?;
         Ok(response)
     }
+
+    /// Reads the body chunk by chunk, so that a body without (or with a wrong) Content-Length
+    /// is rejected as soon as it exceeds `max_response_size`, without buffering all of it.
+    async fn _read_body(&self, mut response: reqwest::Response) -> Result<Vec<u8>> {
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.wrap_err(HttpRequestError)? {
+            if let Some(max_size) = self.max_response_size {
+                Self::_check_response_size(body.len() + chunk.len(), max_size)?;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    fn _check_response_size(size: usize, max_size: usize) -> Result<()> {
+        if size > max_size {
+            return Err(ResponseTooLarge { size, max_size }.into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for ConnectOpts {
+    fn default() -> Self {
+        ConnectOpts {
+            connect_timeout: Some(Duration::from_secs(10)),
+            request_timeout: Some(Duration::from_secs(60)),
+            tcp_keepalive: Some(Duration::from_secs(30)),
+            max_response_size: Some(64 * 1024 * 1024),
+            https_only: false,
+        }
+    }
 }
 
 impl ScriptEndpoint<'_, '_> {
@@ -382,7 +477,7 @@ impl Display for ScriptType {
 mod tests {
     use bitcoinsuite_error::Result;
 
-    use crate::{ChronikClient, ChronikClientError};
+    use crate::{ChronikClient, ChronikClientError, ConnectOpts};
 
     #[test]
     fn test_constructor_trailing_slash() -> Result<()> {
@@ -403,4 +498,74 @@ mod tests {
         assert_eq!(err, ChronikClientError::InvalidUrlSchema(url));
         Ok(())
     }
+
+    #[test]
+    fn test_connect() -> Result<()> {
+        let client = ChronikClient::connect(
+            "https://chronik.be.cash/xec".to_string(),
+            ConnectOpts {
+                https_only: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(client.ws_url(), "wss://chronik.be.cash/xec/ws");
+
+        let url = "http://chronik.be.cash/xec".to_string();
+        let err = ChronikClient::connect(
+            url.clone(),
+            ConnectOpts {
+                https_only: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err()
+        .downcast::<ChronikClientError>()?;
+        assert_eq!(err, ChronikClientError::HttpsRequired(url));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_response_size_chunked() -> Result<()> {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await?;
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await?;
+            // No Content-Length, the size is only known while reading the body
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n")
+                .await?;
+            for _ in 0..3 {
+                socket.write_all(b"64\r\n").await?;
+                socket.write_all(&[0; 100]).await?;
+                socket.write_all(b"\r\n").await?;
+            }
+            socket.write_all(b"0\r\n\r\n").await?;
+            Ok::<_, std::io::Error>(())
+        });
+        let client = ChronikClient::connect(
+            url,
+            ConnectOpts {
+                max_response_size: Some(150),
+                ..Default::default()
+            },
+        )?;
+        let err = client
+            .blockchain_info()
+            .await
+            .unwrap_err()
+            .downcast::<ChronikClientError>()?;
+        assert!(matches!(
+            err,
+            ChronikClientError::ResponseTooLarge { size, max_size: 150 } if size > 150,
+        ));
+        // The server may see the connection closed early
+        let _ = server.await;
+        Ok(())
+    }
 }
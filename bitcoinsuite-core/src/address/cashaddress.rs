@@ -3,13 +3,15 @@ use std::{borrow::Cow, str::FromStr};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
-use crate::{AddressType, Hashed, Script, ShaRmd160};
+use crate::{ecc::PubKey, AddressType, Hashed, Net, Script, ShaRmd160};
 
 pub const BITCOINCASH: &str = "bitcoincash";
+pub const BCHTEST: &str = "bchtest";
 pub const BCHREG: &str = "bchreg";
 pub const SIMPLELEDGER: &str = "simpleledger";
 pub const ECASH: &str = "ecash";
 pub const ETOKEN: &str = "etoken";
+pub const ECTEST: &str = "ectest";
 pub const ECREGTEST: &str = "ecregtest";
 
 const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
@@ -34,6 +36,8 @@ pub enum CashAddressError {
     MissingPrefix,
     #[error("Invalid payload length: {0}")]
     InvalidPayloadLength(usize),
+    #[error("Address has the wrong prefix, expected {expected}")]
+    WrongPrefix { expected: &'static str },
 }
 
 impl<'a> CashAddress<'a> {
@@ -61,6 +65,35 @@ impl<'a> CashAddress<'a> {
         })
     }
 
+    /// Parse `cash_addr`, failing with [`CashAddressError::WrongPrefix`] if it doesn't have
+    /// `expected_prefix`, e.g. when a testnet address is entered on mainnet.
+    pub fn parse_with_prefix(
+        cash_addr: &str,
+        expected_prefix: &'static str,
+    ) -> Result<CashAddress<'static>, CashAddressError> {
+        let address = cash_addr.parse::<CashAddress>()?;
+        if address.prefix() != expected_prefix {
+            return Err(CashAddressError::WrongPrefix {
+                expected: expected_prefix,
+            });
+        }
+        Ok(address)
+    }
+
+    /// Parse an eCash address of `net`, see [`Net::cashaddr_prefix`].
+    pub fn parse_net(cash_addr: &str, net: Net) -> Result<CashAddress<'static>, CashAddressError> {
+        Self::parse_with_prefix(cash_addr, net.cashaddr_prefix())
+    }
+
+    /// P2PKH address of the pubkey, hashing its compressed serialization.
+    pub fn p2pkh_from_pubkey(prefix: impl Into<Cow<'a, str>>, pubkey: &PubKey) -> Self {
+        CashAddress::from_hash(
+            prefix,
+            AddressType::P2PKH,
+            ShaRmd160::digest(pubkey.as_slice().into()),
+        )
+    }
+
     pub fn from_redeem_script(prefix: impl Into<Cow<'a, str>>, redeem_script: Script) -> Self {
         CashAddress::from_hash(
             prefix,
@@ -317,8 +350,8 @@ fn _from_cash_addr(
 #[cfg(test)]
 mod tests {
     use crate::{
-        AddressType, BitcoinSuiteError, CashAddress, CashAddressError, Hashed, Script, ShaRmd160,
-        BITCOINCASH, SIMPLELEDGER,
+        ecc::PubKey, AddressType, BitcoinSuiteError, CashAddress, CashAddressError, Hashed, Net,
+        Script, ShaRmd160, BITCOINCASH, ECASH, ECTEST, SIMPLELEDGER,
     };
 
     #[test]
@@ -424,6 +457,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_net() -> Result<(), CashAddressError> {
+        let hash = ShaRmd160::new([1; 20]);
+        let mainnet_addr = CashAddress::from_hash(ECASH, AddressType::P2PKH, hash.clone());
+        let testnet_addr = CashAddress::from_hash(ECTEST, AddressType::P2PKH, hash);
+        assert_eq!(
+            CashAddress::parse_net(mainnet_addr.as_str(), Net::Mainnet)?,
+            mainnet_addr,
+        );
+        assert_eq!(
+            CashAddress::parse_net(testnet_addr.as_str(), Net::Testnet)?,
+            testnet_addr,
+        );
+        assert_eq!(
+            CashAddress::parse_with_prefix(mainnet_addr.as_str(), ECASH)?,
+            mainnet_addr,
+        );
+
+        let err = CashAddress::parse_net(testnet_addr.as_str(), Net::Mainnet).unwrap_err();
+        assert_eq!(err, CashAddressError::WrongPrefix { expected: "ecash" });
+        assert_eq!(
+            err.to_string(),
+            "Address has the wrong prefix, expected ecash",
+        );
+        assert_eq!(
+            CashAddress::parse_net(mainnet_addr.as_str(), Net::Regtest),
+            Err(CashAddressError::WrongPrefix {
+                expected: "ecregtest",
+            }),
+        );
+        // Malformed addresses fail as usual
+        assert_eq!(
+            CashAddress::parse_net("ecash:qqqq", Net::Mainnet),
+            Err(CashAddressError::InvalidChecksum),
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_parse_fail_no_prefix() {
         let err = "qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqfnhks603"
@@ -457,6 +528,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_p2pkh_from_pubkey() {
+        let pubkey = PubKey::new_unchecked([2; 33]);
+        let addr = CashAddress::p2pkh_from_pubkey(BITCOINCASH, &pubkey);
+        assert_eq!(
+            addr,
+            CashAddress::from_hash(
+                BITCOINCASH,
+                AddressType::P2PKH,
+                ShaRmd160::digest(pubkey.as_slice().into()),
+            ),
+        );
+        assert_eq!(addr.to_script(), Script::p2pkh(addr.hash()));
+    }
+
     #[test]
     fn test_from_redeem_script() -> Result<(), BitcoinSuiteError> {
         let addr =
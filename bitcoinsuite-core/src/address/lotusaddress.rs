@@ -74,6 +74,7 @@ impl LotusAddress {
         let mut lotus_addr = prefix.to_string();
         let net_char = match net {
             Net::Mainnet => '_',
+            Net::Testnet => 'T',
             Net::Regtest => 'R',
         };
         lotus_addr.push(net_char);
@@ -112,10 +113,11 @@ impl FromStr for LotusAddress {
         if prefix.is_empty() {
             return Err(MissingPrefix);
         }
-        // net: "_" for mainnet, "R" for regtest, "T" for testnet (unsupported)
+        // net: "_" for mainnet, "R" for regtest, "T" for testnet
         let net_char = s.chars().nth(prefix.len()).ok_or(MissingNetChar)?;
         let net = match net_char {
             '_' => Net::Mainnet,
+            'T' => Net::Testnet,
             'R' => Net::Regtest,
             _ => return Err(UnsupportedNet(net_char)),
         };
@@ -1,8 +1,17 @@
+use std::io::Write;
+
 use crate::{
-    encoding::{read_compact_size, write_compact_size},
+    encoding::{
+        read_compact_size, read_var_bytes, write_compact_size, write_compact_size_to,
+        write_var_bytes,
+    },
     ByteArray, Bytes, BytesMut, Result,
 };
 
+/// Max. number of bytes [`BitcoinCode::deser`] allocates for a [`Vec`] up front, like the
+/// node's MAX_VECTOR_ALLOCATE.
+const MAX_VEC_PREALLOC_BYTES: usize = 5_000_000;
+
 pub trait BitcoinCode: Sized {
     fn ser_to(&self, bytes: &mut BytesMut);
     fn deser(data: &mut Bytes) -> Result<Self>;
@@ -11,17 +20,28 @@ pub trait BitcoinCode: Sized {
         self.ser_to(&mut bytes);
         bytes.freeze()
     }
+
+    /// Writes the same bytes as [`BitcoinCode::ser`] to `writer`, e.g. a file or socket.
+    ///
+    /// By default, this serializes into a buffer first. Containers and txs override it to
+    /// write their parts one by one, so e.g. a block is never buffered as a whole.
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.ser())
+    }
 }
 
 impl BitcoinCode for Bytes {
     fn ser_to(&self, bytes: &mut BytesMut) {
-        write_compact_size(bytes, self.len() as u64);
-        bytes.put_slice(self.as_ref());
+        write_var_bytes(bytes, self.as_ref());
+    }
+
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_compact_size_to(writer, self.len() as u64)?;
+        writer.write_all(self)
     }
 
     fn deser(data: &mut Bytes) -> Result<Self> {
-        let size = read_compact_size(data)?;
-        Ok(data.split_to(size as usize)?)
+        read_var_bytes(data)
     }
 }
 
@@ -30,6 +50,10 @@ impl<const N: usize> BitcoinCode for ByteArray<N> {
         bytes.put_slice(self.as_ref());
     }
 
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(self.as_ref())
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(data.split_to_array::<N>()?)
     }
@@ -43,9 +67,21 @@ impl<T: BitcoinCode> BitcoinCode for Vec<T> {
         }
     }
 
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_compact_size_to(writer, self.len() as u64)?;
+        for part in self {
+            part.serialize_to(writer)?;
+        }
+        Ok(())
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         let size = read_compact_size(data)? as usize;
-        let mut vec = Vec::with_capacity(size);
+        // Don't trust the size for allocation: every item takes up at least one byte of `data`,
+        // but may take up many more bytes in memory, so also cap the allocated bytes and let the
+        // vec grow beyond that if the items are actually there.
+        let max_capacity = MAX_VEC_PREALLOC_BYTES / std::mem::size_of::<T>().max(1);
+        let mut vec = Vec::with_capacity(size.min(data.len()).min(max_capacity));
         for _ in 0..size {
             let item = T::deser(data)?;
             vec.push(item);
@@ -72,6 +108,10 @@ macro_rules! integer_impls {
                     bytes.put_slice(&self.to_le_bytes())
                 }
 
+                fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+
                 fn deser(data: &mut Bytes) -> Result<Self> {
                     let array = data.split_to_array::<$SIZE>()?;
                     let value = $T::from_le_bytes(array.array());
@@ -94,6 +134,9 @@ mod tests {
 
     fn verify_ser<T: BitcoinCode + PartialEq + Debug>(a: T, b: &[u8]) {
         assert_eq!(a.ser().as_ref(), b);
+        let mut written = Vec::new();
+        a.serialize_to(&mut written).expect("Write fail");
+        assert_eq!(written, b);
         let deser = T::deser(&mut b.into()).expect("Deser fail");
         assert_eq!(a, deser);
     }
@@ -75,6 +75,11 @@ impl BitcoinCode for BitcoinBlock {
         self.txs.ser_to(bytes);
     }
 
+    fn serialize_to<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.header.serialize_to(writer)?;
+        self.txs.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(BitcoinBlock {
             header: BitcoinCode::deser(data)?,
@@ -249,8 +254,8 @@ impl LotusBlock {
 #[cfg(test)]
 mod tests {
     use crate::{
-        lotus_txid, BitcoinBlock, BitcoinHeader, Hashed, LotusBlock, LotusHeader, OutPoint, Script,
-        SequenceNo, Sha256d, TxInput, TxOutput, UnhashedTx,
+        lotus_txid, BitcoinBlock, BitcoinCode, BitcoinHeader, Hashed, LotusBlock, LotusHeader,
+        OutPoint, Script, SequenceNo, Sha256d, TxInput, TxOutput, UnhashedTx,
     };
 
     #[allow(clippy::inconsistent_digit_grouping)]
@@ -308,6 +313,9 @@ mod tests {
             genesis_block.header.calc_hash(),
             Sha256d::from_hex_be(genesis_hash_hex).unwrap()
         );
+        let mut written = Vec::new();
+        genesis_block.serialize_to(&mut written).unwrap();
+        assert_eq!(written, genesis_block.ser().to_vec());
     }
 
     #[test]
@@ -1,6 +1,10 @@
-use std::{hash::Hash, ops::Deref};
+use std::{
+    hash::Hash,
+    ops::{Bound, Deref, RangeBounds},
+};
 
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 use thiserror::Error;
 
 use crate::ByteArray;
@@ -9,6 +13,12 @@ use crate::ByteArray;
 pub enum BytesError {
     #[error("Index {split_idx} is out of bounds for array with length {len}")]
     InvalidSplit { split_idx: usize, len: usize },
+    #[error("Range {start}..{end} is out of bounds for array with length {len}")]
+    InvalidSlice {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
@@ -79,6 +89,42 @@ impl Bytes {
         }
     }
 
+    /// Returns the bytes in `range` without copying.
+    ///
+    /// The returned `Bytes` shares the reference-counted buffer of `self`, so the buffer is kept
+    /// alive for as long as either of them (or any other slice of it) is alive.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Result<Bytes, BytesError> {
+        let len = self.data.len();
+        // Bounds past usize::MAX are out of bounds for any array
+        let start = match range.start_bound() {
+            Bound::Included(&start) => Some(start),
+            Bound::Excluded(&start) => start.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1),
+            Bound::Excluded(&end) => Some(end),
+            Bound::Unbounded => Some(len),
+        };
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) => (start, end),
+            (start, end) => {
+                return Err(BytesError::InvalidSlice {
+                    start: start.unwrap_or(usize::MAX),
+                    end: end.unwrap_or(usize::MAX),
+                    len,
+                })
+            }
+        };
+        if start > end || end > len {
+            return Err(BytesError::InvalidSlice { start, end, len });
+        }
+        Ok(Bytes::from_bytes(self.data.slice(start..end)))
+    }
+
+    /// Splits off the first `at` bytes and returns them, leaving the rest in `self`.
+    ///
+    /// Like [`Bytes::slice`], this doesn't copy; both halves share the same buffer.
     pub fn split_to(&mut self, at: usize) -> Result<Bytes, BytesError> {
         if self.data.len() < at {
             return Err(BytesError::InvalidSplit {
@@ -104,6 +150,12 @@ impl Bytes {
     pub fn hex(&self) -> String {
         hex::encode(&self.data)
     }
+
+    /// Compares in constant time (for equal lengths), use this instead of `==` when the bytes are
+    /// derived from secrets (e.g. signatures or MACs).
+    pub fn ct_eq(&self, other: &Bytes) -> bool {
+        self.data.as_ref().ct_eq(other.data.as_ref()).into()
+    }
 }
 
 impl From<Vec<u8>> for Bytes {
@@ -123,3 +175,98 @@ impl<'a> From<&'a [u8]> for Bytes {
         Bytes::from_bytes(slice.to_vec())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use crate::{Bytes, BytesError};
+
+    #[test]
+    fn test_slice() -> Result<(), BytesError> {
+        let bytes = Bytes::from_slice(&[1, 2, 3, 4, 5]);
+        assert_eq!(bytes.slice(..)?.as_ref(), &[1, 2, 3, 4, 5]);
+        assert_eq!(bytes.slice(1..3)?.as_ref(), &[2, 3]);
+        assert_eq!(bytes.slice(1..=3)?.as_ref(), &[2, 3, 4]);
+        assert_eq!(bytes.slice(3..)?.as_ref(), &[4, 5]);
+        assert_eq!(bytes.slice(..2)?.as_ref(), &[1, 2]);
+        assert_eq!(bytes.slice(5..)?.as_ref(), &[] as &[u8]);
+        assert_eq!(
+            bytes.slice(4..6),
+            Err(BytesError::InvalidSlice {
+                start: 4,
+                end: 6,
+                len: 5,
+            }),
+        );
+        assert_eq!(
+            bytes.slice(3..2),
+            Err(BytesError::InvalidSlice {
+                start: 3,
+                end: 2,
+                len: 5,
+            }),
+        );
+        assert_eq!(
+            bytes.slice(2..=usize::MAX),
+            Err(BytesError::InvalidSlice {
+                start: 2,
+                end: usize::MAX,
+                len: 5,
+            }),
+        );
+        assert_eq!(
+            bytes.slice((Bound::Excluded(usize::MAX), Bound::Unbounded)),
+            Err(BytesError::InvalidSlice {
+                start: usize::MAX,
+                end: 5,
+                len: 5,
+            }),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice_shares_buffer() -> Result<(), BytesError> {
+        let bytes = Bytes::from(vec![1, 2, 3, 4, 5]);
+        let parent_ptr = bytes.as_ptr();
+        let slice = bytes.slice(2..4)?;
+        assert_eq!(slice.as_ptr(), parent_ptr.wrapping_add(2));
+        // slice keeps the buffer alive after the parent is gone
+        std::mem::drop(bytes);
+        assert_eq!(slice.as_ref(), &[3, 4]);
+        assert_eq!(slice.as_ptr(), parent_ptr.wrapping_add(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let bytes = Bytes::from_slice(&[1, 2, 3]);
+        assert!(bytes.ct_eq(&Bytes::from_slice(&[1, 2, 3])));
+        assert!(!bytes.ct_eq(&Bytes::from_slice(&[1, 2, 4])));
+        assert!(!bytes.ct_eq(&Bytes::from_slice(&[0, 2, 3])));
+        assert!(!bytes.ct_eq(&Bytes::from_slice(&[1, 2])));
+        assert!(!bytes.ct_eq(&Bytes::from_slice(&[1, 2, 3, 4])));
+        assert!(!bytes.ct_eq(&Bytes::new()));
+        assert!(Bytes::new().ct_eq(&Bytes::new()));
+    }
+
+    #[test]
+    fn test_split_to() -> Result<(), BytesError> {
+        let mut bytes = Bytes::from(vec![1, 2, 3, 4, 5]);
+        let parent_ptr = bytes.as_ptr();
+        let head = bytes.split_to(2)?;
+        assert_eq!(head.as_ref(), &[1, 2]);
+        assert_eq!(bytes.as_ref(), &[3, 4, 5]);
+        assert_eq!(head.as_ptr(), parent_ptr);
+        assert_eq!(bytes.as_ptr(), parent_ptr.wrapping_add(2));
+        assert_eq!(
+            bytes.split_to(4),
+            Err(BytesError::InvalidSplit {
+                split_idx: 4,
+                len: 3,
+            }),
+        );
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
 mod pubkey;
 mod seckey;
+mod xpub;
 
 use crate::{ByteArray, Bytes};
 
 pub use crate::ecc::pubkey::*;
 pub use crate::ecc::seckey::*;
+pub use crate::ecc::xpub::*;
 
 use thiserror::Error;
 
@@ -27,12 +29,14 @@ pub enum EccError {
     InvalidPublicKey,
     #[error("Invalid secret key")]
     InvalidSecretKey,
-    #[error("Pubkey has invalid length, expected {:.0} but got {0}", 33)]
+    #[error("Pubkey has invalid length: {0}")]
     InvalidPubKeyLen(usize),
     #[error("Invalid recovery_id: {0}")]
     InvalidRecoveryId(i32),
     #[error("Failed recovering signature")]
     RecoveryFailed,
+    #[error("Invalid tweak")]
+    InvalidTweak,
     #[error("Invalid hex: {0}")]
     Hex(#[from] hex::FromHexError),
 }
@@ -40,6 +44,9 @@ pub enum EccError {
 pub trait Ecc {
     fn pubkey_from_array(&self, pubkey: [u8; PUBKEY_LENGTH]) -> Result<PubKey, EccError>;
 
+    /// Parse a compressed (33 bytes) or uncompressed (65 bytes) pubkey.
+    fn pubkey_from_slice(&self, pubkey: &[u8]) -> Result<PubKey, EccError>;
+
     fn seckey_from_array(&self, seckey: [u8; 32]) -> Result<SecKey, EccError>;
 
     fn sign(&self, seckey: &SecKey, msg: ByteArray<32>) -> Bytes;
@@ -62,6 +69,10 @@ pub trait Ecc {
 
     fn derive_pubkey(&self, seckey: &SecKey) -> PubKey;
 
+    /// Compute `pubkey + tweak * G`, failing if `tweak` is not smaller than the curve order
+    /// or the result is the point at infinity.
+    fn pubkey_tweak_add(&self, pubkey: &PubKey, tweak: [u8; 32]) -> Result<PubKey, EccError>;
+
     fn serialize_pubkey_uncompressed(&self, pubkey: &PubKey) -> [u8; 65];
 
     fn normalize_sig(&self, sig: &Bytes) -> Result<Bytes, EccError>;
@@ -84,6 +95,10 @@ impl Ecc for DummyEcc {
         Ok(PubKey::new_unchecked([0; PUBKEY_LENGTH]))
     }
 
+    fn pubkey_from_slice(&self, _pubkey: &[u8]) -> Result<PubKey, EccError> {
+        Ok(PubKey::new_unchecked([0; PUBKEY_LENGTH]))
+    }
+
     fn seckey_from_array(&self, _seckey: [u8; 32]) -> Result<SecKey, EccError> {
         Ok(SecKey::new_unchecked([0; 32]))
     }
@@ -118,6 +133,10 @@ impl Ecc for DummyEcc {
         PubKey::new_unchecked([0; PUBKEY_LENGTH])
     }
 
+    fn pubkey_tweak_add(&self, pubkey: &PubKey, _tweak: [u8; 32]) -> Result<PubKey, EccError> {
+        Ok(*pubkey)
+    }
+
     fn serialize_pubkey_uncompressed(&self, _pubkey: &PubKey) -> [u8; 65] {
         [0; 65]
     }
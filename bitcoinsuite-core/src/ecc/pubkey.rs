@@ -1,9 +1,14 @@
 use hex_literal::hex;
 
-use crate::{ecc::EccError, ByteArray};
+use crate::{
+    ecc::{Ecc, EccError},
+    ByteArray,
+};
 
 pub const PUBKEY_LENGTH: usize = 33;
+pub const UNCOMPRESSED_PUBKEY_LENGTH: usize = 65;
 
+/// Point on the curve, always stored in its compressed serialization.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PubKey([u8; PUBKEY_LENGTH]);
 
@@ -12,6 +17,11 @@ impl PubKey {
         PubKey(pubkey)
     }
 
+    /// Parse and validate a compressed or uncompressed pubkey.
+    pub fn from_slice(ecc: &impl Ecc, pubkey: &[u8]) -> Result<Self, EccError> {
+        ecc.pubkey_from_slice(pubkey)
+    }
+
     pub fn from_hex_unchecked(hex: &str) -> Result<Self, EccError> {
         let pubkey = hex::decode(hex)?;
         Ok(PubKey(
@@ -33,6 +43,14 @@ impl PubKey {
     pub fn array(&self) -> [u8; PUBKEY_LENGTH] {
         self.0
     }
+
+    pub fn compressed(&self) -> [u8; PUBKEY_LENGTH] {
+        self.0
+    }
+
+    pub fn uncompressed(&self, ecc: &impl Ecc) -> [u8; UNCOMPRESSED_PUBKEY_LENGTH] {
+        ecc.serialize_pubkey_uncompressed(self)
+    }
 }
 
 impl Default for PubKey {
@@ -1,12 +1,46 @@
 use std::fmt::Debug;
 
+use digest::Digest;
 use secrecy::{ExposeSecret, Secret};
+use thiserror::Error;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{
+    ecc::{Ecc, EccError},
+    Net,
+};
 
 pub const SECKEY_LENGTH: usize = 32;
+pub const WIF_CHECKSUM_LEN: usize = 4;
+pub const WIF_VERSION_MAINNET: u8 = 0x80;
+pub const WIF_VERSION_TESTNET: u8 = 0xef;
 
+/// Secret key, whose bytes are wiped from memory when dropped; each clone is wiped
+/// independently.
 #[derive(Clone)]
 pub struct SecKey(Secret<[u8; SECKEY_LENGTH]>);
 
+#[derive(Debug, Error, PartialEq)]
+pub enum WifError {
+    #[error("Invalid base58")]
+    InvalidBase58(bs58::decode::Error),
+
+    #[error("Invalid WIF length, expected 37 or 38 but got {0}")]
+    InvalidLength(usize),
+
+    #[error("Invalid WIF version byte: 0x{0:02x}")]
+    InvalidVersion(u8),
+
+    #[error("Invalid WIF compression flag: 0x{0:02x}")]
+    InvalidCompressionFlag(u8),
+
+    #[error("Invalid checksum, expected {expected} but got {actual}")]
+    InvalidChecksum { expected: String, actual: String },
+
+    #[error("Invalid secret key: {0}")]
+    Ecc(#[from] EccError),
+}
+
 impl SecKey {
     pub fn new_unchecked(seckey: [u8; SECKEY_LENGTH]) -> SecKey {
         SecKey(Secret::new(seckey))
@@ -15,6 +49,78 @@ impl SecKey {
     pub fn as_slice(&self) -> &[u8] {
         self.0.expose_secret()
     }
+
+    /// Encode the secret key in the Wallet Import Format, with the version byte depending on
+    /// `net`. `compressed` indicates whether the key is used with compressed pubkeys.
+    pub fn to_wif(&self, net: Net, compressed: bool) -> String {
+        let version = match net {
+            Net::Mainnet => WIF_VERSION_MAINNET,
+            Net::Testnet | Net::Regtest => WIF_VERSION_TESTNET,
+        };
+        let mut data = Vec::with_capacity(1 + SECKEY_LENGTH + 1 + WIF_CHECKSUM_LEN);
+        data.push(version);
+        data.extend_from_slice(self.as_slice());
+        if compressed {
+            data.push(1);
+        }
+        let checksum = wif_checksum(&data);
+        data.extend_from_slice(&checksum);
+        let wif = bs58::encode(&data).into_string();
+        data.zeroize();
+        wif
+    }
+
+    /// Decode a WIF string, accepting any net, and return the secret key and whether it is
+    /// used with compressed pubkeys.
+    pub fn from_wif(ecc: &impl Ecc, s: &str) -> Result<(SecKey, bool), WifError> {
+        let mut data = bs58::decode(s)
+            .into_vec()
+            .map_err(WifError::InvalidBase58)?;
+        Self::from_wif_data(ecc, &mut data)
+    }
+
+    /// Parse base58-decoded WIF `data`, which is zeroized afterwards, also if it is invalid.
+    fn from_wif_data(ecc: &impl Ecc, data: &mut Vec<u8>) -> Result<(SecKey, bool), WifError> {
+        let result = Self::parse_wif_data(ecc, data);
+        data.zeroize();
+        result
+    }
+
+    fn parse_wif_data(ecc: &impl Ecc, data: &[u8]) -> Result<(SecKey, bool), WifError> {
+        let compressed = match data.len() {
+            37 => false,
+            38 => true,
+            len => return Err(WifError::InvalidLength(len)),
+        };
+        let (payload, checksum) = data.split_at(data.len() - WIF_CHECKSUM_LEN);
+        let expected_checksum = wif_checksum(payload);
+        if checksum != expected_checksum {
+            return Err(WifError::InvalidChecksum {
+                expected: hex::encode(expected_checksum),
+                actual: hex::encode(checksum),
+            });
+        }
+        if payload[0] != WIF_VERSION_MAINNET && payload[0] != WIF_VERSION_TESTNET {
+            return Err(WifError::InvalidVersion(payload[0]));
+        }
+        if compressed && payload[SECKEY_LENGTH + 1] != 1 {
+            return Err(WifError::InvalidCompressionFlag(payload[SECKEY_LENGTH + 1]));
+        }
+        let mut seckey = [0; SECKEY_LENGTH];
+        seckey.copy_from_slice(&payload[1..SECKEY_LENGTH + 1]);
+        let result = ecc.seckey_from_array(seckey);
+        seckey.zeroize();
+        Ok((result?, compressed))
+    }
+}
+
+/// Checksum of the WIF `payload`, hashed from the slice so the secret isn't copied into a buffer
+/// that wouldn't be zeroized.
+fn wif_checksum(payload: &[u8]) -> [u8; WIF_CHECKSUM_LEN] {
+    let hash = sha2::Sha256::digest(sha2::Sha256::digest(payload));
+    let mut checksum = [0; WIF_CHECKSUM_LEN];
+    checksum.copy_from_slice(&hash[..WIF_CHECKSUM_LEN]);
+    checksum
 }
 
 impl Debug for SecKey {
@@ -23,6 +129,16 @@ impl Debug for SecKey {
     }
 }
 
+impl Zeroize for SecKey {
+    fn zeroize(&mut self) {
+        // The replaced secret wipes its bytes when dropped
+        self.0 = Secret::new([0; SECKEY_LENGTH]);
+    }
+}
+
+/// The inner [`Secret`] wipes the key bytes on drop.
+impl ZeroizeOnDrop for SecKey {}
+
 impl Default for SecKey {
     fn default() -> Self {
         SecKey(Secret::new([0; SECKEY_LENGTH]))
@@ -31,7 +147,12 @@ impl Default for SecKey {
 
 #[cfg(test)]
 mod tests {
-    use super::SecKey;
+    use hex_literal::hex;
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+
+    use crate::{ecc::DummyEcc, Net};
+
+    use super::{wif_checksum, SecKey, WifError};
 
     #[test]
     fn test_as_slice() {
@@ -39,9 +160,92 @@ mod tests {
         assert_eq!(seckey.as_slice(), &[1; 32]);
     }
 
+    #[test]
+    fn test_zeroize() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<SecKey>();
+
+        let mut seckey = SecKey::new_unchecked([1; 32]);
+        let cloned = seckey.clone();
+        seckey.zeroize();
+        assert_eq!(seckey.as_slice(), &[0; 32]);
+        // Clones are independent
+        assert_eq!(cloned.as_slice(), &[1; 32]);
+    }
+
     #[test]
     fn test_format_debug_doesnt_leak() {
         let seckey = SecKey::new_unchecked([1; 32]);
         assert_eq!(format!("{:?}", seckey), "SecKey([SECRET])");
     }
+
+    #[test]
+    fn test_to_wif() {
+        let seckey = SecKey::new_unchecked(hex!(
+            "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d"
+        ));
+        assert_eq!(
+            seckey.to_wif(Net::Mainnet, false),
+            "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ",
+        );
+        assert_eq!(
+            seckey.to_wif(Net::Mainnet, true),
+            "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617",
+        );
+        let seckey = SecKey::new_unchecked([3; 32]);
+        assert_eq!(
+            seckey.to_wif(Net::Regtest, true),
+            "cMgZD2qsGReP1UvGbNQ7moL6PZFgzsuPFV3St8sGwpNxED4hqkEM",
+        );
+    }
+
+    #[test]
+    fn test_from_wif_failure() {
+        assert_eq!(
+            SecKey::from_wif(
+                &DummyEcc,
+                "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTK"
+            )
+            .unwrap_err(),
+            WifError::InvalidChecksum {
+                expected: "507a5b8d".to_string(),
+                actual: "507a5b8e".to_string(),
+            },
+        );
+        assert_eq!(
+            SecKey::from_wif(&DummyEcc, "111").unwrap_err(),
+            WifError::InvalidLength(3),
+        );
+    }
+
+    #[test]
+    fn test_from_wif_zeroizes_data() {
+        let with_checksum = |mut payload: Vec<u8>| {
+            payload.extend_from_slice(&wif_checksum(&payload));
+            payload
+        };
+        let mut payload = vec![0x80];
+        payload.extend_from_slice(&[1; 32]);
+        payload.push(1);
+        let mut invalid_checksum = with_checksum(payload.clone());
+        *invalid_checksum.last_mut().unwrap() ^= 1;
+        let mut invalid_version = payload.clone();
+        invalid_version[0] = 0;
+        let mut invalid_flag = payload.clone();
+        invalid_flag[33] = 2;
+        // Every error path of the parser, and success
+        let cases = [
+            (vec![1; 36], false),
+            (invalid_checksum, false),
+            (with_checksum(invalid_version), false),
+            (with_checksum(invalid_flag), false),
+            (with_checksum(payload), true),
+        ];
+        for (mut data, is_valid) in cases {
+            let result = SecKey::from_wif_data(&DummyEcc, &mut data);
+            assert_eq!(result.is_ok(), is_valid);
+            // Zeroizing a Vec wipes and then clears it
+            assert!(data.is_empty());
+        }
+    }
 }
@@ -0,0 +1,320 @@
+use std::fmt::Display;
+
+use digest::Digest;
+use thiserror::Error;
+
+use crate::{
+    ecc::{Ecc, EccError, PubKey, PUBKEY_LENGTH},
+    Bytes, Hashed, Net, Sha256d, ShaRmd160,
+};
+
+pub const XPUB_VERSION_MAINNET: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+pub const XPUB_VERSION_TESTNET: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+/// Child indices from this one on are hardened and can't be derived from a pubkey.
+pub const HARDENED_INDEX: u32 = 0x8000_0000;
+const XPUB_PAYLOAD_LEN: usize = 78;
+const XPUB_CHECKSUM_LEN: usize = 4;
+
+/// BIP32 extended public key, used to derive child pubkeys without the secret keys, e.g. for
+/// watch-only wallets.
+///
+/// Only non-hardened derivation is supported, see [`ExtendedPubKey::derive_pub`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtendedPubKey {
+    net: Net,
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: [u8; 32],
+    pubkey: PubKey,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum XPubError {
+    #[error("Invalid base58")]
+    InvalidBase58(bs58::decode::Error),
+
+    #[error("Invalid xpub length, expected 82 but got {0}")]
+    InvalidLength(usize),
+
+    #[error("Invalid checksum, expected {expected} but got {actual}")]
+    InvalidChecksum { expected: String, actual: String },
+
+    #[error("Invalid xpub version bytes: {0}")]
+    InvalidVersion(String),
+
+    #[error("Xpub is for {actual:?}, expected {expected:?}")]
+    WrongNet { expected: Net, actual: Net },
+
+    #[error("Invalid pubkey prefix: 0x{0:02x}")]
+    InvalidPubKeyPrefix(u8),
+
+    #[error("Invalid pubkey: {0}")]
+    InvalidPubKey(EccError),
+
+    #[error("Cannot derive hardened child {0} from a pubkey")]
+    HardenedIndex(u32),
+
+    #[error("Child {0} is invalid, use the next index")]
+    InvalidChild(u32),
+
+    #[error("Cannot derive beyond depth 255")]
+    MaxDepthExceeded,
+}
+
+impl ExtendedPubKey {
+    /// Parse an xpub of any net; mainnet "xpub..." or testnet "tpub...". Fails if the pubkey
+    /// isn't a point on the curve, which `ecc` checks.
+    pub fn from_str(ecc: &impl Ecc, s: &str) -> Result<Self, XPubError> {
+        let data = bs58::decode(s)
+            .into_vec()
+            .map_err(XPubError::InvalidBase58)?;
+        if data.len() != XPUB_PAYLOAD_LEN + XPUB_CHECKSUM_LEN {
+            return Err(XPubError::InvalidLength(data.len()));
+        }
+        let (payload, checksum) = data.split_at(XPUB_PAYLOAD_LEN);
+        let expected_checksum = Sha256d::digest(Bytes::from_slice(payload));
+        let expected_checksum = &expected_checksum.as_slice()[..XPUB_CHECKSUM_LEN];
+        if checksum != expected_checksum {
+            return Err(XPubError::InvalidChecksum {
+                expected: hex::encode(expected_checksum),
+                actual: hex::encode(checksum),
+            });
+        }
+        let version: [u8; 4] = payload[..4].try_into().unwrap();
+        let net = match version {
+            XPUB_VERSION_MAINNET => Net::Mainnet,
+            XPUB_VERSION_TESTNET => Net::Testnet,
+            _ => return Err(XPubError::InvalidVersion(hex::encode(version))),
+        };
+        let mut parent_fingerprint = [0; 4];
+        parent_fingerprint.copy_from_slice(&payload[5..9]);
+        let mut child_number = [0; 4];
+        child_number.copy_from_slice(&payload[9..13]);
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&payload[13..45]);
+        let mut pubkey = [0; PUBKEY_LENGTH];
+        pubkey.copy_from_slice(&payload[45..]);
+        if pubkey[0] != 0x02 && pubkey[0] != 0x03 {
+            return Err(XPubError::InvalidPubKeyPrefix(pubkey[0]));
+        }
+        ecc.pubkey_from_array(pubkey)
+            .map_err(XPubError::InvalidPubKey)?;
+        Ok(ExtendedPubKey {
+            net,
+            depth: payload[4],
+            parent_fingerprint,
+            child_number: u32::from_be_bytes(child_number),
+            chain_code,
+            pubkey: PubKey::new_unchecked(pubkey),
+        })
+    }
+
+    /// Parse an xpub, failing if its version bytes don't match `net`. Testnet and regtest
+    /// share the same version bytes.
+    pub fn from_str_net(ecc: &impl Ecc, s: &str, net: Net) -> Result<Self, XPubError> {
+        let xpub = ExtendedPubKey::from_str(ecc, s)?;
+        if version_bytes(xpub.net) != version_bytes(net) {
+            return Err(XPubError::WrongNet {
+                expected: net,
+                actual: xpub.net,
+            });
+        }
+        Ok(ExtendedPubKey { net, ..xpub })
+    }
+
+    /// Derive the non-hardened child pubkey at `index` (public derivation of BIP32).
+    pub fn derive_pub(&self, ecc: &impl Ecc, index: u32) -> Result<ExtendedPubKey, XPubError> {
+        if index >= HARDENED_INDEX {
+            return Err(XPubError::HardenedIndex(index));
+        }
+        let depth = self
+            .depth
+            .checked_add(1)
+            .ok_or(XPubError::MaxDepthExceeded)?;
+        let hash = hmac_sha512(
+            &self.chain_code,
+            &[self.pubkey.as_slice(), &index.to_be_bytes()],
+        );
+        let mut tweak = [0; 32];
+        tweak.copy_from_slice(&hash[..32]);
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&hash[32..]);
+        let pubkey = ecc
+            .pubkey_tweak_add(&self.pubkey, tweak)
+            .map_err(|err| match err {
+                EccError::InvalidTweak => XPubError::InvalidChild(index),
+                err => XPubError::InvalidPubKey(err),
+            })?;
+        Ok(ExtendedPubKey {
+            net: self.net,
+            depth,
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+            chain_code,
+            pubkey,
+        })
+    }
+
+    /// First 4 bytes of the hash160 of the pubkey, which identify the parent of derived keys.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let hash = ShaRmd160::digest(Bytes::from_slice(self.pubkey.as_slice()));
+        let mut fingerprint = [0; 4];
+        fingerprint.copy_from_slice(&hash.as_slice()[..4]);
+        fingerprint
+    }
+
+    pub fn net(&self) -> Net {
+        self.net
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        self.parent_fingerprint
+    }
+
+    pub fn child_number(&self) -> u32 {
+        self.child_number
+    }
+
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    pub fn pubkey(&self) -> &PubKey {
+        &self.pubkey
+    }
+}
+
+impl Display for ExtendedPubKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut data = Vec::with_capacity(XPUB_PAYLOAD_LEN + XPUB_CHECKSUM_LEN);
+        data.extend_from_slice(&version_bytes(self.net));
+        data.push(self.depth);
+        data.extend_from_slice(&self.parent_fingerprint);
+        data.extend_from_slice(&self.child_number.to_be_bytes());
+        data.extend_from_slice(&self.chain_code);
+        data.extend_from_slice(self.pubkey.as_slice());
+        let checksum = Sha256d::digest(Bytes::from_slice(&data));
+        data.extend_from_slice(&checksum.as_slice()[..XPUB_CHECKSUM_LEN]);
+        write!(f, "{}", bs58::encode(&data).into_string())
+    }
+}
+
+fn version_bytes(net: Net) -> [u8; 4] {
+    match net {
+        Net::Mainnet => XPUB_VERSION_MAINNET,
+        Net::Testnet | Net::Regtest => XPUB_VERSION_TESTNET,
+    }
+}
+
+/// HMAC-SHA512 (RFC 2104) of the concatenated `data`, keyed by a chain code.
+fn hmac_sha512(key: &[u8; 32], data: &[&[u8]]) -> [u8; 64] {
+    const BLOCK_SIZE: usize = 128;
+    let mut key_block = [0; BLOCK_SIZE];
+    key_block[..key.len()].copy_from_slice(key);
+    let mut inner = sha2::Sha512::new();
+    inner.update(key_block.map(|byte| byte ^ 0x36));
+    for data in data {
+        inner.update(data);
+    }
+    let mut outer = sha2::Sha512::new();
+    outer.update(key_block.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use crate::{
+        ecc::{DummyEcc, ExtendedPubKey, XPubError, HARDENED_INDEX},
+        Net,
+    };
+
+    use super::hmac_sha512;
+
+    const XPUB: &str = "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB";
+
+    #[test]
+    fn test_hmac_sha512() {
+        // RFC 4231 test case 2, with the key padded to 32 bytes
+        let mut key = [0; 32];
+        key[..4].copy_from_slice(b"Jefe");
+        assert_eq!(
+            hmac_sha512(&key, &[b"what do ya want ", b"for nothing?"]),
+            hex!(
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554"
+                "9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+            ),
+        );
+    }
+
+    #[test]
+    fn test_parse_xpub() -> Result<(), XPubError> {
+        let xpub = ExtendedPubKey::from_str(&DummyEcc, XPUB)?;
+        assert_eq!(xpub.net(), Net::Mainnet);
+        assert_eq!(xpub.depth(), 0);
+        assert_eq!(xpub.parent_fingerprint(), [0; 4]);
+        assert_eq!(xpub.child_number(), 0);
+        assert_eq!(
+            xpub.chain_code(),
+            &hex!("60499f801b896d83179a4374aeb7822aaeaceaa0db1f85ee3e904c4defbd9689"),
+        );
+        assert_eq!(
+            xpub.pubkey().hex(),
+            "03cbcaa9c98c877a26977d00825c956a238e8dddfbd322cce4f74b0b5bd6ace4a7",
+        );
+        assert_eq!(xpub.to_string(), XPUB);
+        assert_eq!(
+            ExtendedPubKey::from_str_net(&DummyEcc, XPUB, Net::Mainnet)?,
+            xpub
+        );
+        assert_eq!(
+            ExtendedPubKey::from_str_net(&DummyEcc, XPUB, Net::Regtest),
+            Err(XPubError::WrongNet {
+                expected: Net::Regtest,
+                actual: Net::Mainnet,
+            }),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_xpub_failure() {
+        let mut bad_checksum = XPUB.to_string();
+        bad_checksum.pop();
+        bad_checksum.push('C');
+        assert!(matches!(
+            ExtendedPubKey::from_str(&DummyEcc, &bad_checksum),
+            Err(XPubError::InvalidChecksum { .. }),
+        ));
+        assert_eq!(
+            ExtendedPubKey::from_str(&DummyEcc, "111"),
+            Err(XPubError::InvalidLength(3)),
+        );
+        // xprv of BIP32 test vector 2
+        assert_eq!(
+            ExtendedPubKey::from_str(&DummyEcc, "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U"),
+            Err(XPubError::InvalidVersion("0488ade4".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_derive_hardened() -> Result<(), XPubError> {
+        let xpub = ExtendedPubKey::from_str(&DummyEcc, XPUB)?;
+        assert_eq!(
+            xpub.derive_pub(&DummyEcc, HARDENED_INDEX),
+            Err(XPubError::HardenedIndex(HARDENED_INDEX)),
+        );
+        let child = xpub.derive_pub(&DummyEcc, HARDENED_INDEX - 1)?;
+        assert_eq!(child.depth(), 1);
+        assert_eq!(child.child_number(), HARDENED_INDEX - 1);
+        assert_eq!(child.parent_fingerprint(), xpub.fingerprint());
+        Ok(())
+    }
+}
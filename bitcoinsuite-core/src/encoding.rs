@@ -1,31 +1,70 @@
-use crate::{Bytes, BytesError, BytesMut};
+use std::io::Write;
 
-pub fn read_compact_size(bytes: &mut Bytes) -> Result<u64, BytesError> {
+use crate::{BitcoinSuiteError, Bytes, BytesMut, Result};
+
+/// Reads a CompactSize, rejecting non-canonical encodings (e.g. 0xfd for a value < 0xfd).
+pub fn read_compact_size(bytes: &mut Bytes) -> Result<u64> {
     let first_byte = bytes.split_to(1)?[0];
-    match first_byte {
-        0..=0xfc => Ok(first_byte as u64),
-        0xfd => Ok(u16::from_le_bytes(bytes.split_to_array::<2>()?.array()) as u64),
-        0xfe => Ok(u32::from_le_bytes(bytes.split_to_array::<4>()?.array()) as u64),
-        0xff => Ok(u64::from_le_bytes(bytes.split_to_array::<8>()?.array())),
+    let (size, min_size) = match first_byte {
+        0..=0xfc => return Ok(first_byte as u64),
+        0xfd => (
+            u16::from_le_bytes(bytes.split_to_array::<2>()?.array()) as u64,
+            0xfd,
+        ),
+        0xfe => (
+            u32::from_le_bytes(bytes.split_to_array::<4>()?.array()) as u64,
+            0x1_0000,
+        ),
+        0xff => (
+            u64::from_le_bytes(bytes.split_to_array::<8>()?.array()),
+            0x1_0000_0000,
+        ),
+    };
+    if size < min_size {
+        return Err(BitcoinSuiteError::InvalidVarInt);
     }
+    Ok(size)
+}
+
+pub fn read_var_bytes(bytes: &mut Bytes) -> Result<Bytes> {
+    let size = read_compact_size(bytes)?;
+    Ok(bytes.split_to(size as usize)?)
 }
 
+/// Like [`read_compact_size`], but reading from a slice. Returns `None` if `slice` is too short
+/// or the encoding is non-canonical.
 pub fn read_compact_size_slice(slice: &[u8]) -> Option<(usize, u64)> {
     let first_byte = *slice.first()?;
-    match first_byte {
-        0..=0xfc => Some((1, first_byte as u64)),
-        0xfd => Some((
+    let (len, size, min_size) = match first_byte {
+        0..=0xfc => return Some((1, first_byte as u64)),
+        0xfd => (
             2,
             u16::from_le_bytes(slice.get(1..3)?.try_into().unwrap()) as u64,
-        )),
-        0xfe => Some((
+            0xfd,
+        ),
+        0xfe => (
             4,
             u32::from_le_bytes(slice.get(1..5)?.try_into().unwrap()) as u64,
-        )),
-        0xff => Some((
+            0x1_0000,
+        ),
+        0xff => (
             8,
-            u64::from_le_bytes(slice.get(1..9)?.try_into().unwrap()) as u64,
-        )),
+            u64::from_le_bytes(slice.get(1..9)?.try_into().unwrap()),
+            0x1_0000_0000,
+        ),
+    };
+    if size < min_size {
+        return None;
+    }
+    Some((len, size))
+}
+
+pub fn compact_size_len(size: u64) -> usize {
+    match size {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
     }
 }
 
@@ -46,3 +85,105 @@ pub fn write_compact_size(bytes: &mut BytesMut, size: u64) {
         }
     }
 }
+
+/// Like [`write_compact_size`], but writing to `writer`.
+pub fn write_compact_size_to<W: Write>(writer: &mut W, size: u64) -> std::io::Result<()> {
+    match size {
+        0..=0xfc => writer.write_all(&[size as u8]),
+        0xfd..=0xffff => {
+            writer.write_all(&[0xfd])?;
+            writer.write_all(&(size as u16).to_le_bytes())
+        }
+        0x10000..=0xffff_ffff => {
+            writer.write_all(&[0xfe])?;
+            writer.write_all(&(size as u32).to_le_bytes())
+        }
+        _ => {
+            writer.write_all(&[0xff])?;
+            writer.write_all(&size.to_le_bytes())
+        }
+    }
+}
+
+pub fn write_var_bytes(bytes: &mut BytesMut, data: &[u8]) {
+    write_compact_size(bytes, data.len() as u64);
+    bytes.put_slice(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        encoding::{
+            compact_size_len, read_compact_size, read_compact_size_slice, read_var_bytes,
+            write_compact_size, write_var_bytes,
+        },
+        BitcoinSuiteError, BytesError, BytesMut,
+    };
+
+    fn verify_compact_size(size: u64, expected: &[u8]) {
+        let mut bytes = BytesMut::new();
+        write_compact_size(&mut bytes, size);
+        assert_eq!(bytes.as_slice(), expected);
+        assert_eq!(compact_size_len(size), expected.len());
+        let mut data = bytes.freeze();
+        assert_eq!(
+            read_compact_size_slice(data.as_ref()),
+            Some((expected.len().max(2) - 1, size)),
+        );
+        assert_eq!(read_compact_size(&mut data).unwrap(), size);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_compact_size() {
+        verify_compact_size(0, &[0]);
+        verify_compact_size(0xfc, &[0xfc]);
+        verify_compact_size(0xfd, &[0xfd, 0xfd, 0]);
+        verify_compact_size(0xffff, &[0xfd, 0xff, 0xff]);
+        verify_compact_size(0x1_0000, &[0xfe, 0, 0, 1, 0]);
+        verify_compact_size(0xffff_ffff, &[0xfe, 0xff, 0xff, 0xff, 0xff]);
+        verify_compact_size(0x1_0000_0000, &[0xff, 0, 0, 0, 0, 1, 0, 0, 0]);
+        verify_compact_size(u64::MAX, &[0xff; 9]);
+    }
+
+    #[test]
+    fn test_compact_size_non_canonical() {
+        for data in [
+            [0xfd, 0xfc, 0].as_ref(),
+            &[0xfd, 0, 0],
+            &[0xfe, 0xff, 0xff, 0, 0],
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0],
+        ] {
+            match read_compact_size(&mut data.into()) {
+                Err(BitcoinSuiteError::InvalidVarInt) => {}
+                result => panic!("Unexpected result: {:?}", result),
+            }
+            assert_eq!(read_compact_size_slice(data), None);
+        }
+        match read_compact_size(&mut [0xfd, 0xfd].as_ref().into()) {
+            Err(BitcoinSuiteError::Bytes(BytesError::InvalidSplit {
+                split_idx: 2,
+                len: 1,
+            })) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert_eq!(read_compact_size_slice(&[0xfd, 0xfd]), None);
+        assert_eq!(read_compact_size_slice(&[]), None);
+    }
+
+    #[test]
+    fn test_var_bytes() {
+        for size in [0, 1, 0xfc, 0xfd, 0xffff, 0x1_0000] {
+            let payload = vec![0xab; size];
+            let mut bytes = BytesMut::new();
+            write_var_bytes(&mut bytes, &payload);
+            assert_eq!(bytes.as_slice().len(), compact_size_len(size as u64) + size);
+            let mut data = bytes.freeze();
+            assert_eq!(
+                read_var_bytes(&mut data).unwrap().as_ref(),
+                payload.as_slice()
+            );
+            assert!(data.is_empty());
+        }
+    }
+}
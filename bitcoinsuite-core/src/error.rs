@@ -1,7 +1,7 @@
 use hex::FromHexError;
 use thiserror::Error;
 
-use crate::{ecc::EccError, BytesError, SignError};
+use crate::{ecc::EccError, BytesError, LockTime, OpReturnError, SignError, TxBuilderError};
 
 #[derive(Error, Debug)]
 pub enum BitcoinSuiteError {
@@ -15,16 +15,36 @@ pub enum BitcoinSuiteError {
     NumberParseError,
     #[error("Invalid VarInt")]
     InvalidVarInt,
-    #[error("Unknown network: {0}")]
+    #[error("Unknown network {0:?}, expected one of BCH, XEC, XPI or XRG")]
     UnknownNetwork(String),
+    #[error("Unknown net {0:?}, expected one of mainnet, testnet or regtest")]
+    UnknownNet(String),
     #[error("OP_CODESEPARATOR #{0} not found")]
     CodesepNotFound(usize),
     #[error("From hex error: {0}")]
     Hex(#[from] FromHexError),
     #[error("Sign error: {0}")]
     Sign(#[from] SignError),
+    #[error("TxBuilder error: {0}")]
+    TxBuilder(#[from] TxBuilderError),
     #[error("Ecc error: {0}")]
     Ecc(#[from] EccError),
+    #[error("Unknown SignField tag: {0}")]
+    UnknownSignFieldTag(u8),
+    #[error("Found {0} trailing bytes after tx")]
+    TrailingBytes(usize),
+    #[error("Relative lock time of {0} seconds exceeds the maximum of 33553920 seconds")]
+    RelativeTimeTooLarge(u32),
+    #[error("Invalid lock time: {0:?}")]
+    InvalidLockTime(LockTime),
+    #[error("OP_RETURN error: {0}")]
+    OpReturn(#[from] OpReturnError),
+    #[error("Invalid outpoint, expected <txid>:<out_idx>, got: {0}")]
+    InvalidOutPoint(String),
+    #[error("Amount of {0} sats is negative")]
+    NegativeAmount(i64),
+    #[error("Amount of {amount} sats exceeds the max money of {max_money} sats")]
+    AboveMaxMoney { amount: i64, max_money: i64 },
 }
 
 pub type Result<T> = std::result::Result<T, BitcoinSuiteError>;
@@ -0,0 +1,72 @@
+use std::fmt::Display;
+
+/// Fee rate of a tx, stored in sats per 1000 bytes, the unit of the node's relay policy.
+///
+/// Without SegWit, the virtual size of a tx is its serialized size, so a rate of 1 sat per
+/// vbyte is the same as 1000 sats per kB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FeeRate {
+    sats_per_kb: i64,
+}
+
+impl FeeRate {
+    /// Rate paying `sats` for every 1000 bytes.
+    pub const fn per_kb(sats: i64) -> Self {
+        FeeRate { sats_per_kb: sats }
+    }
+
+    /// Rate paying `sats` for every byte.
+    pub const fn per_vbyte(sats: i64) -> Self {
+        FeeRate {
+            sats_per_kb: sats.saturating_mul(1000),
+        }
+    }
+
+    pub fn sats_per_kb(&self) -> i64 {
+        self.sats_per_kb
+    }
+
+    /// Fee for a tx of `vsize` bytes, rounded down, in sats.
+    pub fn fee_for_size(&self, vsize: usize) -> i64 {
+        vsize as i64 * self.sats_per_kb / 1000
+    }
+
+    /// Like [`FeeRate::fee_for_size`], but `None` if the fee overflows.
+    pub fn checked_fee_for_size(&self, vsize: usize) -> Option<i64> {
+        (vsize as i64)
+            .checked_mul(self.sats_per_kb)
+            .map(|fee| fee / 1000)
+    }
+}
+
+impl Display for FeeRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} sats/kB", self.sats_per_kb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FeeRate;
+
+    #[test]
+    fn test_fee_rate_units() {
+        assert_ne!(FeeRate::per_kb(1), FeeRate::per_vbyte(1));
+        assert_ne!(FeeRate::per_kb(1000), FeeRate::per_vbyte(1000));
+        assert_eq!(FeeRate::per_kb(1000), FeeRate::per_vbyte(1));
+        assert_eq!(FeeRate::per_vbyte(2).sats_per_kb(), 2000);
+        assert_eq!(FeeRate::per_vbyte(i64::MAX).sats_per_kb(), i64::MAX);
+        assert_eq!(FeeRate::per_kb(1000).to_string(), "1000 sats/kB");
+    }
+
+    #[test]
+    fn test_fee_for_size() {
+        assert_eq!(FeeRate::per_kb(1000).fee_for_size(226), 226);
+        assert_eq!(FeeRate::per_vbyte(1).fee_for_size(226), 226);
+        assert_eq!(FeeRate::per_kb(1).fee_for_size(226), 0);
+        assert_eq!(FeeRate::per_kb(1500).fee_for_size(225), 337);
+        assert_eq!(FeeRate::per_vbyte(5).fee_for_size(100), 500);
+        assert_eq!(FeeRate::per_kb(1000).checked_fee_for_size(226), Some(226));
+        assert_eq!(FeeRate::per_kb(i64::MAX).checked_fee_for_size(2), None);
+    }
+}
@@ -5,6 +5,8 @@ use std::{
 };
 
 use digest::Digest;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
     byte_array::ByteArray,
@@ -66,7 +68,7 @@ pub trait Hashed: Display + Debug + Eq + PartialEq + AsRef<[u8]> + Hash + Sized
 }
 
 macro_rules! hash_algo {
-    ($NAME: ident, $SIZE: literal, $DIGEST_FN: path) => {
+    ($NAME: ident, $SIZE: literal, $DIGEST_FN: path, $TO_HEX: ident, $FROM_HEX: ident) => {
         #[derive(Clone, Eq, PartialEq, Default, Hash)]
         pub struct $NAME(ByteArray<$SIZE>);
 
@@ -107,7 +109,7 @@ macro_rules! hash_algo {
                 &self,
                 fmt: &mut std::fmt::Formatter<'_>,
             ) -> std::result::Result<(), std::fmt::Error> {
-                write!(fmt, "{}({})", stringify!($NAME), self.to_hex_be())
+                write!(fmt, "{}({})", stringify!($NAME), self.$TO_HEX())
             }
         }
 
@@ -116,7 +118,36 @@ macro_rules! hash_algo {
                 &self,
                 fmt: &mut std::fmt::Formatter<'_>,
             ) -> std::result::Result<(), std::fmt::Error> {
-                write!(fmt, "{}", self.to_hex_be())
+                write!(fmt, "{}", self.$TO_HEX())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl Serialize for $NAME {
+            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                if serializer.is_human_readable() {
+                    self.$TO_HEX().serialize(serializer)
+                } else {
+                    self.0.serialize(serializer)
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> Deserialize<'de> for $NAME {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                if deserializer.is_human_readable() {
+                    let hex = String::deserialize(deserializer)?;
+                    Self::$FROM_HEX(&hex).map_err(serde::de::Error::custom)
+                } else {
+                    Ok($NAME(ByteArray::deserialize(deserializer)?))
+                }
             }
         }
 
@@ -143,6 +174,10 @@ macro_rules! hash_algo {
                 self.0.ser_to(bytes)
             }
 
+            fn serialize_to<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+                self.0.serialize_to(writer)
+            }
+
             fn deser(data: &mut Bytes) -> Result<Self>
             where
                 Self: Sized,
@@ -173,14 +208,220 @@ macro_rules! hash_algo {
     };
 }
 
-hash_algo!(Sha1, 20, sha1::Sha1::digest);
-hash_algo!(Ripemd160, 20, ripemd::Ripemd160::digest);
-hash_algo!(Sha256, 32, sha2::Sha256::digest);
+hash_algo!(Sha1, 20, sha1::Sha1::digest, to_hex_be, from_hex_be);
+hash_algo!(
+    Ripemd160,
+    20,
+    ripemd::Ripemd160::digest,
+    to_hex_be,
+    from_hex_be
+);
+hash_algo!(Sha256, 32, sha2::Sha256::digest, to_hex_be, from_hex_be);
 fn sha256d(data: &[u8]) -> [u8; 32] {
     sha2::Sha256::digest(&sha2::Sha256::digest(data)).into()
 }
-hash_algo!(Sha256d, 32, sha256d);
+// Displayed like in block explorers, see Sha256d::to_hex_display
+hash_algo!(Sha256d, 32, sha256d, to_hex_display, from_hex_display);
 fn sha_rmd160(data: &[u8]) -> [u8; 20] {
     ripemd::Ripemd160::digest(&sha2::Sha256::digest(data)).into()
 }
-hash_algo!(ShaRmd160, 20, sha_rmd160);
+// Displayed like in scripts, see ShaRmd160::to_hex_display
+hash_algo!(ShaRmd160, 20, sha_rmd160, to_hex_display, from_hex_display);
+
+impl Sha256d {
+    /// Finishes the double hash from the intermediate single SHA-256 hash.
+    pub fn from_sha256(sha256: &Sha256) -> Self {
+        Sha256d::new(sha2::Sha256::digest(sha256.as_slice()).into())
+    }
+
+    /// Parses a hash in display order, i.e. byte-reversed, as shown by block explorers and the
+    /// node's RPC. Same as [`Hashed::from_hex_be`]; [`Hashed::from_hex`] parses in byte order.
+    pub fn from_hex_display(hex: &str) -> Result<Self> {
+        Self::from_hex_be(hex)
+    }
+
+    /// Hex in display order, i.e. byte-reversed, as shown by block explorers and the node's RPC.
+    pub fn to_hex_display(&self) -> String {
+        self.to_hex_be()
+    }
+}
+
+/// Incrementally computes a [`Sha256d`], so large data doesn't have to be held in one buffer.
+#[derive(Clone, Default)]
+pub struct Sha256dHasher {
+    hasher: sha2::Sha256,
+}
+
+impl Sha256dHasher {
+    pub fn new() -> Self {
+        Sha256dHasher::default()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    pub fn finalize(self) -> Sha256d {
+        Sha256d::new(sha2::Sha256::digest(&self.hasher.finalize()).into())
+    }
+}
+
+impl ShaRmd160 {
+    /// Finishes the RIPEMD-160 of the intermediate SHA-256 hash.
+    pub fn from_sha256(sha256: &Sha256) -> Self {
+        ShaRmd160::new(ripemd::Ripemd160::digest(sha256.as_slice()).into())
+    }
+
+    /// Parses a hash in display order, which for this hash is byte order, as it appears in
+    /// scripts and addresses. Same as [`Hashed::from_hex`].
+    pub fn from_hex_display(hex: &str) -> Result<Self> {
+        <Self as Hashed>::from_hex(hex)
+    }
+
+    /// Hex in display order, which for this hash is byte order, as it appears in scripts and
+    /// addresses.
+    pub fn to_hex_display(&self) -> String {
+        hex::encode(self.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BitcoinSuiteError, Bytes, Hashed, Ripemd160, Sha1, Sha256, Sha256d, Sha256dHasher,
+        ShaRmd160,
+    };
+
+    #[test]
+    fn test_sha256d_hasher() {
+        let data = (0..10_000u32)
+            .map(|i| (i * 7 % 256) as u8)
+            .collect::<Vec<_>>();
+        let expected = Sha256d::digest(Bytes::from_slice(&data));
+        for chunk_sizes in [&[1][..], &[3, 64, 1000], &[63, 65, 0, 10_000]] {
+            let mut hasher = Sha256dHasher::new();
+            let mut remaining = data.as_slice();
+            for &chunk_size in chunk_sizes.iter().cycle() {
+                if remaining.is_empty() {
+                    break;
+                }
+                let (chunk, rest) = remaining.split_at(chunk_size.min(remaining.len()));
+                hasher.update(chunk);
+                remaining = rest;
+            }
+            assert_eq!(hasher.finalize(), expected);
+        }
+        assert_eq!(
+            Sha256dHasher::new().finalize(),
+            Sha256d::digest(Bytes::new())
+        );
+    }
+
+    #[test]
+    fn test_digest_vectors() -> Result<(), BitcoinSuiteError> {
+        let empty = Bytes::new();
+        let abc = Bytes::from_slice(b"abc");
+        assert_eq!(
+            <Sha256 as Hashed>::from_hex(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            )?,
+            Sha256::digest(empty.clone()),
+        );
+        assert_eq!(
+            <Sha256 as Hashed>::from_hex(
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            )?,
+            Sha256::digest(abc.clone()),
+        );
+        assert_eq!(
+            <Ripemd160 as Hashed>::from_hex("9c1185a5c5e9fc54612808977ee8f548b2258d31")?,
+            Ripemd160::digest(empty),
+        );
+        assert_eq!(
+            <Ripemd160 as Hashed>::from_hex("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")?,
+            Ripemd160::digest(abc.clone()),
+        );
+        assert_eq!(
+            <Sha1 as Hashed>::from_hex("a9993e364706816aba3e25717850c26c9cd0d89d")?,
+            Sha1::digest(abc.clone()),
+        );
+
+        let sha256 = Sha256::digest(abc.clone());
+        let sha256d = <Sha256d as Hashed>::from_hex(
+            "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358",
+        )?;
+        assert_eq!(Sha256d::digest(abc.clone()), sha256d);
+        assert_eq!(Sha256d::from_sha256(&sha256), sha256d);
+        assert_eq!(
+            Sha256d::digest(abc.clone()).as_slice(),
+            Sha256::digest(Bytes::from_slice(sha256.as_slice())).as_slice(),
+        );
+
+        let sha_rmd160 = ShaRmd160::from_hex("bb1be98c142444d7a56aa3981c3942a978e4dc33")?;
+        assert_eq!(ShaRmd160::digest(abc), sha_rmd160);
+        assert_eq!(ShaRmd160::from_sha256(&sha256), sha_rmd160);
+        assert_eq!(
+            sha_rmd160.as_slice(),
+            Ripemd160::digest(Bytes::from_slice(sha256.as_slice())).as_slice(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256d_hex() -> Result<(), BitcoinSuiteError> {
+        // Genesis block coinbase txid
+        let txid_hex = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+        let txid = Sha256d::from_hex_display(txid_hex)?;
+        assert_eq!(txid.as_slice()[0], 0x3b);
+        assert_eq!(txid.as_slice()[31], 0x4a);
+        assert_eq!(txid.to_hex_display(), txid_hex);
+        assert_eq!(txid.to_string(), txid_hex);
+        assert_eq!(format!("{:?}", txid), format!("Sha256d({})", txid_hex));
+        assert_eq!(txid, Sha256d::from_hex_be(txid_hex)?);
+        // Hashed::from_hex isn't shadowed and parses in byte order
+        assert_eq!(
+            <Sha256d as Hashed>::from_hex(txid_hex)?.to_hex_be(),
+            "3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a",
+        );
+        assert!(Sha256d::from_hex_display("4a5e").is_err());
+        assert!(Sha256d::from_hex_display("xx").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_hash_serde() -> Result<(), BitcoinSuiteError> {
+        let txid_hex = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+        let txid = Sha256d::from_hex_display(txid_hex)?;
+        let json = serde_json::to_string(&txid).unwrap();
+        assert_eq!(json, format!("\"{}\"", txid_hex));
+        assert_eq!(serde_json::from_str::<Sha256d>(&json).unwrap(), txid);
+        let encoded = bincode::serialize(&txid).unwrap();
+        assert_eq!(bincode::deserialize::<Sha256d>(&encoded).unwrap(), txid);
+
+        let hash_hex = "da1745e9b549bd0bfa1a569971c77eba30cd5a4b";
+        let hash = ShaRmd160::from_hex_display(hash_hex)?;
+        let json = serde_json::to_string(&hash).unwrap();
+        assert_eq!(json, format!("\"{}\"", hash_hex));
+        assert_eq!(serde_json::from_str::<ShaRmd160>(&json).unwrap(), hash);
+        let encoded = bincode::serialize(&hash).unwrap();
+        assert_eq!(bincode::deserialize::<ShaRmd160>(&encoded).unwrap(), hash);
+
+        assert!(serde_json::from_str::<Sha256d>("\"4a5e\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha_rmd160_hex() -> Result<(), BitcoinSuiteError> {
+        let hash_hex = "da1745e9b549bd0bfa1a569971c77eba30cd5a4b";
+        let hash = ShaRmd160::from_hex_display(hash_hex)?;
+        assert_eq!(hash.as_slice()[0], 0xda);
+        assert_eq!(hash.as_slice()[19], 0x4b);
+        assert_eq!(hash.to_hex_display(), hash_hex);
+        assert_eq!(hash.to_string(), hash_hex);
+        assert_eq!(format!("{:?}", hash), format!("ShaRmd160({})", hash_hex));
+        assert_eq!(hash, <ShaRmd160 as Hashed>::from_hex(hash_hex)?);
+        assert!(ShaRmd160::from_hex_display("da17").is_err());
+        Ok(())
+    }
+}
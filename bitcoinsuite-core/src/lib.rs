@@ -9,12 +9,16 @@ pub mod compression;
 pub mod ecc;
 pub mod encoding;
 mod error;
+mod fee_rate;
 mod hash;
+mod lock_time;
 mod merkle;
 mod network;
 mod op;
 pub mod opcode;
+mod satoshi;
 mod script;
+mod script_builder;
 mod sequence;
 mod sighashtype;
 mod sign;
@@ -29,11 +33,15 @@ pub use crate::byte_array::*;
 pub use crate::bytes::*;
 pub use crate::bytes_mut::*;
 pub use crate::error::*;
+pub use crate::fee_rate::*;
 pub use crate::hash::*;
+pub use crate::lock_time::*;
 pub use crate::merkle::*;
 pub use crate::network::*;
 pub use crate::op::*;
+pub use crate::satoshi::*;
 pub use crate::script::*;
+pub use crate::script_builder::*;
 pub use crate::sequence::*;
 pub use crate::sighashtype::*;
 pub use crate::sign::*;
@@ -0,0 +1,68 @@
+use crate::BitcoinSuiteError;
+
+/// Lock times below this are interpreted as block heights, others as unix timestamps.
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+/// Absolute lock time of a tx, i.e. the tx can only be mined in a block after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockTime {
+    /// Block height, must be below [`LOCKTIME_THRESHOLD`].
+    Height(u32),
+    /// Unix timestamp in seconds, compared against the median time past; must be at least
+    /// [`LOCKTIME_THRESHOLD`].
+    Time(u32),
+}
+
+impl LockTime {
+    pub fn from_u32(lock_time: u32) -> Self {
+        match lock_time < LOCKTIME_THRESHOLD {
+            true => LockTime::Height(lock_time),
+            false => LockTime::Time(lock_time),
+        }
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        match *self {
+            LockTime::Height(height) => height,
+            LockTime::Time(time) => time,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), BitcoinSuiteError> {
+        match *self {
+            LockTime::Height(height) if height >= LOCKTIME_THRESHOLD => {
+                Err(BitcoinSuiteError::InvalidLockTime(*self))
+            }
+            LockTime::Time(time) if time < LOCKTIME_THRESHOLD => {
+                Err(BitcoinSuiteError::InvalidLockTime(*self))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitcoinSuiteError, LockTime};
+
+    #[test]
+    fn test_lock_time() {
+        assert_eq!(LockTime::from_u32(0), LockTime::Height(0));
+        assert_eq!(
+            LockTime::from_u32(499_999_999),
+            LockTime::Height(499_999_999)
+        );
+        assert_eq!(LockTime::from_u32(500_000_000), LockTime::Time(500_000_000));
+        assert_eq!(LockTime::from_u32(1_650_000_000).as_u32(), 1_650_000_000);
+        assert!(LockTime::Height(700_000).validate().is_ok());
+        assert!(LockTime::Time(1_650_000_000).validate().is_ok());
+        match LockTime::Height(500_000_000).validate() {
+            Err(BitcoinSuiteError::InvalidLockTime(LockTime::Height(500_000_000))) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        match LockTime::Time(700_000).validate() {
+            Err(BitcoinSuiteError::InvalidLockTime(LockTime::Time(700_000))) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+}
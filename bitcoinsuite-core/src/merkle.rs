@@ -1,4 +1,4 @@
-use crate::{BytesMut, Hashed};
+use crate::{BytesMut, Hashed, Sha256d};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum MerkleMode {
@@ -10,6 +10,57 @@ pub fn get_merkle_root<H: Hashed + Clone>(leaves: Vec<H>, mode: MerkleMode) -> H
     get_merkle_root_and_height(leaves, mode).0
 }
 
+/// Merkle root of the txids of a block, using Bitcoin's rule of duplicating the last hash of
+/// layers with an odd number of hashes.
+pub fn merkle_root(txids: &[Sha256d]) -> Sha256d {
+    get_merkle_root(txids.to_vec(), MerkleMode::Bitcoin)
+}
+
+/// Hashes required to prove the txid at `index` is part of the merkle root, from the bottom
+/// layer to the top. Panics if `index` is out of bounds.
+pub fn merkle_branch(txids: &[Sha256d], mut index: usize) -> Vec<Sha256d> {
+    assert!(index < txids.len(), "Merkle branch index out of bounds");
+    let mut branch = Vec::new();
+    let mut layer = txids.to_vec();
+    while layer.len() > 1 {
+        if layer.len() % 2 == 1 {
+            layer.push(layer.last().unwrap().clone());
+        }
+        branch.push(layer[index ^ 1].clone());
+        layer = layer
+            .chunks_exact(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        index /= 2;
+    }
+    branch
+}
+
+/// Verifies a branch built by [`merkle_branch`] links `txid` at `index` to `merkle_root`.
+pub fn verify_merkle_branch(
+    txid: &Sha256d,
+    mut index: usize,
+    branch: &[Sha256d],
+    merkle_root: &Sha256d,
+) -> bool {
+    let mut hash = txid.clone();
+    for sibling in branch {
+        hash = match index % 2 {
+            0 => hash_pair(&hash, sibling),
+            _ => hash_pair(sibling, &hash),
+        };
+        index /= 2;
+    }
+    index == 0 && &hash == merkle_root
+}
+
+fn hash_pair<H: Hashed>(left: &H, right: &H) -> H {
+    let mut bytes = BytesMut::new();
+    bytes.put_slice(left.as_slice());
+    bytes.put_slice(right.as_slice());
+    H::digest(bytes.freeze())
+}
+
 pub fn get_merkle_root_and_height<H: Hashed + Clone>(
     mut leaves: Vec<H>,
     mode: MerkleMode,
@@ -30,12 +81,73 @@ pub fn get_merkle_root_and_height<H: Hashed + Clone>(
         }
         let mut next_layer = Vec::new();
         for pair in leaves.chunks_exact(2) {
-            let mut bytes = BytesMut::new();
-            bytes.put_slice(pair[0].as_slice());
-            bytes.put_slice(pair[1].as_slice());
-            next_layer.push(H::digest(bytes.freeze()));
+            next_layer.push(hash_pair(&pair[0], &pair[1]));
         }
         leaves = next_layer;
     }
     (leaves.remove(0), height)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{merkle_branch, merkle_root, verify_merkle_branch, BytesMut, Hashed, Sha256d};
+
+    fn hash_pair(left: &Sha256d, right: &Sha256d) -> Sha256d {
+        let mut bytes = BytesMut::new();
+        bytes.put_slice(left.as_slice());
+        bytes.put_slice(right.as_slice());
+        Sha256d::digest(bytes.freeze())
+    }
+
+    #[test]
+    fn test_merkle_single_tx() {
+        let txid = Sha256d::new([1; 32]);
+        assert_eq!(merkle_root(&[txid.clone()]), txid);
+        assert_eq!(merkle_branch(&[txid.clone()], 0), vec![]);
+        assert!(verify_merkle_branch(&txid, 0, &[], &txid));
+        assert!(!verify_merkle_branch(&txid, 1, &[], &txid));
+    }
+
+    #[test]
+    fn test_merkle_odd_count() {
+        let txids = (1..=3).map(|i| Sha256d::new([i; 32])).collect::<Vec<_>>();
+        let left = hash_pair(&txids[0], &txids[1]);
+        let right = hash_pair(&txids[2], &txids[2]);
+        let root = hash_pair(&left, &right);
+        assert_eq!(merkle_root(&txids), root);
+
+        assert_eq!(
+            merkle_branch(&txids, 0),
+            vec![txids[1].clone(), right.clone()]
+        );
+        assert_eq!(merkle_branch(&txids, 1), vec![txids[0].clone(), right]);
+        assert_eq!(merkle_branch(&txids, 2), vec![txids[2].clone(), left]);
+        for (index, txid) in txids.iter().enumerate() {
+            let branch = merkle_branch(&txids, index);
+            assert!(verify_merkle_branch(txid, index, &branch, &root));
+            assert!(!verify_merkle_branch(
+                &Sha256d::new([9; 32]),
+                index,
+                &branch,
+                &root
+            ));
+        }
+        // Wrong position
+        let branch = merkle_branch(&txids, 0);
+        assert!(!verify_merkle_branch(&txids[0], 1, &branch, &root));
+    }
+
+    #[test]
+    fn test_merkle_many_txs() {
+        for num_txs in 1..20u8 {
+            let txids = (0..num_txs)
+                .map(|i| Sha256d::new([i; 32]))
+                .collect::<Vec<_>>();
+            let root = merkle_root(&txids);
+            for (index, txid) in txids.iter().enumerate() {
+                let branch = merkle_branch(&txids, index);
+                assert!(verify_merkle_branch(txid, index, &branch, &root));
+            }
+        }
+    }
+}
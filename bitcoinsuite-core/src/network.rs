@@ -2,7 +2,10 @@ use std::{fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use crate::BitcoinSuiteError;
+use crate::{
+    BitcoinSuiteError, Script, BCHREG, BCHTEST, BITCOINCASH, DEFAULT_MAX_OP_RETURN_BYTES, ECASH,
+    ECREGTEST, ECTEST, LOTUS_PREFIX,
+};
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Network {
@@ -16,10 +19,68 @@ pub enum Network {
 #[serde(rename_all = "camelCase")]
 pub enum Net {
     Mainnet,
+    Testnet,
     Regtest,
 }
 
+/// Relay policy and consensus constants of a network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NetworkParams {
+    /// Minimum value of an output to be relayed, in sats.
+    pub dust_amount: i64,
+    /// Default minimum fee rate for a tx to be relayed, in sats per 1000 bytes.
+    pub min_relay_fee_per_kb: i64,
+    /// Maximum size of an OP_RETURN output script to be relayed, in bytes.
+    pub max_op_return_bytes: usize,
+    /// Whether txs with more than one OP_RETURN output are relayed; if so, the sizes of all
+    /// OP_RETURN scripts add up towards `max_op_return_bytes`.
+    pub multiple_op_returns: bool,
+    /// Maximum number of sats that can ever exist, `None` for networks without a cap.
+    pub max_money: Option<i64>,
+    /// Prefix of addresses on this network, e.g. "ecash".
+    pub address_prefix: &'static str,
+}
+
 impl Network {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::BCH => "BCH",
+            Network::XEC => "XEC",
+            Network::XPI => "XPI",
+            Network::XRG => "XRG",
+        }
+    }
+
+    pub fn params(&self, net: Net) -> NetworkParams {
+        NetworkParams {
+            dust_amount: self.dust_amount(),
+            min_relay_fee_per_kb: self.min_relay_fee_per_kb(),
+            max_op_return_bytes: DEFAULT_MAX_OP_RETURN_BYTES,
+            multiple_op_returns: match self {
+                Network::BCH | Network::XEC => true,
+                Network::XPI | Network::XRG => false,
+            },
+            max_money: match self {
+                Network::BCH => Some(21_000_000 * 100_000_000),
+                Network::XEC => Some(21_000_000_000_000 * 100),
+                Network::XPI | Network::XRG => None,
+            },
+            address_prefix: match (self, net) {
+                (Network::BCH, Net::Mainnet) => BITCOINCASH,
+                (Network::BCH, Net::Testnet) => BCHTEST,
+                (Network::BCH, Net::Regtest) => BCHREG,
+                (Network::XEC, Net::Mainnet) => ECASH,
+                (Network::XEC, Net::Testnet) => ECTEST,
+                (Network::XEC, Net::Regtest) => ECREGTEST,
+                // Lotus encodes the net separately from the prefix
+                (Network::XPI, _) => LOTUS_PREFIX,
+                (Network::XRG, Net::Mainnet) => "ergon",
+                (Network::XRG, Net::Testnet) => "ergtest",
+                (Network::XRG, Net::Regtest) => "ergreg",
+            },
+        }
+    }
+
     pub fn dust_amount(&self) -> i64 {
         match self {
             Network::XRG => 2,
@@ -27,6 +88,26 @@ impl Network {
         }
     }
 
+    /// Dust threshold of an output with the given script, i.e. the value below which spending it
+    /// would cost more than a third of its value at the min relay fee. Unspendable outputs, see
+    /// [`Script::is_unspendable`], have no threshold.
+    pub fn dust_amount_for_script(&self, script: &Script) -> i64 {
+        // Size of the input spending a P2PKH output, which is assumed for all outputs
+        const SPEND_INPUT_SIZE: usize = 148;
+        match self {
+            Network::XRG => self.dust_amount(),
+            _ if script.is_unspendable() => 0,
+            _ => {
+                let output_size = 8 + script.ser_size();
+                3 * (output_size + SPEND_INPUT_SIZE) as i64 * self.min_relay_fee_per_kb() / 1000
+            }
+        }
+    }
+
+    pub fn min_relay_fee_per_kb(&self) -> i64 {
+        1000
+    }
+
     pub fn coin_decimals(&self) -> u32 {
         match self {
             Network::XEC => 2,
@@ -43,29 +124,142 @@ impl Network {
     }
 }
 
+/// Minimum fee a node relays a tx of `tx_size` bytes with, in sats.
+pub fn min_relay_fee(tx_size: usize, network: &Network) -> i64 {
+    tx_size as i64 * network.min_relay_fee_per_kb() / 1000
+}
+
+impl Net {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Net::Mainnet => "mainnet",
+            Net::Testnet => "testnet",
+            Net::Regtest => "regtest",
+        }
+    }
+
+    /// Prefix of eCash addresses on this net, e.g. "ectest" for testnet.
+    /// For other networks, see [`NetworkParams::address_prefix`].
+    pub fn cashaddr_prefix(&self) -> &'static str {
+        match self {
+            Net::Mainnet => ECASH,
+            Net::Testnet => ECTEST,
+            Net::Regtest => ECREGTEST,
+        }
+    }
+}
+
 impl Display for Network {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        f.write_str(self.as_str())
+    }
+}
+
+impl Display for Net {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
 impl FromStr for Network {
     type Err = BitcoinSuiteError;
 
+    /// Parses the ticker or the name of the network, ignoring case, e.g. "XEC" or "ecash".
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "BCH" => Ok(Network::BCH),
-            "XEC" => Ok(Network::XEC),
-            "XPI" => Ok(Network::XPI),
-            "XRG" => Ok(Network::XRG),
+        match s.to_ascii_lowercase().as_str() {
+            "bch" | "bitcoincash" => Ok(Network::BCH),
+            "xec" | "ecash" => Ok(Network::XEC),
+            "xpi" | "lotus" => Ok(Network::XPI),
+            "xrg" | "ergon" => Ok(Network::XRG),
             _ => Err(BitcoinSuiteError::UnknownNetwork(s.to_string())),
         }
     }
 }
 
+impl FromStr for Net {
+    type Err = BitcoinSuiteError;
+
+    /// Parses e.g. "mainnet" or "main", ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" | "main" => Ok(Net::Mainnet),
+            "testnet" | "test" => Ok(Net::Testnet),
+            "regtest" | "reg" => Ok(Net::Regtest),
+            _ => Err(BitcoinSuiteError::UnknownNet(s.to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{BitcoinSuiteError, Network};
+    use crate::{min_relay_fee, BitcoinSuiteError, Net, Network, NetworkParams, Script, ShaRmd160};
+
+    #[test]
+    fn test_dust_amount_for_script() {
+        let p2pkh = Script::p2pkh(&ShaRmd160::new([0; 20]));
+        let p2sh = Script::p2sh(&ShaRmd160::new([0; 20]));
+        let multisig = Script::multisig(1, [[2; 33].as_ref(), [3; 33].as_ref()]);
+        assert_eq!(Network::XEC.dust_amount_for_script(&p2pkh), 546);
+        assert_eq!(Network::XEC.dust_amount_for_script(&p2sh), 540);
+        assert_eq!(Network::XEC.dust_amount_for_script(&multisig), 684);
+        assert!(
+            Network::XEC.dust_amount_for_script(&multisig)
+                > Network::XEC.dust_amount_for_script(&p2pkh)
+        );
+        assert_eq!(
+            Network::XEC.dust_amount_for_script(&Script::opreturn(&[b"hello"])),
+            0,
+        );
+        assert_eq!(Network::XRG.dust_amount_for_script(&multisig), 2);
+    }
+
+    #[test]
+    fn test_min_relay_fee() {
+        assert_eq!(min_relay_fee(0, &Network::XEC), 0);
+        assert_eq!(min_relay_fee(219, &Network::XEC), 219);
+        assert_eq!(min_relay_fee(219, &Network::BCH), 219);
+    }
+
+    #[test]
+    fn test_params() {
+        let xec_mainnet = NetworkParams {
+            dust_amount: 546,
+            min_relay_fee_per_kb: 1000,
+            max_op_return_bytes: 223,
+            multiple_op_returns: true,
+            max_money: Some(2_100_000_000_000_000),
+            address_prefix: "ecash",
+        };
+        assert_eq!(Network::XEC.params(Net::Mainnet), xec_mainnet);
+        assert_eq!(
+            Network::XEC.params(Net::Testnet),
+            NetworkParams {
+                address_prefix: "ectest",
+                ..xec_mainnet
+            },
+        );
+        assert_eq!(
+            Network::XEC.params(Net::Regtest),
+            NetworkParams {
+                address_prefix: "ecregtest",
+                ..xec_mainnet
+            },
+        );
+        assert_eq!(
+            Network::BCH.params(Net::Mainnet).max_money,
+            Some(2_100_000_000_000_000),
+        );
+        assert_eq!(Network::XPI.params(Net::Regtest).address_prefix, "lotus");
+        assert_eq!(Network::XPI.params(Net::Mainnet).max_money, None);
+        assert_eq!(Network::XRG.params(Net::Mainnet).dust_amount, 2);
+        assert!(!Network::XPI.params(Net::Mainnet).multiple_op_returns);
+        for net in [Net::Mainnet, Net::Testnet, Net::Regtest] {
+            assert_eq!(
+                net.cashaddr_prefix(),
+                Network::XEC.params(net).address_prefix,
+            );
+        }
+    }
 
     #[test]
     fn test_display() {
@@ -73,6 +267,7 @@ mod tests {
         assert_eq!(Network::XEC.to_string(), "XEC");
         assert_eq!(Network::XPI.to_string(), "XPI");
         assert_eq!(Network::XRG.to_string(), "XRG");
+        assert_eq!(Network::XEC.as_str(), "XEC");
     }
 
     #[test]
@@ -81,10 +276,57 @@ mod tests {
         assert_eq!("XEC".parse::<Network>()?, Network::XEC);
         assert_eq!("XPI".parse::<Network>()?, Network::XPI);
         assert_eq!("XRG".parse::<Network>()?, Network::XRG);
-        match "bch".parse::<Network>() {
-            Err(BitcoinSuiteError::UnknownNetwork(s)) => assert_eq!(s, "bch"),
-            _ => panic!("Unexpected parse result"),
+        for (s, network) in [
+            ("bch", Network::BCH),
+            ("BitcoinCash", Network::BCH),
+            ("xec", Network::XEC),
+            ("eCash", Network::XEC),
+            ("Xpi", Network::XPI),
+            ("lotus", Network::XPI),
+            ("xrg", Network::XRG),
+            ("ERGON", Network::XRG),
+        ] {
+            assert_eq!(s.parse::<Network>()?, network);
+        }
+        for s in ["", "btc", "XEC ", "ecashh"] {
+            match s.parse::<Network>() {
+                Err(BitcoinSuiteError::UnknownNetwork(unknown)) => assert_eq!(unknown, s),
+                _ => panic!("Unexpected parse result"),
+            }
+        }
+        assert_eq!(
+            BitcoinSuiteError::UnknownNetwork("btc".to_string()).to_string(),
+            "Unknown network \"btc\", expected one of BCH, XEC, XPI or XRG",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_net() -> Result<(), Box<dyn std::error::Error>> {
+        for (s, net) in [
+            ("mainnet", Net::Mainnet),
+            ("Main", Net::Mainnet),
+            ("testnet", Net::Testnet),
+            ("TEST", Net::Testnet),
+            ("Regtest", Net::Regtest),
+            ("reg", Net::Regtest),
+        ] {
+            assert_eq!(s.parse::<Net>()?, net);
+        }
+        for net in [Net::Mainnet, Net::Testnet, Net::Regtest] {
+            assert_eq!(net.as_str().parse::<Net>()?, net);
+            assert_eq!(net.to_string(), net.as_str());
+        }
+        for s in ["", "mainnet2", "signet", "re gtest"] {
+            match s.parse::<Net>() {
+                Err(BitcoinSuiteError::UnknownNet(unknown)) => assert_eq!(unknown, s),
+                _ => panic!("Unexpected parse result"),
+            }
         }
+        assert_eq!(
+            BitcoinSuiteError::UnknownNet("signet".to_string()).to_string(),
+            "Unknown net \"signet\", expected one of mainnet, testnet or regtest",
+        );
         Ok(())
     }
 }
@@ -0,0 +1,152 @@
+use std::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{BitcoinSuiteError, NetworkParams};
+
+/// Amount of sats, with arithmetic that fails on overflow instead of wrapping or panicking.
+///
+/// Intermediate amounts, e.g. a balance minus fees, may be negative; use
+/// [`Satoshi::check_output_value`] before putting an amount into an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(transparent))]
+pub struct Satoshi(i64);
+
+impl Satoshi {
+    pub const ZERO: Satoshi = Satoshi(0);
+
+    pub const fn new(sats: i64) -> Self {
+        Satoshi(sats)
+    }
+
+    pub const fn as_i64(&self) -> i64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Satoshi) -> Option<Satoshi> {
+        self.0.checked_add(other.0).map(Satoshi)
+    }
+
+    pub fn checked_sub(self, other: Satoshi) -> Option<Satoshi> {
+        self.0.checked_sub(other.0).map(Satoshi)
+    }
+
+    pub fn checked_mul(self, factor: i64) -> Option<Satoshi> {
+        self.0.checked_mul(factor).map(Satoshi)
+    }
+
+    /// Sum of `amounts`, or `None` if it overflows.
+    pub fn checked_sum(amounts: impl IntoIterator<Item = Satoshi>) -> Option<Satoshi> {
+        amounts
+            .into_iter()
+            .try_fold(Satoshi::ZERO, |sum, amount| sum.checked_add(amount))
+    }
+
+    /// Checks that the amount is not negative, the minimum requirement for an output value.
+    pub fn check_non_negative(self) -> Result<Satoshi, BitcoinSuiteError> {
+        if self.0 < 0 {
+            return Err(BitcoinSuiteError::NegativeAmount(self.0));
+        }
+        Ok(self)
+    }
+
+    /// Checks that the amount is valid as the value of an output on a network with `params`,
+    /// i.e. it is not negative and doesn't exceed the network's max money, if any.
+    pub fn check_output_value(self, params: &NetworkParams) -> Result<Satoshi, BitcoinSuiteError> {
+        self.check_non_negative()?;
+        if let Some(max_money) = params.max_money {
+            if self.0 > max_money {
+                return Err(BitcoinSuiteError::AboveMaxMoney {
+                    amount: self.0,
+                    max_money,
+                });
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl From<i64> for Satoshi {
+    fn from(sats: i64) -> Self {
+        Satoshi(sats)
+    }
+}
+
+impl From<Satoshi> for i64 {
+    fn from(amount: Satoshi) -> Self {
+        amount.0
+    }
+}
+
+impl Display for Satoshi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} sats", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitcoinSuiteError, Net, Network, Satoshi};
+
+    #[test]
+    fn test_satoshi_checked_arithmetic() {
+        let amount = Satoshi::new(10_000);
+        assert_eq!(
+            amount.checked_add(Satoshi::new(5)),
+            Some(Satoshi::new(10_005))
+        );
+        assert_eq!(
+            amount.checked_sub(Satoshi::new(20_000)),
+            Some(Satoshi::new(-10_000))
+        );
+        assert_eq!(amount.checked_mul(3), Some(Satoshi::new(30_000)));
+        assert_eq!(Satoshi::new(i64::MAX).checked_add(Satoshi::new(1)), None);
+        assert_eq!(Satoshi::new(i64::MIN).checked_sub(Satoshi::new(1)), None);
+        assert_eq!(Satoshi::new(i64::MAX / 2 + 1).checked_mul(2), None);
+        assert_eq!(
+            Satoshi::checked_sum([Satoshi::new(1), Satoshi::new(2)]),
+            Some(Satoshi::new(3)),
+        );
+        assert_eq!(
+            Satoshi::checked_sum([Satoshi::new(i64::MAX), Satoshi::new(1)]),
+            None,
+        );
+        assert_eq!(i64::from(Satoshi::from(546)), 546);
+        assert_eq!(Satoshi::new(546).to_string(), "546 sats");
+    }
+
+    #[test]
+    fn test_satoshi_check_output_value() {
+        let xec = Network::XEC.params(Net::Mainnet);
+        let max_money = xec.max_money.unwrap();
+        assert_eq!(
+            Satoshi::ZERO.check_output_value(&xec).unwrap(),
+            Satoshi::ZERO,
+        );
+        assert_eq!(
+            Satoshi::new(max_money).check_output_value(&xec).unwrap(),
+            Satoshi::new(max_money),
+        );
+        match Satoshi::new(-1).check_output_value(&xec) {
+            Err(BitcoinSuiteError::NegativeAmount(-1)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        match Satoshi::new(max_money + 1).check_output_value(&xec) {
+            Err(BitcoinSuiteError::AboveMaxMoney {
+                amount,
+                max_money: actual_max_money,
+            }) => {
+                assert_eq!(amount, max_money + 1);
+                assert_eq!(actual_max_money, max_money);
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        // Lotus has no max money
+        let xpi = Network::XPI.params(Net::Mainnet);
+        assert!(Satoshi::new(i64::MAX).check_output_value(&xpi).is_ok());
+        assert!(Satoshi::new(-1).check_output_value(&xpi).is_err());
+        assert!(Satoshi::new(i64::MAX).check_non_negative().is_ok());
+        assert!(Satoshi::new(-1).check_non_negative().is_err());
+    }
+}
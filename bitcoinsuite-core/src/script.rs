@@ -1,11 +1,22 @@
 use crate::{
     bytes::Bytes,
     ecc::{PubKey, PUBKEY_LENGTH},
+    encoding::compact_size_len,
     opcode::*,
     BitcoinCode, BitcoinSuiteError, BytesError, BytesMut, Hashed, Op, Result, ShaRmd160,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+/// Maximum size of an OP_RETURN output script (including the OP_RETURN opcode) that is still
+/// relayed by default.
+pub const DEFAULT_MAX_OP_RETURN_BYTES: usize = 223;
+
+/// Maximum size of a script the node executes; larger output scripts can never be spent.
+pub const MAX_SCRIPT_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
 pub struct Script {
     bytecode: Bytes,
 }
@@ -20,6 +31,14 @@ pub enum ScriptVariant {
     Other(Script),
 }
 
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpReturnError {
+    #[error("OP_RETURN script has {size} bytes, exceeding the limit of {max_bytes} bytes")]
+    TooLarge { size: usize, max_bytes: usize },
+    #[error("OP_RETURN push #{idx} has {size} bytes, but at most 255 are supported")]
+    PushTooLarge { idx: usize, size: usize },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
 pub struct ScriptOpIter {
     remaining_bytecode: Bytes,
@@ -30,6 +49,10 @@ impl BitcoinCode for Script {
         self.bytecode.ser_to(bytes)
     }
 
+    fn serialize_to<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.bytecode.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> crate::Result<Self> {
         Ok(Script {
             bytecode: Bytes::deser(data)?,
@@ -78,6 +101,31 @@ impl Script {
         }
     }
 
+    /// Like [`Script::opreturn`], but fails if the script wouldn't be relayed by default.
+    pub fn try_opreturn(data: &[&[u8]]) -> std::result::Result<Self, OpReturnError> {
+        Self::try_opreturn_with_limit(data, DEFAULT_MAX_OP_RETURN_BYTES)
+    }
+
+    /// Like [`Script::opreturn`], but fails if the script would exceed `max_bytes`, or a push
+    /// wouldn't fit OP_PUSHDATA1.
+    pub fn try_opreturn_with_limit(
+        data: &[&[u8]],
+        max_bytes: usize,
+    ) -> std::result::Result<Self, OpReturnError> {
+        if let Some((idx, item)) = data.iter().enumerate().find(|(_, item)| item.len() > 0xff) {
+            return Err(OpReturnError::PushTooLarge {
+                idx,
+                size: item.len(),
+            });
+        }
+        let script = Script::opreturn(data);
+        let size = script.bytecode().len();
+        if size > max_bytes {
+            return Err(OpReturnError::TooLarge { size, max_bytes });
+        }
+        Ok(script)
+    }
+
     pub fn p2pk(pubkey: &PubKey) -> Self {
         let mut bytes = BytesMut::new();
         bytes.put_slice(&[0x21]);
@@ -185,6 +233,10 @@ impl Script {
         self.bytecode.hex()
     }
 
+    pub fn ser_size(&self) -> usize {
+        compact_size_len(self.bytecode.len() as u64) + self.bytecode.len()
+    }
+
     pub fn to_p2sh(&self) -> Self {
         Script::p2sh(&ShaRmd160::digest(self.bytecode.clone()))
     }
@@ -200,6 +252,31 @@ impl Script {
             .unwrap_or_default()
     }
 
+    /// Data pushed after the OP_RETURN of an OP_RETURN script, the read-side counterpart of
+    /// [`Script::opreturn`]. OP_0 yields an empty chunk.
+    ///
+    /// `None` if the script isn't an OP_RETURN, a push is truncated, or any other opcode follows
+    /// the OP_RETURN.
+    pub fn op_return_pushes(&self) -> Option<Vec<Bytes>> {
+        let mut ops = self.ops();
+        if ops.next()?.ok()? != Op::Code(OP_RETURN) {
+            return None;
+        }
+        ops.map(|op| match op.ok()? {
+            Op::Push(_, data) => Some(data),
+            Op::Code(OP_0) => Some(Bytes::default()),
+            Op::Code(_) => None,
+        })
+        .collect()
+    }
+
+    /// Whether an output with this script can provably never be spent, i.e. it starts with
+    /// OP_RETURN or exceeds [`MAX_SCRIPT_SIZE`]. Such outputs are exempt from dust rules, but any
+    /// value they hold is burned.
+    pub fn is_unspendable(&self) -> bool {
+        self.is_opreturn() || self.bytecode.len() > MAX_SCRIPT_SIZE
+    }
+
     pub fn cut_out_codesep(&self, n_codesep: Option<usize>) -> Result<Script> {
         if let Some(n_codesep) = n_codesep {
             let mut n_codeseps_found = 0;
@@ -278,6 +355,35 @@ impl Iterator for ScriptOpIter {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for Script {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            self.hex().serialize(serializer)
+        } else {
+            self.bytecode.serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Script {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let hex = String::deserialize(deserializer)?;
+            Script::from_hex(&hex).map_err(serde::de::Error::custom)
+        } else {
+            Ok(Script::new(Bytes::deserialize(deserializer)?))
+        }
+    }
+}
+
 impl std::fmt::Display for Script {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Capacity adjusted to avoid reallocations in common scenarios: P2PKH and any scriptSig.
@@ -302,7 +408,8 @@ mod tests {
     use hex_literal::hex;
 
     use crate::{
-        ecc::PubKey, opcode::*, BitcoinSuiteError, Hashed, Script, ScriptVariant, ShaRmd160,
+        ecc::PubKey, opcode::*, BitcoinSuiteError, Bytes, Hashed, OpReturnError, Script,
+        ScriptVariant, ShaRmd160, MAX_SCRIPT_SIZE,
     };
 
     #[test]
@@ -373,6 +480,114 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_try_opreturn() {
+        // 1 + 2 + 220 = 223 bytes
+        let script = Script::try_opreturn(&[&[0; 220]]).unwrap();
+        assert_eq!(script, Script::opreturn(&[&[0; 220]]));
+        assert_eq!(script.bytecode().len(), 223);
+        assert_eq!(
+            Script::try_opreturn(&[&[0; 221]]),
+            Err(OpReturnError::TooLarge {
+                size: 224,
+                max_bytes: 223,
+            }),
+        );
+        // 1 + (1 + 4) + 2 + 215 = 223 bytes
+        assert!(Script::try_opreturn(&[b"SLP\0", &[0; 215]]).is_ok());
+        assert!(Script::try_opreturn(&[b"SLP\0", &[0; 216]]).is_err());
+        assert_eq!(
+            Script::try_opreturn_with_limit(&[&[0; 220]], 222),
+            Err(OpReturnError::TooLarge {
+                size: 223,
+                max_bytes: 222,
+            }),
+        );
+        assert!(Script::try_opreturn_with_limit(&[&[0; 255]], 1000).is_ok());
+        assert_eq!(
+            Script::try_opreturn_with_limit(&[&[], &[0; 256]], 1000),
+            Err(OpReturnError::PushTooLarge { idx: 1, size: 256 }),
+        );
+    }
+
+    #[test]
+    fn test_op_return_pushes() {
+        // Single push, via OP_PUSHDATA1
+        assert_eq!(
+            Script::opreturn(&[&[0; 100]]).op_return_pushes(),
+            Some(vec![Bytes::from_slice(&[0; 100])]),
+        );
+        // Multiple pushes, including empty ones
+        assert_eq!(
+            Script::opreturn(&[b"SLP\0", &[], &[1, 2, 3], &[4; 255]]).op_return_pushes(),
+            Some(vec![
+                Bytes::from_slice(b"SLP\0"),
+                Bytes::default(),
+                Bytes::from_slice(&[1, 2, 3]),
+                Bytes::from_slice(&[4; 255]),
+            ]),
+        );
+        assert_eq!(
+            Script::from_slice(&[OP_RETURN, OP_0, 0x01, 0xaa]).op_return_pushes(),
+            Some(vec![Bytes::default(), Bytes::from_slice(&[0xaa])]),
+        );
+        let mut pushdata2 = vec![OP_RETURN, OP_PUSHDATA2, 0x00, 0x01];
+        pushdata2.extend([5; 0x100]);
+        assert_eq!(
+            Script::from_slice(&pushdata2).op_return_pushes(),
+            Some(vec![Bytes::from_slice(&[5; 0x100])]),
+        );
+        // Bare OP_RETURN has no pushes
+        assert_eq!(
+            Script::from_slice(&[OP_RETURN]).op_return_pushes(),
+            Some(vec![]),
+        );
+        // Not an OP_RETURN
+        assert_eq!(Script::from_slice(&[]).op_return_pushes(), None);
+        assert_eq!(
+            Script::p2pkh(&ShaRmd160::new([0; 20])).op_return_pushes(),
+            None,
+        );
+        assert_eq!(
+            Script::from_slice(&[0x01, 0xaa, OP_RETURN]).op_return_pushes(),
+            None,
+        );
+        // Truncated pushes
+        assert_eq!(
+            Script::from_slice(&[OP_RETURN, 0x04, b'S', b'L']).op_return_pushes(),
+            None,
+        );
+        assert_eq!(
+            Script::from_slice(&[OP_RETURN, OP_PUSHDATA1]).op_return_pushes(),
+            None,
+        );
+        assert_eq!(
+            Script::from_slice(&[OP_RETURN, OP_PUSHDATA2, 0x01]).op_return_pushes(),
+            None,
+        );
+        assert_eq!(
+            Script::from_slice(&[OP_RETURN, OP_PUSHDATA4, 0xff, 0xff, 0xff, 0xff, 0x00])
+                .op_return_pushes(),
+            None,
+        );
+        // Non-push opcodes aren't data
+        assert_eq!(
+            Script::from_slice(&[OP_RETURN, 0x01, 0xaa, OP_1]).op_return_pushes(),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_is_unspendable() {
+        assert!(Script::opreturn(&[b"hello"]).is_unspendable());
+        assert!(Script::from_slice(&[OP_RETURN]).is_unspendable());
+        assert!(!Script::from_slice(&[]).is_unspendable());
+        assert!(!Script::from_slice(&[OP_1, OP_RETURN]).is_unspendable());
+        assert!(!Script::p2pkh(&ShaRmd160::new([0; 20])).is_unspendable());
+        assert!(!Script::from_slice(&[OP_1; MAX_SCRIPT_SIZE]).is_unspendable());
+        assert!(Script::from_slice(&[OP_1; MAX_SCRIPT_SIZE + 1]).is_unspendable());
+    }
+
     #[test]
     fn test_p2pk() -> Result<(), Box<dyn std::error::Error>> {
         assert_eq!(
@@ -0,0 +1,138 @@
+use crate::{opcode::*, Bytes, BytesMut, Op, Script};
+
+/// Assembles a [`Script`] op by op, always using the minimal push encoding required by the
+/// node's MINIMALDATA rule.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptBuilder {
+    bytecode: BytesMut,
+}
+
+impl ScriptBuilder {
+    pub fn new() -> Self {
+        ScriptBuilder::default()
+    }
+
+    /// Append a single opcode; use [`ScriptBuilder::push_data`] for pushes.
+    pub fn push_opcode(mut self, opcode: u8) -> Self {
+        self.bytecode.put_slice(&[opcode]);
+        self
+    }
+
+    /// Push `data` using the smallest encoding: OP_0 for empty data, OP_1NEGATE and OP_1 to
+    /// OP_16 for single bytes they represent, otherwise the shortest push opcode.
+    pub fn push_data(mut self, data: &[u8]) -> Self {
+        let op = match data {
+            [] => Op::Code(OP_0),
+            [byte @ 1..=16] => Op::Code(OP_1 + *byte - 1),
+            [0x81] => Op::Code(OP_1NEGATE),
+            _ => Op::push_bytes(Bytes::from_slice(data)),
+        };
+        op.ser_op(&mut self.bytecode)
+            .expect("Op::push_bytes is always consistent");
+        self
+    }
+
+    /// Push `num` as minimally encoded script number.
+    pub fn push_int(self, num: i64) -> Self {
+        self.push_data(&ser_script_num_i64(num))
+    }
+
+    /// Append the ops of `script`.
+    pub fn push_script(mut self, script: &Script) -> Self {
+        self.bytecode.put_slice(script.bytecode());
+        self
+    }
+
+    pub fn build(self) -> Script {
+        Script::new(self.bytecode.freeze())
+    }
+}
+
+/// Little-endian sign-magnitude encoding of `num`, without superfluous bytes.
+fn ser_script_num_i64(num: i64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut abs_num = num.unsigned_abs();
+    while abs_num > 0 {
+        bytes.push((abs_num & 0xff) as u8);
+        abs_num >>= 8;
+    }
+    // The sign is stored in the highest bit; add a byte if the magnitude already uses it
+    if let Some(&last) = bytes.last() {
+        if last & 0x80 != 0 {
+            bytes.push(if num < 0 { 0x80 } else { 0x00 });
+        } else if num < 0 {
+            *bytes.last_mut().unwrap() |= 0x80;
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{opcode::*, Script, ScriptBuilder, ShaRmd160};
+
+    #[test]
+    fn test_push_int() {
+        let script = |num| ScriptBuilder::new().push_int(num).build();
+        assert_eq!(script(0), Script::from_slice(&[OP_0]));
+        assert_eq!(script(1), Script::from_slice(&[OP_1]));
+        assert_eq!(script(16), Script::from_slice(&[OP_16]));
+        assert_eq!(script(17), Script::from_slice(&[0x01, 0x11]));
+        assert_eq!(script(127), Script::from_slice(&[0x01, 0x7f]));
+        assert_eq!(script(128), Script::from_slice(&[0x02, 0x80, 0x00]));
+        assert_eq!(script(255), Script::from_slice(&[0x02, 0xff, 0x00]));
+        assert_eq!(script(256), Script::from_slice(&[0x02, 0x00, 0x01]));
+        assert_eq!(script(-1), Script::from_slice(&[OP_1NEGATE]));
+        assert_eq!(script(-2), Script::from_slice(&[0x01, 0x82]));
+        assert_eq!(script(-127), Script::from_slice(&[0x01, 0xff]));
+        assert_eq!(script(-128), Script::from_slice(&[0x02, 0x80, 0x80]));
+        assert_eq!(
+            script(i64::MAX),
+            Script::from_slice(&[0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]),
+        );
+        assert_eq!(
+            script(i64::MIN),
+            Script::from_slice(&[0x09, 0, 0, 0, 0, 0, 0, 0, 0x80, 0x80]),
+        );
+    }
+
+    #[test]
+    fn test_push_data() {
+        let script = |data: &[u8]| ScriptBuilder::new().push_data(data).build();
+        assert_eq!(script(&[]), Script::from_slice(&[OP_0]));
+        assert_eq!(script(&[0x00]), Script::from_slice(&[0x01, 0x00]));
+        assert_eq!(script(&[0x05]), Script::from_slice(&[OP_5]));
+        assert_eq!(script(&[0x11]), Script::from_slice(&[0x01, 0x11]));
+        assert_eq!(script(&[0x81]), Script::from_slice(&[OP_1NEGATE]));
+        assert_eq!(
+            script(&[0xaa; 0x4b]),
+            Script::from_slice(&[[0x4b].as_ref(), &[0xaa; 0x4b]].concat()),
+        );
+        assert_eq!(
+            script(&[0xaa; 0x4c]),
+            Script::from_slice(&[[OP_PUSHDATA1, 0x4c].as_ref(), &[0xaa; 0x4c]].concat()),
+        );
+        assert_eq!(
+            script(&[0xaa; 0x100]),
+            Script::from_slice(&[[OP_PUSHDATA2, 0x00, 0x01].as_ref(), &[0xaa; 0x100]].concat()),
+        );
+    }
+
+    #[test]
+    fn test_build_script() {
+        let script = ScriptBuilder::new()
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_HASH160)
+            .push_data(&[0x11; 20])
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_CHECKSIG)
+            .build();
+        let p2pkh = Script::p2pkh(&ShaRmd160::new([0x11; 20]));
+        assert_eq!(script, p2pkh);
+        let script = ScriptBuilder::new().push_int(2).push_script(&p2pkh).build();
+        assert_eq!(
+            script.bytecode().as_ref(),
+            [[OP_2].as_ref(), p2pkh.bytecode().as_ref()].concat(),
+        );
+    }
+}
@@ -1,6 +1,12 @@
-use crate::{BitcoinCode, Bytes, BytesMut, Result};
+use crate::{BitcoinCode, BitcoinSuiteError, Bytes, BytesMut, Result};
 
+pub const CSV_DISABLE_FLAG: u32 = 1 << 31;
 pub const CSV_TYPE_FLAG: u32 = 1 << 22;
+pub const CSV_VALUE_MASK: u32 = 0xffff;
+/// Relative time locks are encoded in units of 512 seconds.
+pub const CSV_TIME_GRANULARITY: u32 = 512;
+/// Inputs with a sequence number of at most this signal replaceability (BIP125).
+pub const MAX_RBF_SEQUENCE: u32 = 0xffff_fffd;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SequenceNo {
@@ -12,6 +18,17 @@ impl SequenceNo {
         SequenceNo { num: 0xffff_ffff }
     }
 
+    /// Sequence number signaling replaceability (BIP125) without enabling a relative lock time.
+    ///
+    /// eCash nodes don't implement RBF: the first-seen tx stays in the mempool and conflicting
+    /// txs are rejected, regardless of this flag. It is still useful for wallets that follow the
+    /// BIP125 convention, e.g. to warn that a tx might be replaced on other chains or by miners.
+    pub fn enable_rbf() -> Self {
+        SequenceNo {
+            num: MAX_RBF_SEQUENCE,
+        }
+    }
+
     pub fn from_u32(num: u32) -> Self {
         SequenceNo { num }
     }
@@ -19,6 +36,49 @@ impl SequenceNo {
     pub fn as_u32(&self) -> u32 {
         self.num
     }
+
+    /// Input can only be spent `num_blocks` blocks after the output it spends (BIP68).
+    pub fn from_relative_blocks(num_blocks: u16) -> Self {
+        SequenceNo {
+            num: num_blocks as u32,
+        }
+    }
+
+    /// Input can only be spent `seconds` after the output it spends (BIP68), rounded up to the
+    /// next multiple of 512 seconds.
+    pub fn from_relative_time(seconds: u32) -> Result<Self> {
+        let units =
+            (seconds as u64 + CSV_TIME_GRANULARITY as u64 - 1) / CSV_TIME_GRANULARITY as u64;
+        if units > CSV_VALUE_MASK as u64 {
+            return Err(BitcoinSuiteError::RelativeTimeTooLarge(seconds));
+        }
+        Ok(SequenceNo {
+            num: CSV_TYPE_FLAG | units as u32,
+        })
+    }
+
+    /// Whether the input signals replaceability according to BIP125, see [`Self::enable_rbf`].
+    pub fn is_rbf_signaling(&self) -> bool {
+        self.num <= MAX_RBF_SEQUENCE
+    }
+
+    pub fn is_relative_locktime(&self) -> bool {
+        self.num & CSV_DISABLE_FLAG == 0
+    }
+
+    pub fn relative_blocks(&self) -> Option<u16> {
+        if !self.is_relative_locktime() || self.num & CSV_TYPE_FLAG != 0 {
+            return None;
+        }
+        Some((self.num & CSV_VALUE_MASK) as u16)
+    }
+
+    pub fn relative_time_seconds(&self) -> Option<u32> {
+        if !self.is_relative_locktime() || self.num & CSV_TYPE_FLAG == 0 {
+            return None;
+        }
+        Some((self.num & CSV_VALUE_MASK) * CSV_TIME_GRANULARITY)
+    }
 }
 
 impl BitcoinCode for SequenceNo {
@@ -26,9 +86,96 @@ impl BitcoinCode for SequenceNo {
         self.num.ser_to(bytes)
     }
 
+    fn serialize_to<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.num.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(SequenceNo {
             num: u32::deser(data)?,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitcoinCode, BitcoinSuiteError, SequenceNo, TxInput, UnhashedTx};
+
+    #[test]
+    fn test_relative_blocks() -> Result<(), BitcoinSuiteError> {
+        for num_blocks in [0, 1, 144, 0xffff] {
+            let sequence = SequenceNo::from_relative_blocks(num_blocks);
+            assert_eq!(sequence.as_u32(), num_blocks as u32);
+            assert!(sequence.is_relative_locktime());
+            assert_eq!(sequence.relative_blocks(), Some(num_blocks));
+            assert_eq!(sequence.relative_time_seconds(), None);
+            let decoded = SequenceNo::deser(&mut sequence.ser())?;
+            assert_eq!(decoded.relative_blocks(), Some(num_blocks));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_time() -> Result<(), BitcoinSuiteError> {
+        for (seconds, expected_num, expected_seconds) in [
+            (0, 0x0040_0000, 0),
+            (512, 0x0040_0001, 512),
+            (513, 0x0040_0002, 1024),
+            (3600, 0x0040_0008, 4096),
+            (0xffff * 512, 0x0040_ffff, 0xffff * 512),
+        ] {
+            let sequence = SequenceNo::from_relative_time(seconds)?;
+            assert_eq!(sequence.as_u32(), expected_num);
+            assert!(sequence.is_relative_locktime());
+            assert_eq!(sequence.relative_blocks(), None);
+            assert_eq!(sequence.relative_time_seconds(), Some(expected_seconds));
+            let decoded = SequenceNo::deser(&mut sequence.ser())?;
+            assert_eq!(decoded.relative_time_seconds(), Some(expected_seconds));
+        }
+        for seconds in [0xffff * 512 + 1, u32::MAX] {
+            match SequenceNo::from_relative_time(seconds) {
+                Err(BitcoinSuiteError::RelativeTimeTooLarge(s)) => assert_eq!(s, seconds),
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_disabled() {
+        let sequence = SequenceNo::finalized();
+        assert!(!sequence.is_relative_locktime());
+        assert_eq!(sequence.relative_blocks(), None);
+        assert_eq!(sequence.relative_time_seconds(), None);
+        let sequence = SequenceNo::from_u32(0x8040_0010);
+        assert!(!sequence.is_relative_locktime());
+        assert_eq!(sequence.relative_time_seconds(), None);
+    }
+
+    #[test]
+    fn test_rbf_signaling() {
+        assert_eq!(SequenceNo::enable_rbf().as_u32(), 0xffff_fffd);
+        assert!(SequenceNo::enable_rbf().is_rbf_signaling());
+        assert!(!SequenceNo::enable_rbf().is_relative_locktime());
+        assert!(!SequenceNo::finalized().is_rbf_signaling());
+        assert!(!SequenceNo::from_u32(0xffff_fffe).is_rbf_signaling());
+        assert!(SequenceNo::from_u32(0xffff_fffd).is_rbf_signaling());
+        assert!(SequenceNo::from_u32(0).is_rbf_signaling());
+
+        let input = |sequence| TxInput {
+            sequence,
+            ..Default::default()
+        };
+        let mut tx = UnhashedTx {
+            inputs: vec![
+                input(SequenceNo::finalized()),
+                input(SequenceNo::from_u32(0xffff_fffe)),
+            ],
+            ..Default::default()
+        };
+        assert!(!tx.signals_rbf());
+        tx.inputs.push(input(SequenceNo::enable_rbf()));
+        assert!(tx.signals_rbf());
+        assert!(!UnhashedTx::default().signals_rbf());
+    }
+}
@@ -57,11 +57,66 @@ impl SigHashType {
         input_type: SigHashTypeInputs::AnyoneCanPay,
         output_type: SigHashTypeOutputs::Single,
     };
+    pub const ALL_LEGACY: SigHashType = SigHashType {
+        variant: SigHashTypeVariant::Legacy,
+        input_type: SigHashTypeInputs::Fixed,
+        output_type: SigHashTypeOutputs::All,
+    };
+    pub const ALL_LEGACY_ANYONECANPAY: SigHashType = SigHashType {
+        variant: SigHashTypeVariant::Legacy,
+        input_type: SigHashTypeInputs::AnyoneCanPay,
+        output_type: SigHashTypeOutputs::All,
+    };
+    pub const NONE_LEGACY: SigHashType = SigHashType {
+        variant: SigHashTypeVariant::Legacy,
+        input_type: SigHashTypeInputs::Fixed,
+        output_type: SigHashTypeOutputs::None,
+    };
+    pub const NONE_LEGACY_ANYONECANPAY: SigHashType = SigHashType {
+        variant: SigHashTypeVariant::Legacy,
+        input_type: SigHashTypeInputs::AnyoneCanPay,
+        output_type: SigHashTypeOutputs::None,
+    };
+    pub const SINGLE_LEGACY: SigHashType = SigHashType {
+        variant: SigHashTypeVariant::Legacy,
+        input_type: SigHashTypeInputs::Fixed,
+        output_type: SigHashTypeOutputs::Single,
+    };
+    pub const SINGLE_LEGACY_ANYONECANPAY: SigHashType = SigHashType {
+        variant: SigHashTypeVariant::Legacy,
+        input_type: SigHashTypeInputs::AnyoneCanPay,
+        output_type: SigHashTypeOutputs::Single,
+    };
+
+    /// Which outputs are signed, i.e. the lower bits of the flags.
+    pub fn base_type(&self) -> SigHashTypeOutputs {
+        self.output_type
+    }
+
+    pub fn is_anyone_can_pay(&self) -> bool {
+        self.input_type == SigHashTypeInputs::AnyoneCanPay
+    }
+
+    /// Whether the fork id bit is set, which selects the BIP143 sighash algorithm required by
+    /// BCH, eCash and Lotus.
+    pub fn is_bip143(&self) -> bool {
+        self.variant == SigHashTypeVariant::Bip143
+    }
 
     pub fn to_u32(&self) -> u32 {
         self.input_type.to_u32() | self.output_type.to_u32() | self.variant.to_u32()
     }
 
+    /// Sighash byte appended to signatures.
+    pub fn to_byte(&self) -> u8 {
+        self.to_u32() as u8
+    }
+
+    /// Parse the sighash byte of a signature, `None` if it has unknown flags.
+    pub fn from_byte(byte: u8) -> Option<SigHashType> {
+        Self::from_u32(byte as u32)
+    }
+
     pub fn from_u32(flags: u32) -> Option<SigHashType> {
         if flags & 0xffff_ff00 != 0 {
             return None;
@@ -169,6 +224,58 @@ mod tests {
         assert_eq!(SigHashType::SINGLE_BIP143_ANYONECANPAY.to_u32(), 0xc3);
     }
 
+    #[test]
+    fn test_sighash_from_byte() {
+        use SigHashTypeOutputs::*;
+        for (byte, sighash, base_type, is_anyone_can_pay, is_bip143) in [
+            (0x01, SigHashType::ALL_LEGACY, All, false, false),
+            (0x02, SigHashType::NONE_LEGACY, None, false, false),
+            (0x03, SigHashType::SINGLE_LEGACY, Single, false, false),
+            (0x81, SigHashType::ALL_LEGACY_ANYONECANPAY, All, true, false),
+            (
+                0x82,
+                SigHashType::NONE_LEGACY_ANYONECANPAY,
+                None,
+                true,
+                false,
+            ),
+            (
+                0x83,
+                SigHashType::SINGLE_LEGACY_ANYONECANPAY,
+                Single,
+                true,
+                false,
+            ),
+            (0x41, SigHashType::ALL_BIP143, All, false, true),
+            (0x42, SigHashType::NONE_BIP143, None, false, true),
+            (0x43, SigHashType::SINGLE_BIP143, Single, false, true),
+            (0xc1, SigHashType::ALL_BIP143_ANYONECANPAY, All, true, true),
+            (
+                0xc2,
+                SigHashType::NONE_BIP143_ANYONECANPAY,
+                None,
+                true,
+                true,
+            ),
+            (
+                0xc3,
+                SigHashType::SINGLE_BIP143_ANYONECANPAY,
+                Single,
+                true,
+                true,
+            ),
+        ] {
+            assert_eq!(SigHashType::from_byte(byte), Some(sighash));
+            assert_eq!(sighash.to_byte(), byte);
+            assert_eq!(sighash.base_type(), base_type);
+            assert_eq!(sighash.is_anyone_can_pay(), is_anyone_can_pay);
+            assert_eq!(sighash.is_bip143(), is_bip143);
+        }
+        assert_eq!(SigHashType::from_byte(0x00), Option::None);
+        assert_eq!(SigHashType::from_byte(0x44), Option::None);
+        assert_eq!(SigHashType::from_byte(0x61), Option::None);
+    }
+
     #[test]
     fn test_sighash_from_u32() {
         assert_eq!(SigHashType::from_u32(0xdead0041), None);
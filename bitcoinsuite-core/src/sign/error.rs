@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::SigHashType;
+use crate::{Op, OutPoint, SigHashType};
 
 #[derive(Error, Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum SignError {
@@ -32,6 +32,110 @@ pub enum SignError {
     },
     #[error("OP_CODESEPARATOR #{0} not found")]
     CodesepNotFound(usize),
+    #[error("Leftover outputs must be resolved before creating a PartialTx")]
+    UnresolvedLeftover,
+    #[error("No partial txs to combine")]
+    NoPartialTxs,
+    #[error("Partial tx {tx_idx} has a different version, outputs or lock time than tx 0")]
+    InconsistentPartialTx { tx_idx: usize },
+    #[error("Input {input_idx} of partial tx {tx_idx} spends an outpoint spent before")]
+    DuplicatePartialTxInput { tx_idx: usize, input_idx: usize },
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TxBuilderError {
+    #[error("Outputs need {needed}, but inputs only have {available}")]
+    InsufficientFunds { needed: i64, available: i64 },
+    #[error("Input {input_idx} has neither a signatory nor an input script")]
+    MissingSignatory { input_idx: usize },
+    #[error("Input {input_idx} has no sign data with a value")]
+    MissingSignData { input_idx: usize },
+    #[error("Input {input_idx} has a signatory, but no sign data with an output script")]
+    MissingOutputScript { input_idx: usize },
+    #[error("Output {idx} has value {value}, which is below the dust threshold of {threshold}")]
+    DustOutput {
+        idx: usize,
+        value: i64,
+        threshold: i64,
+    },
+    #[error("Fee or amounts overflow")]
+    FeeOverflow,
+    #[error("Fee is {shortfall} sats short of the min relay fee of {min_fee}")]
+    BelowMinRelayFee { shortfall: i64, min_fee: i64 },
+    #[error("Redeem script doesn't hash to the P2SH script of the spent output")]
+    RedeemScriptMismatch,
+    #[error("Output {idx} has value {value}, which would be burned by its unspendable script")]
+    UnspendableOutputWithValue { idx: usize, value: i64 },
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ScriptVerifyError {
+    #[error("Tx has {num_inputs} inputs, but {num_prevouts} prevouts were given")]
+    PrevoutsMismatch {
+        num_inputs: usize,
+        num_prevouts: usize,
+    },
+    #[error("Input {input_idx} spends {expected}, but its prevout is {actual}")]
+    WrongPrevout {
+        input_idx: usize,
+        expected: OutPoint,
+        actual: OutPoint,
+    },
+    #[error("Input {input_idx} failed script verification: {error}")]
+    InputFailed {
+        input_idx: usize,
+        error: ScriptError,
+    },
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ScriptError {
+    #[error("Invalid script encoding")]
+    InvalidScriptEncoding,
+    #[error("Input script must only push data")]
+    InputScriptNotPushOnly,
+    #[error("Push of {size} bytes exceeds the max stack element size")]
+    PushTooLarge { size: usize },
+    #[error("Push is not minimally encoded")]
+    NonMinimalPush,
+    #[error("Script exceeds the max number of opcodes")]
+    OpCountExceeded,
+    #[error("Stack exceeds the max number of items")]
+    StackSizeExceeded,
+    #[error("Opcode {} is not supported", op_code(.0))]
+    UnsupportedOpcode(u8),
+    #[error("Not enough items on the stack for {}", op_code(.0))]
+    StackUnderflow(u8),
+    #[error("Unbalanced OP_IF/OP_ELSE/OP_ENDIF")]
+    UnbalancedConditional,
+    #[error("Executed OP_RETURN")]
+    OpReturn,
+    #[error("{} failed", op_code(.0))]
+    VerifyFailed(u8),
+    #[error("Script evaluated to false")]
+    EvalFalse,
+    #[error("Script left {stack_size} items on the stack, expected exactly 1")]
+    CleanStack { stack_size: usize },
+    #[error("Invalid script number")]
+    InvalidNumber,
+    #[error("Invalid number of pubkeys or signatures for OP_CHECKMULTISIG")]
+    InvalidMultisigCount,
+    #[error("Invalid dummy element for OP_CHECKMULTISIG")]
+    InvalidMultisigDummy,
+    #[error("Invalid pubkey")]
+    InvalidPubKey,
+    #[error("Invalid signature format")]
+    InvalidSignatureFormat,
+    #[error("Sighash type {0:#04x} is invalid, it must use the fork id")]
+    InvalidSigHashType(u8),
+    #[error("Non-empty signature failed to verify")]
+    NullFail,
+    #[error("Could not compute sighash: {0}")]
+    Sighash(#[from] SignError),
+}
+
+fn op_code(opcode: &u8) -> Op {
+    Op::Code(*opcode)
 }
 
 pub type Result<T> = std::result::Result<T, SignError>;
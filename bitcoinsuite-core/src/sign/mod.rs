@@ -1,11 +1,15 @@
 pub mod error;
+mod partial_tx;
 mod sign_data;
 mod signatory;
 mod tx_builder;
 mod unsigned_tx;
+mod verify;
 
-pub use self::error::SignError;
+pub use self::error::{ScriptError, ScriptVerifyError, SignError, TxBuilderError};
+pub use self::partial_tx::*;
 pub use self::sign_data::*;
 pub use self::signatory::*;
 pub use self::tx_builder::*;
 pub use self::unsigned_tx::*;
+pub use self::verify::*;
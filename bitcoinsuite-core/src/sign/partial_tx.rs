@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+
+use crate::{
+    ecc::Ecc, BitcoinCode, BitcoinSuiteError, Bytes, BytesMut, SignData, SignError, Signatory,
+    TxBuilder, TxBuilderOutput, UnhashedTx, UnsignedTx,
+};
+
+pub struct PartialTx {
+    tx: UnhashedTx,
+    signatories: Vec<Option<Box<dyn Signatory>>>,
+}
+
+impl PartialTx {
+    pub fn new(tx: UnhashedTx) -> Self {
+        let signatories = tx.inputs.iter().map(|_| None).collect();
+        PartialTx { tx, signatories }
+    }
+
+    pub fn from_builder(tx_builder: &TxBuilder) -> Result<Self, BitcoinSuiteError> {
+        let outputs = tx_builder
+            .outputs
+            .iter()
+            .map(|output| match output {
+                TxBuilderOutput::Fixed(output) => Ok(output.clone()),
+                TxBuilderOutput::Leftover(_) => Err(SignError::UnresolvedLeftover),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PartialTx::new(UnhashedTx {
+            version: tx_builder.version,
+            inputs: tx_builder
+                .inputs
+                .iter()
+                .map(|input| input.input().clone())
+                .collect(),
+            outputs,
+            lock_time: tx_builder.lock_time,
+        }))
+    }
+
+    pub fn tx(&self) -> &UnhashedTx {
+        &self.tx
+    }
+
+    pub fn set_signatory(&mut self, input_idx: usize, signatory: Box<dyn Signatory>) {
+        self.signatories[input_idx] = Some(signatory);
+    }
+
+    /// Signs all inputs that have a signatory attached, so the tx can be passed on to the next
+    /// signer with the collected signatures in its input scripts.
+    pub fn sign(&mut self, ecc: &dyn Ecc) -> Result<(), BitcoinSuiteError> {
+        let mut unsigned_tx = UnsignedTx::new(std::mem::take(&mut self.tx));
+        let mut result = Ok(());
+        for (input_idx, signatory) in self.signatories.iter_mut().enumerate() {
+            if let Some(signatory) = signatory.take() {
+                result = signatory.sign_input(ecc, unsigned_tx.input_at(input_idx));
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+        self.tx = unsigned_tx.into_tx();
+        result
+    }
+
+    pub fn finalize(mut self, ecc: &dyn Ecc) -> Result<UnhashedTx, BitcoinSuiteError> {
+        self.sign(ecc)?;
+        Ok(self.tx)
+    }
+}
+
+/// Merge txs whose inputs were signed independently, e.g. the contributions to an assurance
+/// contract, into one tx spending all their inputs in order.
+///
+/// All txs must have the same version, outputs and lock time. The signatures stay valid only if
+/// they don't cover the other inputs, i.e. they use ALL|ANYONECANPAY or NONE|ANYONECANPAY;
+/// SINGLE|ANYONECANPAY commits to the output at the input's index, which may change here.
+pub fn combine_partial_txs(txs: Vec<UnhashedTx>) -> Result<UnhashedTx, SignError> {
+    let mut txs = txs.into_iter();
+    let mut combined = txs.next().ok_or(SignError::NoPartialTxs)?;
+    let mut spent = HashSet::new();
+    for (input_idx, input) in combined.inputs.iter().enumerate() {
+        if !spent.insert(input.prev_out.clone()) {
+            return Err(SignError::DuplicatePartialTxInput {
+                tx_idx: 0,
+                input_idx,
+            });
+        }
+    }
+    for (tx_idx, tx) in txs.enumerate().map(|(idx, tx)| (idx + 1, tx)) {
+        if tx.version != combined.version
+            || tx.outputs != combined.outputs
+            || tx.lock_time != combined.lock_time
+        {
+            return Err(SignError::InconsistentPartialTx { tx_idx });
+        }
+        for (input_idx, input) in tx.inputs.into_iter().enumerate() {
+            if !spent.insert(input.prev_out.clone()) {
+                return Err(SignError::DuplicatePartialTxInput { tx_idx, input_idx });
+            }
+            combined.inputs.push(input);
+        }
+    }
+    Ok(combined)
+}
+
+impl BitcoinCode for PartialTx {
+    fn ser_to(&self, bytes: &mut BytesMut) {
+        self.tx.ser_to(bytes);
+        let sign_data = self
+            .tx
+            .inputs
+            .iter()
+            .map(|input| input.sign_data.clone().unwrap_or_default())
+            .collect::<Vec<_>>();
+        sign_data.ser_to(bytes);
+    }
+
+    fn deser(data: &mut Bytes) -> crate::Result<Self> {
+        let mut tx = UnhashedTx::deser(data)?;
+        let sign_data = Vec::<SignData>::deser(data)?;
+        if sign_data.len() != tx.inputs.len() {
+            return Err(BitcoinSuiteError::InvalidSize {
+                expected: tx.inputs.len(),
+                actual: sign_data.len(),
+            });
+        }
+        for (input, sign_data) in tx.inputs.iter_mut().zip(sign_data) {
+            input.sign_data = match sign_data == SignData::default() {
+                true => None,
+                false => Some(sign_data),
+            };
+        }
+        Ok(PartialTx::new(tx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        combine_partial_txs,
+        ecc::{DummyEcc, PubKey, SecKey},
+        BitcoinCode, BitcoinSuiteError, Hashed, OutPoint, P2PKHSignatory, PartialTx, Script,
+        SequenceNo, Sha256d, ShaRmd160, SigHashType, SignData, SignError, SignField, TxBuilder,
+        TxBuilderOutput, TxInput, TxOutput, UnhashedTx, UnsignedTx,
+    };
+
+    #[test]
+    fn test_partial_tx() -> Result<(), Box<dyn std::error::Error>> {
+        let pubkey = PubKey::new_unchecked([2; 33]);
+        let p2pkh_script = Script::p2pkh(&ShaRmd160::digest(pubkey.array().into()));
+        let tx = UnhashedTx {
+            version: 1,
+            inputs: vec![
+                TxInput {
+                    prev_out: OutPoint::default(),
+                    script: Script::from_slice(&[0x01, 0x51]),
+                    sequence: SequenceNo::finalized(),
+                    sign_data: None,
+                },
+                TxInput {
+                    prev_out: OutPoint::default(),
+                    script: Script::default(),
+                    sequence: SequenceNo::finalized(),
+                    sign_data: Some(SignData::new(vec![
+                        SignField::Value(12345),
+                        SignField::OutputScript(p2pkh_script),
+                    ])),
+                },
+            ],
+            outputs: vec![TxOutput::default()],
+            lock_time: 0,
+        };
+
+        // Watch-only side
+        let tx_builder = TxBuilder::from_tx(tx.clone());
+        let partial_tx = PartialTx::from_builder(&tx_builder)?;
+        assert_eq!(partial_tx.tx(), &tx);
+        let raw_partial_tx = partial_tx.ser();
+
+        // Signing side
+        let mut partial_tx = PartialTx::deser(&mut raw_partial_tx.clone())?;
+        assert_eq!(partial_tx.tx(), &tx);
+        partial_tx.set_signatory(
+            1,
+            Box::new(P2PKHSignatory {
+                seckey: SecKey::new_unchecked([1; 32]),
+                pubkey,
+                sig_hash_type: SigHashType::ALL_BIP143,
+            }),
+        );
+        let signed_tx = partial_tx.finalize(&DummyEcc)?;
+        assert_eq!(
+            signed_tx.inputs[0].script,
+            Script::from_slice(&[0x01, 0x51])
+        );
+        assert_eq!(
+            signed_tx.inputs[1].script,
+            Script::from_slice(&[[65].as_ref(), &[0; 64], &[0x41], &[33], &[2; 33]].concat()),
+        );
+        assert_eq!(signed_tx.outputs, tx.outputs);
+
+        // Sign data count doesn't match inputs
+        let mut raw = raw_partial_tx.to_vec();
+        raw.truncate(tx.ser().len());
+        raw.push(0);
+        match PartialTx::deser(&mut raw.into()) {
+            Err(BitcoinSuiteError::InvalidSize {
+                expected: 2,
+                actual: 0,
+            }) => {}
+            Err(err) => panic!("Unexpected error: {}", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+
+        // Unresolved leftover
+        let mut tx_builder = TxBuilder::from_tx(tx);
+        tx_builder
+            .outputs
+            .push(TxBuilderOutput::Leftover(Script::default()));
+        match PartialTx::from_builder(&tx_builder) {
+            Err(BitcoinSuiteError::Sign(SignError::UnresolvedLeftover)) => {}
+            Err(err) => panic!("Unexpected error: {}", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_combine_partial_txs() -> Result<(), Box<dyn std::error::Error>> {
+        let partial_tx = |out_idx: u32, value: i64| UnhashedTx {
+            version: 1,
+            inputs: vec![TxInput {
+                prev_out: OutPoint {
+                    txid: Sha256d::new([1; 32]),
+                    out_idx,
+                },
+                script: Script::default(),
+                sequence: SequenceNo::finalized(),
+                sign_data: Some(SignData::new(vec![
+                    SignField::Value(value),
+                    SignField::OutputScript(Script::p2pkh(&ShaRmd160::new([2; 20]))),
+                ])),
+            }],
+            outputs: vec![TxOutput {
+                value: 30_000,
+                script: Script::p2pkh(&ShaRmd160::new([3; 20])),
+            }],
+            lock_time: 0,
+        };
+        let tx1 = partial_tx(0, 10_000);
+        let tx2 = partial_tx(1, 20_000);
+        let combined = combine_partial_txs(vec![tx1.clone(), tx2.clone()])?;
+        assert_eq!(
+            combined.inputs,
+            vec![tx1.inputs[0].clone(), tx2.inputs[0].clone()]
+        );
+        assert_eq!(combined.outputs, tx1.outputs);
+
+        // ANYONECANPAY preimages don't change by combining, ALL preimages do
+        let preimage = |tx: &UnhashedTx, input_idx: usize, sig_hash_type| {
+            UnsignedTx::new(tx.clone())
+                .input_at(input_idx)
+                .sighash_preimage(sig_hash_type, None)
+                .map(|preimage| preimage.bytes)
+        };
+        let anyone_can_pay = SigHashType::ALL_BIP143_ANYONECANPAY;
+        assert_eq!(
+            preimage(&combined, 1, anyone_can_pay)?,
+            preimage(&tx2, 0, anyone_can_pay)?,
+        );
+        assert_ne!(
+            preimage(&combined, 1, SigHashType::ALL_BIP143)?,
+            preimage(&tx2, 0, SigHashType::ALL_BIP143)?,
+        );
+
+        assert_eq!(combine_partial_txs(vec![]), Err(SignError::NoPartialTxs));
+        let mut tx3 = partial_tx(2, 30_000);
+        tx3.lock_time = 1;
+        assert_eq!(
+            combine_partial_txs(vec![tx1.clone(), tx2.clone(), tx3]),
+            Err(SignError::InconsistentPartialTx { tx_idx: 2 }),
+        );
+        assert_eq!(
+            combine_partial_txs(vec![tx1, tx2.clone(), tx2]),
+            Err(SignError::DuplicatePartialTxInput {
+                tx_idx: 2,
+                input_idx: 0,
+            }),
+        );
+        Ok(())
+    }
+}
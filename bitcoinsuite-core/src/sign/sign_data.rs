@@ -1,4 +1,4 @@
-use crate::{Script, SignError};
+use crate::{BitcoinCode, BitcoinSuiteError, Bytes, BytesMut, Script, SignError};
 
 use crate::sign::error::Result;
 
@@ -18,28 +18,28 @@ impl SignData {
     pub fn new(fields: Vec<SignField>) -> Self {
         SignData { fields }
     }
+
+    pub fn fields(&self) -> &[SignField] {
+        &self.fields
+    }
 }
 
 impl SignData {
     pub fn find_script_code(&self) -> Result<Script> {
-        let mut is_p2sh = false;
-        for field in &self.fields {
-            match field {
-                SignField::OutputScript(script) => {
-                    if !script.is_p2sh() {
-                        return Ok(script.clone());
-                    }
-                    is_p2sh = true;
-                }
-                SignField::RedeemScript(script) => {
-                    return Ok(script.clone());
-                }
-                _ => {}
+        let output_script = self.fields.iter().find_map(|field| match field {
+            SignField::OutputScript(script) => Some(script),
+            _ => None,
+        });
+        let redeem_script = self.fields.iter().find_map(|field| match field {
+            SignField::RedeemScript(script) => Some(script),
+            _ => None,
+        });
+        match (output_script, redeem_script) {
+            (Some(output_script), redeem_script) => {
+                compute_script_code(output_script, redeem_script)
             }
-        }
-        match is_p2sh {
-            true => Err(SignError::MissingScriptCodeP2SH),
-            false => Err(SignError::MissingScriptCode),
+            (None, Some(redeem_script)) => Ok(redeem_script.clone()),
+            (None, None) => Err(SignError::MissingScriptCode),
         }
     }
 
@@ -53,9 +53,67 @@ impl SignData {
     }
 }
 
+/// Compute the scriptCode that is hashed into the sighash when spending `output_script`.
+/// P2SH outputs are signed against their redeem script, which therefore must be given; any
+/// other output script is used as-is.
+pub fn compute_script_code(
+    output_script: &Script,
+    redeem_script: Option<&Script>,
+) -> Result<Script> {
+    if !output_script.is_p2sh() {
+        return Ok(output_script.clone());
+    }
+    redeem_script
+        .cloned()
+        .ok_or(SignError::MissingScriptCodeP2SH)
+}
+
+impl BitcoinCode for SignData {
+    fn ser_to(&self, bytes: &mut BytesMut) {
+        self.fields.ser_to(bytes)
+    }
+
+    fn deser(data: &mut Bytes) -> crate::Result<Self> {
+        Ok(SignData {
+            fields: BitcoinCode::deser(data)?,
+        })
+    }
+}
+
+impl BitcoinCode for SignField {
+    fn ser_to(&self, bytes: &mut BytesMut) {
+        match self {
+            SignField::OutputScript(script) => {
+                0u8.ser_to(bytes);
+                script.ser_to(bytes);
+            }
+            SignField::RedeemScript(script) => {
+                1u8.ser_to(bytes);
+                script.ser_to(bytes);
+            }
+            SignField::Value(value) => {
+                2u8.ser_to(bytes);
+                value.ser_to(bytes);
+            }
+        }
+    }
+
+    fn deser(data: &mut Bytes) -> crate::Result<Self> {
+        match u8::deser(data)? {
+            0 => Ok(SignField::OutputScript(BitcoinCode::deser(data)?)),
+            1 => Ok(SignField::RedeemScript(BitcoinCode::deser(data)?)),
+            2 => Ok(SignField::Value(BitcoinCode::deser(data)?)),
+            tag => Err(BitcoinSuiteError::UnknownSignFieldTag(tag)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{sign::error::Result, Script, SignData, SignError, SignField};
+    use crate::{
+        compute_script_code, ecc::PubKey, sign::error::Result, BitcoinCode, BitcoinSuiteError,
+        Hashed, Script, ShaRmd160, SignData, SignError, SignField,
+    };
 
     #[test]
     fn test_find_script_code_success() -> Result<()> {
@@ -109,6 +167,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compute_script_code() -> Result<()> {
+        let pubkey = PubKey::new_unchecked([2; 33]);
+        let p2pkh_script = Script::p2pkh(&ShaRmd160::digest(pubkey.array().into()));
+        assert_eq!(compute_script_code(&p2pkh_script, None)?, p2pkh_script);
+        // Redeem script is ignored for non-P2SH outputs
+        assert_eq!(
+            compute_script_code(&p2pkh_script, Some(&Script::from_slice(&[0x51])))?,
+            p2pkh_script,
+        );
+
+        let p2sh_script = p2pkh_script.to_p2sh();
+        assert_eq!(
+            compute_script_code(&p2sh_script, Some(&p2pkh_script))?,
+            p2pkh_script,
+        );
+        assert_eq!(
+            compute_script_code(&p2sh_script, None).unwrap_err(),
+            SignError::MissingScriptCodeP2SH,
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_find_value() -> Result<()> {
         let sign_data = SignData::new(vec![SignField::Value(1234)]);
@@ -128,4 +209,28 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ser_sign_data() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let sign_data = SignData::new(vec![
+            SignField::OutputScript(Script::from_slice(&[0x51]).to_p2sh()),
+            SignField::RedeemScript(Script::from_slice(&[0x51])),
+            SignField::Value(1234),
+        ]);
+        let ser = sign_data.ser();
+        assert_eq!(
+            ser.hex(),
+            "03\
+             0017a914da1745e9b549bd0bfa1a569971c77eba30cd5a4b87\
+             010151\
+             02d204000000000000",
+        );
+        assert_eq!(SignData::deser(&mut ser.clone())?, sign_data);
+        assert_eq!(SignData::deser(&mut [0].into())?, SignData::default());
+        match SignData::deser(&mut [1, 3].into()) {
+            Err(BitcoinSuiteError::UnknownSignFieldTag(3)) => {}
+            result => panic!("Unexpected: {:?}", result),
+        }
+        Ok(())
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
     ecc::{Ecc, PubKey, SecKey},
-    BytesMut, Hashed, Result, Script, Sha256d, SigHashType, UnsignedTxInput,
+    BytesMut, Hashed, Op, Result, Script, Sha256d, SigHashType, SignData, SignField,
+    UnsignedTxInput,
 };
 
 pub trait Signatory {
@@ -22,18 +23,51 @@ impl Signatory for P2PKHSignatory {
         let sig = ecc.schnorr_sign(&self.seckey, sighash);
         let mut sig_flagged = BytesMut::new();
         sig_flagged.put_bytes(sig);
-        sig_flagged.put_slice(&[self.sig_hash_type.to_u32() as u8]);
+        sig_flagged.put_slice(&[self.sig_hash_type.to_byte()]);
         *input.input_script_mut() = Script::p2pkh_spend(&self.pubkey, sig_flagged.freeze());
         Ok(())
     }
 }
 
+/// Spends a P2SH output by running `inner_signatory` against `redeem_script` and then pushing
+/// the serialized redeem script onto the resulting input script.
+pub struct P2SHSignatory {
+    pub redeem_script: Script,
+    pub inner_signatory: Box<dyn Signatory>,
+}
+
+impl Signatory for P2SHSignatory {
+    fn sign_input<'tx>(&self, ecc: &dyn Ecc, mut input: UnsignedTxInput<'tx>) -> Result<()> {
+        // Make the inner signatory use the redeem script as scriptCode
+        let sign_data = input.input_sign_data_mut();
+        let mut fields = vec![SignField::RedeemScript(self.redeem_script.clone())];
+        if let Some(old_sign_data) = sign_data.as_ref() {
+            fields.extend(
+                old_sign_data
+                    .fields()
+                    .iter()
+                    .filter(|field| !matches!(field, SignField::RedeemScript(_)))
+                    .cloned(),
+            );
+        }
+        *sign_data = Some(SignData::new(fields));
+        self.inner_signatory.sign_input(ecc, input.reborrow())?;
+        let input_script = input.input_script_mut();
+        let mut bytecode = BytesMut::new();
+        bytecode.put_bytes(input_script.bytecode().clone());
+        Op::push_bytes(self.redeem_script.bytecode().clone()).ser_op(&mut bytecode)?;
+        *input_script = Script::new(bytecode.freeze());
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         ecc::{DummyEcc, PubKey, SecKey},
-        Hashed, OutPoint, P2PKHSignatory, Script, SequenceNo, ShaRmd160, SigHashType, SignData,
-        SignField, Signatory, TxInput, TxOutput, UnhashedTx, UnsignedTx,
+        Hashed, OutPoint, P2PKHSignatory, P2SHSignatory, Script, SequenceNo, ShaRmd160,
+        SigHashType, SignData, SignField, Signatory, TxInput, TxOutput, UnhashedTx, UnsignedTx,
+        UnsignedTxInput,
     };
 
     #[test]
@@ -90,4 +124,77 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_p2sh_signatory() -> Result<(), Box<dyn std::error::Error>> {
+        let ecc = DummyEcc;
+        let seckey = SecKey::new_unchecked([1; 32]);
+        let pubkey = PubKey::new_unchecked([2; 33]);
+        let redeem_script = Script::p2pkh(&ShaRmd160::digest(pubkey.array().into()));
+
+        /// Checks the scriptCode the wrapped signatory signs against
+        struct ScriptCodeSignatory(Script);
+        impl Signatory for ScriptCodeSignatory {
+            fn sign_input<'tx>(
+                &self,
+                _: &dyn crate::ecc::Ecc,
+                mut input: UnsignedTxInput<'tx>,
+            ) -> crate::Result<()> {
+                let preimage = input.sighash_preimage(SigHashType::ALL_BIP143, None)?;
+                assert_eq!(preimage.script_code, self.0);
+                *input.input_script_mut() = Script::from_slice(&[0x51]);
+                Ok(())
+            }
+        }
+
+        let tx = UnhashedTx {
+            version: 1,
+            inputs: vec![TxInput {
+                prev_out: OutPoint::default(),
+                script: Script::default(),
+                sequence: SequenceNo::finalized(),
+                sign_data: Some(SignData::new(vec![
+                    SignField::Value(12345),
+                    SignField::OutputScript(redeem_script.to_p2sh()),
+                ])),
+            }],
+            outputs: vec![TxOutput::default()],
+            lock_time: 0,
+        };
+
+        let signatory = P2SHSignatory {
+            redeem_script: redeem_script.clone(),
+            inner_signatory: Box::new(ScriptCodeSignatory(redeem_script.clone())),
+        };
+        let mut unsigned_tx = UnsignedTx::new_dummy(tx.clone());
+        signatory.sign_input(&ecc, unsigned_tx.input_at(0))?;
+        let expected_script =
+            Script::from_slice(&[[0x51, 25].as_ref(), redeem_script.bytecode().as_ref()].concat());
+        assert_eq!(unsigned_tx.tx().inputs[0].script, expected_script);
+
+        let signatory = P2SHSignatory {
+            redeem_script: redeem_script.clone(),
+            inner_signatory: Box::new(P2PKHSignatory {
+                seckey,
+                pubkey,
+                sig_hash_type: SigHashType::ALL_BIP143,
+            }),
+        };
+        let mut unsigned_tx = UnsignedTx::new_dummy(tx);
+        signatory.sign_input(&ecc, unsigned_tx.input_at(0))?;
+        let expected_script = Script::from_slice(
+            &[
+                [65].as_ref(),
+                &[0; 64],
+                &[SigHashType::ALL_BIP143.to_u32() as u8],
+                &[33],
+                &[2; 33],
+                &[25],
+                redeem_script.bytecode().as_ref(),
+            ]
+            .concat(),
+        );
+        assert_eq!(unsigned_tx.tx().inputs[0].script, expected_script);
+        Ok(())
+    }
 }
@@ -1,22 +1,27 @@
+use std::cmp::Ordering;
+
 use crate::{
     ecc::{DummyEcc, Ecc},
-    BitcoinCode, BitcoinSuiteError, Script, SignError, Signatory, TxInput, TxOutput, UnhashedTx,
-    UnsignedTx,
+    min_relay_fee, BitcoinCode, BitcoinSuiteError, FeeRate, LockTime, Net, Network, NetworkParams,
+    P2SHSignatory, Satoshi, Script, SequenceNo, SignData, SignError, SignField, Signatory,
+    TxBuilderError, TxInput, TxOutput, UnhashedTx, UnsignedTx, Utxo,
 };
 
-use crate::sign::error::Result;
-
 #[derive(Default)]
 pub struct TxBuilder {
     pub version: i32,
     pub inputs: Vec<TxBuilderInput>,
     pub outputs: Vec<TxBuilderOutput>,
     pub lock_time: u32,
+    /// Whether signing sorts the outputs as specified by BIP69, see [`TxBuilder::sort_bip69`].
+    pub is_bip69: bool,
 }
 
 pub struct TxBuilderInput {
     input: TxInput,
     signatory: Option<Box<dyn Signatory>>,
+    /// Size of the input script another party will sign this input with, if it's external.
+    external_script_size: Option<usize>,
 }
 
 pub enum TxBuilderOutput {
@@ -35,39 +40,127 @@ impl TxBuilder {
                 .collect(),
             outputs: tx.outputs.into_iter().map(TxBuilderOutput::Fixed).collect(),
             lock_time: tx.lock_time,
+            is_bip69: false,
         }
     }
 
-    fn input_sum(&self) -> Option<i64> {
-        let mut input_sum = 0;
-        for builder_input in &self.inputs {
-            match &builder_input.input.sign_data {
-                Some(sign_data) => match sign_data.find_value() {
-                    Ok(value) => input_sum += value,
-                    Err(SignError::MissingValue) => return None,
-                    _ => unreachable!(),
-                },
-                None => return None,
+    /// Sets the tx's lock time. The node ignores the lock time if all inputs are final, so any
+    /// input with a finalized sequence number is changed to be non-final; therefore, the lock
+    /// time should be set after all inputs have been added.
+    pub fn set_lock_time(
+        &mut self,
+        lock_time: LockTime,
+    ) -> std::result::Result<(), BitcoinSuiteError> {
+        lock_time.validate()?;
+        self.lock_time = lock_time.as_u32();
+        for builder_input in &mut self.inputs {
+            if builder_input.input.sequence == SequenceNo::finalized() {
+                builder_input.input.sequence = SequenceNo::from_u32(0xffff_fffe);
             }
         }
-        Some(input_sum)
+        Ok(())
+    }
+
+    /// Sorts inputs and outputs as specified by BIP69, which has to happen before signing.
+    /// Inputs are sorted by outpoint, with txids compared in display order. Outputs are sorted
+    /// by value, then script. The leftover output's value is only known when signing, so it is
+    /// placed last for now, and signing sorts all outputs again once it is known.
+    pub fn sort_bip69(&mut self) {
+        self.inputs
+            .sort_by(|a, b| a.input.prev_out.cmp(&b.input.prev_out));
+        self.outputs.sort_by(|a, b| match (a, b) {
+            (TxBuilderOutput::Fixed(a), TxBuilderOutput::Fixed(b)) => bip69_output_cmp(a, b),
+            (TxBuilderOutput::Fixed(_), TxBuilderOutput::Leftover(_)) => Ordering::Less,
+            (TxBuilderOutput::Leftover(_), TxBuilderOutput::Fixed(_)) => Ordering::Greater,
+            (TxBuilderOutput::Leftover(_), TxBuilderOutput::Leftover(_)) => Ordering::Equal,
+        });
+        self.is_bip69 = true;
+    }
+
+    /// Checks that every input can be signed, returning the indices of those that can't: inputs
+    /// that aren't external and have neither a signatory nor an input script, inputs with a
+    /// signatory but without an output script in their sign data, and, if there is a leftover
+    /// output, inputs without a value in their sign data. Signing fails on the first of these.
+    pub fn validate_ready(&self) -> std::result::Result<(), Vec<usize>> {
+        let not_ready = (0..self.inputs.len())
+            .filter(|&input_idx| self.input_not_ready(input_idx).is_some())
+            .collect::<Vec<_>>();
+        if !not_ready.is_empty() {
+            return Err(not_ready);
+        }
+        Ok(())
+    }
+
+    fn input_not_ready(&self, input_idx: usize) -> Option<TxBuilderError> {
+        let builder_input = &self.inputs[input_idx];
+        if builder_input.signatory.is_none()
+            && builder_input.input.script.bytecode().is_empty()
+            && !builder_input.is_external()
+        {
+            return Some(TxBuilderError::MissingSignatory { input_idx });
+        }
+        let has_output_script = builder_input
+            .input
+            .sign_data
+            .as_ref()
+            .map(|sign_data| {
+                sign_data
+                    .fields()
+                    .iter()
+                    .any(|field| matches!(field, SignField::OutputScript(_)))
+            })
+            .unwrap_or_default();
+        if builder_input.signatory.is_some() && !has_output_script {
+            return Some(TxBuilderError::MissingOutputScript { input_idx });
+        }
+        let has_leftover = self
+            .outputs
+            .iter()
+            .any(|output| matches!(output, TxBuilderOutput::Leftover(_)));
+        let has_value = builder_input
+            .input
+            .sign_data
+            .as_ref()
+            .map(|sign_data| sign_data.find_value().is_ok())
+            .unwrap_or_default();
+        if has_leftover && !has_value {
+            return Some(TxBuilderError::MissingSignData { input_idx });
+        }
+        None
+    }
+
+    fn input_sum(inputs: &[TxInput]) -> std::result::Result<Satoshi, TxBuilderError> {
+        let mut input_sum = Satoshi::ZERO;
+        for (input_idx, input) in inputs.iter().enumerate() {
+            let value = input
+                .sign_data
+                .as_ref()
+                .and_then(|sign_data| sign_data.find_value().ok())
+                .ok_or(TxBuilderError::MissingSignData { input_idx })?;
+            input_sum = input_sum
+                .checked_add(Satoshi::new(value))
+                .ok_or(TxBuilderError::FeeOverflow)?;
+        }
+        Ok(input_sum)
     }
 
     fn prepare_outputs(
         builder_outputs: Vec<TxBuilderOutput>,
-    ) -> Result<(i64, Option<usize>, Vec<TxOutput>)> {
-        let mut fixed_output_sum = 0;
+    ) -> std::result::Result<(Satoshi, Option<usize>, Vec<TxOutput>), BitcoinSuiteError> {
+        let mut fixed_output_sum = Satoshi::ZERO;
         let mut leftover_idx = None;
         let mut outputs = Vec::with_capacity(builder_outputs.len());
         for (output_idx, builder_output) in builder_outputs.into_iter().enumerate() {
             match builder_output {
                 TxBuilderOutput::Fixed(output) => {
-                    fixed_output_sum += output.value;
+                    fixed_output_sum = fixed_output_sum
+                        .checked_add(Satoshi::new(output.value))
+                        .ok_or(TxBuilderError::FeeOverflow)?;
                     outputs.push(output);
                 }
                 TxBuilderOutput::Leftover(script) => {
                     if leftover_idx.is_some() {
-                        return Err(SignError::MultipleLeftover);
+                        return Err(SignError::MultipleLeftover.into());
                     }
                     leftover_idx = Some(output_idx);
                     outputs.push(TxOutput { value: 0, script })
@@ -77,58 +170,221 @@ impl TxBuilder {
         Ok((fixed_output_sum, leftover_idx, outputs))
     }
 
+    /// `tx` with the input script of every external input padded to the size its signer will
+    /// provide, so the size of the fully signed tx can be measured.
+    fn with_external_scripts(
+        tx: &UnhashedTx,
+        external_script_sizes: &[Option<usize>],
+    ) -> UnhashedTx {
+        let mut tx = tx.clone();
+        for (input, &script_size) in tx.inputs.iter_mut().zip(external_script_sizes) {
+            let script_size = script_size.unwrap_or_default();
+            let script_len = input.script.bytecode().len();
+            if script_len < script_size {
+                let mut bytecode = input.script.bytecode().to_vec();
+                bytecode.resize(script_size, 0);
+                input.script = Script::new(bytecode.into());
+            }
+        }
+        tx
+    }
+
+    fn tx_fee(tx_size: usize, fee_rate: FeeRate) -> std::result::Result<Satoshi, TxBuilderError> {
+        fee_rate
+            .checked_fee_for_size(tx_size)
+            .map(Satoshi::new)
+            .ok_or(TxBuilderError::FeeOverflow)
+    }
+
+    /// Checks that the signed tx pays at least [`min_relay_fee`], which is only possible if the
+    /// values of all inputs are known from their sign data. External inputs are counted with
+    /// the script size of their signer.
+    fn check_min_relay_fee(
+        tx: &UnhashedTx,
+        external_script_sizes: &[Option<usize>],
+        network: &Network,
+    ) -> std::result::Result<(), TxBuilderError> {
+        let input_sum = match Self::input_sum(&tx.inputs) {
+            Ok(input_sum) => input_sum,
+            Err(TxBuilderError::MissingSignData { .. }) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let output_sum =
+            Satoshi::checked_sum(tx.outputs.iter().map(|output| Satoshi::new(output.value)))
+                .ok_or(TxBuilderError::FeeOverflow)?;
+        let fee = input_sum
+            .checked_sub(output_sum)
+            .ok_or(TxBuilderError::FeeOverflow)?
+            .as_i64();
+        let tx_size = Self::with_external_scripts(tx, external_script_sizes)
+            .ser()
+            .len();
+        let min_fee = min_relay_fee(tx_size, network);
+        if fee < min_fee {
+            return Err(TxBuilderError::BelowMinRelayFee {
+                shortfall: min_fee - fee,
+                min_fee,
+            });
+        }
+        Ok(())
+    }
+
+    /// Signs the tx, paying `fee_rate` from the leftover output, if any. Fails if the tx would
+    /// pay less than the min relay fee, which is the same for all networks. Output values
+    /// are only checked to be non-negative; use [`TxBuilder::sign_for_network`] to also check
+    /// them against the max money of a network.
+    ///
+    /// The leftover is dropped if it is below `dust_limit` or the dust threshold of its script
+    /// at the min relay fee, see [`Network::dust_amount_for_script`].
     pub fn sign(
+        self,
+        ecc: &dyn Ecc,
+        fee_rate: FeeRate,
+        dust_limit: i64,
+    ) -> std::result::Result<UnhashedTx, BitcoinSuiteError> {
+        self.sign_with_dust_limit(ecc, fee_rate, Network::XEC, None, |script| {
+            dust_limit.max(Network::XEC.dust_amount_for_script(script))
+        })
+    }
+
+    /// Like [`TxBuilder::sign`], with the fee rate given as a bare number of sats per kB.
+    #[deprecated(note = "use TxBuilder::sign with FeeRate::per_kb or FeeRate::per_vbyte")]
+    pub fn sign_fee_per_kb(
         self,
         ecc: &dyn Ecc,
         fee_per_kb: i64,
         dust_limit: i64,
     ) -> std::result::Result<UnhashedTx, BitcoinSuiteError> {
-        let input_sum = self.input_sum();
+        self.sign(ecc, FeeRate::per_kb(fee_per_kb), dust_limit)
+    }
+
+    /// Like [`TxBuilder::sign`], but dust is determined by the size of each output's script, see
+    /// [`Network::dust_amount_for_script`]: a dust leftover is dropped, and dust fixed outputs
+    /// fail signing, as the node wouldn't relay the tx. Outputs above the network's
+    /// [`NetworkParams::max_money`] fail signing too.
+    pub fn sign_for_network(
+        self,
+        ecc: &dyn Ecc,
+        fee_rate: FeeRate,
+        network: Network,
+    ) -> std::result::Result<UnhashedTx, BitcoinSuiteError> {
+        for (idx, builder_output) in self.outputs.iter().enumerate() {
+            if let TxBuilderOutput::Fixed(output) = builder_output {
+                if output.is_dust(network) {
+                    return Err(TxBuilderError::DustOutput {
+                        idx,
+                        value: output.value,
+                        threshold: network.dust_amount_for_script(&output.script),
+                    }
+                    .into());
+                }
+            }
+        }
+        // Max money doesn't depend on the net
+        let params = network.params(Net::Mainnet);
+        self.sign_with_dust_limit(ecc, fee_rate, network, Some(&params), |script| {
+            network.dust_amount_for_script(script)
+        })
+    }
+
+    fn sign_with_dust_limit(
+        self,
+        ecc: &dyn Ecc,
+        fee_rate: FeeRate,
+        network: Network,
+        params: Option<&NetworkParams>,
+        dust_limit: impl Fn(&Script) -> i64,
+    ) -> std::result::Result<UnhashedTx, BitcoinSuiteError> {
+        if let Some(err) = (0..self.inputs.len()).find_map(|idx| self.input_not_ready(idx)) {
+            return Err(err.into());
+        }
+        let external_script_sizes = self
+            .inputs
+            .iter()
+            .map(|input| input.external_script_size)
+            .collect::<Vec<_>>();
         let (inputs, signatories): (Vec<_>, Vec<_>) = self
             .inputs
             .into_iter()
             .map(|input| (input.input, input.signatory))
             .unzip();
         let (fixed_output_sum, leftover_idx, mut outputs) = Self::prepare_outputs(self.outputs)?;
+        // Inputs have to cover the fixed outputs, which can only be checked if all values are known
+        match Self::input_sum(&inputs) {
+            Ok(input_sum) if input_sum < fixed_output_sum => {
+                return Err(TxBuilderError::InsufficientFunds {
+                    needed: fixed_output_sum.as_i64(),
+                    available: input_sum.as_i64(),
+                }
+                .into());
+            }
+            Ok(_) | Err(TxBuilderError::MissingSignData { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
         // If we have a leftover output, we need to measure the tx size and adjust the outputs
         if let Some(leftover_idx) = leftover_idx {
-            let input_sum = match input_sum {
-                Some(input_sum) => input_sum,
-                None => return Err(SignError::MissingValue.into()),
-            };
-            let mut dummy_unsigned_tx = UnsignedTx::new_dummy(UnhashedTx {
-                version: self.version,
-                inputs: inputs.clone(),
-                outputs,
-                lock_time: self.lock_time,
-            });
+            let input_sum = Self::input_sum(&inputs)?;
+            let mut dummy_unsigned_tx = UnsignedTx::new_dummy(Self::with_external_scripts(
+                &UnhashedTx {
+                    version: self.version,
+                    inputs: inputs.clone(),
+                    outputs,
+                    lock_time: self.lock_time,
+                },
+                &external_script_sizes,
+            ));
             for (input_idx, signatory) in signatories.iter().enumerate() {
                 if let Some(signatory) = signatory {
                     signatory.sign_input(&DummyEcc, dummy_unsigned_tx.input_at(input_idx))?;
                 }
             }
             let mut tx_size = dummy_unsigned_tx.tx().ser().len();
-            let mut tx_fee = tx_size as i64 * fee_per_kb / 1000;
+            let mut tx_fee = Self::tx_fee(tx_size, fee_rate)?;
             let mut new_outputs = dummy_unsigned_tx.into_tx().outputs;
+            let needed = fixed_output_sum
+                .checked_add(tx_fee)
+                .ok_or(TxBuilderError::FeeOverflow)?;
             // inputs cannot pay for a dust leftover -> remove
-            let leftover_value = input_sum - (fixed_output_sum + tx_fee);
-            if leftover_value < dust_limit {
+            let leftover_value = input_sum
+                .checked_sub(needed)
+                .ok_or(TxBuilderError::FeeOverflow)?;
+            if leftover_value.as_i64() < dust_limit(&new_outputs[leftover_idx].script) {
                 let output = new_outputs.remove(leftover_idx);
                 tx_size -= output.ser().len();
-                tx_fee = tx_size as i64 * fee_per_kb / 1000;
+                tx_fee = Self::tx_fee(tx_size, fee_rate)?;
             } else {
-                new_outputs[leftover_idx].value = leftover_value;
+                new_outputs[leftover_idx].value = leftover_value.as_i64();
             }
-            if input_sum < fixed_output_sum + tx_fee {
+            let max_fee = input_sum
+                .checked_sub(fixed_output_sum)
+                .ok_or(TxBuilderError::FeeOverflow)?;
+            if max_fee < tx_fee {
                 return Err(SignError::InsufficientInputsForFee {
-                    input_sum,
-                    max_fee: input_sum - fixed_output_sum,
-                    required_fee: tx_fee,
+                    input_sum: input_sum.as_i64(),
+                    max_fee: max_fee.as_i64(),
+                    required_fee: tx_fee.as_i64(),
                 }
                 .into());
             }
             outputs = new_outputs;
         }
+        if self.is_bip69 {
+            outputs.sort_by(bip69_output_cmp);
+        }
+        for (idx, output) in outputs.iter().enumerate() {
+            let value = Satoshi::new(output.value);
+            match params {
+                Some(params) => value.check_output_value(params)?,
+                None => value.check_non_negative()?,
+            };
+            if output.burns_value() {
+                return Err(TxBuilderError::UnspendableOutputWithValue {
+                    idx,
+                    value: output.value,
+                }
+                .into());
+            }
+        }
         let mut unsigned_tx = UnsignedTx::new(UnhashedTx {
             version: self.version,
             inputs,
@@ -140,15 +396,28 @@ impl TxBuilder {
                 signatory.sign_input(ecc, unsigned_tx.input_at(input_idx))?;
             }
         }
-        Ok(unsigned_tx.into_tx())
+        let tx = unsigned_tx.into_tx();
+        Self::check_min_relay_fee(&tx, &external_script_sizes, &network)?;
+        Ok(tx)
     }
 }
 
+/// Order of outputs specified by BIP69: by value, then by script.
+fn bip69_output_cmp(a: &TxOutput, b: &TxOutput) -> Ordering {
+    a.value.cmp(&b.value).then_with(|| {
+        a.script
+            .bytecode()
+            .as_ref()
+            .cmp(b.script.bytecode().as_ref())
+    })
+}
+
 impl TxBuilderInput {
     pub fn new(input: TxInput, signatory: Box<dyn Signatory>) -> Self {
         TxBuilderInput {
             input,
             signatory: Some(signatory),
+            external_script_size: None,
         }
     }
 
@@ -156,9 +425,28 @@ impl TxBuilderInput {
         TxBuilderInput {
             input,
             signatory: None,
+            external_script_size: None,
+        }
+    }
+
+    /// Input signed by another party, e.g. after passing the tx on as a [`PartialTx`]. Signing
+    /// leaves its input script as it is, empty or pre-filled, instead of failing for the missing
+    /// signatory. `script_size` is the size of the input script its signer will provide, which
+    /// is used to pay the fee for it.
+    ///
+    /// [`PartialTx`]: crate::PartialTx
+    pub fn external(input: TxInput, script_size: usize) -> Self {
+        TxBuilderInput {
+            input,
+            signatory: None,
+            external_script_size: Some(script_size),
         }
     }
 
+    pub fn is_external(&self) -> bool {
+        self.external_script_size.is_some()
+    }
+
     pub fn signatory(&self) -> &Option<Box<dyn Signatory>> {
         &self.signatory
     }
@@ -176,13 +464,53 @@ impl TxBuilderInput {
     }
 }
 
+/// Builds and signs a tx sending the P2SH output `prev` to `dest`, paying `fee_rate` from the
+/// spent value. `inner_signatory` has to satisfy `redeem_script`, which has to hash to the
+/// script of `prev`; see [`P2SHSignatory`].
+pub fn p2sh_spend(
+    ecc: &dyn Ecc,
+    redeem_script: Script,
+    inner_signatory: Box<dyn Signatory>,
+    prev: Utxo,
+    dest: Script,
+    fee_rate: FeeRate,
+    network: Network,
+) -> std::result::Result<UnhashedTx, BitcoinSuiteError> {
+    if prev.script != redeem_script.to_p2sh() {
+        return Err(TxBuilderError::RedeemScriptMismatch.into());
+    }
+    let mut tx_builder = TxBuilder {
+        version: 1,
+        ..Default::default()
+    };
+    tx_builder.inputs.push(TxBuilderInput::new(
+        TxInput {
+            prev_out: prev.outpoint,
+            script: Script::default(),
+            sequence: SequenceNo::finalized(),
+            sign_data: Some(SignData::new(vec![
+                SignField::Value(prev.value),
+                SignField::OutputScript(prev.script),
+            ])),
+        },
+        Box::new(P2SHSignatory {
+            redeem_script,
+            inner_signatory,
+        }),
+    ));
+    tx_builder.outputs.push(TxBuilderOutput::Leftover(dest));
+    tx_builder.sign_for_network(ecc, fee_rate, network)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         ecc::{DummyEcc, Ecc},
-        BitcoinCode, BitcoinSuiteError, OutPoint, Result, Script, SequenceNo, SignData, SignError,
-        SignField, Signatory, TxBuilder, TxBuilderOutput, TxInput, TxOutput, UnhashedTx,
-        UnsignedTxInput,
+        opcode::{OP_EQUAL, OP_HASH160},
+        p2sh_spend, BitcoinCode, BitcoinSuiteError, FeeRate, Hashed, LockTime, Net, Network,
+        OutPoint, PartialTx, Result, Script, ScriptBuilder, SequenceNo, Sha256d, ShaRmd160,
+        SignData, SignError, SignField, Signatory, TxBuilder, TxBuilderError, TxBuilderInput,
+        TxBuilderOutput, TxInput, TxOutput, UnhashedTx, UnsignedTxInput, Utxo,
     };
 
     pub struct ConstSignatory(Script);
@@ -193,6 +521,14 @@ mod tests {
         }
     }
 
+    /// Sign data with a value and the output script signatories need.
+    fn sign_data(value: i64) -> Option<SignData> {
+        Some(SignData::new(vec![
+            SignField::Value(value),
+            SignField::OutputScript(Script::default()),
+        ]))
+    }
+
     #[test]
     fn test_sign() -> Result<()> {
         let tx = UnhashedTx {
@@ -201,7 +537,9 @@ mod tests {
                 prev_out: OutPoint::default(),
                 script: Script::default(),
                 sequence: SequenceNo::finalized(),
-                sign_data: None,
+                sign_data: Some(SignData::new(vec![SignField::OutputScript(
+                    Script::default(),
+                )])),
             }],
             outputs: vec![TxOutput::default()],
             lock_time: 0,
@@ -211,14 +549,14 @@ mod tests {
             let mut tx_builder = TxBuilder::from_tx(tx.clone());
             let script = Script::from_slice(&[0x01, 0x51]);
             *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script.clone())));
-            let signed_tx = tx_builder.sign(&DummyEcc, 1000, 546)?;
+            let signed_tx = tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
             assert_eq!(signed_tx.inputs[0].script, script);
             assert_eq!(signed_tx.outputs, vec![TxOutput::default()]);
         }
         {
             // Leftover
             let mut tx = tx.clone();
-            tx.inputs[0].sign_data = Some(SignData::new(vec![SignField::Value(10000)]));
+            tx.inputs[0].sign_data = sign_data(10000);
             tx.outputs[0].value = 2000;
             let mut tx_builder = TxBuilder::from_tx(tx);
             let leftover_script = Script::from_slice(&[52]).to_p2sh();
@@ -227,7 +565,7 @@ mod tests {
                 .push(TxBuilderOutput::Leftover(leftover_script.clone()));
             let script = Script::from_slice(&[0; 8]);
             *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script.clone())));
-            let signed_tx = tx_builder.sign(&DummyEcc, 1000, 546)?;
+            let signed_tx = tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
             let tx_size = signed_tx.ser().len();
             assert_eq!(tx_size, 100);
             assert_eq!(signed_tx.inputs[0].script, script);
@@ -245,10 +583,31 @@ mod tests {
                 ]
             );
         }
+        {
+            // Leftover paying 2 sats per byte, also via the deprecated integer adapter
+            let mut tx = tx.clone();
+            tx.inputs[0].sign_data = sign_data(10000);
+            tx.outputs[0].value = 2000;
+            let build = || {
+                let mut tx_builder = TxBuilder::from_tx(tx.clone());
+                tx_builder
+                    .outputs
+                    .push(TxBuilderOutput::Leftover(Script::default()));
+                *tx_builder.inputs[0].signatory_mut() =
+                    Some(Box::new(ConstSignatory(Script::from_slice(&[0; 8]))));
+                tx_builder
+            };
+            let signed_tx = build().sign(&DummyEcc, FeeRate::per_vbyte(2), 546)?;
+            let tx_size = signed_tx.ser().len() as i64;
+            assert_eq!(signed_tx.outputs[1].value, 8000 - 2 * tx_size);
+            #[allow(deprecated)]
+            let signed_tx_per_kb = build().sign_fee_per_kb(&DummyEcc, 2000, 546)?;
+            assert_eq!(signed_tx_per_kb, signed_tx);
+        }
         {
             // Leftover = dust
             let mut tx = tx.clone();
-            tx.inputs[0].sign_data = Some(SignData::new(vec![SignField::Value(10000)]));
+            tx.inputs[0].sign_data = sign_data(10000);
             tx.outputs[0].value = 9400;
             let mut tx_builder = TxBuilder::from_tx(tx);
             let leftover_script = Script::from_slice(&[52]).to_p2sh();
@@ -257,7 +616,7 @@ mod tests {
                 .push(TxBuilderOutput::Leftover(leftover_script.clone()));
             let script = Script::from_slice(&[0; 8]);
             *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script.clone())));
-            let signed_tx = tx_builder.sign(&DummyEcc, 1000, 500)?;
+            let signed_tx = tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 500)?;
             let tx_size = signed_tx.ser().len();
             assert_eq!(tx_size, 100);
             assert_eq!(signed_tx.inputs[0].script, script);
@@ -278,7 +637,7 @@ mod tests {
         {
             // Leftover < dust
             let mut tx = tx.clone();
-            tx.inputs[0].sign_data = Some(SignData::new(vec![SignField::Value(10000)]));
+            tx.inputs[0].sign_data = sign_data(10000);
             tx.outputs[0].value = 9401;
             let mut tx_builder = TxBuilder::from_tx(tx);
             let leftover_script = Script::from_slice(&[52]).to_p2sh();
@@ -287,7 +646,7 @@ mod tests {
                 .push(TxBuilderOutput::Leftover(leftover_script));
             let script = Script::from_slice(&[0; 8]);
             *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script.clone())));
-            let signed_tx = tx_builder.sign(&DummyEcc, 1000, 500)?;
+            let signed_tx = tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 500)?;
             let tx_size = signed_tx.ser().len();
             assert_eq!(tx_size, 68);
             assert_eq!(signed_tx.inputs[0].script, script);
@@ -299,6 +658,16 @@ mod tests {
                 },]
             );
         }
+        {
+            // Error: missing signatory
+            let tx_builder = TxBuilder::from_tx(tx.clone());
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+                Err(BitcoinSuiteError::TxBuilder(TxBuilderError::MissingSignatory {
+                    input_idx: 0,
+                })) => {}
+                result => panic!("Unexpected: {:?}", result),
+            }
+        }
         {
             // Error: missing value
             let mut tx_builder = TxBuilder::from_tx(tx.clone());
@@ -306,15 +675,35 @@ mod tests {
             tx_builder
                 .outputs
                 .push(TxBuilderOutput::Leftover(leftover_script));
-            match tx_builder.sign(&DummyEcc, 1000, 546) {
-                Err(BitcoinSuiteError::Sign(SignError::MissingValue)) => {}
+            let script = Script::from_slice(&[0; 8]);
+            *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script)));
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+                Err(BitcoinSuiteError::TxBuilder(TxBuilderError::MissingSignData {
+                    input_idx: 0,
+                })) => {}
+                result => panic!("Unexpected: {:?}", result),
+            }
+        }
+        {
+            // Error: fee overflow
+            let mut tx = tx.clone();
+            tx.inputs[0].sign_data = sign_data(10000);
+            let mut tx_builder = TxBuilder::from_tx(tx);
+            let leftover_script = Script::from_slice(&[52]).to_p2sh();
+            tx_builder
+                .outputs
+                .push(TxBuilderOutput::Leftover(leftover_script));
+            let script = Script::from_slice(&[0; 8]);
+            *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script)));
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(i64::MAX), 546) {
+                Err(BitcoinSuiteError::TxBuilder(TxBuilderError::FeeOverflow)) => {}
                 result => panic!("Unexpected: {:?}", result),
             }
         }
         {
             // Error: insufficient inputs for fee
             let mut tx = tx.clone();
-            tx.inputs[0].sign_data = Some(SignData::new(vec![SignField::Value(1000)]));
+            tx.inputs[0].sign_data = sign_data(1000);
             tx.outputs[0].value = 999;
             let mut tx_builder = TxBuilder::from_tx(tx);
             let leftover_script = Script::from_slice(&[52]).to_p2sh();
@@ -323,7 +712,7 @@ mod tests {
                 .push(TxBuilderOutput::Leftover(leftover_script));
             let script = Script::from_slice(&[0; 8]);
             *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script)));
-            match tx_builder.sign(&DummyEcc, 1000, 500) {
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 500) {
                 Err(BitcoinSuiteError::Sign(SignError::InsufficientInputsForFee {
                     input_sum: 1000,
                     required_fee: 68,
@@ -332,10 +721,67 @@ mod tests {
                 result => panic!("Unexpected: {:?}", result),
             }
         }
+        for has_leftover in [false, true] {
+            // Error: inputs can't pay for the fixed outputs, with or without leftover
+            let mut tx = tx.clone();
+            tx.inputs[0].sign_data = sign_data(1000);
+            tx.outputs[0].value = 2000;
+            let mut tx_builder = TxBuilder::from_tx(tx);
+            if has_leftover {
+                tx_builder
+                    .outputs
+                    .push(TxBuilderOutput::Leftover(Script::default()));
+            }
+            let script = Script::from_slice(&[0; 8]);
+            *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script)));
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+                Err(BitcoinSuiteError::TxBuilder(TxBuilderError::InsufficientFunds {
+                    needed: 2000,
+                    available: 1000,
+                })) => {}
+                result => panic!("Unexpected: {:?}", result),
+            }
+        }
+        {
+            // Error: fixed outputs leave less than the min relay fee
+            let mut tx = tx.clone();
+            tx.inputs[0].sign_data = sign_data(1000);
+            tx.outputs[0].value = 990;
+            let mut tx_builder = TxBuilder::from_tx(tx);
+            let script = Script::from_slice(&[0; 8]);
+            *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script)));
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 500) {
+                Err(BitcoinSuiteError::TxBuilder(TxBuilderError::BelowMinRelayFee {
+                    shortfall: 58,
+                    min_fee: 68,
+                })) => {}
+                result => panic!("Unexpected: {:?}", result),
+            }
+        }
+        {
+            // Error: fee rate below the min relay fee rate
+            let mut tx = tx.clone();
+            tx.inputs[0].sign_data = sign_data(10000);
+            tx.outputs[0].value = 2000;
+            let mut tx_builder = TxBuilder::from_tx(tx);
+            let leftover_script = Script::from_slice(&[52]).to_p2sh();
+            tx_builder
+                .outputs
+                .push(TxBuilderOutput::Leftover(leftover_script));
+            let script = Script::from_slice(&[0; 8]);
+            *tx_builder.inputs[0].signatory_mut() = Some(Box::new(ConstSignatory(script)));
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(500), 546) {
+                Err(BitcoinSuiteError::TxBuilder(TxBuilderError::BelowMinRelayFee {
+                    shortfall: 50,
+                    min_fee: 100,
+                })) => {}
+                result => panic!("Unexpected: {:?}", result),
+            }
+        }
         {
             // Error: multiple leftover
             let mut tx = tx;
-            tx.inputs[0].sign_data = Some(SignData::new(vec![SignField::Value(10000)]));
+            tx.inputs[0].sign_data = sign_data(10000);
             tx.outputs[0].value = 2000;
             let mut tx_builder = TxBuilder::from_tx(tx);
             let leftover_script = Script::from_slice(&[52]).to_p2sh();
@@ -345,11 +791,504 @@ mod tests {
             tx_builder
                 .outputs
                 .push(TxBuilderOutput::Leftover(leftover_script));
-            match tx_builder.sign(&DummyEcc, 1000, 546) {
+            match tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
                 Err(BitcoinSuiteError::Sign(SignError::MultipleLeftover)) => {}
                 result => panic!("Unexpected: {:?}", result),
             }
         }
         Ok(())
     }
+
+    #[test]
+    fn test_sign_for_network() -> Result<()> {
+        let p2pkh = Script::p2pkh(&ShaRmd160::new([1; 20]));
+        let multisig = Script::multisig(1, [[2; 33].as_ref(), [3; 33].as_ref()]);
+        let build = |leftover_script: &Script| {
+            let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+                version: 1,
+                inputs: vec![TxInput {
+                    sign_data: sign_data(10000),
+                    ..Default::default()
+                }],
+                outputs: vec![TxOutput {
+                    value: 9300,
+                    script: Script::default(),
+                }],
+                lock_time: 0,
+            });
+            tx_builder
+                .outputs
+                .push(TxBuilderOutput::Leftover(leftover_script.clone()));
+            *tx_builder.inputs[0].signatory_mut() =
+                Some(Box::new(ConstSignatory(Script::default())));
+            tx_builder
+        };
+        // P2PKH leftover of 700 - tx size is above its dust threshold of 546
+        let signed_tx =
+            build(&p2pkh).sign_for_network(&DummyEcc, FeeRate::per_kb(1000), Network::XEC)?;
+        assert_eq!(signed_tx.outputs.len(), 2);
+        assert_eq!(signed_tx.outputs[1].script, p2pkh);
+        assert_eq!(
+            signed_tx.outputs[1].value,
+            700 - signed_tx.ser().len() as i64
+        );
+        // Same value is below the larger multisig script's dust threshold of 684
+        let signed_tx =
+            build(&multisig).sign_for_network(&DummyEcc, FeeRate::per_kb(1000), Network::XEC)?;
+        assert_eq!(signed_tx.outputs.len(), 1);
+        // sign drops it as well, its dust limit is only a lower bound
+        let signed_tx = build(&multisig).sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        assert_eq!(signed_tx.outputs.len(), 1);
+        let signed_tx = build(&p2pkh).sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        assert_eq!(signed_tx.outputs.len(), 2);
+        let signed_tx = build(&p2pkh).sign(&DummyEcc, FeeRate::per_kb(1000), 700)?;
+        assert_eq!(signed_tx.outputs.len(), 1);
+
+        // Fixed outputs are checked against the dust threshold of their own script
+        let p2sh = Script::from_slice(&[0x51]).to_p2sh();
+        let build_fixed = |value| {
+            let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+                version: 1,
+                inputs: vec![TxInput {
+                    sign_data: sign_data(10000),
+                    ..Default::default()
+                }],
+                outputs: vec![
+                    TxOutput {
+                        value: 1000,
+                        script: p2pkh.clone(),
+                    },
+                    TxOutput {
+                        value,
+                        script: p2sh.clone(),
+                    },
+                ],
+                lock_time: 0,
+            });
+            *tx_builder.inputs[0].signatory_mut() =
+                Some(Box::new(ConstSignatory(Script::default())));
+            tx_builder
+        };
+        let signed_tx =
+            build_fixed(540).sign_for_network(&DummyEcc, FeeRate::per_kb(1000), Network::XEC)?;
+        assert_eq!(signed_tx.outputs[1].value, 540);
+        match build_fixed(539).sign_for_network(&DummyEcc, FeeRate::per_kb(1000), Network::XEC) {
+            Err(BitcoinSuiteError::TxBuilder(TxBuilderError::DustOutput {
+                idx: 1,
+                value: 539,
+                threshold: 540,
+            })) => {}
+            result => panic!("Unexpected: {:?}", result),
+        }
+        // sign only uses its dust limit for the leftover
+        let signed_tx = build_fixed(539).sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        assert_eq!(signed_tx.outputs[1].value, 539);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_invalid_output_value() -> Result<()> {
+        let build = |value| {
+            let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+                version: 1,
+                inputs: vec![TxInput {
+                    script: Script::from_slice(&[0x51]),
+                    ..Default::default()
+                }],
+                outputs: vec![TxOutput {
+                    value,
+                    script: Script::opreturn(&[b"hello"]),
+                }],
+                lock_time: 0,
+            });
+            tx_builder.outputs.push(TxBuilderOutput::Fixed(TxOutput {
+                value: 10_000,
+                script: Script::p2pkh(&ShaRmd160::new([1; 20])),
+            }));
+            tx_builder
+        };
+        // OP_RETURN outputs aren't checked for dust, but can't be negative or hold any value
+        match build(-1).sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+            Err(BitcoinSuiteError::NegativeAmount(-1)) => {}
+            result => panic!("Unexpected: {:?}", result),
+        }
+        // Max money is taken from the network; sign doesn't know it
+        let too_much = Network::XEC.params(Net::Mainnet).max_money.unwrap() + 1;
+        match build(too_much).sign_for_network(&DummyEcc, FeeRate::per_kb(1000), Network::XEC) {
+            Err(BitcoinSuiteError::AboveMaxMoney { amount, .. }) => assert_eq!(amount, too_much),
+            result => panic!("Unexpected: {:?}", result),
+        }
+        for result in [
+            build(too_much).sign_for_network(&DummyEcc, FeeRate::per_kb(1000), Network::XPI),
+            build(too_much).sign(&DummyEcc, FeeRate::per_kb(1000), 546),
+        ] {
+            match result {
+                Err(BitcoinSuiteError::TxBuilder(TxBuilderError::UnspendableOutputWithValue {
+                    idx: 0,
+                    ..
+                })) => {}
+                result => panic!("Unexpected: {:?}", result),
+            }
+        }
+        match build(1).sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+            Err(BitcoinSuiteError::TxBuilder(TxBuilderError::UnspendableOutputWithValue {
+                idx: 0,
+                value: 1,
+            })) => {}
+            result => panic!("Unexpected: {:?}", result),
+        }
+        build(0).sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_p2sh_spend() -> Result<()> {
+        let preimage = b"secret";
+        let redeem_script = ScriptBuilder::new()
+            .push_opcode(OP_HASH160)
+            .push_data(ShaRmd160::digest(preimage.as_ref().into()).as_slice())
+            .push_opcode(OP_EQUAL)
+            .build();
+        let unlock_script = ScriptBuilder::new().push_data(preimage).build();
+        let dest = Script::p2pkh(&ShaRmd160::new([1; 20]));
+        let prev = Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([2; 32]),
+                out_idx: 3,
+            },
+            script: redeem_script.to_p2sh(),
+            value: 10_000,
+        };
+        let signed_tx = p2sh_spend(
+            &DummyEcc,
+            redeem_script.clone(),
+            Box::new(ConstSignatory(unlock_script.clone())),
+            prev.clone(),
+            dest.clone(),
+            FeeRate::per_kb(1000),
+            Network::XEC,
+        )?;
+        assert_eq!(signed_tx.inputs.len(), 1);
+        assert_eq!(signed_tx.inputs[0].prev_out, prev.outpoint);
+        assert_eq!(
+            signed_tx.inputs[0].script,
+            ScriptBuilder::new()
+                .push_script(&unlock_script)
+                .push_data(redeem_script.bytecode())
+                .build(),
+        );
+        assert_eq!(signed_tx.outputs.len(), 1);
+        assert_eq!(signed_tx.outputs[0].script, dest);
+        assert_eq!(
+            signed_tx.outputs[0].value,
+            10_000 - signed_tx.ser().len() as i64
+        );
+
+        // Spent output isn't the redeem script's P2SH
+        let result = p2sh_spend(
+            &DummyEcc,
+            redeem_script,
+            Box::new(ConstSignatory(unlock_script)),
+            Utxo {
+                script: dest.clone(),
+                ..prev
+            },
+            dest,
+            FeeRate::per_kb(1000),
+            Network::XEC,
+        );
+        match result {
+            Err(BitcoinSuiteError::TxBuilder(TxBuilderError::RedeemScriptMismatch)) => {}
+            _ => panic!("Unexpected result"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_ready() -> Result<()> {
+        let input = |sign_data: Option<SignData>| TxInput {
+            prev_out: OutPoint::default(),
+            script: Script::default(),
+            sequence: SequenceNo::finalized(),
+            sign_data,
+        };
+        let tx = UnhashedTx {
+            version: 1,
+            inputs: vec![
+                input(sign_data(10_000)),
+                input(sign_data(10_000)),
+                input(Some(SignData::new(vec![SignField::OutputScript(
+                    Script::default(),
+                )]))),
+            ],
+            outputs: vec![TxOutput {
+                value: 1000,
+                script: Script::default(),
+            }],
+            lock_time: 0,
+        };
+        let tx_builder = || {
+            let mut tx_builder = TxBuilder::from_tx(tx.clone());
+            for idx in [0, 2] {
+                *tx_builder.inputs[idx].signatory_mut() =
+                    Some(Box::new(ConstSignatory(Script::from_slice(&[0x51]))));
+            }
+            tx_builder
+        };
+
+        // Input 1 has no signatory
+        let builder = tx_builder();
+        assert_eq!(builder.validate_ready(), Err(vec![1]));
+        match builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+            Err(BitcoinSuiteError::TxBuilder(TxBuilderError::MissingSignatory {
+                input_idx: 1,
+            })) => {}
+            result => panic!("Unexpected: {:?}", result),
+        }
+
+        // With a leftover, input 2 also needs a value
+        let mut builder = tx_builder();
+        builder
+            .outputs
+            .push(TxBuilderOutput::Leftover(Script::from_slice(&[0x51])));
+        assert_eq!(builder.validate_ready(), Err(vec![1, 2]));
+        *builder.inputs[1].signatory_mut() =
+            Some(Box::new(ConstSignatory(Script::from_slice(&[0x51]))));
+        assert_eq!(builder.validate_ready(), Err(vec![2]));
+        match builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+            Err(BitcoinSuiteError::TxBuilder(TxBuilderError::MissingSignData { input_idx: 2 })) => {
+            }
+            result => panic!("Unexpected: {:?}", result),
+        }
+
+        // The signatory of input 0 needs the output script as scriptCode
+        let mut builder = tx_builder();
+        builder.inputs[0].input_mut().sign_data =
+            Some(SignData::new(vec![SignField::Value(10_000)]));
+        builder.inputs[1].input_mut().script = Script::from_slice(&[0x51]);
+        assert_eq!(builder.validate_ready(), Err(vec![0]));
+        match builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546) {
+            Err(BitcoinSuiteError::TxBuilder(TxBuilderError::MissingOutputScript {
+                input_idx: 0,
+            })) => {}
+            result => panic!("Unexpected: {:?}", result),
+        }
+
+        // Dust outputs are reported before inputs that aren't ready
+        let mut builder = tx_builder();
+        builder.outputs[0] = TxBuilderOutput::Fixed(TxOutput {
+            value: 1,
+            script: Script::p2pkh(&ShaRmd160::new([1; 20])),
+        });
+        match builder.sign_for_network(&DummyEcc, FeeRate::per_kb(1000), Network::XEC) {
+            Err(BitcoinSuiteError::TxBuilder(TxBuilderError::DustOutput { idx: 0, .. })) => {}
+            result => panic!("Unexpected: {:?}", result),
+        }
+
+        // An input script is enough without a signatory
+        let mut builder = tx_builder();
+        builder.inputs[1].input_mut().script = Script::from_slice(&[0x51]);
+        assert_eq!(builder.validate_ready(), Ok(()));
+        builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_external_input() -> Result<()> {
+        let input = |out_idx| TxInput {
+            prev_out: OutPoint {
+                txid: Sha256d::new([1; 32]),
+                out_idx,
+            },
+            script: Script::default(),
+            sequence: SequenceNo::finalized(),
+            sign_data: sign_data(10_000),
+        };
+        let leftover_script = Script::p2pkh(&ShaRmd160::new([2; 20]));
+        let tx_builder = |external_input: TxBuilderInput| {
+            let mut tx_builder = TxBuilder {
+                version: 1,
+                ..Default::default()
+            };
+            tx_builder.inputs.push(external_input);
+            tx_builder.inputs.push(TxBuilderInput::new(
+                input(1),
+                Box::new(ConstSignatory(Script::from_slice(&[0x51]))),
+            ));
+            tx_builder.outputs.push(TxBuilderOutput::Fixed(TxOutput {
+                value: 5_000,
+                script: Script::p2pkh(&ShaRmd160::new([3; 20])),
+            }));
+            tx_builder
+                .outputs
+                .push(TxBuilderOutput::Leftover(leftover_script.clone()));
+            tx_builder
+        };
+
+        // Pre-filled input script is left intact, the other input is signed
+        let external_script = Script::from_slice(&[0x52; 107]);
+        let mut external_input = input(0);
+        external_input.script = external_script.clone();
+        let builder = tx_builder(TxBuilderInput::external(external_input, 107));
+        assert!(builder.inputs[0].is_external());
+        assert!(!builder.inputs[1].is_external());
+        assert_eq!(builder.validate_ready(), Ok(()));
+        let signed_tx = builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        assert_eq!(signed_tx.inputs[0].script, external_script);
+        assert_eq!(signed_tx.inputs[1].script, Script::from_slice(&[0x51]));
+        assert_eq!(signed_tx.outputs[1].script, leftover_script);
+        assert_eq!(
+            signed_tx.outputs[1].value,
+            15_000 - signed_tx.ser().len() as i64,
+        );
+
+        // Input still to be signed externally pays the fee for its future input script
+        let builder = tx_builder(TxBuilderInput::external(input(0), 107));
+        assert_eq!(builder.validate_ready(), Ok(()));
+        let signed_tx = builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        assert_eq!(signed_tx.inputs[0].script, Script::default());
+        assert_eq!(signed_tx.inputs[1].script, Script::from_slice(&[0x51]));
+        let partial_tx_fee = 15_000 - signed_tx.outputs[1].value;
+        assert_eq!(partial_tx_fee, signed_tx.ser().len() as i64 + 107);
+
+        // The other party then signs its input
+        let mut partial_tx = PartialTx::new(signed_tx);
+        partial_tx.set_signatory(0, Box::new(ConstSignatory(external_script.clone())));
+        let final_tx = partial_tx.finalize(&DummyEcc)?;
+        assert_eq!(final_tx.inputs[0].script, external_script);
+        assert_eq!(final_tx.inputs[1].script, Script::from_slice(&[0x51]));
+        assert_eq!(partial_tx_fee, final_tx.ser().len() as i64);
+
+        // Not marked as external, the empty input script needs a signatory
+        let builder = tx_builder(TxBuilderInput::from_input(input(0)));
+        assert_eq!(builder.validate_ready(), Err(vec![0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_lock_time() -> Result<()> {
+        let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+            version: 1,
+            inputs: vec![
+                TxInput {
+                    sequence: SequenceNo::finalized(),
+                    ..Default::default()
+                },
+                TxInput {
+                    sequence: SequenceNo::from_relative_blocks(10),
+                    ..Default::default()
+                },
+            ],
+            outputs: vec![],
+            lock_time: 0,
+        });
+        tx_builder.set_lock_time(LockTime::Height(700_000))?;
+        assert_eq!(tx_builder.lock_time, 700_000);
+        assert_eq!(
+            tx_builder.inputs[0].input().sequence,
+            SequenceNo::from_u32(0xffff_fffe)
+        );
+        assert_eq!(
+            tx_builder.inputs[1].input().sequence,
+            SequenceNo::from_relative_blocks(10)
+        );
+        tx_builder.set_lock_time(LockTime::Time(1_650_000_000))?;
+        assert_eq!(tx_builder.lock_time, 1_650_000_000);
+        match tx_builder.set_lock_time(LockTime::Time(700_000)) {
+            Err(BitcoinSuiteError::InvalidLockTime(LockTime::Time(700_000))) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert_eq!(tx_builder.lock_time, 1_650_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_bip69() -> Result<()> {
+        // BIP69 test vector: tx 28204cad1d7fc1d199e8ef4fa22f182de6258a3eaafe1bbe56ebdcacd3069a5f
+        let txid = Sha256d::from_hex_display(
+            "35288d269cee1941eaebb2ea85e32b42cdb2b04284a56d8b14dcc3f5c65d6055",
+        )?;
+        let outpoint0 = OutPoint {
+            txid: txid.clone(),
+            out_idx: 0,
+        };
+        let outpoint1 = OutPoint { txid, out_idx: 1 };
+        let output0 = TxOutput {
+            value: 400_057_456,
+            script: Script::from_hex("76a9144a5fba237213a062f6f57978f796390bdcf8d01588ac")?,
+        };
+        let output1 = TxOutput {
+            value: 40_000_000_000,
+            script: Script::from_hex("76a9145be32612930b8323add2212a4ec03c1562084f8488ac")?,
+        };
+        let input = |prev_out: &OutPoint| TxInput {
+            prev_out: prev_out.clone(),
+            script: Script::from_slice(&[0x51]),
+            ..Default::default()
+        };
+        let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+            version: 1,
+            inputs: vec![input(&outpoint1), input(&outpoint0)],
+            outputs: vec![output1.clone(), output0.clone()],
+            lock_time: 0,
+        });
+        tx_builder.sort_bip69();
+        let tx = tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        assert_eq!(tx.inputs, vec![input(&outpoint0), input(&outpoint1)]);
+        assert_eq!(tx.outputs, vec![output0, output1]);
+
+        // Txids are compared in display order, scripts compared if values are equal, and the
+        // leftover output is sorted by its value once it is known
+        let outpoint_a = OutPoint {
+            txid: Sha256d::from_hex_display(
+                "00000000000000000000000000000000000000000000000000000000000000ff",
+            )?,
+            out_idx: 5,
+        };
+        let outpoint_b = OutPoint {
+            txid: Sha256d::from_hex_display(
+                "ff00000000000000000000000000000000000000000000000000000000000000",
+            )?,
+            out_idx: 0,
+        };
+        assert!(outpoint_a < outpoint_b);
+        let funded_input = |prev_out: &OutPoint| TxInput {
+            sign_data: Some(SignData::new(vec![SignField::Value(5_000)])),
+            ..input(prev_out)
+        };
+        let leftover_script = Script::from_slice(&[0x00]);
+        let output_a = TxOutput {
+            value: 1000,
+            script: Script::from_slice(&[0x51]),
+        };
+        let output_b = TxOutput {
+            value: 1000,
+            script: Script::from_slice(&[0x52]),
+        };
+        let output_c = TxOutput {
+            value: 5000,
+            script: Script::from_slice(&[0x51]),
+        };
+        let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+            version: 1,
+            inputs: vec![funded_input(&outpoint_b), funded_input(&outpoint_a)],
+            outputs: vec![output_c.clone(), output_b.clone(), output_a.clone()],
+            lock_time: 0,
+        });
+        tx_builder
+            .outputs
+            .insert(0, TxBuilderOutput::Leftover(leftover_script.clone()));
+        tx_builder.sort_bip69();
+        let tx = tx_builder.sign(&DummyEcc, FeeRate::per_kb(1000), 546)?;
+        assert_eq!(
+            tx.inputs,
+            vec![funded_input(&outpoint_a), funded_input(&outpoint_b)],
+        );
+        let leftover = TxOutput {
+            value: 10_000 - 7_000 - tx.ser().len() as i64,
+            script: leftover_script,
+        };
+        assert_eq!(tx.outputs, vec![output_a, output_b, leftover, output_c]);
+        Ok(())
+    }
 }
@@ -76,6 +76,14 @@ impl<'tx> UnsignedTxInput<'tx> {
         self.idx
     }
 
+    /// Borrow this input for a shorter lifetime, e.g. to pass it to a wrapped signatory.
+    pub fn reborrow(&mut self) -> UnsignedTxInput<'_> {
+        UnsignedTxInput {
+            idx: self.idx,
+            unsigned_tx: self.unsigned_tx,
+        }
+    }
+
     pub fn sighash_preimage(
         &self,
         sig_hash_type: SigHashType,
@@ -0,0 +1,754 @@
+use crate::{
+    ecc::{Ecc, VerifySignatureError, SCHNORR_SIGNATURE_SIZE},
+    opcode::*,
+    ser_script_num, ByteArray, Bytes, Hashed, Op, Ripemd160, Script, ScriptError,
+    ScriptVerifyError, Sha1, Sha256, Sha256d, ShaRmd160, SigHashType, SignData, SignField,
+    UnhashedTx, UnsignedTx, UnsignedTxInput, Utxo,
+};
+
+/// Maximum size of a stack element, which also limits the size of P2SH redeem scripts.
+pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+
+/// Maximum number of pubkeys of an OP_CHECKMULTISIG.
+pub const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+
+/// Maximum number of non-push opcodes of a script, where the pubkeys of an OP_CHECKMULTISIG
+/// count as one opcode each.
+pub const MAX_OPS_PER_SCRIPT: usize = 201;
+
+/// Maximum number of items on the stack and alt stack combined.
+pub const MAX_STACK_SIZE: usize = 1000;
+
+/// Verify offline that every input script of the signed `tx` satisfies the output script it
+/// spends. `prevouts` are the outputs spent by the inputs of `tx`, in the same order.
+///
+/// This only covers standard templates: P2PK, P2PKH, bare and P2SH multisig (legacy and
+/// Schnorr mode), and scripts built from pushes, flow control, stack, equality, hashing and
+/// signature opcodes, including OP_CHECKDATASIG. Any other opcode fails with
+/// [`ScriptError::UnsupportedOpcode`]. Like the node's standardness rules, input scripts must be
+/// push-only, must leave a clean stack, and failing signatures must be empty. Pushes and script
+/// numbers must be minimally encoded, and the [`MAX_OPS_PER_SCRIPT`] and [`MAX_STACK_SIZE`]
+/// limits apply.
+pub fn verify_tx(
+    tx: &UnhashedTx,
+    prevouts: &[Utxo],
+    ecc: &dyn Ecc,
+) -> std::result::Result<(), ScriptVerifyError> {
+    if tx.inputs.len() != prevouts.len() {
+        return Err(ScriptVerifyError::PrevoutsMismatch {
+            num_inputs: tx.inputs.len(),
+            num_prevouts: prevouts.len(),
+        });
+    }
+    let mut unsigned_tx = UnsignedTx::new(tx.clone());
+    for (input_idx, (input, prevout)) in tx.inputs.iter().zip(prevouts).enumerate() {
+        if input.prev_out != prevout.outpoint {
+            return Err(ScriptVerifyError::WrongPrevout {
+                input_idx,
+                expected: input.prev_out.clone(),
+                actual: prevout.outpoint.clone(),
+            });
+        }
+        let mut interpreter = Interpreter {
+            input: unsigned_tx.input_at(input_idx),
+            value: prevout.value,
+            ecc,
+            stack: Vec::new(),
+            num_ops: 0,
+        };
+        interpreter
+            .verify(&input.script, &prevout.script)
+            .map_err(|error| ScriptVerifyError::InputFailed { input_idx, error })?;
+    }
+    Ok(())
+}
+
+struct Interpreter<'tx, 'ecc> {
+    input: UnsignedTxInput<'tx>,
+    value: i64,
+    ecc: &'ecc dyn Ecc,
+    stack: Vec<Bytes>,
+    num_ops: usize,
+}
+
+type Result<T> = std::result::Result<T, ScriptError>;
+
+impl Interpreter<'_, '_> {
+    fn verify(&mut self, input_script: &Script, output_script: &Script) -> Result<()> {
+        for op in input_script.ops() {
+            match op.map_err(|_| ScriptError::InvalidScriptEncoding)? {
+                Op::Push(..) | Op::Code(OP_0 | OP_1NEGATE | OP_1..=OP_16) => {}
+                Op::Code(_) => return Err(ScriptError::InputScriptNotPushOnly),
+            }
+        }
+        self.eval(input_script)?;
+        let p2sh_stack = self.stack.clone();
+        self.eval(output_script)?;
+        self.check_top_true()?;
+        if output_script.is_p2sh() {
+            self.stack = p2sh_stack;
+            let redeem_script = Script::new(self.pop(OP_HASH160)?);
+            self.eval(&redeem_script)?;
+            self.check_top_true()?;
+        }
+        if self.stack.len() != 1 {
+            return Err(ScriptError::CleanStack {
+                stack_size: self.stack.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, script: &Script) -> Result<()> {
+        let mut exec_stack = Vec::<bool>::new();
+        let mut alt_stack = Vec::new();
+        let mut num_codeseps = 0;
+        let mut codesep_idx = None;
+        self.num_ops = 0;
+        for op in script.ops() {
+            let op = op.map_err(|_| ScriptError::InvalidScriptEncoding)?;
+            let is_executing = exec_stack.iter().all(|&is_executed| is_executed);
+            let opcode = match op {
+                Op::Push(push_op, data) => {
+                    if data.len() > MAX_SCRIPT_ELEMENT_SIZE {
+                        return Err(ScriptError::PushTooLarge { size: data.len() });
+                    }
+                    if is_executing {
+                        if !is_minimal_push(push_op, &data) {
+                            return Err(ScriptError::NonMinimalPush);
+                        }
+                        self.stack.push(data);
+                        self.check_stack_size(&alt_stack)?;
+                    }
+                    continue;
+                }
+                Op::Code(opcode) => opcode,
+            };
+            if opcode > OP_16 {
+                self.count_ops(1)?;
+            }
+            match opcode {
+                OP_IF | OP_NOTIF => {
+                    let mut is_executed = false;
+                    if is_executing {
+                        is_executed = cast_to_bool(&self.pop(opcode)?) == (opcode == OP_IF);
+                    }
+                    exec_stack.push(is_executed);
+                }
+                OP_ELSE => {
+                    let is_executed = exec_stack
+                        .last_mut()
+                        .ok_or(ScriptError::UnbalancedConditional)?;
+                    *is_executed = !*is_executed;
+                }
+                OP_ENDIF => {
+                    exec_stack.pop().ok_or(ScriptError::UnbalancedConditional)?;
+                }
+                OP_CODESEPARATOR => {
+                    if is_executing {
+                        codesep_idx = Some(num_codeseps);
+                    }
+                    num_codeseps += 1;
+                }
+                _ if !is_executing => {}
+                _ => self.eval_opcode(opcode, script, codesep_idx, &mut alt_stack)?,
+            }
+            self.check_stack_size(&alt_stack)?;
+        }
+        if !exec_stack.is_empty() {
+            return Err(ScriptError::UnbalancedConditional);
+        }
+        Ok(())
+    }
+
+    fn eval_opcode(
+        &mut self,
+        opcode: u8,
+        script: &Script,
+        codesep_idx: Option<usize>,
+        alt_stack: &mut Vec<Bytes>,
+    ) -> Result<()> {
+        match opcode {
+            OP_0 => self.stack.push(Bytes::new()),
+            OP_1NEGATE => self.stack.push(Bytes::from_slice(&[0x81])),
+            OP_1..=OP_16 => self.stack.push(Bytes::from_slice(&[opcode - OP_1 + 1])),
+            OP_NOP => {}
+            OP_VERIFY => self.verify_top(opcode)?,
+            OP_RETURN => return Err(ScriptError::OpReturn),
+
+            OP_TOALTSTACK => {
+                let top = self.pop(opcode)?;
+                alt_stack.push(top);
+            }
+            OP_FROMALTSTACK => {
+                let top = alt_stack.pop().ok_or(ScriptError::StackUnderflow(opcode))?;
+                self.stack.push(top);
+            }
+            OP_2DROP => {
+                self.pop(opcode)?;
+                self.pop(opcode)?;
+            }
+            OP_2DUP => {
+                let a = self.peek(opcode, 2)?;
+                let b = self.peek(opcode, 1)?;
+                self.stack.extend([a, b]);
+            }
+            OP_DEPTH => self.stack.push(ser_script_num(self.stack.len() as i32)),
+            OP_DROP => {
+                self.pop(opcode)?;
+            }
+            OP_DUP => {
+                let top = self.peek(opcode, 1)?;
+                self.stack.push(top);
+            }
+            OP_NIP => {
+                let top = self.pop(opcode)?;
+                self.pop(opcode)?;
+                self.stack.push(top);
+            }
+            OP_OVER => {
+                let second = self.peek(opcode, 2)?;
+                self.stack.push(second);
+            }
+            OP_ROT => {
+                self.peek(opcode, 3)?;
+                let third = self.stack.remove(self.stack.len() - 3);
+                self.stack.push(third);
+            }
+            OP_SWAP => {
+                self.peek(opcode, 2)?;
+                let len = self.stack.len();
+                self.stack.swap(len - 1, len - 2);
+            }
+            OP_TUCK => {
+                let top = self.peek(opcode, 1)?;
+                self.peek(opcode, 2)?;
+                self.stack.insert(self.stack.len() - 2, top);
+            }
+            OP_SIZE => {
+                let size = self.peek(opcode, 1)?.len();
+                self.stack.push(ser_script_num(size as i32));
+            }
+
+            OP_EQUAL | OP_EQUALVERIFY => {
+                let a = self.pop(opcode)?;
+                let b = self.pop(opcode)?;
+                self.push_bool(a == b);
+                if opcode == OP_EQUALVERIFY {
+                    self.verify_top(opcode)?;
+                }
+            }
+
+            OP_RIPEMD160 | OP_SHA1 | OP_SHA256 | OP_HASH160 | OP_HASH256 => {
+                let data = self.pop(opcode)?;
+                self.stack.push(match opcode {
+                    OP_RIPEMD160 => Ripemd160::digest(data).as_slice().into(),
+                    OP_SHA1 => Sha1::digest(data).as_slice().into(),
+                    OP_SHA256 => Sha256::digest(data).as_slice().into(),
+                    OP_HASH160 => ShaRmd160::digest(data).as_slice().into(),
+                    _ => Sha256d::digest(data).as_slice().into(),
+                });
+            }
+
+            OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                let pubkey = self.pop(opcode)?;
+                let sig = self.pop(opcode)?;
+                let is_valid = self.check_sig(&sig, &pubkey, script, codesep_idx)?;
+                if !is_valid && !sig.is_empty() {
+                    return Err(ScriptError::NullFail);
+                }
+                self.push_bool(is_valid);
+                if opcode == OP_CHECKSIGVERIFY {
+                    self.verify_top(opcode)?;
+                }
+            }
+            OP_CHECKDATASIG | OP_CHECKDATASIGVERIFY => {
+                let pubkey = self.pop(opcode)?;
+                let msg = self.pop(opcode)?;
+                let sig = self.pop(opcode)?;
+                let is_valid = !sig.is_empty() && {
+                    let msg = Sha256::digest(msg).byte_array().clone();
+                    self.verify_sig(&sig, &pubkey, msg)?
+                };
+                if !is_valid && !sig.is_empty() {
+                    return Err(ScriptError::NullFail);
+                }
+                self.push_bool(is_valid);
+                if opcode == OP_CHECKDATASIGVERIFY {
+                    self.verify_top(opcode)?;
+                }
+            }
+            OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                let is_valid = self.check_multisig(opcode, script, codesep_idx)?;
+                self.push_bool(is_valid);
+                if opcode == OP_CHECKMULTISIGVERIFY {
+                    self.verify_top(opcode)?;
+                }
+            }
+
+            _ => return Err(ScriptError::UnsupportedOpcode(opcode)),
+        }
+        Ok(())
+    }
+
+    fn check_multisig(
+        &mut self,
+        opcode: u8,
+        script: &Script,
+        codesep_idx: Option<usize>,
+    ) -> Result<bool> {
+        let num_pubkeys = parse_script_num(&self.pop(opcode)?)?;
+        if !(0..=MAX_PUBKEYS_PER_MULTISIG as i64).contains(&num_pubkeys) {
+            return Err(ScriptError::InvalidMultisigCount);
+        }
+        self.count_ops(num_pubkeys as usize)?;
+        let pubkeys = self.pop_n(opcode, num_pubkeys as usize)?;
+        let num_sigs = parse_script_num(&self.pop(opcode)?)?;
+        if !(0..=num_pubkeys).contains(&num_sigs) {
+            return Err(ScriptError::InvalidMultisigCount);
+        }
+        let sigs = self.pop_n(opcode, num_sigs as usize)?;
+        let dummy = self.pop(opcode)?;
+
+        if dummy.is_empty() {
+            // Legacy mode: ECDSA sigs are matched against the pubkeys in order
+            let mut remaining_pubkeys = pubkeys.iter();
+            let mut is_valid = true;
+            for (sig_idx, sig) in sigs.iter().enumerate() {
+                if sig.len() == SCHNORR_SIGNATURE_SIZE + 1 {
+                    return Err(ScriptError::InvalidSignatureFormat);
+                }
+                let mut is_matched = false;
+                while remaining_pubkeys.len() >= sigs.len() - sig_idx {
+                    let pubkey = remaining_pubkeys.next().unwrap();
+                    if self.check_sig(sig, pubkey, script, codesep_idx)? {
+                        is_matched = true;
+                        break;
+                    }
+                }
+                if !is_matched {
+                    is_valid = false;
+                    break;
+                }
+            }
+            if !is_valid && sigs.iter().any(|sig| !sig.is_empty()) {
+                return Err(ScriptError::NullFail);
+            }
+            return Ok(is_valid);
+        }
+
+        // Schnorr mode: the dummy is a bitfield selecting the pubkey of each sig
+        let num_pubkeys = num_pubkeys as usize;
+        if dummy.len() != (num_pubkeys + 7) / 8 {
+            return Err(ScriptError::InvalidMultisigDummy);
+        }
+        let checkbits = dummy
+            .iter()
+            .rev()
+            .fold(0u32, |bits, &byte| (bits << 8) | byte as u32);
+        if checkbits >> num_pubkeys != 0 || checkbits.count_ones() as usize != sigs.len() {
+            return Err(ScriptError::InvalidMultisigDummy);
+        }
+        let selected_pubkeys = pubkeys
+            .iter()
+            .enumerate()
+            .filter(|&(pubkey_idx, _)| checkbits & (1 << pubkey_idx) != 0);
+        for (sig, (_, pubkey)) in sigs.iter().zip(selected_pubkeys) {
+            if sig.len() != SCHNORR_SIGNATURE_SIZE + 1 {
+                return Err(ScriptError::InvalidSignatureFormat);
+            }
+            if !self.check_sig(sig, pubkey, script, codesep_idx)? {
+                return Err(ScriptError::NullFail);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Check a tx signature (with sighash byte), `false` if it is empty or doesn't verify.
+    fn check_sig(
+        &mut self,
+        sig: &[u8],
+        pubkey: &[u8],
+        script: &Script,
+        codesep_idx: Option<usize>,
+    ) -> Result<bool> {
+        let (&sig_hash_byte, sig) = match sig.split_last() {
+            Some(split) => split,
+            None => return Ok(false),
+        };
+        let sig_hash_type = SigHashType::from_byte(sig_hash_byte)
+            .filter(SigHashType::is_bip143)
+            .ok_or(ScriptError::InvalidSigHashType(sig_hash_byte))?;
+        *self.input.input_sign_data_mut() = Some(SignData::new(vec![
+            SignField::Value(self.value),
+            SignField::RedeemScript(script.clone()),
+        ]));
+        let preimage = self.input.sighash_preimage(sig_hash_type, codesep_idx)?;
+        let sighash = Sha256d::digest(preimage.bytes).byte_array().clone();
+        self.verify_sig(sig, pubkey, sighash)
+    }
+
+    /// Verify a Schnorr (64 bytes) or DER encoded ECDSA signature of `msg`.
+    fn verify_sig(&self, sig: &[u8], pubkey: &[u8], msg: ByteArray<32>) -> Result<bool> {
+        let pubkey = self
+            .ecc
+            .pubkey_from_slice(pubkey)
+            .map_err(|_| ScriptError::InvalidPubKey)?;
+        let sig = Bytes::from_slice(sig);
+        let result = if sig.len() == SCHNORR_SIGNATURE_SIZE {
+            self.ecc.schnorr_verify(&pubkey, msg, &sig)
+        } else {
+            self.ecc.verify(&pubkey, msg, &sig)
+        };
+        match result {
+            Ok(()) => Ok(true),
+            Err(VerifySignatureError::IncorrectSignature) => Ok(false),
+            Err(VerifySignatureError::InvalidFormat) => Err(ScriptError::InvalidSignatureFormat),
+        }
+    }
+
+    fn count_ops(&mut self, num_ops: usize) -> Result<()> {
+        self.num_ops += num_ops;
+        if self.num_ops > MAX_OPS_PER_SCRIPT {
+            return Err(ScriptError::OpCountExceeded);
+        }
+        Ok(())
+    }
+
+    fn check_stack_size(&self, alt_stack: &[Bytes]) -> Result<()> {
+        if self.stack.len() + alt_stack.len() > MAX_STACK_SIZE {
+            return Err(ScriptError::StackSizeExceeded);
+        }
+        Ok(())
+    }
+
+    fn check_top_true(&self) -> Result<()> {
+        match self.stack.last() {
+            Some(top) if cast_to_bool(top) => Ok(()),
+            _ => Err(ScriptError::EvalFalse),
+        }
+    }
+
+    fn verify_top(&mut self, opcode: u8) -> Result<()> {
+        if !cast_to_bool(&self.pop(opcode)?) {
+            return Err(ScriptError::VerifyFailed(opcode));
+        }
+        Ok(())
+    }
+
+    fn push_bool(&mut self, value: bool) {
+        self.stack.push(match value {
+            true => Bytes::from_slice(&[1]),
+            false => Bytes::new(),
+        });
+    }
+
+    /// Clone the `depth`-th item from the top of the stack, starting at 1.
+    fn peek(&self, opcode: u8, depth: usize) -> Result<Bytes> {
+        self.stack
+            .len()
+            .checked_sub(depth)
+            .map(|idx| self.stack[idx].clone())
+            .ok_or(ScriptError::StackUnderflow(opcode))
+    }
+
+    fn pop(&mut self, opcode: u8) -> Result<Bytes> {
+        self.stack.pop().ok_or(ScriptError::StackUnderflow(opcode))
+    }
+
+    /// Pop the top `n` items, in the order they were pushed.
+    fn pop_n(&mut self, opcode: u8, n: usize) -> Result<Vec<Bytes>> {
+        let idx = self
+            .stack
+            .len()
+            .checked_sub(n)
+            .ok_or(ScriptError::StackUnderflow(opcode))?;
+        Ok(self.stack.split_off(idx))
+    }
+}
+
+fn cast_to_bool(data: &[u8]) -> bool {
+    match data.split_last() {
+        // Negative zero is false too
+        Some((&last, rest)) => rest.iter().any(|&byte| byte != 0) || (last & 0x7f) != 0,
+        None => false,
+    }
+}
+
+/// Whether `data` is pushed with the smallest possible opcode.
+fn is_minimal_push(push_op: u8, data: &[u8]) -> bool {
+    match data {
+        // These must use OP_0, OP_1..OP_16 and OP_1NEGATE
+        [] | [1..=16] | [0x81] => false,
+        _ if data.len() <= 0x4b => push_op as usize == data.len(),
+        _ if data.len() <= 0xff => push_op == OP_PUSHDATA1,
+        _ if data.len() <= 0xffff => push_op == OP_PUSHDATA2,
+        _ => true,
+    }
+}
+
+fn parse_script_num(data: &[u8]) -> Result<i64> {
+    if data.len() > 4 {
+        return Err(ScriptError::InvalidNumber);
+    }
+    let (&last, rest) = match data.split_last() {
+        Some(split) => split,
+        None => return Ok(0),
+    };
+    // The last byte may only be 0x00 or 0x80 if it's needed for the sign bit
+    if last & 0x7f == 0 && rest.last().map_or(true, |&byte| byte & 0x80 == 0) {
+        return Err(ScriptError::InvalidNumber);
+    }
+    let abs = data
+        .iter()
+        .rev()
+        .fold(0i64, |num, &byte| (num << 8) | byte as i64)
+        & !(0x80 << (8 * (data.len() - 1)));
+    Ok(if last & 0x80 != 0 { -abs } else { abs })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ecc::DummyEcc, opcode::*, verify_tx, Bytes, Hashed, OutPoint, Script, ScriptBuilder,
+        ScriptError, ScriptVerifyError, Sha256, Sha256d, TxInput, TxOutput, UnhashedTx, Utxo,
+    };
+
+    use super::{cast_to_bool, parse_script_num, MAX_OPS_PER_SCRIPT, MAX_STACK_SIZE};
+
+    fn spend(input_script: Script, output_script: Script) -> (UnhashedTx, Vec<Utxo>) {
+        let outpoint = OutPoint {
+            txid: Sha256d::new([1; 32]),
+            out_idx: 2,
+        };
+        let tx = UnhashedTx {
+            version: 1,
+            inputs: vec![TxInput {
+                prev_out: outpoint.clone(),
+                script: input_script,
+                ..Default::default()
+            }],
+            outputs: vec![TxOutput::default()],
+            lock_time: 0,
+        };
+        let prevouts = vec![Utxo {
+            outpoint,
+            script: output_script,
+            value: 10_000,
+        }];
+        (tx, prevouts)
+    }
+
+    fn verify(input_script: Script, output_script: Script) -> Result<(), ScriptVerifyError> {
+        let (tx, prevouts) = spend(input_script, output_script);
+        verify_tx(&tx, &prevouts, &DummyEcc)
+    }
+
+    fn input_error(error: ScriptError) -> Result<(), ScriptVerifyError> {
+        Err(ScriptVerifyError::InputFailed {
+            input_idx: 0,
+            error,
+        })
+    }
+
+    #[test]
+    fn test_verify_tx_hash_lock() {
+        let preimage = b"preimage";
+        let hash_lock = ScriptBuilder::new()
+            .push_opcode(OP_SHA256)
+            .push_data(Sha256::digest(Bytes::from_slice(preimage)).as_slice())
+            .push_opcode(OP_EQUAL)
+            .build();
+        let push = |data: &[u8]| ScriptBuilder::new().push_data(data).build();
+        assert_eq!(verify(push(preimage), hash_lock.clone()), Ok(()));
+        assert_eq!(
+            verify(push(b"wrong"), hash_lock.clone()),
+            input_error(ScriptError::EvalFalse),
+        );
+        // Same hash lock, wrapped in P2SH
+        let p2sh_spend = ScriptBuilder::new()
+            .push_data(preimage)
+            .push_data(hash_lock.bytecode())
+            .build();
+        assert_eq!(verify(p2sh_spend, hash_lock.to_p2sh()), Ok(()));
+        assert_eq!(
+            verify(push(hash_lock.bytecode()), hash_lock.to_p2sh()),
+            input_error(ScriptError::StackUnderflow(OP_SHA256)),
+        );
+    }
+
+    #[test]
+    fn test_verify_tx_policy() {
+        let op_true = Script::from_slice(&[OP_1]);
+        assert_eq!(verify(Script::default(), op_true.clone()), Ok(()));
+        assert_eq!(
+            verify(Script::from_slice(&[OP_1]), op_true.clone()),
+            input_error(ScriptError::CleanStack { stack_size: 2 }),
+        );
+        assert_eq!(
+            verify(Script::from_slice(&[OP_1, OP_DUP]), op_true.clone()),
+            input_error(ScriptError::InputScriptNotPushOnly),
+        );
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&[OP_0])),
+            input_error(ScriptError::EvalFalse),
+        );
+        assert_eq!(
+            verify(Script::default(), Script::opreturn(&[b"hello"])),
+            input_error(ScriptError::OpReturn),
+        );
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&[OP_1, OP_1ADD])),
+            input_error(ScriptError::UnsupportedOpcode(OP_1ADD)),
+        );
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&[OP_1, OP_PUSHDATA1])),
+            input_error(ScriptError::InvalidScriptEncoding),
+        );
+    }
+
+    #[test]
+    fn test_verify_tx_flow_control() {
+        // Only the taken branch is executed, so the OP_RETURN is skipped
+        let script = Script::from_slice(&[OP_IF, OP_RETURN, OP_ELSE, OP_2, OP_ENDIF]);
+        assert_eq!(verify(Script::from_slice(&[OP_0]), script.clone()), Ok(()));
+        assert_eq!(
+            verify(Script::from_slice(&[OP_1]), script),
+            input_error(ScriptError::OpReturn),
+        );
+        assert_eq!(
+            verify(
+                Script::from_slice(&[OP_1]),
+                Script::from_slice(&[OP_IF, OP_1]),
+            ),
+            input_error(ScriptError::UnbalancedConditional),
+        );
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&[OP_1, OP_VERIFY])),
+            input_error(ScriptError::EvalFalse),
+        );
+        assert_eq!(
+            verify(
+                Script::default(),
+                Script::from_slice(&[OP_1, OP_2, OP_EQUALVERIFY, OP_1]),
+            ),
+            input_error(ScriptError::VerifyFailed(OP_EQUALVERIFY)),
+        );
+        // Empty sigs make OP_CHECKSIG fail without checking the pubkey
+        assert_eq!(
+            verify(
+                Script::from_slice(&[OP_0, OP_0]),
+                Script::from_slice(&[OP_CHECKSIG, OP_NOTIF, OP_1, OP_ENDIF, OP_1]),
+            ),
+            input_error(ScriptError::CleanStack { stack_size: 2 }),
+        );
+    }
+
+    #[test]
+    fn test_verify_tx_prevouts() {
+        let (mut tx, prevouts) = spend(Script::default(), Script::from_slice(&[OP_1]));
+        assert_eq!(
+            verify_tx(&tx, &[], &DummyEcc),
+            Err(ScriptVerifyError::PrevoutsMismatch {
+                num_inputs: 1,
+                num_prevouts: 0,
+            }),
+        );
+        tx.inputs[0].prev_out.out_idx = 3;
+        assert_eq!(
+            verify_tx(&tx, &prevouts, &DummyEcc),
+            Err(ScriptVerifyError::WrongPrevout {
+                input_idx: 0,
+                expected: tx.inputs[0].prev_out.clone(),
+                actual: prevouts[0].outpoint.clone(),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_script_num() {
+        assert!(!cast_to_bool(&[]));
+        assert!(!cast_to_bool(&[0, 0]));
+        assert!(!cast_to_bool(&[0, 0x80]));
+        assert!(cast_to_bool(&[0, 1]));
+        assert!(cast_to_bool(&[0x81]));
+        assert_eq!(parse_script_num(&[]), Ok(0));
+        assert_eq!(parse_script_num(&[3]), Ok(3));
+        assert_eq!(parse_script_num(&[0x81]), Ok(-1));
+        assert_eq!(parse_script_num(&[0x80, 0x00]), Ok(128));
+        assert_eq!(
+            parse_script_num(&[0xff, 0xff, 0xff, 0xff]),
+            Ok(-0x7fff_ffff)
+        );
+        assert_eq!(parse_script_num(&[0; 5]), Err(ScriptError::InvalidNumber),);
+        assert_eq!(parse_script_num(&[3, 0]), Err(ScriptError::InvalidNumber));
+        assert_eq!(parse_script_num(&[0x80]), Err(ScriptError::InvalidNumber));
+    }
+
+    #[test]
+    fn test_verify_tx_minimal_push() {
+        let op_true = Script::from_slice(&[OP_1]);
+        let push = |push_op: u8, data: &[u8]| Script::from_slice(&[&[push_op], data].concat());
+        assert_eq!(verify(push(2, &[1, 2]), op_true.clone()), Ok(()));
+        let non_minimal = [
+            push(OP_PUSHDATA1, &[2, 1, 2]),
+            push(1, &[5]),
+            push(1, &[0x81]),
+            push(OP_PUSHDATA1, &[0]),
+        ];
+        for input_script in non_minimal {
+            assert_eq!(
+                verify(input_script, op_true.clone()),
+                input_error(ScriptError::NonMinimalPush),
+            );
+        }
+        // Pushes in branches that aren't executed aren't checked
+        let mut script = vec![OP_0, OP_IF, 1, 5, OP_ENDIF, OP_1];
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&script)),
+            Ok(())
+        );
+        script[0] = OP_1;
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&script)),
+            input_error(ScriptError::NonMinimalPush),
+        );
+    }
+
+    #[test]
+    fn test_verify_tx_limits() {
+        let op_true = Script::from_slice(&[OP_1]);
+        // OP_NOP counts towards the op limit, OP_1 doesn't
+        let mut script = vec![OP_NOP; MAX_OPS_PER_SCRIPT];
+        script.push(OP_1);
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&script)),
+            Ok(())
+        );
+        script.push(OP_NOP);
+        assert_eq!(
+            verify(Script::default(), Script::from_slice(&script)),
+            input_error(ScriptError::OpCountExceeded),
+        );
+        // The stack carries over from the input script
+        let pushes = |num_items: usize| Script::from_slice(&vec![OP_1; num_items]);
+        assert_eq!(
+            verify(pushes(MAX_STACK_SIZE - 1), op_true.clone()),
+            input_error(ScriptError::CleanStack {
+                stack_size: MAX_STACK_SIZE,
+            }),
+        );
+        assert_eq!(
+            verify(pushes(MAX_STACK_SIZE), op_true),
+            input_error(ScriptError::StackSizeExceeded),
+        );
+        // Items on the alt stack count too
+        assert_eq!(
+            verify(
+                pushes(MAX_STACK_SIZE - 1),
+                Script::from_slice(&[OP_TOALTSTACK, OP_1, OP_1]),
+            ),
+            input_error(ScriptError::StackSizeExceeded),
+        );
+    }
+}
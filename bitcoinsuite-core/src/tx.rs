@@ -1,6 +1,11 @@
+use std::{fmt::Display, io::Write, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    get_merkle_root_and_height, BitcoinCode, Bytes, BytesMut, Hashed, MerkleMode, Result, Script,
-    SequenceNo, Sha256d, SignData,
+    encoding::compact_size_len, get_merkle_root_and_height, BitcoinCode, BitcoinSuiteError, Bytes,
+    BytesMut, Hashed, MerkleMode, Network, Result, Script, SequenceNo, Sha256d, SignData,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -19,6 +24,7 @@ pub struct Tx {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct OutPoint {
     pub txid: Sha256d,
     pub out_idx: u32,
@@ -57,6 +63,74 @@ impl UnhashedTx {
             raw,
         }
     }
+
+    /// Parses a raw tx, which must span all of `raw`.
+    pub fn from_raw(raw: &Bytes) -> Result<Self> {
+        let mut data = raw.clone();
+        let tx = UnhashedTx::deser(&mut data)?;
+        if !data.is_empty() {
+            return Err(BitcoinSuiteError::TrailingBytes(data.len()));
+        }
+        Ok(tx)
+    }
+
+    pub fn txid(&self) -> Sha256d {
+        Sha256d::digest(self.ser())
+    }
+
+    /// Whether any input signals replaceability, see [`SequenceNo::is_rbf_signaling`].
+    pub fn signals_rbf(&self) -> bool {
+        self.inputs
+            .iter()
+            .any(|input| input.sequence.is_rbf_signaling())
+    }
+
+    /// eCash txs have no witness data, so this is always the same as `txid`.
+    pub fn wtxid(&self) -> Sha256d {
+        self.txid()
+    }
+
+    pub fn ser_size(&self) -> usize {
+        4 + compact_size_len(self.inputs.len() as u64)
+            + self.inputs.iter().map(TxInput::ser_size).sum::<usize>()
+            + compact_size_len(self.outputs.len() as u64)
+            + self.outputs.iter().map(TxOutput::ser_size).sum::<usize>()
+            + 4
+    }
+
+    /// eCash txs have no witness data, so this is always the same as `ser_size`.
+    pub fn ser_size_witness_stripped(&self) -> usize {
+        self.ser_size()
+    }
+
+    /// Virtual size fee rates apply to, see [`crate::FeeRate`]. eCash txs have no witness
+    /// data, so this is always the same as `ser_size`.
+    pub fn vsize(&self) -> usize {
+        self.ser_size()
+    }
+}
+
+impl TxInput {
+    pub fn ser_size(&self) -> usize {
+        36 + self.script.ser_size() + 4
+    }
+}
+
+impl TxOutput {
+    pub fn ser_size(&self) -> usize {
+        8 + self.script.ser_size()
+    }
+
+    /// Whether the value is below the dust threshold of the script, see
+    /// [`Network::dust_amount_for_script`]. Unspendable outputs, e.g. OP_RETURN, are never dust.
+    pub fn is_dust(&self, network: Network) -> bool {
+        !self.script.is_unspendable() && self.value < network.dust_amount_for_script(&self.script)
+    }
+
+    /// Whether the output puts value on an unspendable script, which burns that value.
+    pub fn burns_value(&self) -> bool {
+        self.value != 0 && self.script.is_unspendable()
+    }
 }
 
 pub fn lotus_txid(tx: &UnhashedTx) -> Sha256d {
@@ -98,6 +172,14 @@ impl Tx {
         &self.hash
     }
 
+    pub fn txid(&self) -> &Sha256d {
+        &self.hash
+    }
+
+    pub fn wtxid(&self) -> &Sha256d {
+        &self.hash
+    }
+
     pub fn raw(&self) -> &Bytes {
         &self.raw
     }
@@ -133,6 +215,29 @@ impl OutPoint {
     }
 }
 
+/// Formats as `<txid>:<out_idx>`, with the txid in display byte order like explorers and RPCs.
+impl Display for OutPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.txid, self.out_idx)
+    }
+}
+
+impl FromStr for OutPoint {
+    type Err = BitcoinSuiteError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || BitcoinSuiteError::InvalidOutPoint(s.to_string());
+        let (txid, out_idx) = s.split_once(':').ok_or_else(invalid)?;
+        if txid.len() != 64 {
+            return Err(invalid());
+        }
+        Ok(OutPoint {
+            txid: Sha256d::from_hex_display(txid)?,
+            out_idx: out_idx.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
 impl BitcoinCode for UnhashedTx {
     fn ser_to(&self, bytes: &mut BytesMut) {
         self.version.ser_to(bytes);
@@ -141,6 +246,13 @@ impl BitcoinCode for UnhashedTx {
         self.lock_time.ser_to(bytes);
     }
 
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.version.serialize_to(writer)?;
+        self.inputs.serialize_to(writer)?;
+        self.outputs.serialize_to(writer)?;
+        self.lock_time.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(UnhashedTx {
             version: BitcoinCode::deser(data)?,
@@ -156,6 +268,10 @@ impl BitcoinCode for Tx {
         self.unhashed_tx.ser_to(bytes)
     }
 
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.unhashed_tx.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(UnhashedTx::deser(data)?.hashed())
     }
@@ -167,6 +283,11 @@ impl BitcoinCode for OutPoint {
         self.out_idx.ser_to(bytes);
     }
 
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.txid.serialize_to(writer)?;
+        self.out_idx.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(OutPoint {
             txid: BitcoinCode::deser(data)?,
@@ -182,6 +303,12 @@ impl BitcoinCode for TxInput {
         self.sequence.ser_to(bytes);
     }
 
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.prev_out.serialize_to(writer)?;
+        self.script.serialize_to(writer)?;
+        self.sequence.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(TxInput {
             prev_out: BitcoinCode::deser(data)?,
@@ -198,6 +325,11 @@ impl BitcoinCode for TxOutput {
         self.script.ser_to(bytes);
     }
 
+    fn serialize_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.value.serialize_to(writer)?;
+        self.script.serialize_to(writer)
+    }
+
     fn deser(data: &mut Bytes) -> Result<Self> {
         Ok(TxOutput {
             value: BitcoinCode::deser(data)?,
@@ -205,3 +337,189 @@ impl BitcoinCode for TxOutput {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BitcoinCode, BitcoinSuiteError, Bytes, BytesError, Hashed, Network, OutPoint, Script,
+        SequenceNo, Sha256d, ShaRmd160, TxInput, TxOutput, UnhashedTx,
+    };
+
+    fn example_tx() -> UnhashedTx {
+        UnhashedTx {
+            version: 1,
+            inputs: vec![TxInput {
+                prev_out: OutPoint {
+                    txid: Sha256d::new([0xae; 32]),
+                    out_idx: 1,
+                },
+                script: Script::from_slice(&[0x51]),
+                sequence: SequenceNo::finalized(),
+                sign_data: None,
+            }],
+            outputs: vec![TxOutput {
+                value: 546,
+                script: Script::from_slice(&[0x51]).to_p2sh(),
+            }],
+            lock_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_from_raw() -> Result<(), Box<dyn std::error::Error>> {
+        let tx = example_tx();
+        let raw = tx.ser();
+        let parsed = UnhashedTx::from_raw(&raw)?;
+        assert_eq!(parsed, tx);
+        assert_eq!(parsed.ser(), raw);
+
+        // Trailing bytes
+        let raw_trailing = Bytes::from([raw.as_ref(), &[0, 0]].concat());
+        match UnhashedTx::from_raw(&raw_trailing) {
+            Err(BitcoinSuiteError::TrailingBytes(2)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // Every truncation must fail without panicking
+        for len in 0..raw.len() {
+            match UnhashedTx::from_raw(&Bytes::from_slice(&raw[..len])) {
+                Err(BitcoinSuiteError::Bytes(BytesError::InvalidSplit { .. })) => {}
+                result => panic!("Unexpected result for len {}: {:?}", len, result),
+            }
+        }
+
+        // Absurd input count doesn't allocate
+        let raw_absurd = [
+            [1, 0, 0, 0].as_ref(),
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0f],
+            &[0; 40],
+        ]
+        .concat();
+        match UnhashedTx::from_raw(&raw_absurd.into()) {
+            Err(BitcoinSuiteError::Bytes(BytesError::InvalidSplit { .. })) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_txid() -> Result<(), Box<dyn std::error::Error>> {
+        let tx = example_tx();
+        let txid = Sha256d::digest(tx.ser());
+        assert_eq!(tx.txid(), txid);
+        assert_eq!(tx.wtxid(), txid);
+        let hashed_tx = tx.hashed();
+        assert_eq!(hashed_tx.txid(), &txid);
+        assert_eq!(hashed_tx.wtxid(), &txid);
+        assert_eq!(hashed_tx.hash(), &txid);
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_to() -> Result<(), Box<dyn std::error::Error>> {
+        fn written(code: &impl BitcoinCode) -> std::io::Result<Vec<u8>> {
+            let mut written = Vec::new();
+            code.serialize_to(&mut written)?;
+            Ok(written)
+        }
+        let mut tx = example_tx();
+        assert_eq!(written(&tx)?, tx.ser().to_vec());
+        assert_eq!(written(&tx.outputs[0])?, tx.outputs[0].ser().to_vec());
+        assert_eq!(
+            written(&tx.inputs[0].script)?,
+            tx.inputs[0].script.ser().to_vec()
+        );
+        tx.inputs[0].script = Script::from_slice(&[0x51; 0x10000]);
+        tx.outputs = vec![TxOutput::default(); 0xfd];
+        assert_eq!(written(&tx)?, tx.ser().to_vec());
+        let tx = tx.hashed();
+        assert_eq!(written(&tx)?, tx.ser().to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ser_size() {
+        let mut tx = UnhashedTx::default();
+        assert_eq!(tx.ser_size(), 10);
+        assert_eq!(tx.ser_size(), tx.ser().len());
+        for script_len in [0, 1, 0xfc, 0xfd, 0xffff, 0x10000] {
+            tx.inputs.push(TxInput {
+                prev_out: OutPoint::default(),
+                script: Script::from_slice(&vec![0x51; script_len]),
+                sequence: SequenceNo::finalized(),
+                sign_data: None,
+            });
+            tx.outputs.push(TxOutput {
+                value: 546,
+                script: Script::from_slice(&vec![0x51; script_len]),
+            });
+            assert_eq!(tx.ser_size(), tx.ser().len());
+            assert_eq!(tx.ser_size_witness_stripped(), tx.ser().len());
+            assert_eq!(tx.vsize(), tx.ser().len());
+        }
+        tx.outputs = vec![TxOutput::default(); 0xfd];
+        assert_eq!(tx.ser_size(), tx.ser().len());
+    }
+
+    #[test]
+    fn test_is_dust() {
+        let p2pkh = Script::p2pkh(&ShaRmd160::new([0; 20]));
+        let output = |value, script: &Script| TxOutput {
+            value,
+            script: script.clone(),
+        };
+        assert!(!output(546, &p2pkh).is_dust(Network::XEC));
+        assert!(output(545, &p2pkh).is_dust(Network::XEC));
+        assert!(!output(2, &p2pkh).is_dust(Network::XRG));
+        assert!(output(1, &p2pkh).is_dust(Network::XRG));
+        let opreturn = Script::opreturn(&[b"hello"]);
+        assert!(!output(0, &opreturn).is_dust(Network::XEC));
+        assert!(!output(0, &opreturn).is_dust(Network::XRG));
+        assert!(!output(1, &opreturn).is_dust(Network::XEC));
+    }
+
+    #[test]
+    fn test_burns_value() {
+        let output = |value, script: Script| TxOutput { value, script };
+        assert!(!output(0, Script::opreturn(&[b"hello"])).burns_value());
+        assert!(output(1, Script::opreturn(&[b"hello"])).burns_value());
+        assert!(output(546, Script::opreturn(&[])).burns_value());
+        assert!(!output(546, Script::p2pkh(&ShaRmd160::new([0; 20]))).burns_value());
+    }
+
+    #[test]
+    fn test_outpoint_display_from_str() -> Result<(), BitcoinSuiteError> {
+        let txid_hex = "00000000d1145790a8694403d4063f323d499e655c83426834d4ce2f8dd4a2ee";
+        let outpoint = OutPoint {
+            txid: Sha256d::from_hex_be(txid_hex)?,
+            out_idx: 1,
+        };
+        assert_eq!(outpoint.to_string(), format!("{}:1", txid_hex));
+        assert_eq!(format!("{}:1", txid_hex).parse::<OutPoint>()?, outpoint);
+
+        let outpoint = OutPoint {
+            txid: Sha256d::new([0xae; 32]),
+            out_idx: 0xffff_ffff,
+        };
+        assert_eq!(outpoint.to_string().parse::<OutPoint>()?, outpoint);
+
+        let invalid = [
+            txid_hex.to_string(),
+            format!("{}:", txid_hex),
+            format!("{}:-1", txid_hex),
+            format!("{}:4294967296", txid_hex),
+            format!("{}:1", &txid_hex[2..]),
+            format!("00{}:1", txid_hex),
+        ];
+        for s in invalid {
+            match s.parse::<OutPoint>() {
+                Err(BitcoinSuiteError::InvalidOutPoint(err_str)) => assert_eq!(err_str, s),
+                result => panic!("Unexpected: {:?}", result),
+            }
+        }
+        assert!(format!("{}:1", "zz".repeat(32))
+            .parse::<OutPoint>()
+            .is_err());
+        Ok(())
+    }
+}
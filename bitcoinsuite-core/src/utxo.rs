@@ -1,8 +1,142 @@
-use crate::{OutPoint, Script};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Network, OutPoint, Script, TxOutput};
+
+/// Unspent output of a tx.
+///
+/// Equality and hashing compare all fields. A utxo is identified by its `outpoint` alone though,
+/// `script` and `value` are cached data of the output; use [`utxos_by_outpoint`] to deduplicate
+/// or order utxos by their identity. Two utxos with the same outpoint but different scripts or
+/// values indicate a bug in whatever provided them.
+///
+/// Utxos are ordered by outpoint first, as it's the first field.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Utxo {
     pub outpoint: OutPoint,
     pub script: Script,
     pub value: i64,
 }
+
+impl Utxo {
+    /// Whether the utxo is worth less than the fee for spending it, see [`TxOutput::is_dust`].
+    pub fn is_dust(&self, network: Network) -> bool {
+        TxOutput {
+            value: self.value,
+            script: self.script.clone(),
+        }
+        .is_dust(network)
+    }
+}
+
+/// Index `utxos` by their outpoint, ordered by outpoint. Of utxos with the same outpoint, the
+/// first one is kept.
+pub fn utxos_by_outpoint(utxos: impl IntoIterator<Item = Utxo>) -> BTreeMap<OutPoint, Utxo> {
+    let mut map = BTreeMap::new();
+    for utxo in utxos {
+        map.entry(utxo.outpoint.clone()).or_insert(utxo);
+    }
+    map
+}
+
+/// Split `utxos` into usable and dust utxos, keeping the order of each.
+pub fn filter_dust(utxos: Vec<Utxo>, network: Network) -> (Vec<Utxo>, Vec<Utxo>) {
+    utxos.into_iter().partition(|utxo| !utxo.is_dust(network))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        filter_dust, utxos_by_outpoint, Network, OutPoint, Script, Sha256d, ShaRmd160, Utxo,
+    };
+
+    #[test]
+    fn test_utxos_by_outpoint() {
+        let utxo = |txid_byte, out_idx, value| Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([txid_byte; 32]),
+                out_idx,
+            },
+            script: Script::default(),
+            value,
+        };
+        let utxos = vec![
+            utxo(2, 0, 1000),
+            utxo(1, 1, 2000),
+            utxo(1, 0, 3000),
+            utxo(2, 0, 4000),
+            utxo(1, 1, 2000),
+        ];
+        // Equality and hashing are structural
+        assert_ne!(utxos[0], utxos[3]);
+        assert_eq!(utxos[1], utxos[4]);
+        assert_eq!(utxos.iter().collect::<HashSet<_>>().len(), 4);
+        // First utxo of each outpoint is kept, in outpoint order
+        let by_outpoint = utxos_by_outpoint(utxos);
+        assert_eq!(
+            by_outpoint
+                .values()
+                .map(|utxo| (utxo.outpoint.out_idx, utxo.value))
+                .collect::<Vec<_>>(),
+            vec![(0, 3000), (1, 2000), (0, 1000)],
+        );
+        assert!(by_outpoint.contains_key(&utxo(1, 0, 0).outpoint));
+    }
+
+    #[test]
+    fn test_sort_utxos() {
+        let utxo = |txid_byte, out_idx, value| Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([txid_byte; 32]),
+                out_idx,
+            },
+            script: Script::default(),
+            value,
+        };
+        let mut utxos = vec![
+            utxo(2, 0, 1000),
+            utxo(1, 1, 2000),
+            utxo(1, 0, 3000),
+            utxo(1, 0, 500),
+        ];
+        utxos.sort();
+        assert_eq!(
+            utxos,
+            vec![
+                utxo(1, 0, 500),
+                utxo(1, 0, 3000),
+                utxo(1, 1, 2000),
+                utxo(2, 0, 1000),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_filter_dust() {
+        let utxo = |out_idx, value| Utxo {
+            outpoint: OutPoint {
+                out_idx,
+                ..Default::default()
+            },
+            script: Script::p2pkh(&ShaRmd160::new([0; 20])),
+            value,
+        };
+        let utxos = vec![utxo(0, 546), utxo(1, 545), utxo(2, 10_000), utxo(3, 1)];
+        assert_eq!(
+            filter_dust(utxos.clone(), Network::XEC),
+            (
+                vec![utxos[0].clone(), utxos[2].clone()],
+                vec![utxos[1].clone(), utxos[3].clone()],
+            ),
+        );
+        assert_eq!(
+            filter_dust(utxos.clone(), Network::XRG),
+            (utxos[..3].to_vec(), vec![utxos[3].clone()]),
+        );
+    }
+}
@@ -1,14 +1,32 @@
 use bitcoinsuite_core::{
-    ecc::Ecc, BitcoinCode, Hashed, OutPoint, P2PKHSignatory, Script, SequenceNo, Sha256d,
-    ShaRmd160, SigHashType, SignData, SignField, TxBuilder, TxBuilderInput, TxBuilderOutput,
-    TxInput, TxOutput,
+    combine_partial_txs,
+    ecc::Ecc,
+    opcode::{OP_EQUAL, OP_HASH160},
+    p2sh_spend, BitcoinCode, FeeRate, Hashed, LockTime, Network, OutPoint, P2PKHSignatory,
+    P2SHSignatory, PartialTx, Script, ScriptBuilder, SequenceNo, Sha256d, ShaRmd160, SigHashType,
+    SignData, SignField, Signatory, TxBuilder, TxBuilderInput, TxBuilderOutput, TxInput, TxOutput,
+    UnhashedTx, UnsignedTxInput, Utxo,
 };
 use bitcoinsuite_ecc_secp256k1::EccSecp256k1;
 use bitcoinsuite_test_utils_blockchain::{build_tx, setup_xec_chain};
 
+/// Satisfies a hash lock by pushing its preimage.
+struct PreimageSignatory(Vec<u8>);
+
+impl Signatory for PreimageSignatory {
+    fn sign_input<'tx>(
+        &self,
+        _: &dyn Ecc,
+        mut input: UnsignedTxInput<'tx>,
+    ) -> bitcoinsuite_core::Result<()> {
+        *input.input_script_mut() = ScriptBuilder::new().push_data(&self.0).build();
+        Ok(())
+    }
+}
+
 async fn test_txs() -> Result<(), Box<dyn std::error::Error>> {
     let redeem_script = Script::from_static_slice(&[0x51]);
-    let (bitcoind, mut utxos) = setup_xec_chain(13, &redeem_script).await?;
+    let (bitcoind, mut utxos) = setup_xec_chain(17, &redeem_script).await?;
 
     let ecc = EccSecp256k1::default();
     let seckey = ecc.seckey_from_array([1; 32])?;
@@ -30,6 +48,7 @@ async fn test_txs() -> Result<(), Box<dyn std::error::Error>> {
     );
     let txid_hex = bitcoind.cmd_string("sendrawtransaction", &[&p2pkh_send_tx.ser().hex()])?;
     let txid = Sha256d::from_hex_be(&txid_hex)?;
+    assert_eq!(p2pkh_send_tx.txid(), txid);
 
     let sig_hash_types = vec![
         SigHashType::ALL_BIP143,
@@ -71,8 +90,9 @@ async fn test_txs() -> Result<(), Box<dyn std::error::Error>> {
                 sig_hash_type,
             }),
         ));
-        let signed_tx = tx_builder.sign(&ecc, 1000, 546)?;
-        bitcoind.cmd_string("sendrawtransaction", &[&signed_tx.ser().hex()])?;
+        let signed_tx = tx_builder.sign(&ecc, FeeRate::per_kb(1000), 546)?;
+        let txid_hex = bitcoind.cmd_string("sendrawtransaction", &[&signed_tx.ser().hex()])?;
+        assert_eq!(signed_tx.txid().to_hex_display(), txid_hex);
     }
 
     for (idx, &sig_hash_type) in sig_hash_types.iter().enumerate() {
@@ -113,7 +133,7 @@ async fn test_txs() -> Result<(), Box<dyn std::error::Error>> {
         tx_builder
             .outputs
             .push(TxBuilderOutput::Leftover(leftover_script.clone()));
-        let signed_tx = tx_builder.sign(&ecc, 1000, 546)?;
+        let signed_tx = tx_builder.sign(&ecc, FeeRate::per_kb(1000), 546)?;
         let tx_size = signed_tx.ser().len();
         assert_eq!(
             signed_tx.outputs,
@@ -135,6 +155,176 @@ async fn test_txs() -> Result<(), Box<dyn std::error::Error>> {
         bitcoind.cmd_string("sendrawtransaction", &[&signed_tx.ser().hex()])?;
     }
 
+    // Two parties sign their own input with ALL|ANYONECANPAY, then the txs are combined
+    let seckey2 = ecc.seckey_from_array([2; 32])?;
+    let pubkey2 = ecc.derive_pubkey(&seckey2);
+    let p2pkh_script2 = Script::p2pkh(&ShaRmd160::digest(pubkey2.array().into()));
+    let (miner_outpoint, miner_value) = utxos.pop().unwrap();
+    let contribution = miner_value / 2 - 10_000;
+    let fund_tx = build_tx(
+        miner_outpoint,
+        &redeem_script,
+        vec![
+            TxOutput {
+                value: contribution,
+                script: p2pkh_script.clone(),
+            },
+            TxOutput {
+                value: contribution,
+                script: p2pkh_script2.clone(),
+            },
+        ],
+    );
+    let txid_hex = bitcoind.cmd_string("sendrawtransaction", &[&fund_tx.ser().hex()])?;
+    let fund_txid = Sha256d::from_hex_be(&txid_hex)?;
+    let goal_outputs = vec![TxOutput {
+        value: 2 * contribution - 10_000,
+        script: Script::p2pkh(&ShaRmd160::new([0; 20])),
+    }];
+    let parties = [
+        (0, &seckey, pubkey, &p2pkh_script),
+        (1, &seckey2, pubkey2, &p2pkh_script2),
+    ];
+    let mut partial_txs = Vec::new();
+    for (out_idx, seckey, pubkey, script) in parties {
+        let mut partial_tx = PartialTx::new(UnhashedTx {
+            version: 1,
+            inputs: vec![TxInput {
+                prev_out: OutPoint {
+                    txid: fund_txid.clone(),
+                    out_idx,
+                },
+                script: Script::default(),
+                sequence: SequenceNo::finalized(),
+                sign_data: Some(SignData::new(vec![
+                    SignField::Value(contribution),
+                    SignField::OutputScript(script.clone()),
+                ])),
+            }],
+            outputs: goal_outputs.clone(),
+            lock_time: 0,
+        });
+        partial_tx.set_signatory(
+            0,
+            Box::new(P2PKHSignatory {
+                seckey: seckey.clone(),
+                pubkey,
+                sig_hash_type: SigHashType::ALL_BIP143_ANYONECANPAY,
+            }),
+        );
+        partial_txs.push(partial_tx.finalize(&ecc)?);
+    }
+    // A single contribution doesn't cover the outputs
+    let result = bitcoind.cmd_string("sendrawtransaction", &[&partial_txs[0].ser().hex()]);
+    assert!(result.is_err());
+    let combined_tx = combine_partial_txs(partial_txs)?;
+    bitcoind.cmd_string("sendrawtransaction", &[&combined_tx.ser().hex()])?;
+
+    // Spend a P2SH-wrapped P2PKH output
+    let (miner_outpoint, miner_value) = utxos.pop().unwrap();
+    let p2sh_value = miner_value - 10_000;
+    let p2sh_send_tx = build_tx(
+        miner_outpoint,
+        &redeem_script,
+        vec![TxOutput {
+            value: p2sh_value,
+            script: p2pkh_script.to_p2sh(),
+        }],
+    );
+    let txid_hex = bitcoind.cmd_string("sendrawtransaction", &[&p2sh_send_tx.ser().hex()])?;
+    let p2sh_txid = Sha256d::from_hex_be(&txid_hex)?;
+    let mut tx_builder = TxBuilder {
+        version: 1,
+        ..Default::default()
+    };
+    tx_builder.inputs.push(TxBuilderInput::new(
+        TxInput {
+            prev_out: OutPoint {
+                txid: p2sh_txid,
+                out_idx: 0,
+            },
+            script: Script::default(),
+            sequence: SequenceNo::finalized(),
+            sign_data: Some(SignData::new(vec![
+                SignField::Value(p2sh_value),
+                SignField::OutputScript(p2pkh_script.to_p2sh()),
+            ])),
+        },
+        Box::new(P2SHSignatory {
+            redeem_script: p2pkh_script.clone(),
+            inner_signatory: Box::new(P2PKHSignatory {
+                seckey: seckey.clone(),
+                pubkey,
+                sig_hash_type: SigHashType::ALL_BIP143,
+            }),
+        }),
+    ));
+    tx_builder
+        .outputs
+        .push(TxBuilderOutput::Leftover(redeem_script.to_p2sh()));
+    let signed_tx = tx_builder.sign(&ecc, FeeRate::per_kb(1000), 546)?;
+    bitcoind.cmd_string("sendrawtransaction", &[&signed_tx.ser().hex()])?;
+
+    // Spend a hash-locked P2SH output
+    let preimage = b"bitcoinsuite";
+    let hash_lock_script = ScriptBuilder::new()
+        .push_opcode(OP_HASH160)
+        .push_data(ShaRmd160::digest(preimage.as_ref().into()).as_slice())
+        .push_opcode(OP_EQUAL)
+        .build();
+    let (miner_outpoint, miner_value) = utxos.pop().unwrap();
+    let hash_lock_value = miner_value - 10_000;
+    let hash_lock_send_tx = build_tx(
+        miner_outpoint,
+        &redeem_script,
+        vec![TxOutput {
+            value: hash_lock_value,
+            script: hash_lock_script.to_p2sh(),
+        }],
+    );
+    let txid_hex = bitcoind.cmd_string("sendrawtransaction", &[&hash_lock_send_tx.ser().hex()])?;
+    let hash_lock_spend_tx = p2sh_spend(
+        &ecc,
+        hash_lock_script.clone(),
+        Box::new(PreimageSignatory(preimage.to_vec())),
+        Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::from_hex_be(&txid_hex)?,
+                out_idx: 0,
+            },
+            script: hash_lock_script.to_p2sh(),
+            value: hash_lock_value,
+        },
+        redeem_script.to_p2sh(),
+        FeeRate::per_kb(1000),
+        Network::XEC,
+    )?;
+    bitcoind.cmd_string("sendrawtransaction", &[&hash_lock_spend_tx.ser().hex()])?;
+
+    // Tx with a lock time at the next block's height is only accepted after that block
+    let (miner_outpoint, miner_value) = utxos.pop().unwrap();
+    let mut tx_builder = TxBuilder::from_tx(build_tx(
+        miner_outpoint,
+        &redeem_script,
+        vec![TxOutput {
+            value: miner_value - 10_000,
+            script: Script::p2pkh(&ShaRmd160::new([0; 20])),
+        }],
+    ));
+    let height = bitcoind.cmd_string("getblockcount", &[])?.parse::<u32>()?;
+    tx_builder.set_lock_time(LockTime::Height(height + 1))?;
+    let lock_time_tx = tx_builder.sign(&ecc, FeeRate::per_kb(1000), 546)?;
+    assert_eq!(
+        lock_time_tx.inputs[0].sequence,
+        SequenceNo::from_u32(0xffff_fffe)
+    );
+    let result = bitcoind.cmd_string("sendrawtransaction", &[&lock_time_tx.ser().hex()]);
+    assert!(result.unwrap_err().to_string().contains("non-final"));
+    let address = bitcoind.cmd_json("decodescript", &[&redeem_script.hex()])?;
+    let address = address["p2sh"].as_str().unwrap();
+    bitcoind.cmd_json("generatetoaddress", &["1", address])?;
+    bitcoind.cmd_string("sendrawtransaction", &[&lock_time_tx.ser().hex()])?;
+
     Ok(())
 }
 
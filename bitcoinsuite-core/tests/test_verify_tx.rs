@@ -0,0 +1,317 @@
+use bitcoinsuite_core::{
+    ecc::{Ecc, SecKey},
+    opcode::{OP_0, OP_1, OP_CHECKDATASIG, OP_EQUALVERIFY},
+    verify_tx, Bytes, FeeRate, Hashed, Op, OutPoint, P2PKHSignatory, P2SHSignatory, Script,
+    ScriptBuilder, ScriptError, ScriptVerifyError, SequenceNo, Sha256, Sha256d, ShaRmd160,
+    SigHashType, SigHashTypeOutputs, SignData, SignField, Signatory, TxBuilder, TxBuilderInput,
+    TxBuilderOutput, TxInput, TxOutput, UnhashedTx, UnsignedTxInput, Utxo,
+};
+use bitcoinsuite_ecc_secp256k1::EccSecp256k1;
+
+/// Signs a multisig with signatures of `seckeys`, in order. Without `checkbits`, this uses
+/// legacy mode with ECDSA signatures, otherwise Schnorr mode with `checkbits` as dummy.
+struct MultisigSignatory {
+    seckeys: Vec<SecKey>,
+    checkbits: Option<u8>,
+    sig_hash_type: SigHashType,
+}
+
+impl Signatory for MultisigSignatory {
+    fn sign_input<'tx>(
+        &self,
+        ecc: &dyn Ecc,
+        mut input: UnsignedTxInput<'tx>,
+    ) -> bitcoinsuite_core::Result<()> {
+        let preimage = input.sighash_preimage(self.sig_hash_type, None)?;
+        let sighash = Sha256d::digest(preimage.bytes).byte_array().clone();
+        let mut script = match self.checkbits {
+            None => ScriptBuilder::new().push_opcode(OP_0),
+            Some(checkbits @ 1..=16) => ScriptBuilder::new().push_opcode(OP_1 + checkbits - 1),
+            Some(checkbits) => ScriptBuilder::new().push_data(&[checkbits]),
+        };
+        for seckey in &self.seckeys {
+            let sig = match self.checkbits {
+                None => ecc.sign(seckey, sighash.clone()),
+                Some(_) => ecc.schnorr_sign(seckey, sighash.clone()),
+            };
+            script = script.push_data(&[sig.as_ref(), &[self.sig_hash_type.to_byte()]].concat());
+        }
+        *input.input_script_mut() = script.build();
+        Ok(())
+    }
+}
+
+fn prevout(out_idx: u32, script: Script) -> Utxo {
+    Utxo {
+        outpoint: OutPoint {
+            txid: Sha256d::new([7; 32]),
+            out_idx,
+        },
+        script,
+        value: 100_000,
+    }
+}
+
+fn sign(ecc: &EccSecp256k1, prevout: &Utxo, signatory: Box<dyn Signatory>) -> UnhashedTx {
+    let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+        version: 1,
+        ..Default::default()
+    });
+    tx_builder.inputs.push(TxBuilderInput::new(
+        TxInput {
+            prev_out: prevout.outpoint.clone(),
+            script: Script::default(),
+            sequence: SequenceNo::finalized(),
+            sign_data: Some(SignData::new(vec![
+                SignField::Value(prevout.value),
+                SignField::OutputScript(prevout.script.clone()),
+            ])),
+        },
+        signatory,
+    ));
+    tx_builder
+        .outputs
+        .push(TxBuilderOutput::Leftover(Script::p2pkh(&ShaRmd160::new(
+            [0; 20],
+        ))));
+    tx_builder.sign(ecc, FeeRate::per_kb(1000), 546).unwrap()
+}
+
+/// Flip a bit in the push at `push_idx` of the input script, `offset` bytes from its end.
+fn corrupt(tx: &UnhashedTx, push_idx: usize, offset: usize) -> UnhashedTx {
+    let mut tx = tx.clone();
+    let ops = tx.inputs[0]
+        .script
+        .ops()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let mut builder = ScriptBuilder::new();
+    for (op_idx, op) in ops.into_iter().enumerate() {
+        builder = match op {
+            Op::Push(_, data) => {
+                let mut data = data.to_vec();
+                if op_idx == push_idx {
+                    let len = data.len();
+                    data[len - offset] ^= 1;
+                }
+                builder.push_data(&data)
+            }
+            Op::Code(opcode) => builder.push_opcode(opcode),
+        };
+    }
+    tx.inputs[0].script = builder.build();
+    tx
+}
+
+fn null_fail() -> Result<(), ScriptVerifyError> {
+    Err(ScriptVerifyError::InputFailed {
+        input_idx: 0,
+        error: ScriptError::NullFail,
+    })
+}
+
+#[test]
+fn test_verify_p2pkh() -> Result<(), Box<dyn std::error::Error>> {
+    let ecc = EccSecp256k1::default();
+    let seckey = ecc.seckey_from_array([1; 32])?;
+    let pubkey = ecc.derive_pubkey(&seckey);
+    let prevout = prevout(0, Script::p2pkh(&ShaRmd160::digest(pubkey.array().into())));
+    let sig_hash_types = [
+        SigHashType::ALL_BIP143,
+        SigHashType::NONE_BIP143,
+        SigHashType::SINGLE_BIP143,
+        SigHashType::ALL_BIP143_ANYONECANPAY,
+    ];
+    for sig_hash_type in sig_hash_types {
+        let signatory = P2PKHSignatory {
+            seckey: seckey.clone(),
+            pubkey,
+            sig_hash_type,
+        };
+        let tx = sign(&ecc, &prevout, Box::new(signatory));
+        verify_tx(&tx, &[prevout.clone()], &ecc)?;
+        // Flip a bit of the Schnorr signature, before the sighash byte
+        let corrupted_tx = corrupt(&tx, 0, 2);
+        assert_eq!(
+            verify_tx(&corrupted_tx, &[prevout.clone()], &ecc),
+            null_fail()
+        );
+        // Signature doesn't cover a different output
+        let mut changed_tx = tx.clone();
+        changed_tx.outputs[0].value -= 1;
+        if sig_hash_type.base_type() != SigHashTypeOutputs::None {
+            assert_eq!(
+                verify_tx(&changed_tx, &[prevout.clone()], &ecc),
+                null_fail()
+            );
+        }
+        // Wrong pubkey fails the hash check
+        let other_prevout = Utxo {
+            script: Script::p2pkh(&ShaRmd160::new([1; 20])),
+            ..prevout.clone()
+        };
+        assert_eq!(
+            verify_tx(&tx, &[other_prevout], &ecc),
+            Err(ScriptVerifyError::InputFailed {
+                input_idx: 0,
+                error: ScriptError::VerifyFailed(OP_EQUALVERIFY),
+            }),
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_verify_p2sh_multisig() -> Result<(), Box<dyn std::error::Error>> {
+    let ecc = EccSecp256k1::default();
+    let seckeys = (1..=3)
+        .map(|byte| ecc.seckey_from_array([byte; 32]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pubkeys = seckeys
+        .iter()
+        .map(|seckey| ecc.derive_pubkey(seckey))
+        .collect::<Vec<_>>();
+    let redeem_script = Script::multisig(2, pubkeys.iter().map(|pubkey| pubkey.as_slice()));
+    let prevout = prevout(1, redeem_script.to_p2sh());
+    let signatory = |seckeys: Vec<SecKey>| -> Box<dyn Signatory> {
+        Box::new(P2SHSignatory {
+            redeem_script: redeem_script.clone(),
+            inner_signatory: Box::new(MultisigSignatory {
+                seckeys,
+                checkbits: None,
+                sig_hash_type: SigHashType::ALL_BIP143,
+            }),
+        })
+    };
+
+    let tx = sign(
+        &ecc,
+        &prevout,
+        signatory(vec![seckeys[0].clone(), seckeys[2].clone()]),
+    );
+    verify_tx(&tx, &[prevout.clone()], &ecc)?;
+    // Flip a bit of the second ECDSA signature, before the sighash byte
+    let corrupted_tx = corrupt(&tx, 2, 2);
+    assert_eq!(
+        verify_tx(&corrupted_tx, &[prevout.clone()], &ecc),
+        null_fail()
+    );
+
+    // Signatures must be in the order of the pubkeys
+    let tx = sign(
+        &ecc,
+        &prevout,
+        signatory(vec![seckeys[2].clone(), seckeys[0].clone()]),
+    );
+    assert_eq!(verify_tx(&tx, &[prevout.clone()], &ecc), null_fail());
+
+    // The redeem script must match the P2SH hash
+    let other_prevout = Utxo {
+        script: Script::multisig(1, pubkeys.iter().map(|pubkey| pubkey.as_slice())).to_p2sh(),
+        ..prevout
+    };
+    assert_eq!(
+        verify_tx(&tx, &[other_prevout], &ecc),
+        Err(ScriptVerifyError::InputFailed {
+            input_idx: 0,
+            error: ScriptError::EvalFalse,
+        }),
+    );
+    Ok(())
+}
+
+#[test]
+fn test_verify_p2sh_multisig_schnorr() -> Result<(), Box<dyn std::error::Error>> {
+    let ecc = EccSecp256k1::default();
+    let seckeys = (1..=3)
+        .map(|byte| ecc.seckey_from_array([byte; 32]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pubkeys = seckeys
+        .iter()
+        .map(|seckey| ecc.derive_pubkey(seckey))
+        .collect::<Vec<_>>();
+    let redeem_script = Script::multisig(2, pubkeys.iter().map(|pubkey| pubkey.as_slice()));
+    let prevout = prevout(3, redeem_script.to_p2sh());
+    let sign_checkbits = |checkbits: u8| {
+        let signatory = P2SHSignatory {
+            redeem_script: redeem_script.clone(),
+            inner_signatory: Box::new(MultisigSignatory {
+                seckeys: vec![seckeys[0].clone(), seckeys[2].clone()],
+                checkbits: Some(checkbits),
+                sig_hash_type: SigHashType::ALL_BIP143,
+            }),
+        };
+        sign(&ecc, &prevout, Box::new(signatory))
+    };
+
+    // Checkbits select the 1st and 3rd pubkey
+    let tx = sign_checkbits(0b101);
+    verify_tx(&tx, &[prevout.clone()], &ecc)?;
+    // Flip a bit of the second Schnorr signature, before the sighash byte
+    let corrupted_tx = corrupt(&tx, 2, 2);
+    assert_eq!(
+        verify_tx(&corrupted_tx, &[prevout.clone()], &ecc),
+        null_fail()
+    );
+
+    // Checkbits select the wrong pubkey for the second signature
+    let tx = sign_checkbits(0b011);
+    assert_eq!(verify_tx(&tx, &[prevout.clone()], &ecc), null_fail());
+
+    // Checkbits must select exactly one pubkey per signature
+    let tx = sign_checkbits(0b111);
+    assert_eq!(
+        verify_tx(&tx, &[prevout.clone()], &ecc),
+        Err(ScriptVerifyError::InputFailed {
+            input_idx: 0,
+            error: ScriptError::InvalidMultisigDummy,
+        }),
+    );
+    Ok(())
+}
+
+#[test]
+fn test_verify_checkdatasig() -> Result<(), Box<dyn std::error::Error>> {
+    let ecc = EccSecp256k1::default();
+    let seckey = ecc.seckey_from_array([4; 32])?;
+    let pubkey = ecc.derive_pubkey(&seckey);
+    let script = ScriptBuilder::new()
+        .push_data(pubkey.as_slice())
+        .push_opcode(OP_CHECKDATASIG)
+        .build();
+    let prevout = prevout(2, script);
+    let msg = b"oracle message";
+    let msg_hash = Sha256::digest(Bytes::from_slice(msg)).byte_array().clone();
+    let spend = |sig: &[u8]| UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: prevout.outpoint.clone(),
+            script: ScriptBuilder::new().push_data(sig).push_data(msg).build(),
+            sequence: SequenceNo::finalized(),
+            sign_data: None,
+        }],
+        outputs: vec![TxOutput::default()],
+        lock_time: 0,
+    };
+    for sig in [
+        ecc.sign(&seckey, msg_hash.clone()),
+        ecc.schnorr_sign(&seckey, msg_hash.clone()),
+    ] {
+        verify_tx(&spend(&sig), &[prevout.clone()], &ecc)?;
+        let mut corrupted_sig = sig.to_vec();
+        *corrupted_sig.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            verify_tx(&spend(&corrupted_sig), &[prevout.clone()], &ecc),
+            null_fail(),
+        );
+    }
+    // An empty signature fails without erroring, but leaves false on the stack
+    assert_eq!(
+        verify_tx(&spend(&[]), &[prevout.clone()], &ecc),
+        Err(ScriptVerifyError::InputFailed {
+            input_idx: 0,
+            error: ScriptError::EvalFalse,
+        }),
+    );
+    Ok(())
+}
@@ -1,5 +1,12 @@
+mod naive_musig;
+
+pub use crate::naive_musig::*;
+
 use bitcoinsuite_core::{
-    ecc::{Ecc, EccError, PubKey, SecKey, VerifySignatureError, PUBKEY_LENGTH},
+    ecc::{
+        Ecc, EccError, PubKey, SecKey, VerifySignatureError, PUBKEY_LENGTH,
+        UNCOMPRESSED_PUBKEY_LENGTH,
+    },
     ByteArray, Bytes,
 };
 use secp256k1_abc::{
@@ -26,6 +33,14 @@ impl Ecc for EccSecp256k1 {
         Ok(PubKey::new_unchecked(pubkey))
     }
 
+    fn pubkey_from_slice(&self, pubkey: &[u8]) -> Result<PubKey, EccError> {
+        if pubkey.len() != PUBKEY_LENGTH && pubkey.len() != UNCOMPRESSED_PUBKEY_LENGTH {
+            return Err(EccError::InvalidPubKeyLen(pubkey.len()));
+        }
+        let pubkey = PublicKey::from_slice(pubkey).map_err(|_| EccError::InvalidPublicKey)?;
+        Ok(PubKey::new_unchecked(pubkey.serialize()))
+    }
+
     fn seckey_from_array(&self, seckey: [u8; 32]) -> Result<SecKey, EccError> {
         SecretKey::from_slice(&seckey).map_err(|_| EccError::InvalidSecretKey)?;
         Ok(SecKey::new_unchecked(seckey))
@@ -80,6 +95,15 @@ impl Ecc for EccSecp256k1 {
         PubKey::new_unchecked(pubkey.serialize())
     }
 
+    fn pubkey_tweak_add(&self, pubkey: &PubKey, tweak: [u8; 32]) -> Result<PubKey, EccError> {
+        let mut pubkey =
+            PublicKey::from_slice(pubkey.as_slice()).map_err(|_| EccError::InvalidPublicKey)?;
+        pubkey
+            .add_exp_assign(&self.curve, &tweak)
+            .map_err(|_| EccError::InvalidTweak)?;
+        Ok(PubKey::new_unchecked(pubkey.serialize()))
+    }
+
     fn serialize_pubkey_uncompressed(&self, pubkey: &PubKey) -> [u8; 65] {
         PublicKey::from_slice(pubkey.as_slice())
             .expect("Invalid pubkey")
@@ -122,7 +146,13 @@ impl Ecc for EccSecp256k1 {
 #[cfg(test)]
 mod tests {
     use super::EccSecp256k1;
-    use bitcoinsuite_core::ecc::{Ecc, EccError, PubKey, VerifySignatureError};
+    use bitcoinsuite_core::{
+        ecc::{
+            Ecc, EccError, ExtendedPubKey, PubKey, SecKey, VerifySignatureError, WifError,
+            XPubError,
+        },
+        CashAddress, Net, ECASH,
+    };
     use hex_literal::hex;
 
     #[test]
@@ -144,6 +174,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pubkey_from_slice() {
+        let ecc = EccSecp256k1::default();
+        let compressed = hex!("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+        let uncompressed = hex!(
+            "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+             483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+        );
+        let pubkey = PubKey::from_slice(&ecc, &compressed).unwrap();
+        assert_eq!(pubkey.compressed(), compressed);
+        assert_eq!(pubkey.uncompressed(&ecc), uncompressed);
+        assert_eq!(PubKey::from_slice(&ecc, &uncompressed).unwrap(), pubkey);
+
+        // Off-curve point
+        let mut off_curve = uncompressed;
+        off_curve[64] ^= 1;
+        assert_eq!(
+            PubKey::from_slice(&ecc, &off_curve).unwrap_err(),
+            EccError::InvalidPublicKey,
+        );
+        assert_eq!(
+            PubKey::from_slice(&ecc, &[4; 65]).unwrap_err(),
+            EccError::InvalidPublicKey,
+        );
+        // Wrong lengths
+        assert_eq!(
+            PubKey::from_slice(&ecc, &compressed[..32]).unwrap_err(),
+            EccError::InvalidPubKeyLen(32),
+        );
+        assert_eq!(
+            PubKey::from_slice(&ecc, &[]).unwrap_err(),
+            EccError::InvalidPubKeyLen(0),
+        );
+    }
+
     #[test]
     fn test_seckey_from_array() {
         let ecc = EccSecp256k1::default();
@@ -154,6 +219,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_seckey_wif() {
+        let ecc = EccSecp256k1::default();
+        let seckey = ecc.seckey_from_array([3; 32]).unwrap();
+        for net in [Net::Mainnet, Net::Testnet, Net::Regtest] {
+            for compressed in [false, true] {
+                let wif = seckey.to_wif(net, compressed);
+                let (decoded, decoded_compressed) = SecKey::from_wif(&ecc, &wif).unwrap();
+                assert_eq!(decoded.as_slice(), seckey.as_slice());
+                assert_eq!(decoded_compressed, compressed);
+            }
+        }
+        let (seckey, compressed) =
+            SecKey::from_wif(&ecc, "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617").unwrap();
+        assert_eq!(
+            seckey.as_slice(),
+            hex!("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d"),
+        );
+        assert!(compressed);
+        // All-zero key is rejected by the curve
+        assert_eq!(
+            SecKey::from_wif(&ecc, &SecKey::default().to_wif(Net::Mainnet, true)).unwrap_err(),
+            WifError::Ecc(EccError::InvalidSecretKey),
+        );
+    }
+
+    #[test]
+    fn test_pubkey_tweak_add() {
+        let ecc = EccSecp256k1::default();
+        let pubkey = ecc.derive_pubkey(&ecc.seckey_from_array([2; 32]).unwrap());
+        let mut tweak = [0; 32];
+        tweak[31] = 3;
+        // (k + 3) * G == k * G + 3 * G
+        let mut tweaked_seckey = [2; 32];
+        tweaked_seckey[31] = 5;
+        assert_eq!(
+            ecc.pubkey_tweak_add(&pubkey, tweak).unwrap(),
+            ecc.derive_pubkey(&ecc.seckey_from_array(tweaked_seckey).unwrap()),
+        );
+        // Curve order
+        let order = hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        assert_eq!(
+            ecc.pubkey_tweak_add(&pubkey, order).unwrap_err(),
+            EccError::InvalidTweak,
+        );
+    }
+
+    #[test]
+    fn test_xpub_invalid_point() {
+        let ecc = EccSecp256k1::default();
+        // There's no point with x = 0
+        let invalid_pubkey = PubKey::new_unchecked(hex!(
+            "020000000000000000000000000000000000000000000000000000000000000000"
+        ));
+        assert_eq!(
+            ecc.pubkey_tweak_add(&invalid_pubkey, [1; 32]).unwrap_err(),
+            EccError::InvalidPublicKey,
+        );
+        // BIP32 test vector 2 master key, with the pubkey replaced by 02 00...00
+        let xpub = "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu4jiPPBfrrn2YDEW7FeNWcw6tTTFkKaoiWudjVupsGfnBYbxnDP";
+        assert_eq!(
+            ExtendedPubKey::from_str(&ecc, xpub).unwrap_err(),
+            XPubError::InvalidPubKey(EccError::InvalidPublicKey),
+        );
+    }
+
+    #[test]
+    fn test_xpub_derive_pub() {
+        let ecc = EccSecp256k1::default();
+        // BIP32 test vector 2, m -> m/0
+        let xpub = ExtendedPubKey::from_str_net(
+            &ecc,
+            "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB",
+            Net::Mainnet,
+        )
+        .unwrap();
+        assert_eq!(
+            xpub.derive_pub(&ecc, 0).unwrap().to_string(),
+            "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH",
+        );
+        // BIP32 test vector 1, m/0H -> m/0H/1
+        let xpub = ExtendedPubKey::from_str_net(
+            &ecc,
+            "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+            Net::Mainnet,
+        )
+        .unwrap();
+        assert_eq!(
+            xpub.derive_pub(&ecc, 1).unwrap().to_string(),
+            "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+        );
+
+        // First receive addresses of the test vector 2 master key
+        let xpub = ExtendedPubKey::from_str(&ecc, "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB")
+            .unwrap();
+        let addresses = (0..3)
+            .map(|index| {
+                let child = xpub.derive_pub(&ecc, index).unwrap();
+                CashAddress::p2pkh_from_pubkey(ECASH, child.pubkey())
+                    .as_str()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            addresses,
+            vec![
+                "ecash:qpdxrluwk74v5vqsmwt7hknkzgtppduqjcvyf3cm6n",
+                "ecash:qqhhj2nc9n62mwejrlnydj8zyptrvjdclga4qy0nv4",
+                "ecash:qrwvtwfm2gth678e0vlj6fvmn2rwu9qrky2ppcc452",
+            ],
+        );
+    }
+
     #[test]
     fn test_sign() {
         let ecc = EccSecp256k1::default();
@@ -161,6 +339,7 @@ mod tests {
         let msg = [3; 32];
         let sig = ecc.sign(&seckey, msg.into());
         assert_eq!(sig.hex(), "304402207228f8a93734f17480911e04ee5d83d8ccb1e880c8b46f71ce1c2f99c87627bd022069a70f991882d15929b565507cb380108719c69f8105e2c16c6e1d4b4efb747f");
+        assert!(sig.ct_eq(&ecc.sign(&seckey, msg.into())));
         let pubkey = ecc.derive_pubkey(&seckey);
         assert_eq!(
             pubkey.hex(),
@@ -266,6 +445,9 @@ mod tests {
         let msg = [3; 32];
         let (recover_id, signature) = ecc.sign_recoverable(&seckey, msg.into());
         let pubkey = ecc.recover_sig(&signature, recover_id, msg.into()).unwrap();
+        let (recover_id2, signature2) = ecc.sign_recoverable(&seckey, msg.into());
+        assert_eq!(recover_id, recover_id2);
+        assert!(signature.ct_eq(&signature2));
         let expected_pk = ecc.derive_pubkey(&seckey);
         assert_eq!(pubkey, expected_pk);
         let wrong_pubkey = ecc
@@ -0,0 +1,286 @@
+//! Naive n-of-n key and nonce aggregation for Schnorr signatures.
+//!
+//! The aggregated signature is a regular Schnorr signature for the sum of all pubkeys, so a
+//! multi-party spend looks like a single-sig spend on chain.
+//!
+//! This is NOT MuSig and only meant for prototyping: keys are simply added up, which allows
+//! rogue key attacks unless every participant proves knowledge of their secret key
+//! beforehand, and public nonces have to be committed to before being revealed.
+
+use bitcoinsuite_core::{
+    ecc::{EccError, PubKey, SecKey},
+    ByteArray, Bytes, Hashed, Sha256,
+};
+use secp256k1_abc::{rand::thread_rng, PublicKey, SecretKey};
+
+use crate::EccSecp256k1;
+
+/// Field size of secp256k1, as little-endian u64 limbs.
+const FIELD_SIZE: [u64; 4] = [
+    0xffff_fffe_ffff_fc2f,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+];
+
+/// Group order of secp256k1, as little-endian u64 limbs.
+const GROUP_ORDER: [u64; 4] = [
+    0xbfd2_5e8c_d036_4141,
+    0xbaae_dce6_af48_a03b,
+    0xffff_ffff_ffff_fffe,
+    0xffff_ffff_ffff_ffff,
+];
+
+/// Secret nonce of a signer; consumed when signing so it can't be reused.
+pub struct SecNonce(SecretKey);
+
+/// Sum of all public nonces, negated if necessary so its y coordinate is a quadratic residue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggNonce {
+    point: PublicKey,
+    negate: bool,
+}
+
+/// Signature share of a single signer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialSig([u8; 32]);
+
+impl EccSecp256k1 {
+    /// Add up the pubkeys of all signers.
+    pub fn combine_pubkeys(&self, pubkeys: &[PubKey]) -> Result<PubKey, EccError> {
+        let point = combine_points(pubkeys)?;
+        Ok(PubKey::new_unchecked(point.serialize()))
+    }
+
+    /// Generate a fresh random nonce; the returned pubkey is shared with the other signers.
+    pub fn gen_nonce(&self) -> (SecNonce, PubKey) {
+        let seckey = SecretKey::new(&mut thread_rng());
+        let pubkey = PublicKey::from_secret_key(&self.curve, &seckey);
+        (SecNonce(seckey), PubKey::new_unchecked(pubkey.serialize()))
+    }
+
+    /// Add up the public nonces of all signers.
+    pub fn combine_nonces(&self, nonces: &[PubKey]) -> Result<AggNonce, EccError> {
+        let mut point = combine_points(nonces)?;
+        let uncompressed = point.serialize_uncompressed();
+        let negate = !is_quadratic_residue(&uncompressed[33..]);
+        if negate {
+            point.negate_assign(&self.curve);
+        }
+        Ok(AggNonce { point, negate })
+    }
+
+    /// Compute this signer's share `s_i = k_i + e * x_i`.
+    pub fn partial_schnorr_sign(
+        &self,
+        seckey: &SecKey,
+        sec_nonce: SecNonce,
+        agg_nonce: &AggNonce,
+        agg_pubkey: &PubKey,
+        msg: ByteArray<32>,
+    ) -> Result<PartialSig, EccError> {
+        let challenge = challenge(agg_nonce, agg_pubkey, msg);
+        let mut nonce = sec_nonce.0;
+        if agg_nonce.negate {
+            nonce.negate_assign();
+        }
+        let mut sig =
+            SecretKey::from_slice(seckey.as_slice()).map_err(|_| EccError::InvalidSecretKey)?;
+        sig.mul_assign(&challenge)
+            .map_err(|_| EccError::InvalidSecretKey)?;
+        sig.add_assign(&nonce[..])
+            .map_err(|_| EccError::InvalidSecretKey)?;
+        let mut partial_sig = [0; 32];
+        partial_sig.copy_from_slice(&sig[..]);
+        Ok(PartialSig(partial_sig))
+    }
+
+    /// Add up all signature shares into a Schnorr signature valid for the combined pubkey.
+    pub fn aggregate_partial_sigs(
+        &self,
+        agg_nonce: &AggNonce,
+        partial_sigs: &[PartialSig],
+    ) -> Result<Bytes, EccError> {
+        let (first, rest) = partial_sigs
+            .split_first()
+            .ok_or(EccError::InvalidSignatureFormat)?;
+        let mut sig =
+            SecretKey::from_slice(&first.0).map_err(|_| EccError::InvalidSignatureFormat)?;
+        for partial_sig in rest {
+            sig.add_assign(&partial_sig.0)
+                .map_err(|_| EccError::InvalidSignatureFormat)?;
+        }
+        let mut schnorr_sig = Vec::with_capacity(64);
+        schnorr_sig.extend_from_slice(&agg_nonce.point.serialize()[1..]);
+        schnorr_sig.extend_from_slice(&sig[..]);
+        Ok(schnorr_sig.into())
+    }
+}
+
+fn combine_points(pubkeys: &[PubKey]) -> Result<PublicKey, EccError> {
+    let mut points = pubkeys
+        .iter()
+        .map(|pubkey| PublicKey::from_slice(pubkey.as_slice()));
+    let mut sum = points
+        .next()
+        .ok_or(EccError::InvalidPublicKey)?
+        .map_err(|_| EccError::InvalidPublicKey)?;
+    for point in points {
+        let point = point.map_err(|_| EccError::InvalidPublicKey)?;
+        sum = sum
+            .combine(&point)
+            .map_err(|_| EccError::InvalidPublicKey)?;
+    }
+    Ok(sum)
+}
+
+/// e = SHA256(R.x || P || m) mod n, as in BCH Schnorr signatures.
+fn challenge(agg_nonce: &AggNonce, agg_pubkey: &PubKey, msg: ByteArray<32>) -> [u8; 32] {
+    let mut preimage = Vec::with_capacity(32 + 33 + 32);
+    preimage.extend_from_slice(&agg_nonce.point.serialize()[1..]);
+    preimage.extend_from_slice(agg_pubkey.as_slice());
+    preimage.extend_from_slice(&msg);
+    let hash = Sha256::digest(preimage.into());
+    let mut challenge = limbs_from_be(hash.as_slice());
+    // hash < 2^256 < 2n, so a single subtraction reduces it
+    if !is_less(&challenge, &GROUP_ORDER) {
+        challenge = sub(&challenge, &GROUP_ORDER);
+    }
+    limbs_to_be(&challenge)
+}
+
+/// Whether the field element y is a quadratic residue, i.e. its Jacobi symbol is 1.
+/// Uses the binary Jacobi algorithm, which only needs shifts and subtractions.
+fn is_quadratic_residue(y: &[u8]) -> bool {
+    let mut a = limbs_from_be(y);
+    let mut n = FIELD_SIZE;
+    let mut is_positive = true;
+    loop {
+        if a == [0; 4] {
+            return n == [1, 0, 0, 0] && is_positive;
+        }
+        while a[0] & 1 == 0 {
+            a = shr1(&a);
+            if matches!(n[0] & 7, 3 | 5) {
+                is_positive = !is_positive;
+            }
+        }
+        if is_less(&a, &n) {
+            std::mem::swap(&mut a, &mut n);
+            if a[0] & 3 == 3 && n[0] & 3 == 3 {
+                is_positive = !is_positive;
+            }
+        }
+        a = sub(&a, &n);
+    }
+}
+
+fn limbs_from_be(bytes: &[u8]) -> [u64; 4] {
+    let mut limbs = [0; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+fn limbs_to_be(limbs: &[u64; 4]) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    for (chunk, limb) in bytes.rchunks_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+fn is_less(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    a.iter().rev().lt(b.iter().rev())
+}
+
+/// a - b, requires a >= b.
+fn sub(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut result = [0; 4];
+    let mut borrow = false;
+    for ((result, &a), &b) in result.iter_mut().zip(a).zip(b) {
+        let (diff, borrow1) = a.overflowing_sub(b);
+        let (diff, borrow2) = diff.overflowing_sub(borrow as u64);
+        *result = diff;
+        borrow = borrow1 || borrow2;
+    }
+    result
+}
+
+fn shr1(a: &[u64; 4]) -> [u64; 4] {
+    let mut result = [0; 4];
+    let higher_limbs = a.iter().skip(1).chain([&0]);
+    for ((result, &limb), &higher_limb) in result.iter_mut().zip(a).zip(higher_limbs) {
+        *result = (limb >> 1) | (higher_limb << 63);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{ecc::Ecc, ByteArray};
+
+    use crate::EccSecp256k1;
+
+    use super::{is_quadratic_residue, limbs_to_be};
+
+    #[test]
+    fn test_is_quadratic_residue() {
+        let small = |num: u64| limbs_to_be(&[num, 0, 0, 0]);
+        assert!(is_quadratic_residue(&small(1)));
+        assert!(is_quadratic_residue(&small(4)));
+        assert!(is_quadratic_residue(&small(9)));
+        // p = 3 mod 4, so -1 is not a quadratic residue
+        assert!(!is_quadratic_residue(&limbs_to_be(&[
+            0xffff_fffe_ffff_fc2e,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+        ])));
+    }
+
+    #[test]
+    fn test_naive_musig() {
+        let ecc = EccSecp256k1::default();
+        let msg = ByteArray::new([7; 32]);
+        // Run multiple sessions, so both signs of the aggregated nonce are covered
+        for num_signers in 1..=8 {
+            let seckeys = (0..num_signers)
+                .map(|idx| ecc.seckey_from_array([idx as u8 + 1; 32]).unwrap())
+                .collect::<Vec<_>>();
+            let pubkeys = seckeys
+                .iter()
+                .map(|seckey| ecc.derive_pubkey(seckey))
+                .collect::<Vec<_>>();
+            let agg_pubkey = ecc.combine_pubkeys(&pubkeys).unwrap();
+            let (sec_nonces, pub_nonces): (Vec<_>, Vec<_>) =
+                (0..num_signers).map(|_| ecc.gen_nonce()).unzip();
+            let agg_nonce = ecc.combine_nonces(&pub_nonces).unwrap();
+            let partial_sigs = seckeys
+                .iter()
+                .zip(sec_nonces)
+                .map(|(seckey, sec_nonce)| {
+                    ecc.partial_schnorr_sign(
+                        seckey,
+                        sec_nonce,
+                        &agg_nonce,
+                        &agg_pubkey,
+                        msg.clone(),
+                    )
+                    .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let sig = ecc
+                .aggregate_partial_sigs(&agg_nonce, &partial_sigs)
+                .unwrap();
+            assert_eq!(sig.len(), 64);
+            ecc.schnorr_verify(&agg_pubkey, msg.clone(), &sig).unwrap();
+            // Signature doesn't verify for any single signer
+            if num_signers > 1 {
+                assert!(ecc.schnorr_verify(&pubkeys[0], msg.clone(), &sig).is_err());
+            }
+        }
+        assert!(ecc.combine_pubkeys(&[]).is_err());
+    }
+}
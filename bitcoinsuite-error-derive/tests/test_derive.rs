@@ -1,4 +1,4 @@
-use bitcoinsuite_error::{ErrorMeta, ErrorSeverity};
+use bitcoinsuite_error::{find_meta, ErrorMeta, ErrorSeverity, Report, WrapErr};
 use thiserror::Error;
 
 #[test]
@@ -78,3 +78,41 @@ fn test_derive() {
         assert_eq!(error.tags().as_ref(), &[]);
     }
 }
+
+#[test]
+fn test_find_meta() {
+    #[derive(Error, ErrorMeta, Debug)]
+    enum InnerError {
+        #[invalid_user_input()]
+        #[error("Insufficient funds")]
+        InsufficientFunds,
+    }
+
+    #[derive(Error, ErrorMeta, Debug)]
+    enum OtherError {
+        #[bug()]
+        #[error("Other")]
+        Other,
+    }
+
+    let report = Report::new(InnerError::InsufficientFunds);
+    let meta = find_meta::<InnerError>(&report).unwrap();
+    assert_eq!(meta.error_code(), "insufficient-funds");
+    assert_eq!(meta.severity(), ErrorSeverity::InvalidUserInput);
+    assert!(find_meta::<OtherError>(&report).is_none());
+
+    // Also found below added context
+    let result: Result<(), _> = Err(InnerError::InsufficientFunds);
+    let report = result.wrap_err("Signing failed").unwrap_err();
+    let meta = find_meta::<InnerError>(&report).unwrap();
+    assert_eq!(meta.error_code(), "insufficient-funds");
+    assert_eq!(report.to_string(), "Signing failed");
+
+    // Each type only finds its own errors
+    let report = Report::new(OtherError::Other);
+    let meta = find_meta::<OtherError>(&report).unwrap();
+    assert_eq!(meta.error_code(), "other");
+    assert_eq!(meta.severity(), ErrorSeverity::Bug);
+    assert_eq!(report.to_string(), "Other");
+    assert!(find_meta::<InnerError>(&report).is_none());
+}
@@ -42,6 +42,17 @@ impl ErrorFmt for eyre::Report {
     }
 }
 
+/// Finds the first error of type `E` in the report's chain, e.g. to branch on its error code.
+pub fn find_meta<E>(report: &Report) -> Option<&dyn ErrorMeta>
+where
+    E: ErrorMeta + std::error::Error + Send + Sync + 'static,
+{
+    report
+        .chain()
+        .find_map(|err| err.downcast_ref::<E>())
+        .map(|err| err as &dyn ErrorMeta)
+}
+
 pub fn report_to_details(
     report: &Report,
     detail_func: impl Fn(&Report) -> Option<&dyn ErrorMeta>,
@@ -85,7 +96,11 @@ pub fn install() -> Result<()> {
     let lock = ERROR_HANDLE_LOCK.lock().unwrap();
     let is_started = ERROR_HANDLE_IS_STARTED.load(atomic::Ordering::SeqCst);
     if !is_started {
-        stable_eyre::install()?;
+        // Without RUST_BACKTRACE or RUST_LIB_BACKTRACE set by the caller, reports only capture
+        // backtraces with the "backtrace" feature
+        stable_eyre::HookBuilder::default()
+            .capture_backtrace_by_default(cfg!(feature = "backtrace"))
+            .install()?;
         ERROR_HANDLE_IS_STARTED.store(true, atomic::Ordering::SeqCst);
     }
     std::mem::drop(lock);
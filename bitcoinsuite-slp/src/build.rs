@@ -1,97 +1,862 @@
-use bitcoinsuite_core::Script;
+use bitcoinsuite_core::{NetworkParams, Script, Sha256d, TxOutput};
 
 use crate::{
     consts::{
-        SLP_LOKAD_ID, SLP_TOKEN_TYPE_V1, SLP_TOKEN_TYPE_V1_NFT1_CHILD, SLP_TOKEN_TYPE_V1_NFT1_GROUP,
+        SLP_DUST_AMOUNT, SLP_LOKAD_ID, SLP_OUTPUT_QUANTITY_FIELD_NAMES, SLP_TOKEN_TYPE_V1,
+        SLP_TOKEN_TYPE_V1_NFT1_CHILD, SLP_TOKEN_TYPE_V1_NFT1_GROUP, SLP_TX_TYPE_BURN,
+        SLP_TX_TYPE_GENESIS, SLP_TX_TYPE_MINT, SLP_TX_TYPE_SEND,
     },
-    SlpAmount, SlpGenesisInfo, SlpTokenType, TokenId,
+    SlpAmount, SlpError, SlpGenesisInfo, SlpParseData, SlpToken, SlpTokenType, SlpTxType, TokenId,
 };
 
-fn token_type_bytes(token_type: SlpTokenType) -> &'static [u8] {
+fn token_type_bytes(token_type: SlpTokenType) -> Result<&'static [u8], SlpError> {
     match token_type {
-        SlpTokenType::Fungible => SLP_TOKEN_TYPE_V1,
-        SlpTokenType::Nft1Group => SLP_TOKEN_TYPE_V1_NFT1_GROUP,
-        SlpTokenType::Nft1Child => SLP_TOKEN_TYPE_V1_NFT1_CHILD,
-        SlpTokenType::Unknown => panic!("Cannot use 'Unknown' token type here"),
+        SlpTokenType::Fungible => Ok(SLP_TOKEN_TYPE_V1),
+        SlpTokenType::Nft1Group => Ok(SLP_TOKEN_TYPE_V1_NFT1_GROUP),
+        SlpTokenType::Nft1Child => Ok(SLP_TOKEN_TYPE_V1_NFT1_CHILD),
+        SlpTokenType::Unknown => Err(SlpError::UnknownTokenType),
     }
 }
 
+/// Output a GENESIS or MINT tx places its mint baton at, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MintBaton {
+    /// Output index of the mint baton, or `None` to end the token's minting.
+    ///
+    /// Output 0 is the OP_RETURN and output 1 receives the minted tokens, so SLP only allows
+    /// indices from 2 to 255.
+    pub vout: Option<u8>,
+}
+
+impl MintBaton {
+    /// No mint baton, no more tokens can be minted afterwards.
+    pub const NONE: MintBaton = MintBaton { vout: None };
+
+    /// Mint baton at output `vout`.
+    pub fn at(vout: u8) -> Self {
+        MintBaton { vout: Some(vout) }
+    }
+
+    /// Check that the mint baton points to one of the `num_outputs` outputs of the tx (including
+    /// the OP_RETURN), and not to the OP_RETURN or the token output.
+    pub fn check_outputs(&self, num_outputs: usize) -> Result<(), SlpError> {
+        let vout = match self.vout {
+            Some(vout) => vout,
+            None => return Ok(()),
+        };
+        if vout < 2 {
+            return Err(SlpError::InvalidMintBatonIdx {
+                actual: vout as usize,
+            });
+        }
+        if vout as usize >= num_outputs {
+            return Err(SlpError::MintBatonOutputMissing { vout, num_outputs });
+        }
+        Ok(())
+    }
+
+    fn to_bytes(self) -> Result<Vec<u8>, SlpError> {
+        self.check_outputs(usize::MAX)?;
+        Ok(self.vout.into_iter().collect())
+    }
+}
+
+impl From<Option<u8>> for MintBaton {
+    fn from(vout: Option<u8>) -> Self {
+        MintBaton { vout }
+    }
+}
+
+/// Build an OP_RETURN in the SLP layout: LOKAD ID, token type, tx type followed by `fields`.
+///
+/// This allows building OP_RETURNs of related token protocols. Note that SLP nodes like BCHD
+/// only validate OP_RETURNs with the [`SLP_LOKAD_ID`].
+pub fn build_token_opreturn(
+    lokad_id: &[u8; 4],
+    token_type: &[u8],
+    tx_type: &[u8],
+    fields: &[&[u8]],
+) -> Script {
+    let mut pushes: Vec<&[u8]> = Vec::with_capacity(3 + fields.len());
+    pushes.push(lokad_id);
+    pushes.push(token_type);
+    pushes.push(tx_type);
+    pushes.extend_from_slice(fields);
+    Script::opreturn(&pushes)
+}
+
 pub fn genesis_opreturn(
     genesis_info: &SlpGenesisInfo,
     token_type: SlpTokenType,
-    mint_baton_out_idx: Option<usize>,
+    mint_baton: MintBaton,
     initial_quantity: u64,
-) -> Script {
-    Script::opreturn(&[
+) -> Result<Script, SlpError> {
+    if genesis_info.decimals > 9 {
+        return Err(SlpError::InvalidDecimals {
+            actual: genesis_info.decimals as usize,
+        });
+    }
+    Ok(build_token_opreturn(
         SLP_LOKAD_ID,
-        token_type_bytes(token_type),
-        b"GENESIS",
-        &genesis_info.token_ticker,
-        &genesis_info.token_name,
-        &genesis_info.token_document_url,
-        match &genesis_info.token_document_hash {
-            Some(hash) => hash,
-            None => &[],
-        },
-        &[genesis_info.decimals.try_into().unwrap()],
-        &match mint_baton_out_idx {
-            Some(out_idx) => vec![out_idx.try_into().unwrap()],
-            None => vec![],
-        },
-        &initial_quantity.to_be_bytes(),
-    ])
+        token_type_bytes(token_type)?,
+        SLP_TX_TYPE_GENESIS,
+        &[
+            &genesis_info.token_ticker,
+            &genesis_info.token_name,
+            &genesis_info.token_document_url,
+            match &genesis_info.token_document_hash {
+                Some(hash) => hash,
+                None => &[],
+            },
+            &[genesis_info.decimals as u8],
+            &mint_baton.to_bytes()?,
+            &initial_quantity.to_be_bytes(),
+        ],
+    ))
+}
+
+/// Build the OP_RETURN of a GENESIS tx for a fixed supply token, which has no mint baton, so
+/// `initial_quantity` is all that can ever exist.
+pub fn genesis_opreturn_fixed(
+    genesis_info: &SlpGenesisInfo,
+    token_type: SlpTokenType,
+    initial_quantity: SlpAmount,
+) -> Result<Script, SlpError> {
+    if initial_quantity <= SlpAmount::ZERO {
+        return Err(SlpError::InitialQuantityNotPositive {
+            actual: initial_quantity,
+        });
+    }
+    let initial_quantity = u64::from_be_bytes(initial_quantity.to_be_bytes()?);
+    genesis_opreturn(genesis_info, token_type, MintBaton::NONE, initial_quantity)
 }
 
 pub fn mint_opreturn(
     token_id: &TokenId,
     token_type: SlpTokenType,
-    mint_baton_out_idx: Option<usize>,
+    mint_baton: MintBaton,
     additional_quantity: u64,
-) -> Script {
-    Script::opreturn(&[
+) -> Result<Script, SlpError> {
+    Ok(build_token_opreturn(
         SLP_LOKAD_ID,
-        token_type_bytes(token_type),
-        b"MINT",
-        token_id.as_slice_be(),
-        &match mint_baton_out_idx {
-            Some(out_idx) => vec![out_idx.try_into().unwrap()],
-            None => vec![],
-        },
-        &additional_quantity.to_be_bytes(),
-    ])
+        token_type_bytes(token_type)?,
+        SLP_TX_TYPE_MINT,
+        &[
+            token_id.as_slice_be(),
+            &mint_baton.to_bytes()?,
+            &additional_quantity.to_be_bytes(),
+        ],
+    ))
 }
 
 pub fn send_opreturn(
     token_id: &TokenId,
     token_type: SlpTokenType,
     send_amounts: &[SlpAmount],
-) -> Script {
-    let mut pushes: Vec<&[u8]> = vec![
-        SLP_LOKAD_ID,
-        token_type_bytes(token_type),
-        b"SEND",
-        token_id.as_slice_be(),
-    ];
+) -> Result<Script, SlpError> {
+    if send_amounts.is_empty() {
+        return Err(SlpError::NoSendAmounts);
+    }
+    if send_amounts.len() > SLP_OUTPUT_QUANTITY_FIELD_NAMES.len() {
+        return Err(SlpError::TooManySendAmounts {
+            expected: SLP_OUTPUT_QUANTITY_FIELD_NAMES.len(),
+            actual: send_amounts.len(),
+        });
+    }
     let send_amounts = send_amounts
         .iter()
-        .map(|amount| amount.base_amount() as u64)
-        .map(|amount| amount.to_be_bytes())
-        .collect::<Vec<_>>();
-    pushes.extend(send_amounts.iter().map(|slice| slice.as_ref()));
-    Script::opreturn(&pushes)
+        .map(SlpAmount::to_be_bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut fields: Vec<&[u8]> = vec![token_id.as_slice_be()];
+    fields.extend(send_amounts.iter().map(|slice| slice.as_ref()));
+    Ok(build_token_opreturn(
+        SLP_LOKAD_ID,
+        token_type_bytes(token_type)?,
+        SLP_TX_TYPE_SEND,
+        &fields,
+    ))
 }
 
 pub fn burn_opreturn(
     token_id: &TokenId,
     token_type: SlpTokenType,
     burn_amount: SlpAmount,
-) -> Script {
-    let burn_amount = burn_amount.base_amount() as u64;
-    Script::opreturn(&[
+) -> Result<Script, SlpError> {
+    Ok(build_token_opreturn(
         SLP_LOKAD_ID,
-        token_type_bytes(token_type),
-        b"BURN",
-        token_id.as_slice_be(),
-        &burn_amount.to_be_bytes(),
-    ])
+        token_type_bytes(token_type)?,
+        SLP_TX_TYPE_BURN,
+        &[token_id.as_slice_be(), &burn_amount.to_be_bytes()?],
+    ))
+}
+
+/// Like [`genesis_opreturn`], but also returns the [`SlpParseData`] that [`parse_slp_tx`] yields
+/// for a tx with this OP_RETURN and just enough outputs for the tokens and the mint baton.
+///
+/// The token ID is all zeros, as the txid is only known once the tx is signed.
+///
+/// [`parse_slp_tx`]: crate::parse_slp_tx
+pub fn genesis_opreturn_preview(
+    genesis_info: &SlpGenesisInfo,
+    token_type: SlpTokenType,
+    mint_baton: MintBaton,
+    initial_quantity: u64,
+) -> Result<(Script, SlpParseData), SlpError> {
+    let opreturn = genesis_opreturn(genesis_info, token_type, mint_baton, initial_quantity)?;
+    let parse_data = SlpParseData {
+        output_tokens: mint_output_tokens(mint_baton, initial_quantity),
+        slp_token_type: token_type,
+        slp_tx_type: SlpTxType::Genesis(Box::new(genesis_info.clone())),
+        token_id: TokenId::new(Sha256d::new([0; 32])),
+    };
+    Ok((opreturn, parse_data))
+}
+
+/// Like [`mint_opreturn`], but also returns the [`SlpParseData`] of the tx, see
+/// [`genesis_opreturn_preview`].
+pub fn mint_opreturn_preview(
+    token_id: &TokenId,
+    token_type: SlpTokenType,
+    mint_baton: MintBaton,
+    additional_quantity: u64,
+) -> Result<(Script, SlpParseData), SlpError> {
+    let opreturn = mint_opreturn(token_id, token_type, mint_baton, additional_quantity)?;
+    let parse_data = SlpParseData {
+        output_tokens: mint_output_tokens(mint_baton, additional_quantity),
+        slp_token_type: token_type,
+        slp_tx_type: SlpTxType::Mint,
+        token_id: token_id.clone(),
+    };
+    Ok((opreturn, parse_data))
+}
+
+/// Like [`send_opreturn`], but also returns the [`SlpParseData`] of the tx, see
+/// [`genesis_opreturn_preview`].
+pub fn send_opreturn_preview(
+    token_id: &TokenId,
+    token_type: SlpTokenType,
+    send_amounts: &[SlpAmount],
+) -> Result<(Script, SlpParseData), SlpError> {
+    let opreturn = send_opreturn(token_id, token_type, send_amounts)?;
+    let mut output_tokens = vec![SlpToken::EMPTY];
+    output_tokens.extend(send_amounts.iter().map(|&amount| SlpToken {
+        amount,
+        is_mint_baton: false,
+    }));
+    let parse_data = SlpParseData {
+        output_tokens,
+        slp_token_type: token_type,
+        slp_tx_type: SlpTxType::Send,
+        token_id: token_id.clone(),
+    };
+    Ok((opreturn, parse_data))
+}
+
+fn mint_output_tokens(mint_baton: MintBaton, quantity: u64) -> Vec<SlpToken> {
+    let num_outputs = match mint_baton.vout {
+        Some(vout) => vout as usize + 1,
+        None => 2,
+    };
+    let mut output_tokens = vec![SlpToken::EMPTY; num_outputs];
+    output_tokens[1] = SlpToken::amount(quantity as i128);
+    if let Some(vout) = mint_baton.vout {
+        output_tokens[vout as usize] = SlpToken::MINT_BATON;
+    }
+    output_tokens
+}
+
+/// Build the OP_RETURN script and token outputs of a tx deliberately burning
+/// the tokens of `inputs`, optionally keeping some amount at the given script.
+///
+/// Without `keep`, this results in a BURN tx, which burns all `inputs` and
+/// whose burns are marked as intended by validation. With `keep`, a SEND tx
+/// sending the kept amount to output 1 is built, burning the difference; as
+/// SEND txs can't declare burns, the caller marks them as intended using
+/// [`SlpValidTxData::mark_intended_send_burns`](crate::SlpValidTxData::mark_intended_send_burns).
+/// The returned outputs have to be placed after the OP_RETURN output.
+pub fn build_slp_burn(
+    token_id: &TokenId,
+    token_type: SlpTokenType,
+    inputs: &[SlpToken],
+    keep: Option<(SlpAmount, Script)>,
+) -> Result<(Script, Vec<TxOutput>), SlpError> {
+    if inputs.iter().any(|token| token.is_mint_baton) {
+        return Err(SlpError::WrongBurnMintBaton);
+    }
+    let input_sum = inputs.iter().map(|token| token.amount).sum::<SlpAmount>();
+    match keep {
+        Some((keep_amount, keep_script)) if keep_amount > SlpAmount::ZERO => {
+            if keep_amount > input_sum {
+                return Err(SlpError::OutputSumExceedInputSum {
+                    output_sum: keep_amount,
+                    input_sum,
+                });
+            }
+            let opreturn = send_opreturn(token_id, token_type, &[keep_amount])?;
+            let keep_output = TxOutput {
+                value: SLP_DUST_AMOUNT,
+                script: keep_script,
+            };
+            Ok((opreturn, vec![keep_output]))
+        }
+        _ => Ok((burn_opreturn(token_id, token_type, input_sum)?, vec![])),
+    }
+}
+
+/// Build the outputs of a GENESIS tx: the OP_RETURN followed by `outputs`.
+///
+/// `outputs[0]` receives the initial quantity, and the mint baton has to point to one of
+/// `outputs`, otherwise the baton would be lost.
+pub fn build_slp_genesis(
+    genesis_info: &SlpGenesisInfo,
+    token_type: SlpTokenType,
+    mint_baton: MintBaton,
+    initial_quantity: u64,
+    outputs: Vec<TxOutput>,
+) -> Result<Vec<TxOutput>, SlpError> {
+    mint_baton.check_outputs(outputs.len() + 1)?;
+    let opreturn = genesis_opreturn(genesis_info, token_type, mint_baton, initial_quantity)?;
+    let mut tx_outputs = Vec::with_capacity(outputs.len() + 1);
+    tx_outputs.push(TxOutput {
+        value: 0,
+        script: opreturn,
+    });
+    tx_outputs.extend(outputs);
+    Ok(tx_outputs)
+}
+
+/// Build the outputs of a SEND tx with an additional OP_RETURN carrying `memo`: the SLP
+/// OP_RETURN, then `outputs`, then the memo OP_RETURN.
+///
+/// `send_amounts[i]` is sent to `outputs[i]`; the memo comes last so it never takes a token
+/// output index. Whether a second OP_RETURN is relayed is node policy, so this fails unless
+/// `params` allows multiple OP_RETURNs and both scripts together fit `max_op_return_bytes`.
+pub fn build_slp_send_with_memo(
+    token_id: &TokenId,
+    token_type: SlpTokenType,
+    send_amounts: &[SlpAmount],
+    outputs: Vec<TxOutput>,
+    memo: &[&[u8]],
+    params: &NetworkParams,
+) -> Result<Vec<TxOutput>, SlpError> {
+    if !params.multiple_op_returns {
+        return Err(SlpError::MultipleOpReturnsNotRelayed);
+    }
+    if send_amounts.len() > outputs.len() {
+        return Err(SlpError::TokenOutputMissing {
+            idx: send_amounts.len(),
+            num_outputs: outputs.len() + 1,
+        });
+    }
+    let opreturn = send_opreturn(token_id, token_type, send_amounts)?;
+    let memo = Script::try_opreturn_with_limit(memo, usize::MAX).map_err(SlpError::InvalidMemo)?;
+    let size = opreturn.bytecode().len() + memo.bytecode().len();
+    if size > params.max_op_return_bytes {
+        return Err(SlpError::CombinedOpReturnsTooLarge {
+            size,
+            max_size: params.max_op_return_bytes,
+        });
+    }
+    let mut tx_outputs = Vec::with_capacity(outputs.len() + 2);
+    tx_outputs.push(TxOutput {
+        value: 0,
+        script: opreturn,
+    });
+    tx_outputs.extend(outputs);
+    tx_outputs.push(TxOutput {
+        value: 0,
+        script: memo,
+    });
+    Ok(tx_outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{
+        Net, Network, OpReturnError, Script, Sha256d, ShaRmd160, TxOutput, UnhashedTx,
+    };
+    use bitcoinsuite_error::Result;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        build_slp_burn, build_slp_genesis, build_slp_send_with_memo, build_token_opreturn,
+        burn_opreturn, genesis_opreturn, genesis_opreturn_fixed, genesis_opreturn_preview,
+        mint_opreturn, mint_opreturn_preview, parse_slp_tx, send_opreturn, send_opreturn_preview,
+        validate_slp_tx, MintBaton, SlpAmount, SlpBurn, SlpError, SlpGenesisInfo, SlpSpentOutput,
+        SlpToken, SlpTokenType, SlpTxType, SlpValidTxData, TokenId,
+    };
+
+    fn validate_burn(
+        token_id: &TokenId,
+        inputs: &[SlpToken],
+        opreturn: Script,
+        outputs: Vec<TxOutput>,
+    ) -> Result<SlpValidTxData> {
+        let mut tx = UnhashedTx::default();
+        tx.outputs.push(TxOutput {
+            value: 0,
+            script: opreturn,
+        });
+        tx.outputs.extend(outputs);
+        let parse_data = parse_slp_tx(&Sha256d::default(), &tx)?;
+        let spent_outputs = inputs
+            .iter()
+            .map(|&token| SlpSpentOutput {
+                token_id: token_id.clone(),
+                token_type: SlpTokenType::Fungible,
+                token,
+                group_token_id: None,
+            })
+            .collect::<Vec<_>>();
+        let spent_outputs = spent_outputs.iter().map(Some).collect::<Vec<_>>();
+        Ok(validate_slp_tx(parse_data, &spent_outputs)?)
+    }
+
+    #[test]
+    fn test_build_slp_burn_all() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let inputs = [SlpToken::amount(10), SlpToken::amount(20)];
+        let (opreturn, outputs) = build_slp_burn(&token_id, SlpTokenType::Fungible, &inputs, None)?;
+        assert_eq!(outputs, vec![]);
+        let valid_data = validate_burn(&token_id, &inputs, opreturn, outputs)?;
+        assert_eq!(
+            valid_data.slp_burns,
+            inputs
+                .iter()
+                .map(|&token| Some(Box::new(SlpBurn {
+                    token,
+                    token_id: token_id.clone(),
+                    is_intended: true,
+                })))
+                .collect::<Vec<_>>(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_slp_burn_keep() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let inputs = [SlpToken::amount(10), SlpToken::amount(20)];
+        let keep_script = Script::p2pkh(&ShaRmd160::new([2; 20]));
+        let (opreturn, outputs) = build_slp_burn(
+            &token_id,
+            SlpTokenType::Fungible,
+            &inputs,
+            Some((SlpAmount::new(12), keep_script.clone())),
+        )?;
+        assert_eq!(
+            outputs,
+            vec![TxOutput {
+                value: 546,
+                script: keep_script,
+            }],
+        );
+        let mut valid_data = validate_burn(&token_id, &inputs, opreturn, outputs)?;
+        assert_eq!(
+            valid_data.slp_tx_data.output_tokens,
+            vec![SlpToken::EMPTY, SlpToken::amount(12)],
+        );
+        let burned = valid_data
+            .slp_burns
+            .iter()
+            .flatten()
+            .map(|burn| burn.token.amount)
+            .sum::<SlpAmount>();
+        assert_eq!(burned, SlpAmount::new(18));
+        assert!(valid_data
+            .slp_burns
+            .iter()
+            .flatten()
+            .all(|burn| !burn.is_intended));
+
+        // Only the requested burn amount can be marked as intended
+        assert_eq!(
+            valid_data
+                .clone()
+                .mark_intended_send_burns(SlpAmount::new(17)),
+            Err(SlpError::WrongBurnInvalidAmount {
+                expected: SlpAmount::new(17),
+                actual: SlpAmount::new(18),
+            }),
+        );
+        valid_data.mark_intended_send_burns(SlpAmount::new(18))?;
+        assert_eq!(
+            valid_data.slp_burns,
+            vec![
+                None,
+                Some(Box::new(SlpBurn {
+                    token: SlpToken::amount(18),
+                    token_id: token_id.clone(),
+                    is_intended: true,
+                })),
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_slp_burn_failure() {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        assert_eq!(
+            build_slp_burn(
+                &token_id,
+                SlpTokenType::Fungible,
+                &[SlpToken::amount(10), SlpToken::MINT_BATON],
+                None,
+            ),
+            Err(SlpError::WrongBurnMintBaton),
+        );
+        assert_eq!(
+            build_slp_burn(
+                &token_id,
+                SlpTokenType::Fungible,
+                &[SlpToken::amount(10)],
+                Some((SlpAmount::new(11), Script::default())),
+            ),
+            Err(SlpError::OutputSumExceedInputSum {
+                output_sum: SlpAmount::new(11),
+                input_sum: SlpAmount::new(10),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_send_opreturn_num_amounts() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let amounts = vec![SlpAmount::new(7); 19];
+        let opreturn = send_opreturn(&token_id, SlpTokenType::Fungible, &amounts)?;
+        let mut tx = UnhashedTx::default();
+        tx.outputs.push(TxOutput {
+            value: 0,
+            script: opreturn,
+        });
+        let parse_data = parse_slp_tx(&Sha256d::default(), &tx)?;
+        assert_eq!(parse_data.slp_tx_type, SlpTxType::Send);
+        assert_eq!(parse_data.output_tokens.len(), 20);
+
+        let amounts = vec![SlpAmount::new(7); 20];
+        assert_eq!(
+            send_opreturn(&token_id, SlpTokenType::Fungible, &amounts),
+            Err(SlpError::TooManySendAmounts {
+                expected: 19,
+                actual: 20,
+            }),
+        );
+        assert_eq!(
+            send_opreturn(&token_id, SlpTokenType::Fungible, &[]),
+            Err(SlpError::NoSendAmounts),
+        );
+        assert_eq!(
+            send_opreturn(&token_id, SlpTokenType::Unknown, &[SlpAmount::new(7)]),
+            Err(SlpError::UnknownTokenType),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_opreturn_large_amount() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let amount = SlpAmount::new(0x0102_0304_0506_0708);
+        let opreturn = send_opreturn(&token_id, SlpTokenType::Fungible, &[amount])?;
+        // Last push is the amount, big-endian
+        assert!(opreturn.bytecode().ends_with(&[8, 1, 2, 3, 4, 5, 6, 7, 8]));
+        let mut tx = UnhashedTx::default();
+        tx.outputs.push(TxOutput {
+            value: 0,
+            script: opreturn,
+        });
+        let parse_data = parse_slp_tx(&Sha256d::default(), &tx)?;
+        assert_eq!(parse_data.output_tokens[1].amount, amount);
+        Ok(())
+    }
+
+    #[test]
+    fn test_opreturn_amount_out_of_range() {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let too_big = SlpAmount::new(u64::MAX as i128 + 1);
+        assert_eq!(
+            send_opreturn(&token_id, SlpTokenType::Fungible, &[too_big]),
+            Err(SlpError::AmountOutOfRange { amount: too_big }),
+        );
+        assert_eq!(
+            send_opreturn(&token_id, SlpTokenType::Fungible, &[SlpAmount::new(-1)]),
+            Err(SlpError::AmountOutOfRange {
+                amount: SlpAmount::new(-1),
+            }),
+        );
+        assert_eq!(
+            burn_opreturn(&token_id, SlpTokenType::Fungible, too_big),
+            Err(SlpError::AmountOutOfRange { amount: too_big }),
+        );
+        assert_eq!(
+            mint_opreturn(&token_id, SlpTokenType::Fungible, MintBaton::at(1), 10),
+            Err(SlpError::InvalidMintBatonIdx { actual: 1 }),
+        );
+    }
+
+    #[test]
+    fn test_build_slp_genesis() -> Result<()> {
+        let genesis_info = SlpGenesisInfo::default();
+        let output = TxOutput {
+            value: 546,
+            script: Script::p2pkh(&ShaRmd160::new([1; 20])),
+        };
+        let outputs = build_slp_genesis(
+            &genesis_info,
+            SlpTokenType::Fungible,
+            MintBaton::at(2),
+            20,
+            vec![output.clone(), output.clone()],
+        )?;
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(
+            outputs[0].script,
+            genesis_opreturn(&genesis_info, SlpTokenType::Fungible, Some(2).into(), 20)?,
+        );
+        let tx = UnhashedTx {
+            outputs,
+            ..Default::default()
+        };
+        let parse_data = parse_slp_tx(&Sha256d::default(), &tx)?;
+        assert_eq!(
+            parse_data.output_tokens,
+            vec![SlpToken::EMPTY, SlpToken::amount(20), SlpToken::MINT_BATON,],
+        );
+
+        assert_eq!(
+            build_slp_genesis(
+                &genesis_info,
+                SlpTokenType::Fungible,
+                MintBaton::at(0),
+                20,
+                vec![output.clone()],
+            ),
+            Err(SlpError::InvalidMintBatonIdx { actual: 0 }),
+        );
+        assert_eq!(
+            build_slp_genesis(
+                &genesis_info,
+                SlpTokenType::Fungible,
+                MintBaton::at(3),
+                20,
+                vec![output.clone(), output.clone()],
+            ),
+            Err(SlpError::MintBatonOutputMissing {
+                vout: 3,
+                num_outputs: 3,
+            }),
+        );
+        assert!(build_slp_genesis(
+            &genesis_info,
+            SlpTokenType::Fungible,
+            MintBaton::NONE,
+            20,
+            vec![output],
+        )
+        .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_genesis_opreturn_fixed() -> Result<()> {
+        let genesis_info = SlpGenesisInfo {
+            token_ticker: b"FIX".as_ref().into(),
+            decimals: 2,
+            ..Default::default()
+        };
+        let opreturn =
+            genesis_opreturn_fixed(&genesis_info, SlpTokenType::Fungible, SlpAmount::new(1000))?;
+        let output = TxOutput {
+            value: 546,
+            script: Script::p2pkh(&ShaRmd160::new([1; 20])),
+        };
+        let tx = UnhashedTx {
+            outputs: vec![
+                TxOutput {
+                    value: 0,
+                    script: opreturn,
+                },
+                output.clone(),
+                output.clone(),
+                output,
+            ],
+            ..Default::default()
+        };
+        let parse_data = parse_slp_tx(&Sha256d::default(), &tx)?;
+        assert_eq!(
+            parse_data.slp_tx_type,
+            SlpTxType::Genesis(Box::new(genesis_info.clone())),
+        );
+        assert_eq!(
+            parse_data.output_tokens,
+            vec![
+                SlpToken::EMPTY,
+                SlpToken::amount(1000),
+                SlpToken::EMPTY,
+                SlpToken::EMPTY,
+            ],
+        );
+        assert_eq!(
+            genesis_opreturn_fixed(&genesis_info, SlpTokenType::Fungible, SlpAmount::ZERO),
+            Err(SlpError::InitialQuantityNotPositive {
+                actual: SlpAmount::ZERO,
+            }),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_opreturn_preview() -> Result<()> {
+        let parse = |opreturn: Script, num_outputs: usize| {
+            let mut tx = UnhashedTx::default();
+            tx.outputs.push(TxOutput {
+                value: 0,
+                script: opreturn,
+            });
+            tx.outputs.resize(num_outputs, TxOutput::default());
+            parse_slp_tx(&Sha256d::new([0; 32]), &tx)
+        };
+        let genesis_info = SlpGenesisInfo {
+            token_ticker: b"PRE".as_ref().into(),
+            ..Default::default()
+        };
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        for mint_baton in [MintBaton::NONE, MintBaton::at(2), MintBaton::at(5)] {
+            let (opreturn, parse_data) =
+                genesis_opreturn_preview(&genesis_info, SlpTokenType::Fungible, mint_baton, 1000)?;
+            let num_outputs = parse_data.output_tokens.len();
+            assert_eq!(parse(opreturn, num_outputs)?, parse_data);
+
+            let (opreturn, parse_data) =
+                mint_opreturn_preview(&token_id, SlpTokenType::Fungible, mint_baton, 50)?;
+            let num_outputs = parse_data.output_tokens.len();
+            assert_eq!(parse(opreturn, num_outputs)?, parse_data);
+        }
+        let (_, parse_data) =
+            genesis_opreturn_preview(&genesis_info, SlpTokenType::Fungible, MintBaton::at(3), 7)?;
+        assert_eq!(
+            parse_data.output_tokens,
+            vec![
+                SlpToken::EMPTY,
+                SlpToken::amount(7),
+                SlpToken::EMPTY,
+                SlpToken::MINT_BATON,
+            ],
+        );
+
+        let amounts = [SlpAmount::new(5), SlpAmount::ZERO, SlpAmount::new(3)];
+        let (opreturn, parse_data) =
+            send_opreturn_preview(&token_id, SlpTokenType::Nft1Group, &amounts)?;
+        assert_eq!(parse(opreturn, 4)?, parse_data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_token_opreturn() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        assert_eq!(
+            build_token_opreturn(
+                b"SLP\0",
+                &[1],
+                b"SEND",
+                &[token_id.as_slice_be(), &7u64.to_be_bytes()],
+            ),
+            send_opreturn(&token_id, SlpTokenType::Fungible, &[SlpAmount::new(7)])?,
+        );
+        assert_eq!(
+            build_token_opreturn(b"TEST", &[0x42], b"INFO", &[b"abc", b""]),
+            Script::from_hex("6a0454455354014204494e464f036162634c00")?,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_slp_send_with_memo() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let amounts = [SlpAmount::new(10), SlpAmount::new(20)];
+        let outputs = vec![
+            TxOutput {
+                value: 546,
+                script: Script::p2pkh(&ShaRmd160::new([2; 20])),
+            };
+            3
+        ];
+        let xec_params = Network::XEC.params(Net::Mainnet);
+        let tx_outputs = build_slp_send_with_memo(
+            &token_id,
+            SlpTokenType::Fungible,
+            &amounts,
+            outputs.clone(),
+            &[b"memo", b"hello"],
+            &xec_params,
+        )?;
+        assert_eq!(tx_outputs.len(), 5);
+        assert_eq!(
+            tx_outputs[0].script,
+            send_opreturn(&token_id, SlpTokenType::Fungible, &amounts)?,
+        );
+        assert_eq!(tx_outputs[1..4], outputs);
+        assert_eq!(tx_outputs[4].script, Script::opreturn(&[b"memo", b"hello"]));
+        assert_eq!(tx_outputs[4].value, 0);
+        let mut tx = UnhashedTx::default();
+        tx.outputs = tx_outputs;
+        let parse_data = parse_slp_tx(&Sha256d::default(), &tx)?;
+        assert_eq!(
+            parse_data.output_tokens,
+            vec![
+                SlpToken::EMPTY,
+                SlpToken::amount(10),
+                SlpToken::amount(20),
+                SlpToken::EMPTY,
+                SlpToken::EMPTY,
+            ],
+        );
+
+        // SEND with 2 amounts is 64 bytes, leaving 159 bytes for the memo OP_RETURN, which
+        // spends 3 bytes on OP_RETURN and OP_PUSHDATA1
+        let build = |memo: &[u8], params| {
+            build_slp_send_with_memo(
+                &token_id,
+                SlpTokenType::Fungible,
+                &amounts,
+                outputs.clone(),
+                &[memo],
+                params,
+            )
+        };
+        build(&[0; 156], &xec_params)?;
+        assert_eq!(
+            build(&[0; 157], &xec_params),
+            Err(SlpError::CombinedOpReturnsTooLarge {
+                size: 224,
+                max_size: 223,
+            }),
+        );
+        assert_eq!(
+            build(&[0; 256], &xec_params),
+            Err(SlpError::InvalidMemo(OpReturnError::PushTooLarge {
+                idx: 0,
+                size: 256,
+            })),
+        );
+        assert_eq!(
+            build(b"memo", &Network::XPI.params(Net::Mainnet)),
+            Err(SlpError::MultipleOpReturnsNotRelayed),
+        );
+        assert_eq!(
+            build_slp_send_with_memo(
+                &token_id,
+                SlpTokenType::Fungible,
+                &amounts,
+                outputs[..1].to_vec(),
+                &[b"memo"],
+                &xec_params,
+            ),
+            Err(SlpError::TokenOutputMissing {
+                idx: 2,
+                num_outputs: 2,
+            }),
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,363 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bitcoinsuite_core::{CashAddress, Sha256d};
+use bitcoinsuite_error::Result;
+use futures::{Stream, StreamExt};
+
+use crate::{
+    AddressEvent, BlockEvent, BlockId, MempoolPackageInfo, NodeStatus, SlpGenesisInfo,
+    SlpNodeInterface, SlpTx, SlpUtxo, TokenId, TokenMetadata,
+};
+
+/// Wraps an [`SlpNodeInterface`] and memoizes confirmed txs of [`SlpNodeInterface::get_tx`],
+/// so resolving the inputs of a multi-input tx doesn't query the node for the same tx over and
+/// over. A cached tx is returned without calling the node at all.
+///
+/// Mempool txs can be replaced, so only txs with a block height are cached. Txs of blocks
+/// disconnected in a reorg are evicted once the [`BlockEvent::Disconnected`] passes through
+/// [`SlpNodeInterface::confirmed_tx_stream`] of this wrapper, so callers relying on the cache
+/// across reorgs have to keep that stream running. All other methods are passed through
+/// unchanged.
+pub struct CachedNode<N> {
+    node: N,
+    cache: Arc<Mutex<LruCache>>,
+}
+
+struct LruCache {
+    capacity: usize,
+    /// Height of the tip, as last reported by the block stream.
+    tip_height: Option<i32>,
+    /// Each confirmed tx, with the time it was last used.
+    entries: HashMap<Sha256d, (SlpTx, u64)>,
+    clock: u64,
+}
+
+impl<N: SlpNodeInterface> CachedNode<N> {
+    /// Cache up to `capacity` confirmed txs, evicting the least recently used first.
+    pub fn new(node: N, capacity: usize) -> Self {
+        CachedNode {
+            node,
+            cache: Arc::new(Mutex::new(LruCache {
+                capacity,
+                tip_height: None,
+                entries: HashMap::new(),
+                clock: 0,
+            })),
+        }
+    }
+
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+}
+
+impl LruCache {
+    fn get(&mut self, txid: &Sha256d) -> Option<SlpTx> {
+        self.clock += 1;
+        let (tx, last_used) = self.entries.get_mut(txid)?;
+        *last_used = self.clock;
+        Some(tx.clone())
+    }
+
+    fn insert(&mut self, txid: Sha256d, tx: SlpTx) {
+        if self.capacity == 0 {
+            return;
+        }
+        if !self.entries.contains_key(&txid) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(txid, _)| txid.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(txid, (tx, self.clock));
+    }
+
+    fn handle_block_event(&mut self, event: &BlockEvent) {
+        match event {
+            BlockEvent::Connected(block) => self.tip_height = Some(block.height),
+            BlockEvent::Disconnected(block_hashes) => {
+                // Disconnected blocks are the topmost ones; without a known tip, any
+                // cached tx could be in them
+                let first_height = self
+                    .tip_height
+                    .map(|tip_height| tip_height - block_hashes.len() as i32 + 1);
+                self.entries
+                    .retain(|_, (tx, _)| match (first_height, tx.block_height()) {
+                        (Some(first_height), Some(height)) => height < first_height,
+                        _ => false,
+                    });
+                self.tip_height = first_height.map(|first_height| first_height - 1);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<N: SlpNodeInterface> SlpNodeInterface for CachedNode<N> {
+    async fn submit_tx(&self, raw_tx: Vec<u8>) -> Result<Sha256d> {
+        self.node.submit_tx(raw_tx).await
+    }
+
+    async fn check_tx(&self, raw_tx: Vec<u8>) -> Result<SlpTx> {
+        self.node.check_tx(raw_tx).await
+    }
+
+    async fn submit_and_parse(&self, raw_tx: Vec<u8>) -> Result<SlpTx> {
+        self.node.submit_and_parse(raw_tx).await
+    }
+
+    async fn get_token_metadata(
+        &self,
+        token_ids: &[TokenId],
+    ) -> Result<HashMap<TokenId, TokenMetadata>> {
+        self.node.get_token_metadata(token_ids).await
+    }
+
+    async fn token_genesis_info(&self, token_id: &TokenId) -> Result<SlpGenesisInfo> {
+        self.node.token_genesis_info(token_id).await
+    }
+
+    async fn address_tx_stream(
+        &self,
+        address: &CashAddress,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<SlpTx>> + Send>>> {
+        self.node.address_tx_stream(address).await
+    }
+
+    async fn address_event_stream(
+        &self,
+        address: &CashAddress,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<AddressEvent>> + Send>>> {
+        self.node.address_event_stream(address).await
+    }
+
+    async fn address_utxos(&self, address: &CashAddress) -> Result<Vec<SlpUtxo>> {
+        self.node.address_utxos(address).await
+    }
+
+    async fn address_history(&self, address: &CashAddress) -> Result<Vec<SlpTx>> {
+        self.node.address_history(address).await
+    }
+
+    async fn node_status(&self) -> Result<NodeStatus> {
+        self.node.node_status().await
+    }
+
+    async fn txs_in_block(&self, block: &BlockId) -> Result<Vec<SlpTx>> {
+        self.node.txs_in_block(block).await
+    }
+
+    async fn get_tx(&self, txid: &Sha256d) -> Result<SlpTx> {
+        let cached_tx = self.cache.lock().unwrap().get(txid);
+        if let Some(tx) = cached_tx {
+            return Ok(tx);
+        }
+        let tx = self.node.get_tx(txid).await?;
+        if tx.block_height().is_some() {
+            self.cache.lock().unwrap().insert(txid.clone(), tx.clone());
+        }
+        Ok(tx)
+    }
+
+    async fn confirmed_tx_stream(
+        &self,
+        from_height: i32,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<BlockEvent>> + Send>>> {
+        let stream = self.node.confirmed_tx_stream(from_height).await?;
+        let cache = Arc::clone(&self.cache);
+        Ok(Box::pin(stream.inspect(move |event| {
+            if let Ok(event) = event {
+                cache.lock().unwrap().handle_block_event(event);
+            }
+        })))
+    }
+
+    async fn mempool_ancestors(&self, txid: &Sha256d) -> Result<MempoolPackageInfo> {
+        self.node.mempool_ancestors(txid).await
+    }
+
+    async fn ping(&self) -> Result<Duration> {
+        self.node.ping().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+    };
+
+    use async_trait::async_trait;
+    use bitcoinsuite_core::{CashAddress, Sha256d};
+    use bitcoinsuite_error::Result;
+    use futures::{Stream, StreamExt};
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        BlockEvent, BlockId, CachedNode, NodeStatus, SlpBlock, SlpError, SlpGenesisInfo,
+        SlpNodeInterface, SlpTx, SlpUtxo, TokenId, TokenMetadata,
+    };
+
+    /// Node knowing every tx, confirmed at the height in `tx_heights` or in the mempool
+    /// otherwise. The block stream reports `block_events`.
+    #[derive(Default)]
+    struct CountingNode {
+        num_get_tx_calls: AtomicUsize,
+        tx_heights: Mutex<HashMap<Sha256d, i32>>,
+        block_events: Mutex<Vec<BlockEvent>>,
+    }
+
+    #[async_trait]
+    impl SlpNodeInterface for CountingNode {
+        async fn submit_tx(&self, _: Vec<u8>) -> Result<Sha256d> {
+            Ok(Sha256d::default())
+        }
+
+        async fn check_tx(&self, _: Vec<u8>) -> Result<SlpTx> {
+            Ok(SlpTx::new(Default::default(), None, vec![]))
+        }
+
+        async fn get_token_metadata(
+            &self,
+            _: &[TokenId],
+        ) -> Result<HashMap<TokenId, TokenMetadata>> {
+            Ok(HashMap::new())
+        }
+
+        async fn token_genesis_info(&self, token_id: &TokenId) -> Result<SlpGenesisInfo> {
+            Err(SlpError::UnknownToken(token_id.clone()).into())
+        }
+
+        async fn address_tx_stream(
+            &self,
+            _: &CashAddress,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<SlpTx>> + Send>>> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn address_utxos(&self, _: &CashAddress) -> Result<Vec<SlpUtxo>> {
+            Ok(vec![])
+        }
+
+        async fn node_status(&self) -> Result<NodeStatus> {
+            Ok(NodeStatus::default())
+        }
+
+        async fn txs_in_block(&self, block: &BlockId) -> Result<Vec<SlpTx>> {
+            Err(SlpError::UnknownBlock(block.clone()).into())
+        }
+
+        async fn get_tx(&self, txid: &Sha256d) -> Result<SlpTx> {
+            self.num_get_tx_calls.fetch_add(1, Ordering::SeqCst);
+            let mut tx = SlpTx::new(Default::default(), None, vec![]);
+            tx.set_block_height(self.tx_heights.lock().unwrap().get(txid).copied());
+            Ok(tx)
+        }
+
+        async fn confirmed_tx_stream(
+            &self,
+            _: i32,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<BlockEvent>> + Send>>> {
+            let events = std::mem::take(&mut *self.block_events.lock().unwrap());
+            Ok(Box::pin(futures::stream::iter(events.into_iter().map(Ok))))
+        }
+    }
+
+    fn txid(byte: u8) -> Sha256d {
+        Sha256d::new([byte; 32])
+    }
+
+    fn num_calls(node: &CachedNode<CountingNode>) -> usize {
+        node.node().num_get_tx_calls.load(Ordering::SeqCst)
+    }
+
+    fn connected(height: i32) -> BlockEvent {
+        BlockEvent::Connected(SlpBlock {
+            height,
+            hash: Sha256d::new([height as u8; 32]),
+            txs: vec![],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_cached_node() -> Result<()> {
+        let node = CachedNode::new(CountingNode::default(), 2);
+        node.node().tx_heights.lock().unwrap().extend([
+            (txid(1), 10),
+            (txid(2), 11),
+            (txid(3), 12),
+        ]);
+        let tx = node.get_tx(&txid(1)).await?;
+        assert_eq!(tx.block_height(), Some(10));
+        assert_eq!(num_calls(&node), 1);
+
+        // Second lookup of the confirmed tx doesn't reach the node
+        assert_eq!(node.get_tx(&txid(1)).await?, tx);
+        assert_eq!(num_calls(&node), 1);
+
+        // Mempool txs aren't cached
+        node.get_tx(&txid(4)).await?;
+        node.get_tx(&txid(4)).await?;
+        assert_eq!(num_calls(&node), 3);
+
+        // Tx 2 is the least recently used, so adding tx 3 evicts it
+        node.get_tx(&txid(2)).await?;
+        node.get_tx(&txid(1)).await?;
+        node.get_tx(&txid(3)).await?;
+        assert_eq!(num_calls(&node), 5);
+        node.get_tx(&txid(1)).await?;
+        node.get_tx(&txid(3)).await?;
+        assert_eq!(num_calls(&node), 5);
+        node.get_tx(&txid(2)).await?;
+        assert_eq!(num_calls(&node), 6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cached_node_reorg() -> Result<()> {
+        let node = CachedNode::new(CountingNode::default(), 10);
+        node.node().tx_heights.lock().unwrap().extend([
+            (txid(1), 10),
+            (txid(2), 11),
+            (txid(3), 12),
+        ]);
+        for byte in 1..=3 {
+            node.get_tx(&txid(byte)).await?;
+        }
+        assert_eq!(num_calls(&node), 3);
+
+        // Blocks 11 and 12 are disconnected, the tx of block 10 stays cached
+        *node.node().block_events.lock().unwrap() = vec![
+            connected(12),
+            BlockEvent::Disconnected(vec![Sha256d::new([12; 32]), Sha256d::new([11; 32])]),
+        ];
+        let events = node
+            .confirmed_tx_stream(12)
+            .await?
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(events.len(), 2);
+        node.get_tx(&txid(1)).await?;
+        assert_eq!(num_calls(&node), 3);
+        node.get_tx(&txid(2)).await?;
+        node.get_tx(&txid(3)).await?;
+        assert_eq!(num_calls(&node), 5);
+        Ok(())
+    }
+}
@@ -2,6 +2,11 @@ pub const SLP_LOKAD_ID: &[u8; 4] = b"SLP\0";
 pub const SLP_TOKEN_TYPE_V1: &[u8; 1] = &[1];
 pub const SLP_TOKEN_TYPE_V1_NFT1_GROUP: &[u8; 1] = &[0x81];
 pub const SLP_TOKEN_TYPE_V1_NFT1_CHILD: &[u8; 1] = &[0x41];
+pub const SLP_DUST_AMOUNT: i64 = 546;
+pub const SLP_TX_TYPE_GENESIS: &[u8] = b"GENESIS";
+pub const SLP_TX_TYPE_MINT: &[u8] = b"MINT";
+pub const SLP_TX_TYPE_SEND: &[u8] = b"SEND";
+pub const SLP_TX_TYPE_BURN: &[u8] = b"BURN";
 pub const SLP_OUTPUT_QUANTITY_FIELD_NAMES: &[&str; 19] = &[
     "output_quantity1",
     "output_quantity2",
@@ -0,0 +1,186 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bitcoinsuite_core::Sha256d;
+use bitcoinsuite_error::Result;
+use futures::{Stream, StreamExt};
+
+use crate::SlpTx;
+
+/// Wraps a stream of txs, e.g. of [`SlpNodeInterface::address_tx_stream`], and drops txs whose
+/// txid has already been emitted among the last `window` txs, so a tx re-delivered after a
+/// reconnect or re-announced on confirmation isn't counted twice.
+///
+/// Only the txids of the last `window` emitted txs are kept, so memory is bounded by `window`
+/// txids (32 bytes each, plus the overhead of a [`HashSet`] and a [`VecDeque`] entry).
+/// A duplicate arriving after more than `window` other txs is emitted again.
+/// Errors are passed through unchanged.
+///
+/// [`SlpNodeInterface::address_tx_stream`]: crate::SlpNodeInterface::address_tx_stream
+pub struct DedupStream<S> {
+    stream: S,
+    window: usize,
+    seen: HashSet<Sha256d>,
+    /// Txids in `seen`, oldest first.
+    seen_order: VecDeque<Sha256d>,
+}
+
+impl<S> DedupStream<S> {
+    pub fn new(stream: S, window: usize) -> Self {
+        DedupStream {
+            stream,
+            window,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Records `txid` as emitted; `false` if it already has been within the window.
+    fn insert(&mut self, txid: Sha256d) -> bool {
+        if self.window == 0 {
+            return true;
+        }
+        if self.seen.contains(&txid) {
+            return false;
+        }
+        if self.seen_order.len() == self.window {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(txid.clone());
+        self.seen_order.push_back(txid);
+        true
+    }
+}
+
+impl<S> Stream for DedupStream<S>
+where
+    S: Stream<Item = Result<SlpTx>> + Unpin,
+{
+    type Item = Result<SlpTx>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(slp_tx))) => {
+                    if self.insert(slp_tx.tx().txid()) {
+                        return Poll::Ready(Some(Ok(slp_tx)));
+                    }
+                }
+                poll => return poll,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::UnhashedTx;
+    use bitcoinsuite_error::Result;
+    use futures::{stream, StreamExt};
+
+    use crate::{DedupStream, SlpError, SlpTx};
+
+    fn make_tx(lock_time: u32) -> SlpTx {
+        SlpTx::new(
+            UnhashedTx {
+                version: 1,
+                inputs: vec![],
+                outputs: vec![],
+                lock_time,
+            },
+            None,
+            vec![],
+        )
+    }
+
+    async fn collect(txs: Vec<Result<SlpTx>>, window: usize) -> Vec<Result<SlpTx>> {
+        DedupStream::new(stream::iter(txs), window).collect().await
+    }
+
+    fn unwrap_txs(items: Vec<Result<SlpTx>>) -> Vec<SlpTx> {
+        items.into_iter().map(|item| item.unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_dedup_stream() {
+        let tx1 = make_tx(1);
+        let tx2 = make_tx(2);
+        let tx3 = make_tx(3);
+
+        // Same tx twice is only emitted once
+        let items = collect(vec![Ok(tx1.clone()), Ok(tx1.clone())], 10).await;
+        assert_eq!(unwrap_txs(items), vec![tx1.clone()]);
+
+        // Duplicates are dropped even if not adjacent
+        let items = collect(
+            vec![
+                Ok(tx1.clone()),
+                Ok(tx2.clone()),
+                Ok(tx1.clone()),
+                Ok(tx3.clone()),
+                Ok(tx2.clone()),
+            ],
+            10,
+        )
+        .await;
+        assert_eq!(
+            unwrap_txs(items),
+            vec![tx1.clone(), tx2.clone(), tx3.clone()],
+        );
+
+        // tx1 left the window of 2 after tx2 and tx3, so it's emitted again
+        let items = collect(
+            vec![
+                Ok(tx1.clone()),
+                Ok(tx2.clone()),
+                Ok(tx3.clone()),
+                Ok(tx1.clone()),
+                Ok(tx3.clone()),
+            ],
+            2,
+        )
+        .await;
+        assert_eq!(
+            unwrap_txs(items),
+            vec![tx1.clone(), tx2.clone(), tx3.clone(), tx1.clone()],
+        );
+
+        // Window of 0 doesn't dedup
+        let items = collect(vec![Ok(tx1.clone()), Ok(tx1.clone())], 0).await;
+        assert_eq!(unwrap_txs(items), vec![tx1.clone(), tx1.clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_dedup_stream_errors() {
+        let tx1 = make_tx(1);
+        let items = collect(
+            vec![
+                Ok(tx1.clone()),
+                Err(SlpError::NoOutputs.into()),
+                Ok(tx1.clone()),
+                Err(SlpError::NoOutputs.into()),
+            ],
+            10,
+        )
+        .await;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().unwrap(), &tx1);
+        for item in &items[1..] {
+            let err = item.as_ref().unwrap_err();
+            assert_eq!(err.downcast_ref::<SlpError>(), Some(&SlpError::NoOutputs));
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use bitcoinsuite_core::{Bytes, BytesError};
+use bitcoinsuite_core::{ByteArray, Bytes, BytesError, OpReturnError, OutPoint, Sha256d};
 use thiserror::Error;
 
-use crate::SlpAmount;
+use crate::{BlockId, SlpAmount, TokenId};
 
 #[derive(Debug, Clone, Error, PartialEq, Eq)]
 pub enum SlpError {
@@ -28,6 +28,28 @@ pub enum SlpError {
     InvalidDecimals { actual: usize },
     #[error("Mint baton at invalid output index, must be between 2 and 255, but got {actual}")]
     InvalidMintBatonIdx { actual: usize },
+    #[error("Initial quantity must be positive, but got {actual}")]
+    InitialQuantityNotPositive { actual: SlpAmount },
+    #[error("Tokens assigned to output {idx}, but tx only has {num_outputs} outputs")]
+    TokenOutputMissing { idx: usize, num_outputs: usize },
+    #[error("Token output {idx} has value {value}, which is dust")]
+    TokenOutputDust { idx: usize, value: i64 },
+    #[error("Output {idx} has value {value}, which would be burned by its unspendable script")]
+    UnspendableOutputWithValue { idx: usize, value: i64 },
+    #[error("OP_RETURN is {size} bytes, more than the standard {max_size} bytes")]
+    OpReturnTooLarge { size: usize, max_size: usize },
+    #[error("Network doesn't relay txs with more than one OP_RETURN output")]
+    MultipleOpReturnsNotRelayed,
+    #[error("OP_RETURNs are {size} bytes combined, more than the standard {max_size} bytes")]
+    CombinedOpReturnsTooLarge { size: usize, max_size: usize },
+    #[error("Invalid memo: {0}")]
+    InvalidMemo(OpReturnError),
+    #[error("Amounts have different decimals: {left} and {right}")]
+    DecimalsMismatch { left: u32, right: u32 },
+    #[error("Unknown token ID {0}")]
+    UnknownToken(TokenId),
+    #[error("Mint baton at output {vout}, but tx only has {num_outputs} outputs")]
+    MintBatonOutputMissing { vout: u8, num_outputs: usize },
     #[error("NFT1 Child Genesis cannot have mint baton")]
     Nft1ChildCannotHaveMintBaton,
     #[error("Invalid NFT1 Child Genesis initial quantity, expected 1 but got {actual}")]
@@ -53,6 +75,10 @@ pub enum SlpError {
     },
     #[error("Invalid NFT1 Child GENESIS: No group token")]
     HasNoNft1Group,
+    #[error("Invalid NFT1 Child GENESIS: Expected group token {expected}, but got {actual}")]
+    WrongNft1Group { expected: TokenId, actual: TokenId },
+    #[error("Not an NFT1 Child GENESIS")]
+    NotNft1ChildGenesis,
     #[error("Invalid MINT: No baton")]
     HasNoMintBaton,
     #[error("Invalid BURN: Burning the wrong token_id")]
@@ -66,12 +92,84 @@ pub enum SlpError {
         expected: SlpAmount,
         actual: SlpAmount,
     },
+    #[error("Too many SEND amounts, expected at most {expected} but got {actual}")]
+    TooManySendAmounts { expected: usize, actual: usize },
+    #[error("SEND needs at least one amount")]
+    NoSendAmounts,
+    #[error("Cannot build a tx of unknown token type")]
+    UnknownTokenType,
+    #[error("Amount {amount} out of range, must fit into 8 unsigned bytes")]
+    AmountOutOfRange { amount: SlpAmount },
+    #[error("Token balance overflowed")]
+    BalanceOverflow,
+    #[error("Insufficient tokens: {needed} needed, but only {available} available")]
+    InsufficientTokens {
+        needed: SlpAmount,
+        available: SlpAmount,
+    },
+    #[error("Insufficient sats: {needed} needed, but only {available} available")]
+    InsufficientSats { needed: i64, available: i64 },
+    #[error("No utxos to sweep")]
+    NothingToSweep,
+    #[error("No tokens of {0} to sweep")]
+    NoTokensToSweep(TokenId),
+    #[error("Sats amount overflowed")]
+    SatsOverflow,
+    #[error("Sweep output of {value} sats would be dust")]
+    SweepOutputDust { value: i64 },
     #[error("Found orphan txs")]
     FoundOrphanTx,
+    #[error("Unknown block: {0:?}")]
+    UnknownBlock(BlockId),
+    #[error("Unknown tx: {0}")]
+    UnknownTx(Sha256d),
+    #[error("Node doesn't support {method}")]
+    Unsupported { method: &'static str },
+    #[error("Input {input_idx} spends {outpoint}, which doesn't exist")]
+    InputMissing {
+        input_idx: usize,
+        outpoint: OutPoint,
+    },
+    #[error("Input {input_idx} spends {outpoint}, which is already spent by {spent_by}")]
+    InputAlreadySpent {
+        input_idx: usize,
+        outpoint: OutPoint,
+        spent_by: Sha256d,
+    },
+    #[error("Address prefix {actual:?} is of a different net, expected {expected:?}")]
+    WrongAddressNet { expected: String, actual: String },
+    #[error("Field {field_name} is not valid UTF-8")]
+    InvalidUtf8 { field_name: &'static str },
+    #[error("GENESIS has no document hash")]
+    MissingDocumentHash,
+    #[error(
+        "Document hash mismatch: expected {}, but got {}",
+        .expected.hex(),
+        .actual.hex()
+    )]
+    DocumentHashMismatch {
+        expected: ByteArray<32>,
+        actual: ByteArray<32>,
+    },
     #[error("Bytes error: {0}")]
     BytesError(#[from] BytesError),
 }
 
+impl SlpError {
+    /// Whether the error means the tx has no SLP OP_RETURN at all, rather than a malformed or
+    /// invalid one.
+    pub fn is_not_slp(&self) -> bool {
+        matches!(
+            self,
+            SlpError::NoOutputs
+                | SlpError::NoOpcodes
+                | SlpError::MissingOpReturn { .. }
+                | SlpError::InvalidLokadId(_)
+                | SlpError::BytesError(_)
+        )
+    }
+}
+
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum BitcoinSuiteSlpError {
     #[error("Unknown coin protocol: {0}")]
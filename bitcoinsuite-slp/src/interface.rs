@@ -1,11 +1,16 @@
-use std::{collections::HashMap, pin::Pin};
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use bitcoinsuite_core::{CashAddress, Sha256d, UnhashedTx};
+use bitcoinsuite_core::{CashAddress, FeeRate, Sha256d, UnhashedTx};
 use bitcoinsuite_error::Result;
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt};
+use tokio::{sync::mpsc, task::JoinHandle};
 
-use crate::{SlpTx, SlpUtxo, TokenId};
+use crate::{AddressBalance, SlpError, SlpGenesisInfo, SlpTx, SlpUtxo, TokenId};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SlpSend {
@@ -18,21 +23,283 @@ pub struct TokenMetadata {
     pub decimals: u32,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NodeStatus {
+    pub best_height: i32,
+    pub best_block_hash: Sha256d,
+    pub is_initial_block_download: bool,
+}
+
+/// Unconfirmed tx together with its unconfirmed ancestors, see
+/// [`SlpNodeInterface::mempool_ancestors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MempoolPackageInfo {
+    pub num_txs: usize,
+    /// Total virtual size of the txs, in bytes.
+    pub size: usize,
+    /// Total fees paid by the txs, in sats.
+    pub fees: i64,
+}
+
+/// Change to the txs of an address, see [`SlpNodeInterface::address_event_stream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressEvent {
+    /// Tx has been added to the mempool.
+    Mempool(SlpTx),
+    /// Tx has been mined in a block at `height`.
+    Confirmed { tx: SlpTx, height: i32 },
+    /// Tx with this txid has been removed, e.g. after a reorg or a double spend.
+    Removed(Sha256d),
+}
+
+/// Block to query, see [`SlpNodeInterface::txs_in_block`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlockId {
+    Height(i32),
+    Hash(Sha256d),
+}
+
+/// Block with its txs in block order, each with its block height set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlpBlock {
+    pub height: i32,
+    pub hash: Sha256d,
+    pub txs: Vec<SlpTx>,
+}
+
+/// Change to the chain, see [`SlpNodeInterface::confirmed_tx_stream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockEvent {
+    /// Block has been connected to the tip of the chain.
+    Connected(SlpBlock),
+    /// Blocks with these hashes have been disconnected in a reorg, tip first; the txs
+    /// confirmed in them have to be rolled back.
+    Disconnected(Vec<Sha256d>),
+}
+
+impl MempoolPackageInfo {
+    /// Fee a child of `child_size` bytes spending the package has to pay, so the package
+    /// including the child pays `fee_rate`. Never less than the child's own fee at `fee_rate`,
+    /// as the node relays each tx based on its own fee.
+    pub fn child_fee(&self, child_size: usize, fee_rate: FeeRate) -> i64 {
+        let package_fee = fee_rate.fee_for_size(self.size + child_size) - self.fees;
+        package_fee.max(fee_rate.fee_for_size(child_size))
+    }
+
+    /// Add `slp_tx` to the package. Fails with [`SlpError::InputMissing`] if any of its
+    /// [`SlpTx::input_prevouts`] is unknown, as its fee can't be computed then.
+    pub fn add_tx(&mut self, slp_tx: &SlpTx) -> Result<(), SlpError> {
+        let tx = slp_tx.tx();
+        let mut input_sum = 0;
+        for (input_idx, input) in tx.inputs.iter().enumerate() {
+            match slp_tx.input_prevouts().get(input_idx) {
+                Some(Some(prevout)) => input_sum += prevout.value,
+                _ => {
+                    return Err(SlpError::InputMissing {
+                        input_idx,
+                        outpoint: input.prev_out.clone(),
+                    })
+                }
+            }
+        }
+        let output_sum = tx.outputs.iter().map(|output| output.value).sum::<i64>();
+        self.num_txs += 1;
+        self.size += tx.vsize();
+        self.fees += input_sum - output_sum;
+        Ok(())
+    }
+}
+
 #[async_trait]
 pub trait SlpNodeInterface: Send + Sync {
     async fn submit_tx(&self, raw_tx: Vec<u8>) -> Result<Sha256d>;
 
+    /// Validate the tx like `submit_tx` would, without broadcasting it.
+    /// The returned [`SlpTx`] has its burns populated.
+    async fn check_tx(&self, raw_tx: Vec<u8>) -> Result<SlpTx>;
+
+    /// Submit the tx like `submit_tx` and return it with its SLP data and burns, as
+    /// [`Self::address_tx_stream`] reports it, without having to wait on the stream.
+    /// By default, the tx is checked with [`Self::check_tx`] right before it is submitted, so
+    /// the returned tx can differ from the one accepted if the node's state changes in between.
+    /// Nodes that can parse the tx as they accept it must override this, and wrappers must
+    /// forward it to the node they wrap.
+    async fn submit_and_parse(&self, raw_tx: Vec<u8>) -> Result<SlpTx> {
+        let slp_tx = self.check_tx(raw_tx.clone()).await?;
+        self.submit_tx(raw_tx).await?;
+        Ok(slp_tx)
+    }
+
     async fn get_token_metadata(
         &self,
         token_ids: &[TokenId],
     ) -> Result<HashMap<TokenId, TokenMetadata>>;
 
+    /// Ticker, name, document and decimals of `token_id` as set in its GENESIS tx.
+    /// Fails with [`SlpError::UnknownToken`] if the node doesn't know the token.
+    ///
+    /// [`SlpError::UnknownToken`]: crate::SlpError::UnknownToken
+    async fn token_genesis_info(&self, token_id: &TokenId) -> Result<SlpGenesisInfo>;
+
     async fn address_tx_stream(
         &self,
         address: &CashAddress,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<SlpTx>> + Send>>>;
 
+    /// Like [`Self::address_tx_stream`], but also reports when txs are confirmed or removed.
+    /// By default, only reports txs of `address_tx_stream` as [`AddressEvent::Mempool`].
+    async fn address_event_stream(
+        &self,
+        address: &CashAddress,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<AddressEvent>> + Send>>> {
+        let stream = self.address_tx_stream(address).await?;
+        Ok(Box::pin(
+            stream.map(|slp_tx| slp_tx.map(AddressEvent::Mempool)),
+        ))
+    }
+
+    /// Runs [`Self::address_tx_stream`] in a task, which forwards its items into a channel
+    /// holding up to `buffer` (at least 1) items; the stream isn't polled while the channel is
+    /// full.
+    /// The task ends, dropping the stream, when the stream ends or the receiver is dropped.
+    async fn address_tx_channel(
+        &self,
+        address: &CashAddress,
+        buffer: usize,
+    ) -> Result<(JoinHandle<()>, mpsc::Receiver<Result<SlpTx>>)> {
+        let mut stream = self.address_tx_stream(address).await?;
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = sender.closed() => return,
+                    item = stream.next() => match item {
+                        Some(item) => {
+                            if sender.send(item).await.is_err() {
+                                return;
+                            }
+                        }
+                        None => return,
+                    },
+                }
+            }
+        });
+        Ok((handle, receiver))
+    }
+
     async fn address_utxos(&self, address: &CashAddress) -> Result<Vec<SlpUtxo>>;
+
+    /// Txs in the mempool or the current chain that spend from or send to `address`, in no
+    /// particular order. Unlike [`Self::address_utxos`], this includes addresses whose coins
+    /// have all been spent.
+    /// By default, fails with [`SlpError::Unsupported`].
+    async fn address_history(&self, _address: &CashAddress) -> Result<Vec<SlpTx>> {
+        Err(SlpError::Unsupported {
+            method: "address_history",
+        }
+        .into())
+    }
+
+    /// Sats and token balances of `address`, computed from [`Self::address_utxos`].
+    async fn address_balance(&self, address: &CashAddress) -> Result<AddressBalance> {
+        let utxos = self.address_utxos(address).await?;
+        Ok(AddressBalance::from_utxos(&utxos)?)
+    }
+
+    /// Distinct token IDs held by `address`, including tokens of which only the mint baton is
+    /// held, in the order of [`Self::address_utxos`].
+    async fn address_token_ids(&self, address: &CashAddress) -> Result<Vec<TokenId>> {
+        let utxos = self.address_utxos(address).await?;
+        let mut seen = HashSet::new();
+        Ok(utxos
+            .into_iter()
+            .filter_map(|utxo| utxo.token_id)
+            .filter(|token_id| seen.insert(token_id.clone()))
+            .collect())
+    }
+
+    async fn node_status(&self) -> Result<NodeStatus>;
+
+    /// Txs of a block of the current chain, in block order, each with its block height set.
+    /// Fails with [`SlpError::UnknownBlock`] if there is no such block.
+    ///
+    /// [`SlpError::UnknownBlock`]: crate::SlpError::UnknownBlock
+    async fn txs_in_block(&self, block: &BlockId) -> Result<Vec<SlpTx>>;
+
+    /// Tx in the mempool or the current chain, with its block height set if confirmed.
+    /// Fails with [`SlpError::UnknownTx`] if the node doesn't know the tx.
+    /// By default, fails with [`SlpError::Unsupported`], and so do [`Self::get_txs`] and
+    /// [`Self::mempool_ancestors`] unless they're overridden too.
+    ///
+    /// [`SlpError::UnknownTx`]: crate::SlpError::UnknownTx
+    async fn get_tx(&self, _txid: &Sha256d) -> Result<SlpTx> {
+        Err(SlpError::Unsupported { method: "get_tx" }.into())
+    }
+
+    /// Calls [`Self::get_tx`] for each of `txids`, with up to `concurrency` (at least 1) calls in
+    /// flight at once, and returns the txs in the order of `txids`.
+    /// Fails with the first error of any call.
+    async fn get_txs(&self, txids: &[Sha256d], concurrency: usize) -> Result<Vec<SlpTx>> {
+        let mut txs = vec![None; txids.len()];
+        let mut results = futures::stream::iter(txids.iter().enumerate())
+            .map(|(idx, txid)| async move { Ok((idx, self.get_tx(txid).await?)) })
+            .buffer_unordered(concurrency.max(1));
+        while let Some((idx, tx)) = results.try_next().await? {
+            txs[idx] = Some(tx);
+        }
+        Ok(txs.into_iter().flatten().collect())
+    }
+
+    /// Replays the blocks from `from_height` up to the tip, then reports blocks as they are
+    /// connected. A reorg is reported as [`BlockEvent::Disconnected`], followed by the blocks
+    /// of the new chain.
+    async fn confirmed_tx_stream(
+        &self,
+        from_height: i32,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<BlockEvent>> + Send>>>;
+
+    /// Size and fees of `txid` and all its unconfirmed ancestors, e.g. to bump a stuck chain
+    /// of txs with a child (CPFP). All zero if `txid` is not in the mempool.
+    /// By default, walks the unconfirmed ancestors with [`Self::get_tx`], one call per tx.
+    async fn mempool_ancestors(&self, txid: &Sha256d) -> Result<MempoolPackageInfo> {
+        let mut package = MempoolPackageInfo::default();
+        let mut visited = HashSet::new();
+        let mut stack = vec![txid.clone()];
+        while let Some(txid) = stack.pop() {
+            if !visited.insert(txid.clone()) {
+                continue;
+            }
+            let slp_tx = match self.get_tx(&txid).await {
+                Ok(slp_tx) => slp_tx,
+                Err(err)
+                    if matches!(err.downcast_ref::<SlpError>(), Some(SlpError::UnknownTx(_))) =>
+                {
+                    continue
+                }
+                Err(err) => return Err(err),
+            };
+            if slp_tx.block_height().is_some() {
+                continue;
+            }
+            package.add_tx(&slp_tx)?;
+            stack.extend(
+                slp_tx
+                    .tx()
+                    .inputs
+                    .iter()
+                    .map(|input| input.prev_out.txid.clone()),
+            );
+        }
+        Ok(package)
+    }
+
+    /// Round-trip time of a cheap request to the node, meant to be called frequently to probe
+    /// whether the node is alive. By default, times [`Self::node_status`].
+    async fn ping(&self) -> Result<Duration> {
+        let start = Instant::now();
+        self.node_status().await?;
+        Ok(start.elapsed())
+    }
 }
 
 #[async_trait]
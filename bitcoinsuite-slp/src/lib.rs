@@ -1,26 +1,40 @@
 mod build;
+mod cached_node;
 pub mod consts;
+mod dedup_stream;
 mod error;
 mod interface;
 mod parse;
 mod rich_tx;
 mod rich_utxo;
+mod scan;
+mod select;
 mod slp_amount;
 mod slp_tx;
 mod slp_utxo;
+mod sweep;
+#[cfg(test)]
+mod test_utils;
+mod timeout_node;
 mod token_id;
 mod validate;
 mod value;
 
 pub use crate::build::*;
+pub use crate::cached_node::*;
+pub use crate::dedup_stream::*;
 pub use crate::error::*;
 pub use crate::interface::*;
 pub use crate::parse::*;
 pub use crate::rich_tx::*;
 pub use crate::rich_utxo::*;
+pub use crate::scan::*;
+pub use crate::select::*;
 pub use crate::slp_amount::*;
 pub use crate::slp_tx::*;
 pub use crate::slp_utxo::*;
+pub use crate::sweep::*;
+pub use crate::timeout_node::*;
 pub use crate::token_id::*;
 pub use crate::validate::*;
 pub use crate::value::*;
@@ -1,9 +1,10 @@
-use bitcoinsuite_core::{opcode::*, ByteArray, Bytes, Op, Sha256d, UnhashedTx};
+use bitcoinsuite_core::{opcode::*, ByteArray, Bytes, Op, Script, Sha256d, UnhashedTx};
 
 use crate::{
     consts::{
         SLP_LOKAD_ID, SLP_OUTPUT_QUANTITY_FIELD_NAMES, SLP_TOKEN_TYPE_V1,
-        SLP_TOKEN_TYPE_V1_NFT1_CHILD, SLP_TOKEN_TYPE_V1_NFT1_GROUP,
+        SLP_TOKEN_TYPE_V1_NFT1_CHILD, SLP_TOKEN_TYPE_V1_NFT1_GROUP, SLP_TX_TYPE_BURN,
+        SLP_TX_TYPE_GENESIS, SLP_TX_TYPE_MINT, SLP_TX_TYPE_SEND,
     },
     SlpAmount, SlpError, SlpGenesisInfo, SlpToken, SlpTokenType, SlpTxType, TokenId,
 };
@@ -21,7 +22,32 @@ pub fn parse_slp_tx(txid: &Sha256d, tx: &UnhashedTx) -> Result<SlpParseData, Slp
     if tx.outputs.is_empty() {
         return Err(SlpError::NoOutputs);
     }
-    let ops = tx.outputs[0].script.ops().collect::<Result<Vec<_>, _>>()?;
+    parse_slp_opreturn(txid, &tx.outputs[0].script, tx.outputs.len())
+}
+
+/// Whether `script` is an OP_RETURN starting with a push of the SLP LOKAD ID, checked on the
+/// raw bytes without parsing the rest. This is a cheap pre-filter, e.g. for indexers scanning
+/// every output: [`parse_slp_tx`] can still fail for scripts passing it, but always fails with
+/// an error for which [`SlpError::is_not_slp`] holds for scripts not passing it.
+pub fn is_slp_opreturn(script: &Script) -> bool {
+    let lokad_id = match script.bytecode().as_ref() {
+        [OP_RETURN, 4, lokad_id @ ..] => lokad_id,
+        [OP_RETURN, OP_PUSHDATA1, 4, lokad_id @ ..] => lokad_id,
+        [OP_RETURN, OP_PUSHDATA2, 4, 0, lokad_id @ ..] => lokad_id,
+        [OP_RETURN, OP_PUSHDATA4, 4, 0, 0, 0, lokad_id @ ..] => lokad_id,
+        _ => return false,
+    };
+    lokad_id.starts_with(SLP_LOKAD_ID)
+}
+
+/// Parse the OP_RETURN of a tx with `num_outputs` outputs; tokens assigned to outputs beyond
+/// that are dropped.
+pub(crate) fn parse_slp_opreturn(
+    txid: &Sha256d,
+    opreturn: &Script,
+    num_outputs: usize,
+) -> Result<SlpParseData, SlpError> {
+    let ops = opreturn.ops().collect::<Result<Vec<_>, _>>()?;
     parse_lokad_id(&ops)?;
     let opreturn_data = parse_opreturn_ops(ops.into_iter())?;
     if opreturn_data.len() < 3 {
@@ -39,7 +65,7 @@ pub fn parse_slp_tx(txid: &Sha256d, tx: &UnhashedTx) -> Result<SlpParseData, Slp
         None => {
             let token = SlpToken::EMPTY;
             return Ok(SlpParseData {
-                output_tokens: tx.outputs.iter().map(|_| token).collect(),
+                output_tokens: vec![token; num_outputs],
                 slp_token_type: SlpTokenType::Unknown,
                 slp_tx_type: SlpTxType::Unknown,
                 token_id: TokenId::new(Sha256d::new([0; 32])),
@@ -48,10 +74,10 @@ pub fn parse_slp_tx(txid: &Sha256d, tx: &UnhashedTx) -> Result<SlpParseData, Slp
     };
 
     let parsed_opreturn = match opreturn_data[2].as_ref() {
-        b"GENESIS" => parse_genesis_data(opreturn_data, slp_token_type)?,
-        b"MINT" => parse_mint_data(opreturn_data)?,
-        b"SEND" => parse_send_data(opreturn_data)?,
-        b"BURN" => parse_burn_data(opreturn_data)?,
+        SLP_TX_TYPE_GENESIS => parse_genesis_data(opreturn_data, slp_token_type)?,
+        SLP_TX_TYPE_MINT => parse_mint_data(opreturn_data)?,
+        SLP_TX_TYPE_SEND => parse_send_data(opreturn_data)?,
+        SLP_TX_TYPE_BURN => parse_burn_data(opreturn_data)?,
         _ => return Err(SlpError::InvalidTxType(opreturn_data[2].clone())),
     };
     let token_id = match (&parsed_opreturn.slp_tx_type, parsed_opreturn.token_id) {
@@ -61,11 +87,7 @@ pub fn parse_slp_tx(txid: &Sha256d, tx: &UnhashedTx) -> Result<SlpParseData, Slp
         }
         _ => unreachable!(),
     };
-    let mut output_tokens = tx
-        .outputs
-        .iter()
-        .map(|_| SlpToken::EMPTY)
-        .collect::<Vec<_>>();
+    let mut output_tokens = vec![SlpToken::EMPTY; num_outputs];
     match parsed_opreturn.outputs {
         ParsedOutputs::MintTokens {
             mint_quantity,
@@ -401,10 +423,67 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use crate::{
-        consts::SLP_OUTPUT_QUANTITY_FIELD_NAMES, parse_slp_tx, SlpAmount, SlpError, SlpGenesisInfo,
-        SlpParseData, SlpToken, SlpTokenType, SlpTxType, TokenId,
+        consts::SLP_OUTPUT_QUANTITY_FIELD_NAMES, genesis_opreturn, is_slp_opreturn, parse_slp_tx,
+        send_opreturn, MintBaton, SlpAmount, SlpError, SlpGenesisInfo, SlpParseData, SlpToken,
+        SlpTokenType, SlpTxType, TokenId,
     };
 
+    #[test]
+    fn test_is_slp_opreturn() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let send = send_opreturn(&token_id, SlpTokenType::Fungible, &[SlpAmount::new(5)])?;
+        let genesis = genesis_opreturn(
+            &SlpGenesisInfo::default(),
+            SlpTokenType::Fungible,
+            MintBaton::default(),
+            100,
+        )?;
+        assert!(is_slp_opreturn(&send));
+        assert!(is_slp_opreturn(&genesis));
+        // LOKAD ID pushed with OP_PUSHDATA1, OP_PUSHDATA2 and OP_PUSHDATA4 is valid SLP too
+        for prefix in [
+            &[0x6a, 0x4c, 0x04][..],
+            &[0x6a, 0x4d, 0x04, 0x00],
+            &[0x6a, 0x4e, 0x04, 0x00, 0x00, 0x00],
+        ] {
+            let script = Script::from_slice(&[prefix, b"SLP\0", &send.bytecode()[6..]].concat());
+            assert!(is_slp_opreturn(&script));
+            parse_slp_tx(
+                &Sha256d::default(),
+                &UnhashedTx {
+                    outputs: vec![TxOutput { value: 0, script }, TxOutput::default()],
+                    ..Default::default()
+                },
+            )?;
+        }
+
+        let not_slp_scripts = [
+            // Non-SLP OP_RETURN
+            Script::opreturn(&[&[0; 100]]),
+            Script::opreturn(&[&b"SLP\x01"[..], &send.bytecode()[6..]]),
+            Script::from_slice(&[0x6a, 0x04, b'S', b'L', b'P']),
+            Script::from_slice(&[0x6a]),
+            // LOKAD ID not right after OP_RETURN
+            Script::from_slice(&[&[0x51][..], send.bytecode().as_ref()].concat()),
+            // Not an OP_RETURN
+            Script::p2pkh(&Default::default()),
+            Script::default(),
+        ];
+        for script in not_slp_scripts {
+            assert!(!is_slp_opreturn(&script));
+            let err = parse_slp_tx(
+                &Sha256d::default(),
+                &UnhashedTx {
+                    outputs: vec![TxOutput { value: 0, script }],
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+            assert!(err.is_not_slp(), "{:?}", err);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_parse_slp_tx() -> Result<()> {
         fn check_script(script: &[u8], expected_err: SlpError) {
@@ -0,0 +1,48 @@
+use bitcoinsuite_core::{
+    ecc::{Ecc, ExtendedPubKey, XPubError},
+    CashAddress,
+};
+use bitcoinsuite_error::Result;
+
+use crate::{AddressBalance, SlpNodeInterface};
+
+/// Find the used addresses of `xpub`, e.g. to restore the balance of a watch-only wallet.
+///
+/// Derives the P2PKH addresses of the non-hardened children of `xpub` in order, using the
+/// eCash prefix of its net, until `gap_limit` consecutive unused addresses are found; any used
+/// address resets the count. `xpub` is the chain to scan, e.g. the receive chain (child 0) of
+/// an account. An address counts as used if it has any txs, see
+/// [`SlpNodeInterface::address_history`], even if its coins have all been spent. Returns the
+/// index and balance of each used address.
+pub async fn scan_addresses(
+    node: &impl SlpNodeInterface,
+    ecc: &impl Ecc,
+    xpub: &ExtendedPubKey,
+    gap_limit: usize,
+) -> Result<Vec<(u32, AddressBalance)>> {
+    let prefix = xpub.net().cashaddr_prefix();
+    let mut used = Vec::new();
+    let mut num_unused = 0;
+    let mut index = 0;
+    while num_unused < gap_limit {
+        let child = match xpub.derive_pub(ecc, index) {
+            Ok(child) => child,
+            // BIP32: skip invalid children
+            Err(XPubError::InvalidChild(_)) => {
+                index += 1;
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let address = CashAddress::p2pkh_from_pubkey(prefix, child.pubkey());
+        if node.address_history(&address).await?.is_empty() {
+            num_unused += 1;
+        } else {
+            num_unused = 0;
+            let balance = node.address_balance(&address).await?;
+            used.push((index, balance));
+        }
+        index += 1;
+    }
+    Ok(used)
+}
@@ -0,0 +1,609 @@
+use std::cmp::Reverse;
+
+use bitcoinsuite_core::{
+    encoding::compact_size_len, opcode::OP_PUSHDATA1, BitcoinCode, FeeRate, Satoshi, Script,
+    ShaRmd160, TxOutput, UnhashedTx, Utxo,
+};
+
+use crate::{
+    consts::{SLP_DUST_AMOUNT, SLP_LOKAD_ID, SLP_TOKEN_TYPE_V1, SLP_TX_TYPE_SEND},
+    send_opreturn, SlpAmount, SlpError, SlpToken, SlpTokenType, SlpUtxo, TokenId,
+};
+
+/// Estimated size of a P2PKH input with a Schnorr signature, used to pay for selected inputs.
+pub const P2PKH_INPUT_SIZE: usize = 141;
+
+/// Size of a P2PKH output: 8 bytes value, 1 byte script length and 25 bytes script.
+const P2PKH_OUTPUT_SIZE: usize = 34;
+
+/// Order in which token utxos are picked by [`select_slp_inputs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectionStrategy {
+    /// Pick the largest token utxos first, which minimizes the number of inputs.
+    LargestFirst,
+    /// Pick the smallest token utxos first, which merges a fragmented token balance into the
+    /// change output, so future txs need fewer inputs.
+    ConsolidateTokens,
+}
+
+/// Inputs picked by [`select_slp_inputs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlpInputSelection {
+    pub token_utxos: Vec<SlpUtxo>,
+    pub sats_utxos: Vec<Utxo>,
+    /// Tokens left over after paying `target_token`, to be sent back as change.
+    pub token_change: SlpAmount,
+    /// Sats left over after paying `target_sats` and the fee for all selected inputs.
+    pub sats_change: i64,
+}
+
+/// Inputs, change and fee of a SEND tx, see [`simulate_slp_send`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlpSendPlan {
+    pub token_utxos: Vec<SlpUtxo>,
+    pub sats_utxos: Vec<Utxo>,
+    /// Tokens sent back to the sender in a change output, if non-zero.
+    pub token_change: SlpAmount,
+    /// Sats sent back to the sender in a change output; 0 if they would be dust, in which case
+    /// they are added to the fee.
+    pub sats_change: i64,
+    /// Estimated fee of the tx, in sats.
+    pub fee: i64,
+}
+
+/// Token amounts of a SEND, see [`plan_slp_amounts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlpChangePlan {
+    /// Tokens sent to the recipient.
+    pub send: SlpAmount,
+    /// Tokens sent back to the sender; zero if the inputs add up to `send` exactly.
+    pub change: SlpAmount,
+}
+
+/// Split the tokens of `inputs` into `send` for the recipient and the rest as change, i.e. the
+/// amounts of the SEND OP_RETURN. Mint batons don't add to the amount.
+/// Fails with [`SlpError::InsufficientTokens`] if the inputs don't cover `send`.
+pub fn plan_slp_amounts(inputs: &[SlpToken], send: SlpAmount) -> Result<SlpChangePlan, SlpError> {
+    let input_sum = inputs.iter().try_fold(SlpAmount::ZERO, |sum, token| {
+        sum.checked_add(token.amount)
+            .ok_or(SlpError::BalanceOverflow)
+    })?;
+    if input_sum < send {
+        return Err(SlpError::InsufficientTokens {
+            needed: send,
+            available: input_sum,
+        });
+    }
+    Ok(SlpChangePlan {
+        send,
+        change: input_sum - send,
+    })
+}
+
+/// Estimate the fee of a SEND tx at `fee_rate` before selecting coins, without building it.
+///
+/// The tx spends `num_token_inputs + num_sats_inputs` P2PKH inputs, estimated using
+/// [`P2PKH_INPUT_SIZE`], and has the SEND OP_RETURN with a token ID of `token_id_len` bytes
+/// and one 8 byte amount per recipient, `num_recipients` P2PKH token outputs (including any
+/// token change) and a P2PKH sats change output. Recipients with P2SH outputs make the tx
+/// 2 bytes smaller each.
+pub fn estimate_slp_tx_fee(
+    num_token_inputs: usize,
+    num_sats_inputs: usize,
+    num_recipients: usize,
+    token_id_len: usize,
+    fee_rate: FeeRate,
+) -> i64 {
+    let push_size = |len: usize| match len {
+        0 => 2,
+        len if len < OP_PUSHDATA1 as usize => 1 + len,
+        len => 2 + len,
+    };
+    let opreturn_size = 1
+        + push_size(SLP_LOKAD_ID.len())
+        + push_size(SLP_TOKEN_TYPE_V1.len())
+        + push_size(SLP_TX_TYPE_SEND.len())
+        + push_size(token_id_len)
+        + num_recipients * push_size(8);
+    let opreturn_output_size = 8 + compact_size_len(opreturn_size as u64) + opreturn_size;
+    let num_inputs = num_token_inputs + num_sats_inputs;
+    let num_p2pkh_outputs = num_recipients + 1;
+    // 4 bytes version and 4 bytes lock time
+    let tx_size = 8
+        + compact_size_len(num_inputs as u64)
+        + num_inputs * P2PKH_INPUT_SIZE
+        + compact_size_len(1 + num_p2pkh_outputs as u64)
+        + opreturn_output_size
+        + num_p2pkh_outputs * P2PKH_OUTPUT_SIZE;
+    fee_rate.fee_for_size(tx_size)
+}
+
+/// Select inputs to send `target_token` of `token_id` and `target_sats`.
+///
+/// Only token utxos of `token_id` are picked; mint batons and other tokens are ignored.
+/// `target_sats` must cover the outputs and the fee of the tx without inputs; the fee for
+/// every selected input at `fee_rate` is added on top, estimated using [`P2PKH_INPUT_SIZE`].
+/// The sats of the selected token utxos count towards `target_sats`, the missing sats are
+/// selected from `sats_utxos`, largest first.
+pub fn select_slp_inputs(
+    strategy: SelectionStrategy,
+    token_utxos: &[SlpUtxo],
+    sats_utxos: &[Utxo],
+    token_id: &TokenId,
+    target_token: SlpAmount,
+    target_sats: i64,
+    fee_rate: FeeRate,
+) -> Result<SlpInputSelection, SlpError> {
+    let mut token_candidates = token_utxos
+        .iter()
+        .filter(|utxo| utxo.token_id.as_ref() == Some(token_id) && !utxo.token.is_mint_baton)
+        .collect::<Vec<_>>();
+    match strategy {
+        SelectionStrategy::LargestFirst => {
+            token_candidates.sort_by_key(|utxo| Reverse(utxo.token.amount))
+        }
+        SelectionStrategy::ConsolidateTokens => {
+            token_candidates.sort_by_key(|utxo| utxo.token.amount)
+        }
+    }
+    let mut selected_token_utxos = Vec::new();
+    let mut token_sum = SlpAmount::ZERO;
+    for utxo in token_candidates {
+        if token_sum >= target_token {
+            break;
+        }
+        token_sum = token_sum
+            .checked_add(utxo.token.amount)
+            .ok_or(SlpError::BalanceOverflow)?;
+        selected_token_utxos.push(utxo.clone());
+    }
+    if token_sum < target_token {
+        return Err(SlpError::InsufficientTokens {
+            needed: target_token,
+            available: token_sum,
+        });
+    }
+
+    let mut sats_candidates = sats_utxos.iter().collect::<Vec<_>>();
+    sats_candidates.sort_by_key(|utxo| Reverse(utxo.value));
+    let mut sats_candidates = sats_candidates.into_iter();
+    let mut selected_sats_utxos = Vec::new();
+    let mut sats_sum = Satoshi::checked_sum(
+        selected_token_utxos
+            .iter()
+            .map(|utxo| Satoshi::new(utxo.utxo.value)),
+    )
+    .ok_or(SlpError::SatsOverflow)?;
+    loop {
+        let num_inputs = selected_token_utxos.len() + selected_sats_utxos.len();
+        let needed = fee_rate
+            .checked_fee_for_size(num_inputs * P2PKH_INPUT_SIZE)
+            .and_then(|fee| Satoshi::new(target_sats).checked_add(Satoshi::new(fee)))
+            .ok_or(SlpError::SatsOverflow)?;
+        if sats_sum >= needed {
+            return Ok(SlpInputSelection {
+                token_utxos: selected_token_utxos,
+                sats_utxos: selected_sats_utxos,
+                token_change: token_sum - target_token,
+                sats_change: sats_sum
+                    .checked_sub(needed)
+                    .ok_or(SlpError::SatsOverflow)?
+                    .as_i64(),
+            });
+        }
+        let utxo = sats_candidates.next().ok_or(SlpError::InsufficientSats {
+            needed: needed.as_i64(),
+            available: sats_sum.as_i64(),
+        })?;
+        sats_sum = sats_sum
+            .checked_add(Satoshi::new(utxo.value))
+            .ok_or(SlpError::SatsOverflow)?;
+        selected_sats_utxos.push(utxo.clone());
+    }
+}
+
+/// Plan a SEND tx sending `sends` of `token_id` without building it, e.g. to preview the
+/// send or show why it isn't possible before the user confirms.
+///
+/// Token utxos are selected from `available` largest first, sats from `sats`, see
+/// [`select_slp_inputs`]. Every send output and the token change output get
+/// [`SLP_DUST_AMOUNT`] sats. Change outputs and inputs are assumed to be P2PKH.
+/// Fails with [`SlpError::InsufficientTokens`] or [`SlpError::InsufficientSats`].
+pub fn simulate_slp_send(
+    available: &[SlpUtxo],
+    token_id: &TokenId,
+    token_type: SlpTokenType,
+    sends: &[(Script, SlpAmount)],
+    sats: &[Utxo],
+    fee_rate: FeeRate,
+) -> Result<SlpSendPlan, SlpError> {
+    let target_token = sends
+        .iter()
+        .try_fold(SlpAmount::ZERO, |sum, (_, amount)| sum.checked_add(*amount))
+        .ok_or(SlpError::BalanceOverflow)?;
+    let select = |token_change: Option<SlpAmount>| -> Result<SlpInputSelection, SlpError> {
+        let outputs = simulated_send_outputs(token_id, token_type, sends, token_change)?;
+        let size = UnhashedTx {
+            version: 1,
+            inputs: vec![],
+            outputs,
+            lock_time: 0,
+        }
+        .ser()
+        .len();
+        let num_dust_outputs = sends.len() + token_change.is_some() as usize;
+        let target_sats = Satoshi::new(SLP_DUST_AMOUNT)
+            .checked_mul(num_dust_outputs as i64)
+            .zip(fee_rate.checked_fee_for_size(size))
+            .and_then(|(dust_sats, fee)| dust_sats.checked_add(Satoshi::new(fee)))
+            .ok_or(SlpError::SatsOverflow)?;
+        select_slp_inputs(
+            SelectionStrategy::LargestFirst,
+            available,
+            sats,
+            token_id,
+            target_token,
+            target_sats.as_i64(),
+            fee_rate,
+        )
+    };
+    // The token change output makes the tx larger, so select again if there is one
+    let mut selection = select(None)?;
+    if selection.token_change > SlpAmount::ZERO {
+        selection = select(Some(selection.token_change))?;
+    }
+    let sats_change = match selection.sats_change < SLP_DUST_AMOUNT {
+        true => 0,
+        false => selection.sats_change,
+    };
+    let input_sum = Satoshi::checked_sum(
+        selection
+            .token_utxos
+            .iter()
+            .map(|utxo| utxo.utxo.value)
+            .chain(selection.sats_utxos.iter().map(|utxo| utxo.value))
+            .map(Satoshi::new),
+    )
+    .ok_or(SlpError::SatsOverflow)?;
+    let num_dust_outputs = sends.len() + (selection.token_change > SlpAmount::ZERO) as usize;
+    let fee = Satoshi::new(SLP_DUST_AMOUNT)
+        .checked_mul(num_dust_outputs as i64)
+        .and_then(|dust_sats| input_sum.checked_sub(dust_sats))
+        .and_then(|fee| fee.checked_sub(Satoshi::new(sats_change)))
+        .ok_or(SlpError::SatsOverflow)?
+        .as_i64();
+    Ok(SlpSendPlan {
+        token_utxos: selection.token_utxos,
+        sats_utxos: selection.sats_utxos,
+        token_change: selection.token_change,
+        sats_change,
+        fee,
+    })
+}
+
+/// Outputs of a SEND tx as simulated by [`simulate_slp_send`], including a sats change output.
+fn simulated_send_outputs(
+    token_id: &TokenId,
+    token_type: SlpTokenType,
+    sends: &[(Script, SlpAmount)],
+    token_change: Option<SlpAmount>,
+) -> Result<Vec<TxOutput>, SlpError> {
+    let change_script = Script::p2pkh(&ShaRmd160::default());
+    let mut amounts = sends.iter().map(|(_, amount)| *amount).collect::<Vec<_>>();
+    amounts.extend(token_change);
+    let mut outputs = vec![TxOutput {
+        value: 0,
+        script: send_opreturn(token_id, token_type, &amounts)?,
+    }];
+    let scripts = sends
+        .iter()
+        .map(|(script, _)| script)
+        .chain(token_change.map(|_| &change_script));
+    outputs.extend(scripts.map(|script| TxOutput {
+        value: SLP_DUST_AMOUNT,
+        script: script.clone(),
+    }));
+    outputs.push(TxOutput {
+        value: 0,
+        script: change_script.clone(),
+    });
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{
+        BitcoinCode, FeeRate, OutPoint, Script, ScriptBuilder, SequenceNo, Sha256d, ShaRmd160,
+        TxInput, TxOutput, UnhashedTx, Utxo,
+    };
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        estimate_slp_tx_fee, plan_slp_amounts, select_slp_inputs, send_opreturn, simulate_slp_send,
+        test_utils::{token_utxo, utxo},
+        SelectionStrategy, SlpAmount, SlpChangePlan, SlpError, SlpInputSelection, SlpSendPlan,
+        SlpToken, SlpTokenType, TokenId,
+    };
+
+    #[test]
+    fn test_plan_slp_amounts() {
+        let inputs = [
+            SlpToken::amount(6),
+            SlpToken::MINT_BATON,
+            SlpToken::amount(4),
+        ];
+        assert_eq!(
+            plan_slp_amounts(&inputs, SlpAmount::new(7)),
+            Ok(SlpChangePlan {
+                send: SlpAmount::new(7),
+                change: SlpAmount::new(3),
+            }),
+        );
+        assert_eq!(
+            plan_slp_amounts(&inputs, SlpAmount::new(10)),
+            Ok(SlpChangePlan {
+                send: SlpAmount::new(10),
+                change: SlpAmount::ZERO,
+            }),
+        );
+        assert_eq!(
+            plan_slp_amounts(&inputs, SlpAmount::new(11)),
+            Err(SlpError::InsufficientTokens {
+                needed: SlpAmount::new(11),
+                available: SlpAmount::new(10),
+            }),
+        );
+        assert_eq!(
+            plan_slp_amounts(&[], SlpAmount::ZERO),
+            Ok(SlpChangePlan {
+                send: SlpAmount::ZERO,
+                change: SlpAmount::ZERO,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_estimate_slp_tx_fee() -> Result<(), SlpError> {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        // Signed P2PKH input: 65 bytes Schnorr signature and 33 bytes pubkey
+        let input_script = ScriptBuilder::new()
+            .push_data(&[1; 65])
+            .push_data(&[2; 33])
+            .build();
+        let p2pkh_output = TxOutput {
+            value: 546,
+            script: Script::p2pkh(&ShaRmd160::new([6; 20])),
+        };
+        let send_tx = |num_inputs: u32, amounts: &[SlpAmount]| -> Result<UnhashedTx, SlpError> {
+            let mut outputs = vec![TxOutput {
+                value: 0,
+                script: send_opreturn(&token_id, SlpTokenType::Fungible, amounts)?,
+            }];
+            // Token outputs and sats change
+            outputs.extend(vec![p2pkh_output.clone(); amounts.len() + 1]);
+            Ok(UnhashedTx {
+                version: 1,
+                inputs: (0..num_inputs)
+                    .map(|out_idx| TxInput {
+                        prev_out: OutPoint {
+                            txid: Sha256d::new([1; 32]),
+                            out_idx,
+                        },
+                        script: input_script.clone(),
+                        sequence: SequenceNo::finalized(),
+                        sign_data: None,
+                    })
+                    .collect(),
+                outputs,
+                lock_time: 0,
+            })
+        };
+
+        // 2 recipients, 2 token inputs and 1 sats input
+        let tx = send_tx(3, &[SlpAmount::new(60), SlpAmount::new(40)])?;
+        assert_eq!(tx.ser().len(), 608);
+        assert_eq!(estimate_slp_tx_fee(2, 1, 2, 32, FeeRate::per_kb(1000)), 608);
+        assert_eq!(
+            estimate_slp_tx_fee(2, 1, 2, 32, FeeRate::per_kb(2000)),
+            1216
+        );
+
+        // Each recipient adds an amount to the OP_RETURN and an output
+        let amounts = vec![SlpAmount::new(1); 19];
+        let tx = send_tx(5, &amounts)?;
+        assert_eq!(
+            estimate_slp_tx_fee(4, 1, 19, 32, FeeRate::per_kb(1000)),
+            tx.ser().len() as i64,
+        );
+        assert_eq!(
+            estimate_slp_tx_fee(4, 1, 19, 32, FeeRate::per_kb(1000))
+                - estimate_slp_tx_fee(4, 1, 18, 32, FeeRate::per_kb(1000)),
+            9 + 34,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_slp_inputs_fragmented() -> Result<(), SlpError> {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+        // 20 dust utxos with 1 token each, one with 50 tokens, plus noise
+        let mut token_utxos = (0..20)
+            .map(|out_idx| token_utxo(out_idx, SlpToken::amount(1), Some(&token_id)))
+            .collect::<Vec<_>>();
+        token_utxos.push(token_utxo(20, SlpToken::amount(50), Some(&token_id)));
+        token_utxos.push(token_utxo(21, SlpToken::MINT_BATON, Some(&token_id)));
+        token_utxos.insert(
+            0,
+            token_utxo(22, SlpToken::amount(1), Some(&other_token_id)),
+        );
+        let sats_utxos = vec![utxo(30, 2_000), utxo(31, 10_000)];
+
+        let selection = select_slp_inputs(
+            SelectionStrategy::ConsolidateTokens,
+            &token_utxos,
+            &sats_utxos,
+            &token_id,
+            SlpAmount::new(10),
+            6_000,
+            FeeRate::per_kb(1000),
+        )?;
+        assert_eq!(
+            selection,
+            SlpInputSelection {
+                token_utxos: token_utxos[1..11].to_vec(),
+                sats_utxos: vec![sats_utxos[1].clone()],
+                token_change: SlpAmount::ZERO,
+                // 10 * 546 + 10_000 - 6_000 - 11 * 141
+                sats_change: 7_909,
+            },
+        );
+
+        let selection = select_slp_inputs(
+            SelectionStrategy::LargestFirst,
+            &token_utxos,
+            &sats_utxos,
+            &token_id,
+            SlpAmount::new(10),
+            6_000,
+            FeeRate::per_kb(1000),
+        )?;
+        assert_eq!(
+            selection,
+            SlpInputSelection {
+                token_utxos: vec![token_utxos[21].clone()],
+                sats_utxos: vec![sats_utxos[1].clone()],
+                token_change: SlpAmount::new(40),
+                // 546 + 10_000 - 6_000 - 2 * 141
+                sats_change: 4_264,
+            },
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_slp_inputs_insufficient() {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+        let token_utxos = vec![
+            token_utxo(0, SlpToken::amount(30), Some(&token_id)),
+            token_utxo(1, SlpToken::amount(40), Some(&token_id)),
+            token_utxo(2, SlpToken::amount(100), Some(&other_token_id)),
+        ];
+        let sats_utxos = vec![utxo(3, 10_000)];
+        for strategy in [
+            SelectionStrategy::LargestFirst,
+            SelectionStrategy::ConsolidateTokens,
+        ] {
+            assert_eq!(
+                select_slp_inputs(
+                    strategy,
+                    &token_utxos,
+                    &sats_utxos,
+                    &token_id,
+                    SlpAmount::new(100),
+                    1_000,
+                    FeeRate::per_kb(1000),
+                ),
+                Err(SlpError::InsufficientTokens {
+                    needed: SlpAmount::new(100),
+                    available: SlpAmount::new(70),
+                }),
+            );
+            assert_eq!(
+                select_slp_inputs(
+                    strategy,
+                    &token_utxos,
+                    &sats_utxos,
+                    &token_id,
+                    SlpAmount::new(70),
+                    11_000,
+                    FeeRate::per_kb(1000),
+                ),
+                Err(SlpError::InsufficientSats {
+                    needed: 11_000 + 3 * 141,
+                    available: 2 * 546 + 10_000,
+                }),
+            );
+        }
+
+        // Sats sum overflows
+        assert_eq!(
+            select_slp_inputs(
+                SelectionStrategy::LargestFirst,
+                &token_utxos,
+                &[utxo(3, i64::MAX)],
+                &token_id,
+                SlpAmount::new(70),
+                11_000,
+                FeeRate::per_kb(1000),
+            ),
+            Err(SlpError::SatsOverflow),
+        );
+    }
+
+    #[test]
+    fn test_simulate_slp_send() -> Result<(), SlpError> {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+        let token_utxos = vec![
+            token_utxo(0, SlpToken::amount(30), Some(&token_id)),
+            token_utxo(1, SlpToken::amount(50), Some(&token_id)),
+            token_utxo(2, SlpToken::amount(100), Some(&other_token_id)),
+        ];
+        let sats_utxos = vec![utxo(3, 2_000), utxo(4, 10_000)];
+        let dest = Script::p2pkh(&ShaRmd160::new([6; 20]));
+        let simulate = |amount, sats_utxos: &[Utxo]| {
+            simulate_slp_send(
+                &token_utxos,
+                &token_id,
+                SlpTokenType::Fungible,
+                &[(dest.clone(), SlpAmount::new(amount))],
+                sats_utxos,
+                FeeRate::per_kb(1000),
+            )
+        };
+
+        // Tx has 3 inputs and 4 outputs: OP_RETURN (73 bytes), send, token change, sats change
+        assert_eq!(
+            simulate(60, &sats_utxos)?,
+            SlpSendPlan {
+                token_utxos: vec![token_utxos[1].clone(), token_utxos[0].clone()],
+                sats_utxos: vec![sats_utxos[1].clone()],
+                token_change: SlpAmount::new(20),
+                // 2 * 546 + 10_000 - 2 * 546 - fee
+                sats_change: 9_392,
+                // 10 + 73 + 3 * 34 + 3 * 141
+                fee: 608,
+            },
+        );
+
+        // Exact token amount needs no token change; dust sats change goes to the fee
+        assert_eq!(
+            simulate(50, &[utxo(3, 700)])?,
+            SlpSendPlan {
+                token_utxos: vec![token_utxos[1].clone()],
+                sats_utxos: vec![utxo(3, 700)],
+                token_change: SlpAmount::ZERO,
+                sats_change: 0,
+                fee: 700,
+            },
+        );
+
+        assert_eq!(
+            simulate(100, &sats_utxos),
+            Err(SlpError::InsufficientTokens {
+                needed: SlpAmount::new(100),
+                available: SlpAmount::new(80),
+            }),
+        );
+        assert_eq!(
+            simulate(60, &[]),
+            Err(SlpError::InsufficientSats {
+                needed: 2 * 546 + 185 + 2 * 141,
+                available: 2 * 546,
+            }),
+        );
+        Ok(())
+    }
+}
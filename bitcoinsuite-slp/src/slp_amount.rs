@@ -5,25 +5,50 @@ use std::{
 };
 
 use bitcoinsuite_core::{BitcoinSuiteError, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::SlpError;
 
+/// Token amount in base units, i.e. as encoded in SLP OP_RETURNs, without applying decimals.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SlpAmount {
     base_amount: i128,
 }
 
+/// Token amount together with the decimals of its token, for parsing and displaying amounts
+/// the way users see them, e.g. "1.50" for 150 base units with 2 decimals.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecimalAmount {
+    amount: SlpAmount,
+    decimals: u32,
+}
+
 impl SlpAmount {
     pub const ZERO: SlpAmount = SlpAmount::new(0);
+    /// Largest amount that fits into the 8 unsigned bytes of an SLP OP_RETURN.
+    pub const MAX: SlpAmount = SlpAmount::new(u64::MAX as i128);
 
     pub fn from_str_decimals(s: &str, decimals: u32) -> Result<Self> {
-        let factor = (10i128).pow(decimals);
+        let factor = 10i128
+            .checked_pow(decimals)
+            .ok_or(BitcoinSuiteError::NumberParseError)?;
         let base_amount = match s.find('.') {
             Some(dot_idx) => {
-                let integer_part = s[..dot_idx]
+                // The sign applies to the fractional part too, e.g. "-0.5"
+                let (is_negative, integer_part_str) = match s[..dot_idx].strip_prefix('-') {
+                    Some(integer_part_str) => (true, integer_part_str),
+                    None => (false, &s[..dot_idx]),
+                };
+                if integer_part_str.starts_with(['-', '+']) {
+                    return Err(BitcoinSuiteError::NumberParseError);
+                }
+                let integer_part = integer_part_str
                     .parse::<i128>()
                     .map_err(|_| BitcoinSuiteError::NumberParseError)?;
-                let integer_part = integer_part * factor;
+                let integer_part = integer_part
+                    .checked_mul(factor)
+                    .ok_or(BitcoinSuiteError::NumberParseError)?;
                 let fract_part_str = &s[dot_idx + 1..];
                 let preceding_zeros = fract_part_str.chars().take_while(|c| *c == '0').count();
                 if fract_part_str.len() > decimals as usize {
@@ -31,21 +56,29 @@ impl SlpAmount {
                 }
                 let num_decimals = fract_part_str.len() as u32;
                 let fract_part_str = &fract_part_str[preceding_zeros..];
-                if fract_part_str.is_empty() {
+                let abs_amount = if fract_part_str.is_empty() {
                     integer_part
                 } else {
                     let fract_part = fract_part_str
                         .parse::<i128>()
                         .map_err(|_| BitcoinSuiteError::NumberParseError)?;
-                    let factor = (10i128).pow(decimals - num_decimals);
-                    integer_part + fract_part * factor
+                    let factor = 10i128.pow(decimals - num_decimals);
+                    fract_part
+                        .checked_mul(factor)
+                        .and_then(|fract_part| integer_part.checked_add(fract_part))
+                        .ok_or(BitcoinSuiteError::NumberParseError)?
+                };
+                if is_negative {
+                    -abs_amount
+                } else {
+                    abs_amount
                 }
             }
-            None => {
-                s.parse::<i128>()
-                    .map_err(|_| BitcoinSuiteError::NumberParseError)?
-                    * factor
-            }
+            None => s
+                .parse::<i128>()
+                .map_err(|_| BitcoinSuiteError::NumberParseError)?
+                .checked_mul(factor)
+                .ok_or(BitcoinSuiteError::NumberParseError)?,
         };
         Ok(SlpAmount { base_amount })
     }
@@ -68,10 +101,28 @@ impl SlpAmount {
         })
     }
 
+    /// Parse an amount pushed in an SLP OP_RETURN, which has to be exactly 8 bytes big-endian.
+    pub fn from_be_slice(slice: &[u8]) -> std::result::Result<Self, SlpError> {
+        SlpAmount::from_u64_be(slice, "amount")
+    }
+
+    /// Encode the amount as 8 bytes big-endian, as pushed in SLP OP_RETURNs.
+    pub fn to_be_bytes(&self) -> std::result::Result<[u8; 8], SlpError> {
+        let base_amount = u64::try_from(self.base_amount)
+            .map_err(|_| SlpError::AmountOutOfRange { amount: *self })?;
+        Ok(base_amount.to_be_bytes())
+    }
+
     pub fn base_amount(&self) -> i128 {
         self.base_amount
     }
 
+    pub fn checked_add(&self, other: Self) -> Option<SlpAmount> {
+        Some(SlpAmount::new(
+            self.base_amount.checked_add(other.base_amount)?,
+        ))
+    }
+
     fn _op(&self, other: Self, f: impl Fn(i128, i128) -> i128) -> SlpAmount {
         SlpAmount {
             base_amount: f(self.base_amount, other.base_amount),
@@ -83,6 +134,61 @@ impl SlpAmount {
             base_amount: f(self.base_amount),
         }
     }
+
+    /// Interpret this amount for a token with `decimals`, e.g. for displaying it.
+    pub fn with_decimals(self, decimals: u32) -> DecimalAmount {
+        DecimalAmount {
+            amount: self,
+            decimals,
+        }
+    }
+}
+
+impl DecimalAmount {
+    /// Parse an amount like "1.50" of a token with `decimals`.
+    pub fn from_str_decimals(s: &str, decimals: u32) -> Result<Self> {
+        Ok(SlpAmount::from_str_decimals(s, decimals)?.with_decimals(decimals))
+    }
+
+    /// Amount in base units, as it has to be encoded in OP_RETURNs.
+    pub fn amount(&self) -> SlpAmount {
+        self.amount
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    /// Add two amounts, fails if their decimals differ or on overflow.
+    pub fn checked_add(&self, other: DecimalAmount) -> std::result::Result<Self, SlpError> {
+        self.check_decimals(other)?;
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or(SlpError::BalanceOverflow)?;
+        Ok(amount.with_decimals(self.decimals))
+    }
+
+    /// Subtract `other`, fails if the decimals differ or on overflow.
+    pub fn checked_sub(&self, other: DecimalAmount) -> std::result::Result<Self, SlpError> {
+        self.check_decimals(other)?;
+        let base_amount = self
+            .amount
+            .base_amount
+            .checked_sub(other.amount.base_amount)
+            .ok_or(SlpError::BalanceOverflow)?;
+        Ok(SlpAmount::new(base_amount).with_decimals(self.decimals))
+    }
+
+    fn check_decimals(&self, other: DecimalAmount) -> std::result::Result<(), SlpError> {
+        if self.decimals != other.decimals {
+            return Err(SlpError::DecimalsMismatch {
+                left: self.decimals,
+                right: other.decimals,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Add for SlpAmount {
@@ -199,3 +305,184 @@ impl std::fmt::Display for SlpAmount {
         self.base_amount.fmt(f)
     }
 }
+
+impl std::fmt::Display for DecimalAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let base_amount = self.amount.base_amount;
+        if self.decimals == 0 {
+            return base_amount.fmt(f);
+        }
+        let abs_amount = base_amount.unsigned_abs();
+        let (integer_part, fract_part) = match 10u128.checked_pow(self.decimals) {
+            Some(factor) => (abs_amount / factor, abs_amount % factor),
+            // Any amount has fewer digits than decimals
+            None => (0, abs_amount),
+        };
+        let sign = if base_amount < 0 { "-" } else { "" };
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            integer_part,
+            fract_part,
+            width = self.decimals as usize,
+        )
+    }
+}
+
+/// Serialized as a decimal string in human readable formats, as JSON numbers can't represent
+/// integers above 2^53 exactly.
+#[cfg(feature = "serde")]
+impl Serialize for SlpAmount {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            self.base_amount.to_string().serialize(serializer)
+        } else {
+            self.base_amount.serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SlpAmount {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let base_amount = String::deserialize(deserializer)?;
+            let base_amount = base_amount
+                .parse::<i128>()
+                .map_err(serde::de::Error::custom)?;
+            Ok(SlpAmount::new(base_amount))
+        } else {
+            Ok(SlpAmount::new(i128::deserialize(deserializer)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_error::Result;
+    use pretty_assertions::assert_eq;
+
+    use crate::{DecimalAmount, SlpAmount, SlpError};
+
+    #[test]
+    fn test_amount_be_bytes() -> Result<()> {
+        let amount = SlpAmount::new(0x0102_0304_0506_0708);
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(amount.to_be_bytes()?, bytes);
+        assert_eq!(SlpAmount::from_be_slice(&bytes)?, amount);
+        let max = SlpAmount::new(u64::MAX as i128);
+        assert_eq!(max.to_be_bytes()?, [0xff; 8]);
+        assert_eq!(SlpAmount::from_be_slice(&[0xff; 8])?, max);
+        assert_eq!(SlpAmount::new(7).to_be_bytes()?, [0, 0, 0, 0, 0, 0, 0, 7]);
+
+        let too_big = SlpAmount::new(u64::MAX as i128 + 1);
+        assert_eq!(
+            too_big.to_be_bytes(),
+            Err(SlpError::AmountOutOfRange { amount: too_big }),
+        );
+        assert_eq!(
+            SlpAmount::new(-1).to_be_bytes(),
+            Err(SlpError::AmountOutOfRange {
+                amount: SlpAmount::new(-1),
+            }),
+        );
+        for len in [0, 7, 9] {
+            assert_eq!(
+                SlpAmount::from_be_slice(&vec![1; len]),
+                Err(SlpError::InvalidFieldSize {
+                    field_name: "amount",
+                    expected: &[8],
+                    actual: len,
+                }),
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal_amount_display() -> Result<()> {
+        assert_eq!(SlpAmount::new(150).to_string(), "150");
+        assert_eq!(SlpAmount::new(150).with_decimals(2).to_string(), "1.50");
+        assert_eq!(SlpAmount::new(150).with_decimals(0).to_string(), "150");
+        assert_eq!(SlpAmount::new(5).with_decimals(3).to_string(), "0.005");
+        assert_eq!(SlpAmount::new(-5).with_decimals(1).to_string(), "-0.5");
+        assert_eq!(SlpAmount::ZERO.with_decimals(2).to_string(), "0.00");
+        let amount = DecimalAmount::from_str_decimals("1.5", 2)?;
+        assert_eq!(amount.amount(), SlpAmount::new(150));
+        assert_eq!(amount.decimals(), 2);
+        assert_eq!(amount.to_string(), "1.50");
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal_amount_negative() -> Result<()> {
+        for (base_amount, decimals, s) in [
+            (-5, 1, "-0.5"),
+            (-50, 2, "-0.50"),
+            (-150, 2, "-1.50"),
+            (-1, 8, "-0.00000001"),
+            (-3, 0, "-3"),
+        ] {
+            let amount = SlpAmount::new(base_amount).with_decimals(decimals);
+            assert_eq!(amount.to_string(), s);
+            assert_eq!(DecimalAmount::from_str_decimals(s, decimals)?, amount);
+        }
+        assert_eq!(
+            SlpAmount::from_str_decimals("-0.5", 2)?,
+            SlpAmount::new(-50),
+        );
+        assert!(SlpAmount::from_str_decimals("--1.5", 2).is_err());
+        assert!(SlpAmount::from_str_decimals("-+1.5", 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal_amount_many_decimals() {
+        // 10^39 doesn't fit into 128 bits
+        assert_eq!(
+            SlpAmount::new(-15).with_decimals(40).to_string(),
+            format!("-0.{}15", "0".repeat(38)),
+        );
+        assert_eq!(
+            SlpAmount::new(i128::MAX).with_decimals(39).to_string(),
+            format!("0.{}", i128::MAX),
+        );
+        assert_eq!(
+            SlpAmount::new(1).with_decimals(38).to_string(),
+            format!("0.{}1", "0".repeat(37)),
+        );
+        assert!(SlpAmount::from_str_decimals("1", 39).is_err());
+        assert!(SlpAmount::from_str_decimals("0.5", 40).is_err());
+        assert!(SlpAmount::from_str_decimals("2", 38).is_err());
+        assert_eq!(
+            SlpAmount::from_str_decimals("1", 38).unwrap(),
+            SlpAmount::new(10i128.pow(38)),
+        );
+    }
+
+    #[test]
+    fn test_decimal_amount_arithmetic() {
+        let a = SlpAmount::new(150).with_decimals(2);
+        let b = SlpAmount::new(25).with_decimals(2);
+        assert_eq!(a.checked_add(b), Ok(SlpAmount::new(175).with_decimals(2)));
+        assert_eq!(a.checked_sub(b), Ok(SlpAmount::new(125).with_decimals(2)));
+        let c = SlpAmount::new(25).with_decimals(3);
+        assert_eq!(
+            a.checked_add(c),
+            Err(SlpError::DecimalsMismatch { left: 2, right: 3 }),
+        );
+        assert_eq!(
+            c.checked_sub(a),
+            Err(SlpError::DecimalsMismatch { left: 3, right: 2 }),
+        );
+        let max = SlpAmount::new(i128::MAX).with_decimals(2);
+        assert_eq!(max.checked_add(b), Err(SlpError::BalanceOverflow));
+    }
+}
@@ -1,9 +1,17 @@
-use bitcoinsuite_core::{ByteArray, Bytes, Script, TxInput, TxOutput, UnhashedTx};
+use std::{collections::HashMap, fmt::Display};
+
+use bitcoinsuite_core::{
+    ByteArray, Bytes, Hashed, Script, Sha256, TxInput, TxOutput, UnhashedTx, Utxo,
+};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    consts::{SLP_TOKEN_TYPE_V1, SLP_TOKEN_TYPE_V1_NFT1_CHILD, SLP_TOKEN_TYPE_V1_NFT1_GROUP},
-    SlpAmount, TokenId,
+    consts::{
+        SLP_TOKEN_TYPE_V1, SLP_TOKEN_TYPE_V1_NFT1_CHILD, SLP_TOKEN_TYPE_V1_NFT1_GROUP,
+        SLP_TX_TYPE_BURN, SLP_TX_TYPE_GENESIS, SLP_TX_TYPE_MINT, SLP_TX_TYPE_SEND,
+    },
+    MintBaton, SlpAmount, SlpError, TokenId,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,12 +19,29 @@ pub struct SlpTx {
     tx: UnhashedTx,
     slp_tx_data: Option<Box<SlpTxData>>,
     slp_burns: Vec<Option<Box<SlpBurn>>>,
+    validity: SlpValidity,
+    /// Output spent by each input, `None` if unknown (e.g. pruned)
+    input_prevouts: Vec<Option<Utxo>>,
+    /// Height of the block the tx is confirmed in, `None` if unconfirmed
+    block_height: Option<i32>,
+}
+
+/// Whether a tx is valid SLP, see [`SlpTx::validity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlpValidity {
+    Valid,
+    /// Tx has an SLP OP_RETURN, but it is malformed or breaks the SLP rules.
+    /// Any tokens spent by the tx are burned.
+    Invalid(SlpError),
+    /// Tx has no SLP OP_RETURN.
+    NotSlp,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SlpTxData {
     pub input_tokens: Vec<SlpToken>,
     pub output_tokens: Vec<SlpToken>,
+    /// Token type, i.e. the version byte of the OP_RETURN (see [`SlpTokenType::to_u16`])
     pub slp_token_type: SlpTokenType,
     pub slp_tx_type: SlpTxType,
     /// 0000...000000 if token_id is incomplete
@@ -24,7 +49,8 @@ pub struct SlpTxData {
     pub group_token_id: Option<Box<TokenId>>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum SlpTokenType {
     Fungible,
     Nft1Group,
@@ -32,7 +58,8 @@ pub enum SlpTokenType {
     Unknown,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum SlpTxType {
     Genesis(Box<SlpGenesisInfo>),
     Send,
@@ -41,7 +68,8 @@ pub enum SlpTxType {
     Unknown,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum SlpTxTypeVariant {
     Genesis,
     Send,
@@ -50,7 +78,8 @@ pub enum SlpTxTypeVariant {
     Unknown,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct SlpGenesisInfo {
     pub token_ticker: Bytes,
     pub token_name: Bytes,
@@ -69,6 +98,17 @@ pub struct SlpTxInput<'tx> {
 pub struct SlpBurn {
     pub token: SlpToken,
     pub token_id: TokenId,
+    /// Whether the tx explicitly declared the burn, i.e. using a BURN message.
+    pub is_intended: bool,
+}
+
+/// Burns of a set of txs, aggregated per token, see [`summarize_burns`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BurnSummary {
+    /// Total amount burned of each token; tokens with only batons burned are omitted.
+    pub amounts: HashMap<TokenId, SlpAmount>,
+    /// Number of mint batons burned of each token.
+    pub mint_batons: HashMap<TokenId, usize>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -84,6 +124,7 @@ pub struct SlpSendOutput {
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct SlpToken {
     pub amount: SlpAmount,
     pub is_mint_baton: bool,
@@ -91,13 +132,7 @@ pub struct SlpToken {
 
 impl SlpTxType {
     pub fn type_str(&self) -> &'static str {
-        match self {
-            SlpTxType::Genesis(_) => "GENESIS",
-            SlpTxType::Mint => "MINT",
-            SlpTxType::Send => "SEND",
-            SlpTxType::Burn(_) => "BURN",
-            SlpTxType::Unknown => "UNKNOWN",
-        }
+        self.as_str()
     }
 }
 
@@ -116,10 +151,33 @@ impl SlpTx {
                 );
             }
         }
+        let validity = match &slp_tx_data {
+            Some(_) => SlpValidity::Valid,
+            None => SlpValidity::NotSlp,
+        };
         SlpTx {
+            input_prevouts: vec![None; tx.inputs.len()],
             tx,
             slp_tx_data: slp_tx_data.map(Box::new),
             slp_burns,
+            validity,
+            block_height: None,
+        }
+    }
+
+    /// Tx that failed to parse or validate as SLP with `error`.
+    pub fn new_invalid(
+        tx: UnhashedTx,
+        error: SlpError,
+        slp_burns: Vec<Option<Box<SlpBurn>>>,
+    ) -> Self {
+        SlpTx {
+            input_prevouts: vec![None; tx.inputs.len()],
+            tx,
+            slp_tx_data: None,
+            slp_burns,
+            validity: SlpValidity::from_error(error),
+            block_height: None,
         }
     }
 
@@ -155,9 +213,216 @@ impl SlpTx {
         self.slp_tx_data.as_deref()
     }
 
+    /// Tokens burned by each input: the burn at index `i` belongs to input `i` and is `None`
+    /// if that input burns nothing. For a summary, see [`SlpTx::total_burned`].
     pub fn burns(&self) -> &[Option<Box<SlpBurn>>] {
         &self.slp_burns
     }
+
+    /// Amount of `token_id` burned across all inputs, not counting mint batons.
+    pub fn burns_for_token(&self, token_id: &TokenId) -> SlpAmount {
+        self.slp_burns
+            .iter()
+            .flatten()
+            .filter(|burn| &burn.token_id == token_id)
+            .map(|burn| burn.token.amount)
+            .sum()
+    }
+
+    /// Amount burned across all inputs by token, not counting mint batons. Tokens of which
+    /// only mint batons are burned are left out.
+    pub fn total_burned(&self) -> HashMap<TokenId, SlpAmount> {
+        let mut total = HashMap::new();
+        for burn in self.slp_burns.iter().flatten() {
+            if burn.token.amount != SlpAmount::ZERO {
+                *total
+                    .entry(burn.token_id.clone())
+                    .or_insert(SlpAmount::ZERO) += burn.token.amount;
+            }
+        }
+        total
+    }
+
+    pub fn validity(&self) -> &SlpValidity {
+        &self.validity
+    }
+
+    pub fn input_prevouts(&self) -> &[Option<Utxo>] {
+        &self.input_prevouts
+    }
+
+    /// Height of the block the tx is confirmed in, `None` if it is unconfirmed.
+    pub fn block_height(&self) -> Option<i32> {
+        self.block_height
+    }
+
+    pub fn set_block_height(&mut self, block_height: Option<i32>) {
+        self.block_height = block_height;
+    }
+
+    pub fn set_input_prevouts(&mut self, input_prevouts: Vec<Option<Utxo>>) {
+        if input_prevouts.len() != self.tx.inputs.len() {
+            panic!(
+                "tx inputs and input prevouts have inconsistent length: {} != {}",
+                self.tx.inputs.len(),
+                input_prevouts.len()
+            );
+        }
+        self.input_prevouts = input_prevouts;
+    }
+
+    /// Describe where `self` and `other` differ, one "field: self != other" line per
+    /// difference, e.g. to make sense of a failed `assert_eq!` in a test. Empty if they are
+    /// equal.
+    pub fn explain_diff(&self, other: &SlpTx) -> Vec<String> {
+        let mut diffs = Vec::new();
+        let mut diff = |field: &dyn Display, left: String, right: String| {
+            if left != right {
+                diffs.push(format!("{}: {} != {}", field, left, right));
+            }
+        };
+        diff(
+            &"txid",
+            self.tx.txid().to_string(),
+            other.tx.txid().to_string(),
+        );
+        diff(
+            &"validity",
+            validity_str(&self.validity),
+            validity_str(&other.validity),
+        );
+        match (self.slp(), other.slp()) {
+            (Some(left), Some(right)) => {
+                diff(
+                    &"token ID",
+                    left.token_id.to_string(),
+                    right.token_id.to_string(),
+                );
+                diff(
+                    &"token type",
+                    format!("{:?}", left.slp_token_type),
+                    format!("{:?}", right.slp_token_type),
+                );
+                diff(
+                    &"tx type",
+                    tx_type_str(&left.slp_tx_type),
+                    tx_type_str(&right.slp_tx_type),
+                );
+                if let (SlpTxType::Genesis(left), SlpTxType::Genesis(right)) =
+                    (&left.slp_tx_type, &right.slp_tx_type)
+                {
+                    diff(
+                        &"GENESIS info",
+                        format!("{:?}", left),
+                        format!("{:?}", right),
+                    );
+                }
+                diff(
+                    &"group token ID",
+                    option_str(left.group_token_id.as_ref()),
+                    option_str(right.group_token_id.as_ref()),
+                );
+                let num_inputs = left.input_tokens.len().max(right.input_tokens.len());
+                for idx in 0..num_inputs {
+                    diff(
+                        &format!("input token {}", idx),
+                        token_str(left.input_tokens.get(idx)),
+                        token_str(right.input_tokens.get(idx)),
+                    );
+                }
+                let num_outputs = left.output_tokens.len().max(right.output_tokens.len());
+                for idx in 0..num_outputs {
+                    diff(
+                        &format!("output token {}", idx),
+                        token_str(left.output_tokens.get(idx)),
+                        token_str(right.output_tokens.get(idx)),
+                    );
+                }
+            }
+            (left, right) => diff(
+                &"SLP data",
+                option_str(left.map(|_| "present")),
+                option_str(right.map(|_| "present")),
+            ),
+        }
+        let num_burns = self.slp_burns.len().max(other.slp_burns.len());
+        for idx in 0..num_burns {
+            diff(
+                &format!("burn {}", idx),
+                burn_str(self.slp_burns.get(idx)),
+                burn_str(other.slp_burns.get(idx)),
+            );
+        }
+        let num_prevouts = self.input_prevouts.len().max(other.input_prevouts.len());
+        for idx in 0..num_prevouts {
+            diff(
+                &format!("input prevout {}", idx),
+                prevout_str(self.input_prevouts.get(idx)),
+                prevout_str(other.input_prevouts.get(idx)),
+            );
+        }
+        diff(
+            &"block height",
+            option_str(self.block_height),
+            option_str(other.block_height),
+        );
+        diffs
+    }
+}
+
+fn option_str(value: Option<impl Display>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "none".to_string(),
+    }
+}
+
+fn validity_str(validity: &SlpValidity) -> String {
+    match validity {
+        SlpValidity::Valid => "valid".to_string(),
+        SlpValidity::Invalid(error) => format!("invalid ({})", error),
+        SlpValidity::NotSlp => "not SLP".to_string(),
+    }
+}
+
+fn tx_type_str(tx_type: &SlpTxType) -> String {
+    match tx_type {
+        SlpTxType::Burn(amount) => format!("BURN {}", amount),
+        _ => tx_type.as_str().to_string(),
+    }
+}
+
+fn token_str(token: Option<&SlpToken>) -> String {
+    match token {
+        None => "none".to_string(),
+        Some(token) if token.is_mint_baton => format!("{} + mint baton", token.amount),
+        Some(token) => token.amount.to_string(),
+    }
+}
+
+fn burn_str(burn: Option<&Option<Box<SlpBurn>>>) -> String {
+    match burn.and_then(|burn| burn.as_ref()) {
+        Some(burn) => format!(
+            "{}{} of {}",
+            if burn.is_intended { "intended " } else { "" },
+            token_str(Some(&burn.token)),
+            burn.token_id,
+        ),
+        None => "none".to_string(),
+    }
+}
+
+fn prevout_str(prevout: Option<&Option<Utxo>>) -> String {
+    match prevout.and_then(|prevout| prevout.as_ref()) {
+        Some(utxo) => format!(
+            "{}:{} ({} sats, script {})",
+            utxo.outpoint.txid,
+            utxo.outpoint.out_idx,
+            utxo.value,
+            utxo.script.hex(),
+        ),
+        None => "none".to_string(),
+    }
 }
 
 impl SlpToken {
@@ -177,9 +442,73 @@ impl SlpToken {
             is_mint_baton: false,
         }
     }
+
+    /// Adds up the amounts, the result is a mint baton if either token is one.
+    pub fn checked_add(&self, other: SlpToken) -> Option<SlpToken> {
+        Some(SlpToken {
+            amount: self.amount.checked_add(other.amount)?,
+            is_mint_baton: self.is_mint_baton || other.is_mint_baton,
+        })
+    }
+}
+
+impl SlpGenesisInfo {
+    /// Document URL as a string, failing if it is not valid UTF-8 instead of converting lossily.
+    pub fn document_url_str(&self) -> Result<&str, SlpError> {
+        std::str::from_utf8(&self.token_document_url).map_err(|_| SlpError::InvalidUtf8 {
+            field_name: "token_document_url",
+        })
+    }
+
+    /// Check that the SHA-256 hash of a fetched `document` matches `token_document_hash`, which
+    /// must be set.
+    pub fn verify_document(&self, document: &[u8]) -> Result<(), SlpError> {
+        let expected = self
+            .token_document_hash
+            .clone()
+            .ok_or(SlpError::MissingDocumentHash)?;
+        let actual = Sha256::digest(Bytes::from_slice(document))
+            .byte_array()
+            .clone();
+        if actual != expected {
+            return Err(SlpError::DocumentHashMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Check that a GENESIS with this info can create `initial_quantity`: the decimals must be
+    /// at most 9, the quantity must fit into 8 unsigned bytes, i.e. be at most
+    /// [`SlpAmount::MAX`], and a fixed supply token, without `mint_baton`, must create some
+    /// tokens, as none can ever be minted later.
+    ///
+    /// Any such quantity can be displayed exactly with the decimals, see
+    /// [`SlpAmount::with_decimals`]; e.g. [`SlpAmount::MAX`] with 9 decimals is
+    /// 18446744073.709551615 tokens.
+    pub fn validate_quantity(
+        &self,
+        initial_quantity: SlpAmount,
+        mint_baton: MintBaton,
+    ) -> Result<(), SlpError> {
+        if self.decimals > 9 {
+            return Err(SlpError::InvalidDecimals {
+                actual: self.decimals as usize,
+            });
+        }
+        initial_quantity.to_be_bytes()?;
+        if mint_baton.vout.is_none() && initial_quantity == SlpAmount::ZERO {
+            return Err(SlpError::InitialQuantityNotPositive {
+                actual: initial_quantity,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl SlpTxType {
+    pub fn as_str(&self) -> &'static str {
+        self.tx_type_variant().as_str()
+    }
+
     pub fn tx_type_variant(&self) -> SlpTxTypeVariant {
         match &self {
             SlpTxType::Genesis(_) => SlpTxTypeVariant::Genesis,
@@ -191,6 +520,51 @@ impl SlpTxType {
     }
 }
 
+impl SlpTxTypeVariant {
+    /// Tx type as it appears in the OP_RETURN, e.g. "GENESIS".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlpTxTypeVariant::Genesis => "GENESIS",
+            SlpTxTypeVariant::Send => "SEND",
+            SlpTxTypeVariant::Mint => "MINT",
+            SlpTxTypeVariant::Burn => "BURN",
+            SlpTxTypeVariant::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// Tx type bytes pushed in the OP_RETURN, `None` for `Unknown`.
+    pub fn as_bytes(&self) -> Option<&'static [u8]> {
+        match self {
+            SlpTxTypeVariant::Genesis => Some(SLP_TX_TYPE_GENESIS),
+            SlpTxTypeVariant::Send => Some(SLP_TX_TYPE_SEND),
+            SlpTxTypeVariant::Mint => Some(SLP_TX_TYPE_MINT),
+            SlpTxTypeVariant::Burn => Some(SLP_TX_TYPE_BURN),
+            SlpTxTypeVariant::Unknown => None,
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SlpError> {
+        match bytes {
+            SLP_TX_TYPE_GENESIS => Ok(SlpTxTypeVariant::Genesis),
+            SLP_TX_TYPE_SEND => Ok(SlpTxTypeVariant::Send),
+            SLP_TX_TYPE_MINT => Ok(SlpTxTypeVariant::Mint),
+            SLP_TX_TYPE_BURN => Ok(SlpTxTypeVariant::Burn),
+            _ => Err(SlpError::InvalidTxType(bytes.into())),
+        }
+    }
+}
+
+impl SlpValidity {
+    /// [`SlpValidity::NotSlp`] if the error means there's no SLP OP_RETURN, otherwise
+    /// [`SlpValidity::Invalid`].
+    pub fn from_error(error: SlpError) -> Self {
+        match error.is_not_slp() {
+            true => SlpValidity::NotSlp,
+            false => SlpValidity::Invalid(error),
+        }
+    }
+}
+
 impl SlpTokenType {
     pub fn to_u16(&self) -> Option<u16> {
         match self {
@@ -201,3 +575,374 @@ impl SlpTokenType {
         }
     }
 }
+
+/// Add up the burns of all `txs`, intended or not, e.g. to track the supply reduction of
+/// the txs of a block.
+pub fn summarize_burns(txs: &[SlpTx]) -> Result<BurnSummary, SlpError> {
+    let mut summary = BurnSummary::default();
+    for burn in txs.iter().flat_map(|tx| tx.burns()).flatten() {
+        if burn.token.is_mint_baton {
+            *summary
+                .mint_batons
+                .entry(burn.token_id.clone())
+                .or_default() += 1;
+        }
+        if burn.token.amount != SlpAmount::ZERO {
+            let amount = summary
+                .amounts
+                .entry(burn.token_id.clone())
+                .or_insert(SlpAmount::ZERO);
+            *amount = amount
+                .checked_add(burn.token.amount)
+                .ok_or(SlpError::BalanceOverflow)?;
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bitcoinsuite_core::{Bytes, Hashed, OutPoint, Sha256, Sha256d, TxInput, UnhashedTx};
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        summarize_burns, BurnSummary, MintBaton, SlpAmount, SlpBurn, SlpError, SlpGenesisInfo,
+        SlpToken, SlpTokenType, SlpTx, SlpTxData, SlpTxType, SlpTxTypeVariant, SlpValidity,
+        TokenId,
+    };
+
+    #[test]
+    fn test_tx_type_variant_bytes() {
+        for variant in [
+            SlpTxTypeVariant::Genesis,
+            SlpTxTypeVariant::Send,
+            SlpTxTypeVariant::Mint,
+            SlpTxTypeVariant::Burn,
+        ] {
+            let bytes = variant.as_bytes().unwrap();
+            assert_eq!(bytes, variant.as_str().as_bytes());
+            assert_eq!(SlpTxTypeVariant::from_bytes(bytes), Ok(variant));
+        }
+        assert_eq!(SlpTxTypeVariant::Unknown.as_bytes(), None);
+        assert_eq!(
+            SlpTxTypeVariant::from_bytes(b"UNKNOWN"),
+            Err(SlpError::InvalidTxType(b"UNKNOWN".as_ref().into())),
+        );
+    }
+
+    #[test]
+    fn test_slp_tx_validity() {
+        let tx = UnhashedTx::default();
+        assert_eq!(
+            SlpTx::new(tx.clone(), None, vec![]).validity(),
+            &SlpValidity::NotSlp,
+        );
+        assert_eq!(
+            SlpTx::new_invalid(tx.clone(), SlpError::NoOutputs, vec![]).validity(),
+            &SlpValidity::NotSlp,
+        );
+        assert_eq!(
+            SlpTx::new_invalid(
+                tx.clone(),
+                SlpError::MissingOpReturn { opcode: 0x51 },
+                vec![]
+            )
+            .validity(),
+            &SlpValidity::NotSlp,
+        );
+        let error = SlpError::OutputSumExceedInputSum {
+            output_sum: SlpAmount::new(10),
+            input_sum: SlpAmount::ZERO,
+        };
+        let slp_tx = SlpTx::new_invalid(tx, error.clone(), vec![]);
+        assert_eq!(slp_tx.validity(), &SlpValidity::Invalid(error));
+        assert_eq!(slp_tx.slp(), None);
+    }
+
+    #[test]
+    fn test_slp_tx_burns() {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([2; 32]));
+        let burn = |token, token_id: &TokenId| {
+            Some(Box::new(SlpBurn {
+                token,
+                token_id: token_id.clone(),
+                is_intended: false,
+            }))
+        };
+        // Tx spending one token output of each of `num_inputs` txs
+        let tx = |num_inputs: u8| UnhashedTx {
+            version: 1,
+            inputs: (0..num_inputs)
+                .map(|idx| TxInput {
+                    prev_out: OutPoint {
+                        txid: Sha256d::new([idx + 10; 32]),
+                        out_idx: 1,
+                    },
+                    ..Default::default()
+                })
+                .collect(),
+            outputs: vec![],
+            lock_time: 0,
+        };
+        let slp_tx = SlpTx::new_invalid(
+            tx(5),
+            SlpError::NoOutputs,
+            vec![
+                burn(SlpToken::amount(3), &token_id),
+                None,
+                burn(SlpToken::amount(4), &token_id),
+                burn(SlpToken::amount(10), &other_token_id),
+                burn(SlpToken::MINT_BATON, &other_token_id),
+            ],
+        );
+        assert_eq!(slp_tx.burns().len(), slp_tx.tx().inputs.len());
+        assert_eq!(slp_tx.burns()[1], None);
+        assert_eq!(
+            slp_tx.burns()[2].as_ref().unwrap().token,
+            SlpToken::amount(4)
+        );
+        assert_eq!(slp_tx.burns_for_token(&token_id), SlpAmount::new(7));
+        assert_eq!(slp_tx.burns_for_token(&other_token_id), SlpAmount::new(10));
+        assert_eq!(
+            slp_tx.burns_for_token(&TokenId::new(Sha256d::new([3; 32]))),
+            SlpAmount::ZERO,
+        );
+        assert_eq!(
+            slp_tx.total_burned(),
+            HashMap::from([
+                (token_id, SlpAmount::new(7)),
+                (other_token_id.clone(), SlpAmount::new(10)),
+            ]),
+        );
+
+        // Burning only a mint baton burns no amount
+        let slp_tx = SlpTx::new(
+            tx(1),
+            None,
+            vec![burn(SlpToken::MINT_BATON, &other_token_id)],
+        );
+        assert_eq!(slp_tx.burns_for_token(&other_token_id), SlpAmount::ZERO);
+        assert_eq!(slp_tx.total_burned(), HashMap::new());
+    }
+
+    #[test]
+    fn test_explain_diff() {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([2; 32]));
+        let tx = UnhashedTx {
+            version: 1,
+            inputs: vec![TxInput::default(); 2],
+            outputs: vec![],
+            lock_time: 0,
+        };
+        let slp_tx_data = SlpTxData {
+            input_tokens: vec![SlpToken::amount(10), SlpToken::EMPTY],
+            output_tokens: vec![SlpToken::EMPTY, SlpToken::amount(6), SlpToken::amount(4)],
+            slp_token_type: SlpTokenType::Fungible,
+            slp_tx_type: SlpTxType::Send,
+            token_id: token_id.clone(),
+            group_token_id: None,
+        };
+        let expected = SlpTx::new(tx.clone(), Some(slp_tx_data.clone()), vec![None, None]);
+        assert_eq!(
+            expected.explain_diff(&expected.clone()),
+            Vec::<String>::new()
+        );
+
+        let actual = SlpTx::new(
+            tx,
+            Some(SlpTxData {
+                output_tokens: vec![SlpToken::EMPTY, SlpToken::amount(5), SlpToken::amount(4)],
+                slp_tx_type: SlpTxType::Burn(1),
+                token_id: other_token_id.clone(),
+                ..slp_tx_data
+            }),
+            vec![
+                None,
+                Some(Box::new(SlpBurn {
+                    token: SlpToken::MINT_BATON,
+                    token_id: token_id.clone(),
+                    is_intended: false,
+                })),
+            ],
+        );
+        assert_eq!(
+            expected.explain_diff(&actual),
+            vec![
+                format!("token ID: {} != {}", token_id, other_token_id),
+                "tx type: SEND != BURN 1".to_string(),
+                "output token 1: 6 != 5".to_string(),
+                format!("burn 1: none != 0 + mint baton of {}", token_id),
+            ],
+        );
+
+        let mut not_slp = SlpTx::new(UnhashedTx::default(), None, vec![]);
+        not_slp.set_block_height(Some(100));
+        assert_eq!(
+            expected.explain_diff(&not_slp),
+            vec![
+                format!(
+                    "txid: {} != {}",
+                    expected.tx().txid(),
+                    UnhashedTx::default().txid(),
+                ),
+                "validity: valid != not SLP".to_string(),
+                "SLP data: present != none".to_string(),
+                "block height: none != 100".to_string(),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_genesis_info_document() -> Result<(), Box<dyn std::error::Error>> {
+        let hash = |hex: &str| -> Result<_, Box<dyn std::error::Error>> {
+            Ok(Sha256::from_hex(hex)?.byte_array().clone())
+        };
+        let info = SlpGenesisInfo {
+            token_document_url: Bytes::from_slice(b"https://example.com/abc.json"),
+            // SHA-256 of "abc"
+            token_document_hash: Some(hash(
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            )?),
+            ..Default::default()
+        };
+        assert_eq!(info.document_url_str(), Ok("https://example.com/abc.json"));
+        assert_eq!(info.verify_document(b"abc"), Ok(()));
+        assert_eq!(
+            info.verify_document(b"abd"),
+            Err(SlpError::DocumentHashMismatch {
+                expected: info.token_document_hash.clone().unwrap(),
+                actual: hash("a52d159f262b2c6ddb724a61840befc36eb30c88877a4030b65cbe86298449c9")?,
+            }),
+        );
+
+        let info = SlpGenesisInfo {
+            token_document_url: Bytes::from_slice(&[0xff, 0xfe]),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.document_url_str(),
+            Err(SlpError::InvalidUtf8 {
+                field_name: "token_document_url",
+            }),
+        );
+        assert_eq!(
+            info.verify_document(b"abc"),
+            Err(SlpError::MissingDocumentHash),
+        );
+        assert_eq!(SlpGenesisInfo::default().document_url_str(), Ok(""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_genesis_info_validate_quantity() {
+        let info = |decimals| SlpGenesisInfo {
+            decimals,
+            ..Default::default()
+        };
+        let baton = MintBaton::at(2);
+        assert_eq!(
+            info(0).validate_quantity(SlpAmount::new(20), MintBaton::NONE),
+            Ok(()),
+        );
+        // 8-byte boundary
+        assert_eq!(SlpAmount::MAX.base_amount(), u64::MAX as i128);
+        for decimals in [0, 9] {
+            for mint_baton in [MintBaton::NONE, baton] {
+                assert_eq!(
+                    info(decimals).validate_quantity(SlpAmount::MAX, mint_baton),
+                    Ok(()),
+                );
+                let too_large = SlpAmount::MAX + SlpAmount::new(1);
+                assert_eq!(
+                    info(decimals).validate_quantity(too_large, mint_baton),
+                    Err(SlpError::AmountOutOfRange { amount: too_large }),
+                );
+            }
+        }
+        assert_eq!(
+            SlpAmount::MAX.with_decimals(9).to_string(),
+            "18446744073.709551615",
+        );
+        assert_eq!(
+            info(0).validate_quantity(SlpAmount::new(-1), baton),
+            Err(SlpError::AmountOutOfRange {
+                amount: SlpAmount::new(-1),
+            }),
+        );
+        // Only a token with a mint baton can start with no supply
+        assert_eq!(info(2).validate_quantity(SlpAmount::ZERO, baton), Ok(()));
+        assert_eq!(
+            info(2).validate_quantity(SlpAmount::ZERO, MintBaton::NONE),
+            Err(SlpError::InitialQuantityNotPositive {
+                actual: SlpAmount::ZERO,
+            }),
+        );
+        assert_eq!(
+            info(10).validate_quantity(SlpAmount::new(20), baton),
+            Err(SlpError::InvalidDecimals { actual: 10 }),
+        );
+    }
+
+    #[test]
+    fn test_summarize_burns() -> Result<(), SlpError> {
+        let token_id = TokenId::new(Sha256d::new([1; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([2; 32]));
+        let burn = |token, token_id: &TokenId, is_intended| {
+            Some(Box::new(SlpBurn {
+                token,
+                token_id: token_id.clone(),
+                is_intended,
+            }))
+        };
+        let txs = vec![
+            SlpTx::new(
+                UnhashedTx::default(),
+                None,
+                vec![
+                    burn(SlpToken::amount(3), &token_id, false),
+                    None,
+                    burn(SlpToken::MINT_BATON, &other_token_id, false),
+                ],
+            ),
+            SlpTx::new(UnhashedTx::default(), None, vec![None]),
+            SlpTx::new_invalid(
+                UnhashedTx::default(),
+                SlpError::NoOutputs,
+                vec![
+                    burn(SlpToken::amount(4), &token_id, true),
+                    burn(SlpToken::amount(10), &other_token_id, false),
+                    burn(SlpToken::MINT_BATON, &other_token_id, false),
+                ],
+            ),
+        ];
+        assert_eq!(
+            summarize_burns(&txs)?,
+            BurnSummary {
+                amounts: HashMap::from([
+                    (token_id.clone(), SlpAmount::new(7)),
+                    (other_token_id.clone(), SlpAmount::new(10)),
+                ]),
+                mint_batons: HashMap::from([(other_token_id.clone(), 2)]),
+            },
+        );
+        assert_eq!(summarize_burns(&[])?, BurnSummary::default());
+
+        let overflow_txs = vec![SlpTx::new(
+            UnhashedTx::default(),
+            None,
+            vec![
+                burn(SlpToken::amount(i128::MAX), &token_id, false),
+                burn(SlpToken::amount(1), &token_id, false),
+            ],
+        )];
+        assert_eq!(
+            summarize_burns(&overflow_txs),
+            Err(SlpError::BalanceOverflow)
+        );
+        Ok(())
+    }
+}
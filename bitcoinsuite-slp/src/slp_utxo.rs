@@ -1,10 +1,284 @@
-use bitcoinsuite_core::Utxo;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::{SlpToken, TokenId};
+use bitcoinsuite_core::{OutPoint, Utxo};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+use crate::{SlpAmount, SlpError, SlpToken, TokenId};
+
+/// Utxo with the tokens it holds.
+///
+/// Like [`Utxo`], equality and hashing compare all fields, and an `SlpUtxo` is identified by its
+/// outpoint alone, see [`slp_utxos_by_outpoint`]. Ordering compares the outpoint first.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct SlpUtxo {
     pub utxo: Utxo,
     pub token: SlpToken,
     pub token_id: Option<TokenId>,
 }
+
+/// Index `utxos` by their outpoint, ordered by outpoint. Of utxos with the same outpoint, the
+/// first one is kept.
+pub fn slp_utxos_by_outpoint(
+    utxos: impl IntoIterator<Item = SlpUtxo>,
+) -> BTreeMap<OutPoint, SlpUtxo> {
+    let mut map = BTreeMap::new();
+    for utxo in utxos {
+        map.entry(utxo.utxo.outpoint.clone()).or_insert(utxo);
+    }
+    map
+}
+
+/// Balance of an address, see [`AddressBalance::from_utxos`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AddressBalance {
+    /// Sats of utxos without tokens; sats of token utxos can only be spent with the tokens.
+    pub sats: i64,
+    /// Spendable amount of each token, excluding mint batons.
+    pub tokens: HashMap<TokenId, SlpAmount>,
+    /// Tokens for which a mint baton is held.
+    pub mint_batons: HashSet<TokenId>,
+}
+
+impl AddressBalance {
+    pub fn from_utxos(utxos: &[SlpUtxo]) -> Result<Self, SlpError> {
+        let mut balance = AddressBalance::default();
+        for utxo in utxos {
+            let token_id = match &utxo.token_id {
+                Some(token_id) => token_id,
+                None => {
+                    balance.sats += utxo.utxo.value;
+                    continue;
+                }
+            };
+            if utxo.token.is_mint_baton {
+                balance.mint_batons.insert(token_id.clone());
+                continue;
+            }
+            let amount = balance.tokens.entry(token_id.clone()).or_default();
+            *amount = amount
+                .checked_add(utxo.token.amount)
+                .ok_or(SlpError::BalanceOverflow)?;
+        }
+        Ok(balance)
+    }
+}
+
+/// Sum of the spendable amounts of `token_id` in `utxos`, ignoring mint batons.
+pub fn sum_token_balance(utxos: &[SlpUtxo], token_id: &TokenId) -> Result<SlpAmount, SlpError> {
+    utxos
+        .iter()
+        .filter(|utxo| utxo.token_id.as_ref() == Some(token_id) && !utxo.token.is_mint_baton)
+        .try_fold(SlpAmount::ZERO, |balance, utxo| {
+            balance
+                .checked_add(utxo.token.amount)
+                .ok_or(SlpError::BalanceOverflow)
+        })
+}
+
+/// Split `utxos` into plain sats utxos and token utxos by token ID, keeping the order of each.
+pub fn group_utxos_by_token(utxos: Vec<SlpUtxo>) -> (Vec<Utxo>, HashMap<TokenId, Vec<SlpUtxo>>) {
+    let mut sats_utxos = Vec::new();
+    let mut token_utxos = HashMap::<_, Vec<_>>::new();
+    for utxo in utxos {
+        match &utxo.token_id {
+            Some(token_id) => token_utxos.entry(token_id.clone()).or_default().push(utxo),
+            None => sats_utxos.push(utxo.utxo),
+        }
+    }
+    (sats_utxos, token_utxos)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::Sha256d;
+    use pretty_assertions::assert_eq;
+
+    use std::collections::{HashMap, HashSet};
+
+    use crate::{
+        group_utxos_by_token, slp_utxos_by_outpoint, sum_token_balance, test_utils::token_utxo,
+        AddressBalance, SlpAmount, SlpError, SlpToken, TokenId,
+    };
+
+    #[test]
+    fn test_slp_utxos_by_outpoint() {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let utxos = vec![
+            token_utxo(1, SlpToken::amount(10), Some(&token_id)),
+            token_utxo(0, SlpToken::MINT_BATON, Some(&token_id)),
+            token_utxo(1, SlpToken::EMPTY, None),
+            token_utxo(0, SlpToken::amount(3), Some(&token_id)),
+        ];
+        // Equality and hashing include the token
+        assert_ne!(utxos[0], utxos[2]);
+        assert_eq!(utxos.iter().collect::<HashSet<_>>().len(), 4);
+        let by_outpoint = slp_utxos_by_outpoint(utxos);
+        assert_eq!(
+            by_outpoint
+                .into_values()
+                .map(|utxo| (utxo.utxo.outpoint.out_idx, utxo.token))
+                .collect::<Vec<_>>(),
+            vec![(0, SlpToken::MINT_BATON), (1, SlpToken::amount(10))],
+        );
+    }
+
+    #[test]
+    fn test_sort_slp_utxos() {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let mut utxos = vec![
+            token_utxo(2, SlpToken::amount(10), Some(&token_id)),
+            token_utxo(0, SlpToken::MINT_BATON, Some(&token_id)),
+            token_utxo(1, SlpToken::EMPTY, None),
+        ];
+        utxos.sort();
+        assert_eq!(
+            utxos
+                .iter()
+                .map(|utxo| utxo.utxo.outpoint.out_idx)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2],
+        );
+    }
+
+    #[test]
+    fn test_sum_token_balance() {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+        // Address after GENESIS: 20 tokens, the mint baton and some sats
+        let mut utxos = vec![
+            token_utxo(0, SlpToken::EMPTY, None),
+            token_utxo(1, SlpToken::amount(20), Some(&token_id)),
+            token_utxo(2, SlpToken::MINT_BATON, Some(&token_id)),
+        ];
+        assert_eq!(sum_token_balance(&utxos, &token_id), Ok(SlpAmount::new(20)));
+        assert_eq!(
+            sum_token_balance(&utxos, &other_token_id),
+            Ok(SlpAmount::ZERO)
+        );
+
+        utxos.push(token_utxo(3, SlpToken::amount(7), Some(&other_token_id)));
+        utxos.push(token_utxo(4, SlpToken::amount(5), Some(&token_id)));
+        assert_eq!(sum_token_balance(&utxos, &token_id), Ok(SlpAmount::new(25)));
+        assert_eq!(
+            sum_token_balance(&utxos, &other_token_id),
+            Ok(SlpAmount::new(7))
+        );
+
+        utxos.push(token_utxo(5, SlpToken::amount(i128::MAX), Some(&token_id)));
+        assert_eq!(
+            sum_token_balance(&utxos, &token_id),
+            Err(SlpError::BalanceOverflow),
+        );
+    }
+
+    #[test]
+    fn test_address_balance() {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+        let mut utxos = vec![
+            token_utxo(0, SlpToken::EMPTY, None),
+            token_utxo(1, SlpToken::amount(20), Some(&token_id)),
+            token_utxo(2, SlpToken::MINT_BATON, Some(&token_id)),
+            token_utxo(3, SlpToken::amount(7), Some(&other_token_id)),
+            token_utxo(4, SlpToken::amount(5), Some(&token_id)),
+            token_utxo(5, SlpToken::EMPTY, None),
+        ];
+        assert_eq!(
+            AddressBalance::from_utxos(&utxos),
+            Ok(AddressBalance {
+                sats: 2 * 546,
+                tokens: HashMap::from([
+                    (token_id.clone(), SlpAmount::new(25)),
+                    (other_token_id, SlpAmount::new(7)),
+                ]),
+                mint_batons: HashSet::from([token_id.clone()]),
+            }),
+        );
+        assert_eq!(
+            AddressBalance::from_utxos(&[]),
+            Ok(AddressBalance::default())
+        );
+
+        utxos.push(token_utxo(6, SlpToken::amount(i128::MAX), Some(&token_id)));
+        assert_eq!(
+            AddressBalance::from_utxos(&utxos),
+            Err(SlpError::BalanceOverflow),
+        );
+    }
+
+    #[test]
+    fn test_group_utxos_by_token() {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+        let utxos = vec![
+            token_utxo(0, SlpToken::amount(20), Some(&token_id)),
+            token_utxo(1, SlpToken::EMPTY, None),
+            token_utxo(2, SlpToken::amount(7), Some(&other_token_id)),
+            token_utxo(3, SlpToken::MINT_BATON, Some(&token_id)),
+            token_utxo(4, SlpToken::EMPTY, None),
+            token_utxo(5, SlpToken::amount(5), Some(&token_id)),
+        ];
+        let (sats_utxos, token_utxos) = group_utxos_by_token(utxos.clone());
+        assert_eq!(
+            sats_utxos,
+            vec![utxos[1].utxo.clone(), utxos[4].utxo.clone()]
+        );
+        assert_eq!(
+            token_utxos,
+            HashMap::from([
+                (
+                    token_id,
+                    vec![utxos[0].clone(), utxos[3].clone(), utxos[5].clone()],
+                ),
+                (other_token_id, vec![utxos[2].clone()]),
+            ]),
+        );
+        assert_eq!(group_utxos_by_token(vec![]), (vec![], HashMap::new()));
+    }
+
+    #[test]
+    fn test_slp_token_checked_add() {
+        assert_eq!(
+            SlpToken::amount(3).checked_add(SlpToken::amount(4)),
+            Some(SlpToken::amount(7)),
+        );
+        assert_eq!(
+            SlpToken::amount(3).checked_add(SlpToken::MINT_BATON),
+            Some(SlpToken {
+                amount: SlpAmount::new(3),
+                is_mint_baton: true,
+            }),
+        );
+        assert_eq!(
+            SlpToken::amount(i128::MAX).checked_add(SlpToken::amount(1)),
+            None,
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_slp_utxo_serde() {
+        use crate::SlpUtxo;
+
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let utxo = token_utxo(2, SlpToken::amount(0x1fff_ffff_ffff_ffff), Some(&token_id));
+        let json = serde_json::to_value(&utxo).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "utxo": {
+                    "outpoint": {"txid": "01".repeat(32), "out_idx": 2},
+                    "script": format!("76a914{}88ac", "02".repeat(20)),
+                    "value": 546,
+                },
+                "token": {"amount": "2305843009213693951", "is_mint_baton": false},
+                "token_id": "04".repeat(32),
+            }),
+        );
+        assert_eq!(serde_json::from_value::<SlpUtxo>(json).unwrap(), utxo);
+        let encoded = bincode::serialize(&utxo).unwrap();
+        assert_eq!(bincode::deserialize::<SlpUtxo>(&encoded).unwrap(), utxo);
+    }
+}
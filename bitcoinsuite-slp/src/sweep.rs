@@ -0,0 +1,222 @@
+use bitcoinsuite_core::{
+    BitcoinCode, FeeRate, Network, Satoshi, Script, SequenceNo, SignData, SignField, TxInput,
+    TxOutput, UnhashedTx, Utxo,
+};
+
+use crate::{
+    send_opreturn, sum_token_balance, SlpAmount, SlpError, SlpTokenType, SlpUtxo, TokenId,
+    P2PKH_INPUT_SIZE,
+};
+
+/// Build a tx spending all `utxos` to a single output to `dest`, paying `fee_rate`.
+///
+/// Inputs have their sign data set but are left unsigned; the fee assumes P2PKH inputs, see
+/// [`P2PKH_INPUT_SIZE`].
+pub fn build_sweep(
+    utxos: &[Utxo],
+    dest: &Script,
+    fee_rate: FeeRate,
+    network: Network,
+) -> Result<UnhashedTx, SlpError> {
+    build_sweep_tx(utxos.iter().collect(), vec![], dest, fee_rate, network)
+}
+
+/// Build a tx sending the entire balance of `token_id` in `utxos` to `dest`, together with all
+/// sats of `utxos` without tokens.
+///
+/// Mint batons and utxos of other tokens are left untouched, so nothing is burned. Fails if
+/// `utxos` hold none of `token_id`.
+pub fn build_token_sweep(
+    utxos: &[SlpUtxo],
+    token_id: &TokenId,
+    token_type: SlpTokenType,
+    dest: &Script,
+    fee_rate: FeeRate,
+    network: Network,
+) -> Result<UnhashedTx, SlpError> {
+    let balance = sum_token_balance(utxos, token_id)?;
+    if balance == SlpAmount::ZERO {
+        return Err(SlpError::NoTokensToSweep(token_id.clone()));
+    }
+    let opreturn = send_opreturn(token_id, token_type, &[balance])?;
+    let inputs = utxos
+        .iter()
+        .filter(|utxo| match &utxo.token_id {
+            Some(utxo_token_id) => utxo_token_id == token_id && !utxo.token.is_mint_baton,
+            None => true,
+        })
+        .map(|utxo| &utxo.utxo)
+        .collect();
+    let opreturn_output = TxOutput {
+        value: 0,
+        script: opreturn,
+    };
+    build_sweep_tx(inputs, vec![opreturn_output], dest, fee_rate, network)
+}
+
+fn build_sweep_tx(
+    utxos: Vec<&Utxo>,
+    mut outputs: Vec<TxOutput>,
+    dest: &Script,
+    fee_rate: FeeRate,
+    network: Network,
+) -> Result<UnhashedTx, SlpError> {
+    if utxos.is_empty() {
+        return Err(SlpError::NothingToSweep);
+    }
+    let total_value = Satoshi::checked_sum(utxos.iter().map(|utxo| Satoshi::new(utxo.value)))
+        .ok_or(SlpError::SatsOverflow)?;
+    let inputs = utxos
+        .iter()
+        .map(|utxo| TxInput {
+            prev_out: utxo.outpoint.clone(),
+            script: Script::default(),
+            sequence: SequenceNo::finalized(),
+            sign_data: Some(SignData::new(vec![
+                SignField::Value(utxo.value),
+                SignField::OutputScript(utxo.script.clone()),
+            ])),
+        })
+        .collect::<Vec<_>>();
+    let num_inputs = inputs.len();
+    outputs.push(TxOutput {
+        value: 0,
+        script: dest.clone(),
+    });
+    let mut tx = UnhashedTx {
+        version: 1,
+        inputs,
+        outputs,
+        lock_time: 0,
+    };
+    // Inputs are still empty, add the estimated size of their input scripts
+    let empty_input_size = TxInput::default().ser().len();
+    let tx_size = tx.ser().len() + num_inputs * (P2PKH_INPUT_SIZE - empty_input_size);
+    let fee = fee_rate
+        .checked_fee_for_size(tx_size)
+        .ok_or(SlpError::SatsOverflow)?;
+    let sweep_output = tx.outputs.last_mut().unwrap();
+    sweep_output.value = total_value
+        .checked_sub(Satoshi::new(fee))
+        .ok_or(SlpError::SatsOverflow)?
+        .as_i64();
+    if sweep_output.is_dust(network) {
+        return Err(SlpError::SweepOutputDust {
+            value: sweep_output.value,
+        });
+    }
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{
+        BitcoinCode, FeeRate, Network, Script, Sha256d, ShaRmd160, SignData, SignField,
+    };
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        build_sweep, build_token_sweep, send_opreturn,
+        test_utils::{token_utxo, utxo},
+        SlpAmount, SlpError, SlpToken, SlpTokenType, SlpUtxo, TokenId,
+    };
+
+    #[test]
+    fn test_build_sweep() -> Result<(), SlpError> {
+        let dest = Script::p2pkh(&ShaRmd160::new([3; 20]));
+        let utxos = vec![utxo(0, 10_000), utxo(1, 5_000)];
+        let tx = build_sweep(&utxos, &dest, FeeRate::per_kb(1000), Network::XEC)?;
+        assert_eq!(tx.inputs.len(), 2);
+        assert_eq!(tx.inputs[1].prev_out, utxos[1].outpoint);
+        assert_eq!(
+            tx.inputs[1].sign_data,
+            Some(SignData::new(vec![
+                SignField::Value(5_000),
+                SignField::OutputScript(utxos[1].script.clone()),
+            ])),
+        );
+        assert_eq!(tx.outputs.len(), 1);
+        assert_eq!(tx.outputs[0].script, dest);
+        // 10 bytes overhead, 2 * 141 bytes inputs, 34 bytes output
+        assert_eq!(tx.ser().len() + 2 * 100, 326);
+        assert_eq!(tx.outputs[0].value, 15_000 - 326);
+
+        assert_eq!(
+            build_sweep(&[utxo(0, 700)], &dest, FeeRate::per_kb(1000), Network::XEC),
+            Err(SlpError::SweepOutputDust { value: 700 - 185 }),
+        );
+        assert_eq!(
+            build_sweep(&[], &dest, FeeRate::per_kb(1000), Network::XEC),
+            Err(SlpError::NothingToSweep),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_token_sweep() -> Result<(), SlpError> {
+        let dest = Script::p2pkh(&ShaRmd160::new([3; 20]));
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+        let utxos = vec![
+            token_utxo(0, SlpToken::amount(20), Some(&token_id)),
+            token_utxo(1, SlpToken::MINT_BATON, Some(&token_id)),
+            token_utxo(2, SlpToken::amount(7), Some(&other_token_id)),
+            SlpUtxo {
+                utxo: utxo(3, 10_000),
+                ..Default::default()
+            },
+            token_utxo(4, SlpToken::amount(5), Some(&token_id)),
+        ];
+        let tx = build_token_sweep(
+            &utxos,
+            &token_id,
+            SlpTokenType::Fungible,
+            &dest,
+            FeeRate::per_kb(1000),
+            Network::XEC,
+        )?;
+        assert_eq!(
+            tx.inputs
+                .iter()
+                .map(|input| input.prev_out.out_idx)
+                .collect::<Vec<_>>(),
+            vec![0, 3, 4],
+        );
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(
+            tx.outputs[0].script,
+            send_opreturn(&token_id, SlpTokenType::Fungible, &[SlpAmount::new(25)])?,
+        );
+        assert_eq!(tx.outputs[0].value, 0);
+        assert_eq!(tx.outputs[1].script, dest);
+        let tx_size = tx.ser().len() as i64 + 3 * 100;
+        assert_eq!(tx.outputs[1].value, 546 + 10_000 + 546 - tx_size);
+
+        assert_eq!(
+            build_token_sweep(
+                &utxos[1..4],
+                &token_id,
+                SlpTokenType::Fungible,
+                &dest,
+                FeeRate::per_kb(1000),
+                Network::XEC,
+            ),
+            Err(SlpError::NoTokensToSweep(token_id.clone())),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_sweep_overflow() {
+        let dest = Script::p2pkh(&ShaRmd160::new([3; 20]));
+        assert_eq!(
+            build_sweep(
+                &[utxo(0, i64::MAX), utxo(1, 1)],
+                &dest,
+                FeeRate::per_kb(1000),
+                Network::XEC,
+            ),
+            Err(SlpError::SatsOverflow),
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! Fixtures shared by the unit tests of this crate.
+
+use bitcoinsuite_core::{OutPoint, Script, Sha256d, ShaRmd160, Utxo};
+
+use crate::{SlpToken, SlpUtxo, TokenId};
+
+/// Utxo of output `out_idx` of the same tx, to a P2PKH script.
+pub fn utxo(out_idx: u32, value: i64) -> Utxo {
+    Utxo {
+        outpoint: OutPoint {
+            txid: Sha256d::new([1; 32]),
+            out_idx,
+        },
+        script: Script::p2pkh(&ShaRmd160::new([2; 20])),
+        value,
+    }
+}
+
+/// Dust utxo like [`utxo`] holding `token` of `token_id`, if any.
+pub fn token_utxo(out_idx: u32, token: SlpToken, token_id: Option<&TokenId>) -> SlpUtxo {
+    SlpUtxo {
+        utxo: utxo(out_idx, 546),
+        token,
+        token_id: token_id.cloned(),
+    }
+}
@@ -0,0 +1,519 @@
+use std::{collections::HashMap, future::Future, pin::Pin, time::Duration};
+
+use async_trait::async_trait;
+use bitcoinsuite_core::{CashAddress, Sha256d};
+use bitcoinsuite_error::{Report, Result};
+use futures::Stream;
+use thiserror::Error;
+
+use crate::{
+    AddressEvent, BlockEvent, BlockId, MempoolPackageInfo, NodeStatus, SlpError, SlpGenesisInfo,
+    SlpNodeInterface, SlpTx, SlpUtxo, TokenId, TokenMetadata,
+};
+
+/// Deadlines for each method of a [`TimeoutNode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    /// Deadline for `submit_tx` and `submit_and_parse`.
+    pub submit_tx: Duration,
+    pub check_tx: Duration,
+    /// Deadline for `get_token_metadata` and `token_genesis_info`.
+    pub get_token_metadata: Duration,
+    /// Deadline for opening the address and block streams; waiting on a stream has no
+    /// deadline.
+    pub address_tx_stream: Duration,
+    /// Deadline for `address_utxos` and `address_history`.
+    pub address_utxos: Duration,
+    /// Deadline for `node_status` and `ping`.
+    pub node_status: Duration,
+    pub mempool_ancestors: Duration,
+    pub txs_in_block: Duration,
+    pub get_tx: Duration,
+    /// How often a call that timed out or failed with a transient error is retried. An
+    /// [`SlpError`] is the node's answer to the request and never retried. `submit_tx`,
+    /// `submit_and_parse` and `ping` are never retried.
+    pub num_retries: usize,
+    /// Delay before each retry.
+    pub retry_delay: Duration,
+}
+
+/// Wraps an [`SlpNodeInterface`] so every call fails after a deadline instead of hanging.
+pub struct TimeoutNode<N> {
+    node: N,
+    config: TimeoutConfig,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TimeoutNodeError {
+    #[error("{method} timed out after {timeout:?}")]
+    Timeout {
+        method: &'static str,
+        timeout: Duration,
+    },
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        TimeoutConfig {
+            submit_tx: Duration::from_secs(10),
+            check_tx: Duration::from_secs(10),
+            get_token_metadata: Duration::from_secs(10),
+            address_tx_stream: Duration::from_secs(10),
+            address_utxos: Duration::from_secs(10),
+            node_status: Duration::from_secs(10),
+            mempool_ancestors: Duration::from_secs(10),
+            txs_in_block: Duration::from_secs(10),
+            get_tx: Duration::from_secs(10),
+            num_retries: 0,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl<N: SlpNodeInterface> TimeoutNode<N> {
+    pub fn new(node: N, config: TimeoutConfig) -> Self {
+        TimeoutNode { node, config }
+    }
+
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    pub fn config(&self) -> &TimeoutConfig {
+        &self.config
+    }
+
+    async fn call<T, Fut>(
+        &self,
+        method: &'static str,
+        timeout: Duration,
+        num_retries: usize,
+        f: impl Fn() -> Fut,
+    ) -> Result<T>
+    where
+        Fut: Future<Output = Result<T>>,
+    {
+        for _ in 0..num_retries {
+            match tokio::time::timeout(timeout, f()).await {
+                Ok(Ok(result)) => return Ok(result),
+                Ok(Err(err)) if !is_transient(&err) => return Err(err),
+                _ => {}
+            }
+            tokio::time::sleep(self.config.retry_delay).await;
+        }
+        match tokio::time::timeout(timeout, f()).await {
+            Ok(result) => result,
+            Err(_) => Err(TimeoutNodeError::Timeout { method, timeout }.into()),
+        }
+    }
+}
+
+/// Whether retrying the call that failed with `err` may succeed; errors like a dropped
+/// connection are transient, while an [`SlpError`] would be returned again.
+fn is_transient(err: &Report) -> bool {
+    !err.chain().any(|err| err.is::<SlpError>())
+}
+
+#[async_trait]
+impl<N: SlpNodeInterface> SlpNodeInterface for TimeoutNode<N> {
+    async fn submit_tx(&self, raw_tx: Vec<u8>) -> Result<Sha256d> {
+        let timeout = self.config.submit_tx;
+        match tokio::time::timeout(timeout, self.node.submit_tx(raw_tx)).await {
+            Ok(result) => result,
+            Err(_) => Err(TimeoutNodeError::Timeout {
+                method: "submit_tx",
+                timeout,
+            }
+            .into()),
+        }
+    }
+
+    async fn check_tx(&self, raw_tx: Vec<u8>) -> Result<SlpTx> {
+        self.call(
+            "check_tx",
+            self.config.check_tx,
+            self.config.num_retries,
+            || self.node.check_tx(raw_tx.clone()),
+        )
+        .await
+    }
+
+    async fn submit_and_parse(&self, raw_tx: Vec<u8>) -> Result<SlpTx> {
+        self.call("submit_and_parse", self.config.submit_tx, 0, || {
+            self.node.submit_and_parse(raw_tx.clone())
+        })
+        .await
+    }
+
+    async fn get_token_metadata(
+        &self,
+        token_ids: &[TokenId],
+    ) -> Result<HashMap<TokenId, TokenMetadata>> {
+        self.call(
+            "get_token_metadata",
+            self.config.get_token_metadata,
+            self.config.num_retries,
+            || self.node.get_token_metadata(token_ids),
+        )
+        .await
+    }
+
+    async fn token_genesis_info(&self, token_id: &TokenId) -> Result<SlpGenesisInfo> {
+        self.call(
+            "token_genesis_info",
+            self.config.get_token_metadata,
+            self.config.num_retries,
+            || self.node.token_genesis_info(token_id),
+        )
+        .await
+    }
+
+    async fn address_tx_stream(
+        &self,
+        address: &CashAddress,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<SlpTx>> + Send>>> {
+        self.call(
+            "address_tx_stream",
+            self.config.address_tx_stream,
+            self.config.num_retries,
+            || self.node.address_tx_stream(address),
+        )
+        .await
+    }
+
+    async fn address_event_stream(
+        &self,
+        address: &CashAddress,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<AddressEvent>> + Send>>> {
+        self.call(
+            "address_event_stream",
+            self.config.address_tx_stream,
+            self.config.num_retries,
+            || self.node.address_event_stream(address),
+        )
+        .await
+    }
+
+    async fn address_utxos(&self, address: &CashAddress) -> Result<Vec<SlpUtxo>> {
+        self.call(
+            "address_utxos",
+            self.config.address_utxos,
+            self.config.num_retries,
+            || self.node.address_utxos(address),
+        )
+        .await
+    }
+
+    async fn address_history(&self, address: &CashAddress) -> Result<Vec<SlpTx>> {
+        self.call(
+            "address_history",
+            self.config.address_utxos,
+            self.config.num_retries,
+            || self.node.address_history(address),
+        )
+        .await
+    }
+
+    async fn node_status(&self) -> Result<NodeStatus> {
+        self.call(
+            "node_status",
+            self.config.node_status,
+            self.config.num_retries,
+            || self.node.node_status(),
+        )
+        .await
+    }
+
+    async fn txs_in_block(&self, block: &BlockId) -> Result<Vec<SlpTx>> {
+        self.call(
+            "txs_in_block",
+            self.config.txs_in_block,
+            self.config.num_retries,
+            || self.node.txs_in_block(block),
+        )
+        .await
+    }
+
+    async fn get_tx(&self, txid: &Sha256d) -> Result<SlpTx> {
+        self.call(
+            "get_tx",
+            self.config.get_tx,
+            self.config.num_retries,
+            || self.node.get_tx(txid),
+        )
+        .await
+    }
+
+    async fn confirmed_tx_stream(
+        &self,
+        from_height: i32,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<BlockEvent>> + Send>>> {
+        self.call(
+            "confirmed_tx_stream",
+            self.config.address_tx_stream,
+            self.config.num_retries,
+            || self.node.confirmed_tx_stream(from_height),
+        )
+        .await
+    }
+
+    async fn mempool_ancestors(&self, txid: &Sha256d) -> Result<MempoolPackageInfo> {
+        self.call(
+            "mempool_ancestors",
+            self.config.mempool_ancestors,
+            self.config.num_retries,
+            || self.node.mempool_ancestors(txid),
+        )
+        .await
+    }
+
+    async fn ping(&self) -> Result<Duration> {
+        // A probe should report a hanging node right away
+        self.call("ping", self.config.node_status, 0, || self.node.ping())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        pin::Pin,
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use bitcoinsuite_core::{CashAddress, Sha256d};
+    use bitcoinsuite_error::{bail, Result};
+    use futures::Stream;
+
+    use crate::{
+        BlockEvent, BlockId, MempoolPackageInfo, NodeStatus, SlpError, SlpGenesisInfo,
+        SlpNodeInterface, SlpTx, SlpUtxo, TimeoutConfig, TimeoutNode, TimeoutNodeError, TokenId,
+        TokenMetadata,
+    };
+
+    /// Node that hangs for the first `num_slow_calls` calls, then fails the next
+    /// `num_failing_calls` calls with a transient error
+    #[derive(Default)]
+    struct SlowNode {
+        num_slow_calls: usize,
+        num_failing_calls: usize,
+        num_calls: AtomicUsize,
+    }
+
+    impl SlowNode {
+        async fn maybe_hang(&self) -> Result<()> {
+            let call_idx = self.num_calls.fetch_add(1, Ordering::SeqCst);
+            if call_idx < self.num_slow_calls {
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+            } else if call_idx < self.num_slow_calls + self.num_failing_calls {
+                bail!("Connection reset");
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl SlpNodeInterface for SlowNode {
+        async fn submit_tx(&self, _: Vec<u8>) -> Result<Sha256d> {
+            self.maybe_hang().await?;
+            Ok(Sha256d::default())
+        }
+
+        async fn check_tx(&self, _: Vec<u8>) -> Result<SlpTx> {
+            self.maybe_hang().await?;
+            Ok(SlpTx::new(Default::default(), None, vec![]))
+        }
+
+        async fn get_token_metadata(
+            &self,
+            _: &[TokenId],
+        ) -> Result<HashMap<TokenId, TokenMetadata>> {
+            self.maybe_hang().await?;
+            Ok(HashMap::new())
+        }
+
+        async fn token_genesis_info(&self, token_id: &TokenId) -> Result<SlpGenesisInfo> {
+            self.maybe_hang().await?;
+            Err(SlpError::UnknownToken(token_id.clone()).into())
+        }
+
+        async fn address_tx_stream(
+            &self,
+            _: &CashAddress,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<SlpTx>> + Send>>> {
+            self.maybe_hang().await?;
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn address_utxos(&self, _: &CashAddress) -> Result<Vec<SlpUtxo>> {
+            self.maybe_hang().await?;
+            Ok(vec![])
+        }
+
+        async fn node_status(&self) -> Result<NodeStatus> {
+            self.maybe_hang().await?;
+            Ok(NodeStatus::default())
+        }
+
+        async fn txs_in_block(&self, block: &BlockId) -> Result<Vec<SlpTx>> {
+            self.maybe_hang().await?;
+            Err(SlpError::UnknownBlock(block.clone()).into())
+        }
+
+        async fn get_tx(&self, txid: &Sha256d) -> Result<SlpTx> {
+            self.maybe_hang().await?;
+            Err(SlpError::UnknownTx(txid.clone()).into())
+        }
+
+        async fn confirmed_tx_stream(
+            &self,
+            _: i32,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<BlockEvent>> + Send>>> {
+            self.maybe_hang().await?;
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn mempool_ancestors(&self, _: &Sha256d) -> Result<MempoolPackageInfo> {
+            self.maybe_hang().await?;
+            Ok(MempoolPackageInfo::default())
+        }
+    }
+
+    fn config(num_retries: usize) -> TimeoutConfig {
+        TimeoutConfig {
+            node_status: Duration::from_millis(50),
+            submit_tx: Duration::from_millis(50),
+            get_tx: Duration::from_millis(50),
+            num_retries,
+            retry_delay: Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_node() -> Result<()> {
+        let node = TimeoutNode::new(SlowNode::default(), config(0));
+        assert_eq!(node.node_status().await?, NodeStatus::default());
+
+        let node = TimeoutNode::new(
+            SlowNode {
+                num_slow_calls: 1,
+                ..Default::default()
+            },
+            config(0),
+        );
+        let err = node.node_status().await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TimeoutNodeError>(),
+            Some(&TimeoutNodeError::Timeout {
+                method: "node_status",
+                timeout: Duration::from_millis(50),
+            }),
+        );
+        assert_eq!(node.node_status().await?, NodeStatus::default());
+
+        // get_tx has its own deadline
+        let node = TimeoutNode::new(
+            SlowNode {
+                num_slow_calls: 1,
+                ..Default::default()
+            },
+            config(0),
+        );
+        let err = node.get_tx(&Sha256d::default()).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TimeoutNodeError>(),
+            Some(&TimeoutNodeError::Timeout {
+                method: "get_tx",
+                timeout: Duration::from_millis(50),
+            }),
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeout_node_retry() -> Result<()> {
+        let node = TimeoutNode::new(
+            SlowNode {
+                num_slow_calls: 2,
+                ..Default::default()
+            },
+            config(2),
+        );
+        assert_eq!(node.node_status().await?, NodeStatus::default());
+        assert_eq!(node.node().num_calls.load(Ordering::SeqCst), 3);
+
+        // Transient errors are retried as well
+        let node = TimeoutNode::new(
+            SlowNode {
+                num_slow_calls: 1,
+                num_failing_calls: 1,
+                ..Default::default()
+            },
+            config(2),
+        );
+        assert_eq!(node.node_status().await?, NodeStatus::default());
+        assert_eq!(node.node().num_calls.load(Ordering::SeqCst), 3);
+
+        // An SlpError is the node's answer and not retried
+        let node = TimeoutNode::new(SlowNode::default(), config(2));
+        let err = node.get_tx(&Sha256d::default()).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<SlpError>(),
+            Some(&SlpError::UnknownTx(Sha256d::default())),
+        );
+        assert_eq!(node.node().num_calls.load(Ordering::SeqCst), 1);
+
+        // submit_tx is never retried
+        let node = TimeoutNode::new(
+            SlowNode {
+                num_slow_calls: 1,
+                ..Default::default()
+            },
+            config(2),
+        );
+        assert!(node.submit_tx(vec![]).await.is_err());
+        assert_eq!(node.node().num_calls.load(Ordering::SeqCst), 1);
+
+        // Neither is submit_and_parse, which hangs in the node's check_tx
+        let node = TimeoutNode::new(
+            SlowNode {
+                num_slow_calls: 1,
+                ..Default::default()
+            },
+            config(2),
+        );
+        let err = node.submit_and_parse(vec![]).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TimeoutNodeError>(),
+            Some(&TimeoutNodeError::Timeout {
+                method: "submit_and_parse",
+                timeout: Duration::from_millis(50),
+            }),
+        );
+        assert_eq!(node.node().num_calls.load(Ordering::SeqCst), 1);
+
+        // Neither is ping
+        let node = TimeoutNode::new(
+            SlowNode {
+                num_slow_calls: 1,
+                ..Default::default()
+            },
+            config(2),
+        );
+        let err = node.ping().await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TimeoutNodeError>(),
+            Some(&TimeoutNodeError::Timeout {
+                method: "ping",
+                timeout: Duration::from_millis(50),
+            }),
+        );
+        assert_eq!(node.node().num_calls.load(Ordering::SeqCst), 1);
+        assert!(node.ping().await? < Duration::from_millis(50));
+        Ok(())
+    }
+}
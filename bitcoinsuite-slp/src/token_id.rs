@@ -1,8 +1,10 @@
 use std::fmt::{Debug, Display};
 
 use bitcoinsuite_core::{Hashed, Result, Sha256d};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Clone, Hash, PartialEq, Eq)]
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TokenId {
     txid: Sha256d,
     token_id_be: [u8; 32],
@@ -54,3 +56,43 @@ impl Display for TokenId {
         <Sha256d as Display>::fmt(&self.txid, f)
     }
 }
+
+#[cfg(feature = "serde")]
+impl Serialize for TokenId {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.txid.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for TokenId {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(TokenId::new(Sha256d::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::Result;
+
+    use crate::TokenId;
+
+    #[test]
+    fn test_token_id_fmt() -> Result<()> {
+        let token_id_hex = "0daf200e3418f2df1158efef36fbb507f12928f1fdcf3543703e64e75a4a9073";
+        let token_id = TokenId::from_token_id_hex(token_id_hex)?;
+        assert_eq!(token_id.to_string(), token_id_hex);
+        assert_eq!(
+            format!("{:?}", token_id),
+            format!("TokenId({})", token_id_hex),
+        );
+        assert_eq!(token_id.as_slice_be()[0], 0x0d);
+        Ok(())
+    }
+}
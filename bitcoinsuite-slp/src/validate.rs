@@ -1,6 +1,8 @@
+use bitcoinsuite_core::{Network, Sha256d, UnhashedTx, DEFAULT_MAX_OP_RETURN_BYTES};
+
 use crate::{
-    SlpAmount, SlpBurn, SlpError, SlpParseData, SlpToken, SlpTokenType, SlpTxData, SlpTxType,
-    TokenId,
+    parse::parse_slp_opreturn, SlpAmount, SlpBurn, SlpError, SlpParseData, SlpToken, SlpTokenType,
+    SlpTxData, SlpTxType, TokenId,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,6 +19,40 @@ pub struct SlpSpentOutput {
     pub group_token_id: Option<Box<TokenId>>,
 }
 
+impl SlpValidTxData {
+    /// Mark the burns of a SEND tx as intended, which must burn exactly `amount` of its token.
+    ///
+    /// Unlike BURN txs, SEND txs can't declare a burn, so this is for the builder of the tx to
+    /// call, e.g. for a tx keeping some of the tokens built with [`build_slp_burn`]. Burns of
+    /// other tokens and of mint batons are left unintended.
+    ///
+    /// [`build_slp_burn`]: crate::build_slp_burn
+    pub fn mark_intended_send_burns(&mut self, amount: SlpAmount) -> Result<(), SlpError> {
+        let token_id = &self.slp_tx_data.token_id;
+        let is_send = self.slp_tx_data.slp_tx_type == SlpTxType::Send;
+        let mut send_burns = self
+            .slp_burns
+            .iter_mut()
+            .flatten()
+            .filter(|burn| is_send && &burn.token_id == token_id && !burn.token.is_mint_baton)
+            .collect::<Vec<_>>();
+        let actual = send_burns
+            .iter()
+            .map(|burn| burn.token.amount)
+            .sum::<SlpAmount>();
+        if actual != amount {
+            return Err(SlpError::WrongBurnInvalidAmount {
+                expected: amount,
+                actual,
+            });
+        }
+        for burn in &mut send_burns {
+            burn.is_intended = true;
+        }
+        Ok(())
+    }
+}
+
 pub fn validate_slp_tx(
     parse_data: SlpParseData,
     spent_outputs: &[Option<&SlpSpentOutput>],
@@ -33,21 +69,14 @@ pub fn validate_slp_tx(
                         slp_burns.push(Some(Box::new(SlpBurn {
                             token: spent_output.token,
                             token_id: spent_output.token_id.clone(),
+                            is_intended: false,
                         })));
                     }
                     None => slp_burns.push(None),
                 }
             }
             if parse_data.slp_token_type == SlpTokenType::Nft1Child {
-                let spent_output = spent_outputs
-                    .get(0)
-                    .and_then(|x| x.as_ref())
-                    .ok_or(SlpError::HasNoNft1Group)?;
-                if spent_output.token_type != SlpTokenType::Nft1Group
-                    || spent_output.token.amount == SlpAmount::default()
-                {
-                    return Err(SlpError::HasNoNft1Group);
-                }
+                let spent_output = nft1_group_input(spent_outputs)?;
                 input_tokens[0] = spent_output.token;
                 slp_burns[0] = None;
                 group_token_id = Some(Box::new(spent_output.token_id.clone()));
@@ -71,6 +100,7 @@ pub fn validate_slp_tx(
                             slp_burns.push(Some(Box::new(SlpBurn {
                                 token: spent_output.token,
                                 token_id: spent_output.token_id.clone(),
+                                is_intended: false,
                             })));
                             input_tokens.push(SlpToken::EMPTY);
                         }
@@ -118,6 +148,7 @@ pub fn validate_slp_tx(
                                         is_mint_baton: false,
                                     },
                                     token_id: spent_output.token_id.clone(),
+                                    is_intended: false,
                                 })));
                             } else {
                                 slp_burns.push(None);
@@ -127,6 +158,7 @@ pub fn validate_slp_tx(
                             slp_burns.push(Some(Box::new(SlpBurn {
                                 token: spent_output.token,
                                 token_id: spent_output.token_id.clone(),
+                                is_intended: false,
                             })));
                             input_tokens.push(SlpToken::EMPTY);
                         }
@@ -152,6 +184,7 @@ pub fn validate_slp_tx(
                         slp_burns.push(Some(Box::new(SlpBurn {
                             token: spent_output.token,
                             token_id: spent_output.token_id.clone(),
+                            is_intended: false,
                         })));
                     }
                     None => slp_burns.push(None),
@@ -162,14 +195,13 @@ pub fn validate_slp_tx(
             let expected = SlpAmount::new(expected_burn.into());
             let mut actual = SlpAmount::ZERO;
             for burn in spent_outputs.iter() {
-                slp_burns.push(None);
                 let burn = match burn {
-                    Some(burn) => burn,
-                    None => continue,
+                    Some(burn) if burn.token != SlpToken::EMPTY => burn,
+                    _ => {
+                        slp_burns.push(None);
+                        continue;
+                    }
                 };
-                if burn.token == SlpToken::EMPTY {
-                    continue;
-                }
                 if burn.token_id != parse_data.token_id {
                     return Err(SlpError::WrongBurnTokenId);
                 }
@@ -178,6 +210,11 @@ pub fn validate_slp_tx(
                 }
                 actual += burn.token.amount;
                 input_tokens.push(burn.token);
+                slp_burns.push(Some(Box::new(SlpBurn {
+                    token: burn.token,
+                    token_id: burn.token_id.clone(),
+                    is_intended: true,
+                })));
             }
             if expected != actual {
                 return Err(SlpError::WrongBurnInvalidAmount { expected, actual });
@@ -197,17 +234,209 @@ pub fn validate_slp_tx(
     })
 }
 
+/// Check that `parse_data` is a valid NFT1 child GENESIS consuming a token of the NFT1 group
+/// `group_token_id` at input 0: the group token must have a non-zero amount, and the child
+/// must have an initial quantity of 1, 0 decimals and no mint baton.
+/// [`validate_slp_tx`] only checks for a group token at input 0, as the parser already
+/// enforces the rest.
+pub fn validate_nft1_child_genesis(
+    parse_data: &SlpParseData,
+    spent_outputs: &[Option<&SlpSpentOutput>],
+    group_token_id: &TokenId,
+) -> Result<(), SlpError> {
+    let genesis_info = match (&parse_data.slp_token_type, &parse_data.slp_tx_type) {
+        (SlpTokenType::Nft1Child, SlpTxType::Genesis(genesis_info)) => genesis_info,
+        _ => return Err(SlpError::NotNft1ChildGenesis),
+    };
+    let group_input = nft1_group_input(spent_outputs)?;
+    if &group_input.token_id != group_token_id {
+        return Err(SlpError::WrongNft1Group {
+            expected: group_token_id.clone(),
+            actual: group_input.token_id.clone(),
+        });
+    }
+    if parse_data
+        .output_tokens
+        .iter()
+        .any(|token| token.is_mint_baton)
+    {
+        return Err(SlpError::Nft1ChildCannotHaveMintBaton);
+    }
+    let initial_quantity = parse_data
+        .output_tokens
+        .iter()
+        .map(|token| token.amount)
+        .sum::<SlpAmount>();
+    if initial_quantity != SlpAmount::new(1) {
+        return Err(SlpError::Nft1ChildInvalidInitialQuantity {
+            actual: initial_quantity,
+        });
+    }
+    if genesis_info.decimals != 0 {
+        return Err(SlpError::Nft1ChildInvalidDecimals {
+            actual: genesis_info.decimals,
+        });
+    }
+    Ok(())
+}
+
+/// NFT1 group token an NFT1 child GENESIS consumes, which must be at input 0.
+fn nft1_group_input<'a>(
+    spent_outputs: &[Option<&'a SlpSpentOutput>],
+) -> Result<&'a SlpSpentOutput, SlpError> {
+    let spent_output = spent_outputs
+        .get(0)
+        .copied()
+        .flatten()
+        .ok_or(SlpError::HasNoNft1Group)?;
+    if spent_output.token_type != SlpTokenType::Nft1Group
+        || spent_output.token.amount == SlpAmount::default()
+    {
+        return Err(SlpError::HasNoNft1Group);
+    }
+    Ok(spent_output)
+}
+
+/// Check that `tx` can be relayed with its tokens intact: the OP_RETURN is a standard sized SLP
+/// OP_RETURN at output 0, no unspendable output holds any value, and every output it assigns
+/// tokens or the mint baton to exists and is not dust. Returns the first violation found.
+pub fn validate_slp_tx_outputs(tx: &UnhashedTx, network: &Network) -> Result<(), SlpError> {
+    let opreturn = &tx.outputs.first().ok_or(SlpError::NoOutputs)?.script;
+    if let Some((idx, output)) = tx
+        .outputs
+        .iter()
+        .enumerate()
+        .find(|(_, output)| output.burns_value())
+    {
+        return Err(SlpError::UnspendableOutputWithValue {
+            idx,
+            value: output.value,
+        });
+    }
+    let size = opreturn.bytecode().len();
+    if size > DEFAULT_MAX_OP_RETURN_BYTES {
+        return Err(SlpError::OpReturnTooLarge {
+            size,
+            max_size: DEFAULT_MAX_OP_RETURN_BYTES,
+        });
+    }
+    // Output indices in SLP are a single byte, so this covers every referenced output
+    let parse_data = parse_slp_opreturn(&Sha256d::default(), opreturn, 256)?;
+    for (idx, token) in parse_data.output_tokens.iter().enumerate() {
+        if *token == SlpToken::EMPTY {
+            continue;
+        }
+        let output = tx.outputs.get(idx).ok_or(SlpError::TokenOutputMissing {
+            idx,
+            num_outputs: tx.outputs.len(),
+        })?;
+        if output.is_dust(*network) {
+            return Err(SlpError::TokenOutputDust {
+                idx,
+                value: output.value,
+            });
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use bitcoinsuite_core::Sha256d;
+    use bitcoinsuite_core::{Network, Script, Sha256d, ShaRmd160, TxOutput, UnhashedTx};
     use bitcoinsuite_error::Result;
     use pretty_assertions::assert_eq;
 
     use crate::{
-        validate_slp_tx, SlpAmount, SlpBurn, SlpError, SlpGenesisInfo, SlpParseData,
-        SlpSpentOutput, SlpToken, SlpTokenType, SlpTxData, SlpTxType, SlpValidTxData, TokenId,
+        genesis_opreturn, send_opreturn, validate_nft1_child_genesis, validate_slp_tx,
+        validate_slp_tx_outputs, MintBaton, SlpAmount, SlpBurn, SlpError, SlpGenesisInfo,
+        SlpParseData, SlpSpentOutput, SlpToken, SlpTokenType, SlpTxData, SlpTxType, SlpValidTxData,
+        TokenId,
     };
 
+    #[test]
+    fn test_validate_slp_tx_outputs() -> Result<()> {
+        let token_id = TokenId::new(Sha256d::new([4; 32]));
+        let output = |value| TxOutput {
+            value,
+            script: Script::p2pkh(&ShaRmd160::new([1; 20])),
+        };
+        let amounts = [SlpAmount::new(10), SlpAmount::ZERO, SlpAmount::new(5)];
+        let send = send_opreturn(&token_id, SlpTokenType::Fungible, &amounts)?;
+        let mut tx = UnhashedTx {
+            outputs: vec![
+                TxOutput {
+                    value: 0,
+                    script: send,
+                },
+                output(546),
+                output(100),
+                output(546),
+            ],
+            ..Default::default()
+        };
+        // Output 2 holds no tokens, so it can be dust
+        validate_slp_tx_outputs(&tx, &Network::XEC)?;
+
+        tx.outputs[0].value = 546;
+        assert_eq!(
+            validate_slp_tx_outputs(&tx, &Network::XEC),
+            Err(SlpError::UnspendableOutputWithValue { idx: 0, value: 546 }),
+        );
+        tx.outputs[0].value = 0;
+
+        tx.outputs[3].value = 545;
+        assert_eq!(
+            validate_slp_tx_outputs(&tx, &Network::XEC),
+            Err(SlpError::TokenOutputDust { idx: 3, value: 545 }),
+        );
+        tx.outputs.pop();
+        assert_eq!(
+            validate_slp_tx_outputs(&tx, &Network::XEC),
+            Err(SlpError::TokenOutputMissing {
+                idx: 3,
+                num_outputs: 3,
+            }),
+        );
+
+        let genesis = genesis_opreturn(
+            &SlpGenesisInfo::default(),
+            SlpTokenType::Fungible,
+            MintBaton::at(2),
+            100,
+        )?;
+        let mut tx = UnhashedTx {
+            outputs: vec![
+                TxOutput {
+                    value: 0,
+                    script: genesis,
+                },
+                output(546),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_slp_tx_outputs(&tx, &Network::XEC),
+            Err(SlpError::TokenOutputMissing {
+                idx: 2,
+                num_outputs: 2,
+            }),
+        );
+        tx.outputs.push(output(546));
+        validate_slp_tx_outputs(&tx, &Network::XEC)?;
+
+        tx.outputs.swap(0, 1);
+        assert!(validate_slp_tx_outputs(&tx, &Network::XEC).is_err());
+        tx.outputs[0].script = Script::opreturn(&[b"SLP\0".as_ref(), &[0; 250]]);
+        assert_eq!(
+            validate_slp_tx_outputs(&tx, &Network::XEC),
+            Err(SlpError::OpReturnTooLarge {
+                size: 258,
+                max_size: 223,
+            }),
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_validate_slp_tx_genesis_failure() -> Result<()> {
         // Missing NFT1 Group token
@@ -354,6 +583,7 @@ mod tests {
                 slp_burns: vec![Some(Box::new(SlpBurn {
                     token: SlpToken::amount(1),
                     token_id: TokenId::new(Sha256d::new([1; 32])),
+                    is_intended: false,
                 }))],
             }),
         );
@@ -423,6 +653,7 @@ mod tests {
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(1),
                         token_id: TokenId::new(Sha256d::new([2; 32])),
+                        is_intended: false,
                     })),
                 ],
             }),
@@ -430,6 +661,131 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_validate_nft1_child_genesis() {
+        let group_token_id = TokenId::new(Sha256d::new([3; 32]));
+        let child_genesis = |output_tokens: Vec<SlpToken>, decimals: u32| SlpParseData {
+            output_tokens,
+            slp_token_type: SlpTokenType::Nft1Child,
+            slp_tx_type: SlpTxType::Genesis(Box::new(SlpGenesisInfo {
+                decimals,
+                ..Default::default()
+            })),
+            token_id: TokenId::new(Sha256d::new([4; 32])),
+        };
+        let group_input = |token_id: &TokenId, token_type, amount| SlpSpentOutput {
+            token_id: token_id.clone(),
+            token_type,
+            token: SlpToken::amount(amount),
+            group_token_id: None,
+        };
+        let valid_genesis = child_genesis(vec![SlpToken::EMPTY, SlpToken::amount(1)], 0);
+        let valid_input = group_input(&group_token_id, SlpTokenType::Nft1Group, 1);
+
+        assert_eq!(
+            validate_nft1_child_genesis(&valid_genesis, &[Some(&valid_input)], &group_token_id),
+            Ok(()),
+        );
+        assert_eq!(
+            validate_nft1_child_genesis(
+                &valid_genesis,
+                &[Some(&valid_input), None],
+                &group_token_id,
+            ),
+            Ok(()),
+        );
+
+        // Only NFT1 child GENESIS txs
+        for (slp_token_type, slp_tx_type) in [
+            (
+                SlpTokenType::Fungible,
+                SlpTxType::Genesis(Box::new(SlpGenesisInfo::default())),
+            ),
+            (SlpTokenType::Nft1Child, SlpTxType::Send),
+        ] {
+            let parse_data = SlpParseData {
+                slp_token_type,
+                slp_tx_type,
+                ..valid_genesis.clone()
+            };
+            assert_eq!(
+                validate_nft1_child_genesis(&parse_data, &[Some(&valid_input)], &group_token_id),
+                Err(SlpError::NotNft1ChildGenesis),
+            );
+        }
+
+        // Input 0 must be a non-zero amount of an NFT1 group token
+        let other_group_token_id = TokenId::new(Sha256d::new([5; 32]));
+        for spent_outputs in [
+            vec![],
+            vec![None, Some(&valid_input)],
+            vec![Some(&group_input(
+                &group_token_id,
+                SlpTokenType::Nft1Group,
+                0,
+            ))],
+            vec![Some(&group_input(
+                &group_token_id,
+                SlpTokenType::Fungible,
+                1,
+            ))],
+            vec![Some(&group_input(
+                &group_token_id,
+                SlpTokenType::Nft1Child,
+                1,
+            ))],
+        ] {
+            assert_eq!(
+                validate_nft1_child_genesis(&valid_genesis, &spent_outputs, &group_token_id),
+                Err(SlpError::HasNoNft1Group),
+            );
+        }
+        assert_eq!(
+            validate_nft1_child_genesis(
+                &valid_genesis,
+                &[Some(&valid_input)],
+                &other_group_token_id,
+            ),
+            Err(SlpError::WrongNft1Group {
+                expected: other_group_token_id.clone(),
+                actual: group_token_id.clone(),
+            }),
+        );
+
+        // Child must have quantity 1, no mint baton and 0 decimals
+        assert_eq!(
+            validate_nft1_child_genesis(
+                &child_genesis(
+                    vec![SlpToken::EMPTY, SlpToken::amount(1), SlpToken::MINT_BATON],
+                    0,
+                ),
+                &[Some(&valid_input)],
+                &group_token_id,
+            ),
+            Err(SlpError::Nft1ChildCannotHaveMintBaton),
+        );
+        for quantity in [0, 2] {
+            assert_eq!(
+                validate_nft1_child_genesis(
+                    &child_genesis(vec![SlpToken::EMPTY, SlpToken::amount(quantity)], 0),
+                    &[Some(&valid_input)],
+                    &group_token_id,
+                ),
+                Err(SlpError::Nft1ChildInvalidInitialQuantity {
+                    actual: SlpAmount::new(quantity),
+                }),
+            );
+        }
+        assert_eq!(
+            validate_nft1_child_genesis(
+                &child_genesis(vec![SlpToken::EMPTY, SlpToken::amount(1)], 2),
+                &[Some(&valid_input)],
+                &group_token_id,
+            ),
+            Err(SlpError::Nft1ChildInvalidDecimals { actual: 2 }),
+        );
+    }
+
     #[test]
     fn test_validate_slp_tx_mint_failure() -> Result<()> {
         // No SLP inputs
@@ -677,20 +1033,24 @@ mod tests {
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(4),
                         token_id: TokenId::new(Sha256d::new([1; 32])),
+                        is_intended: false,
                     })),
                     None,
                     Some(Box::new(SlpBurn {
                         token: SlpToken::MINT_BATON,
                         token_id: TokenId::new(Sha256d::new([2; 32])),
+                        is_intended: false,
                     })),
                     Some(Box::new(SlpBurn {
                         token: SlpToken::MINT_BATON,
                         token_id: TokenId::new(Sha256d::new([1; 32])),
+                        is_intended: false,
                     })),
                     None, // Correct MINT baton not burned
                     Some(Box::new(SlpBurn {
                         token: SlpToken::MINT_BATON,
                         token_id: TokenId::new(Sha256d::new([1; 32])),
+                        is_intended: false,
                     })),
                     None,
                 ],
@@ -769,20 +1129,24 @@ mod tests {
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(4),
                         token_id: TokenId::new(Sha256d::new([1; 32])),
+                        is_intended: false,
                     })),
                     None,
                     Some(Box::new(SlpBurn {
                         token: SlpToken::MINT_BATON,
                         token_id: TokenId::new(Sha256d::new([2; 32])),
+                        is_intended: false,
                     })),
                     None, // Correct MINT baton not burned
                     Some(Box::new(SlpBurn {
                         token: SlpToken::MINT_BATON,
                         token_id: TokenId::new(Sha256d::new([1; 32])),
+                        is_intended: false,
                     })),
                     Some(Box::new(SlpBurn {
                         token: SlpToken::MINT_BATON,
                         token_id: TokenId::new(Sha256d::new([1; 32])),
+                        is_intended: false,
                     })),
                     None,
                 ],
@@ -1129,6 +1493,7 @@ mod tests {
                     Some(Box::new(SlpBurn {
                         token: SlpToken::MINT_BATON,
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     }))
                 ],
             }),
@@ -1204,14 +1569,17 @@ mod tests {
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(0x1fff_ffff_fffe_fffd),
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     })),
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(10),
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     })),
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(10),
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     })),
                 ],
             }),
@@ -1287,14 +1655,17 @@ mod tests {
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(0x1fff_ffff_fffe_fffd),
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     })),
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(10),
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     })),
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(10),
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     })),
                 ],
             }),
@@ -1354,6 +1725,7 @@ mod tests {
                     Some(Box::new(SlpBurn {
                         token: SlpToken::amount(0xefff_ffff_ffff_0000),
                         token_id: TokenId::new(Sha256d::new([4; 32])),
+                        is_intended: false,
                     })),
                     None,
                     None,
@@ -1452,7 +1824,11 @@ mod tests {
                     token_id: TokenId::new(Sha256d::new([1; 32])),
                     group_token_id: None,
                 },
-                slp_burns: vec![None],
+                slp_burns: vec![Some(Box::new(SlpBurn {
+                    token: SlpToken::amount(10),
+                    token_id: TokenId::new(Sha256d::new([1; 32])),
+                    is_intended: true,
+                }))],
             }),
         );
         Ok(())
@@ -1,6 +1,7 @@
 use std::{fmt::Display, str::FromStr};
 
 use bitcoinsuite_core::{Network, Script};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{BitcoinSuiteSlpError, TokenId};
@@ -17,8 +18,9 @@ pub enum CoinProtocol {
     Slp(TokenId),
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum CoinProtocolType {
     Satoshis,
     Slp,
@@ -1,26 +1,95 @@
-use std::{ffi::OsString, str::FromStr};
+mod mock_slp_node;
+mod test_wallet;
+
+pub use crate::mock_slp_node::*;
+pub use crate::test_wallet::*;
+
+use std::{
+    ffi::OsString,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 use bitcoinsuite_bitcoind::{
     cli::BitcoinCli,
     instance::{BitcoindChain, BitcoindConf, BitcoindInstance},
 };
 use bitcoinsuite_core::{
-    BitcoinCode, Hashed, Network, OutPoint, Script, SequenceNo, Sha256d, TxInput, TxOutput,
-    UnhashedTx,
+    BitcoinCode, CashAddress, Hashed, Network, OutPoint, Script, SequenceNo, Sha256d, SignData,
+    TxInput, TxOutput, UnhashedTx,
 };
-use bitcoinsuite_error::Result;
+use bitcoinsuite_error::{bail, Result};
 use bitcoinsuite_test_utils::bin_folder;
 
+/// How to set up a regtest chain for tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainSetup {
+    /// Number of blocks mined to the redeem script, whose coinbase outputs are returned as utxos.
+    pub num_blocks: i32,
+    /// Number of blocks mined on top of those, 100 makes all the coinbase outputs spendable.
+    pub coinbase_maturity: i32,
+    /// If non-zero, the first coinbase output is split into this many outputs to the redeem
+    /// script, which are mined into one extra block and returned in place of it.
+    pub extra_mature_utxos: usize,
+}
+
+/// A node with a set up chain, and the utxos spendable by the redeem script.
+pub struct TestChain {
+    pub bitcoind: BitcoindInstance,
+    pub utxos: Vec<(OutPoint, i64)>,
+    mining_address: String,
+}
+
+impl Default for ChainSetup {
+    fn default() -> Self {
+        ChainSetup {
+            num_blocks: 1,
+            coinbase_maturity: 100,
+            extra_mature_utxos: 0,
+        }
+    }
+}
+
+impl TestChain {
+    /// Mines `num_blocks` blocks to the redeem script and returns their hashes.
+    pub fn mine_blocks(&self, num_blocks: u32) -> Result<Vec<Sha256d>> {
+        let hashes = self.bitcoind.cmd_json(
+            "generatetoaddress",
+            &[&num_blocks.to_string(), &self.mining_address],
+        )?;
+        hashes
+            .members()
+            .map(|hash| Ok(Sha256d::from_hex_be(hash.as_str().unwrap())?))
+            .collect()
+    }
+
+    pub fn block_height(&self) -> Result<i32> {
+        Ok(self.bitcoind.cmd_string("getblockcount", &[])?.parse()?)
+    }
+}
+
 pub async fn setup_xec_chain(
     num_generated_utxos: i32,
     redeem_script: &Script,
 ) -> Result<(BitcoindInstance, Vec<(OutPoint, i64)>)> {
+    let chain = setup_xec_chain_with(
+        ChainSetup {
+            num_blocks: num_generated_utxos,
+            ..Default::default()
+        },
+        redeem_script,
+    )
+    .await?;
+    Ok((chain.bitcoind, chain.utxos))
+}
+
+pub async fn setup_xec_chain_with(setup: ChainSetup, redeem_script: &Script) -> Result<TestChain> {
     let xec_args = vec![
         OsString::from_str("-uaclientname=Bitcoin NOT ABC").unwrap(),
         OsString::from_str("-ecash").unwrap(),
     ];
     let xec_conf = BitcoindConf::from_chain_regtest(bin_folder(), BitcoindChain::XEC, xec_args)?;
-    setup_chain(Network::XEC, xec_conf, num_generated_utxos, redeem_script).await
+    setup_chain_with(Network::XEC, xec_conf, setup, redeem_script).await
 }
 
 pub async fn setup_bch_chain(
@@ -37,12 +106,35 @@ pub fn setup_bitcoind_coins(
     num_generated_utxos: i32,
     address: &str,
     script_hex: &str,
+) -> Result<Vec<(OutPoint, i64)>> {
+    generate_coins(
+        bitcoind,
+        network,
+        num_generated_utxos,
+        100,
+        address,
+        script_hex,
+    )
+}
+
+fn generate_coins(
+    bitcoind: &BitcoinCli,
+    network: Network,
+    num_generated_utxos: i32,
+    coinbase_maturity: i32,
+    address: &str,
+    script_hex: &str,
 ) -> Result<Vec<(OutPoint, i64)>> {
     let blocks = bitcoind.cmd_json(
         "generatetoaddress",
         &[&num_generated_utxos.to_string(), address],
     )?;
-    bitcoind.cmd_json("generatetoaddress", &["100", address])?;
+    if coinbase_maturity > 0 {
+        bitcoind.cmd_json(
+            "generatetoaddress",
+            &[&coinbase_maturity.to_string(), address],
+        )?;
+    }
     let mut utxos = Vec::new();
     for block in blocks.members() {
         let block = bitcoind.cmd_json("getblock", &[block.as_str().unwrap(), "2"])?;
@@ -71,19 +163,76 @@ pub async fn setup_chain(
     num_generated_utxos: i32,
     redeem_script: &Script,
 ) -> Result<(BitcoindInstance, Vec<(OutPoint, i64)>)> {
+    let chain = setup_chain_with(
+        network,
+        bitcoind_conf,
+        ChainSetup {
+            num_blocks: num_generated_utxos,
+            ..Default::default()
+        },
+        redeem_script,
+    )
+    .await?;
+    Ok((chain.bitcoind, chain.utxos))
+}
+
+pub async fn setup_chain_with(
+    network: Network,
+    bitcoind_conf: BitcoindConf,
+    setup: ChainSetup,
+    redeem_script: &Script,
+) -> Result<TestChain> {
+    // The extra utxos are split off the first coinbase output, which needs at least one block
+    if setup.extra_mature_utxos > 0 && setup.num_blocks < 1 {
+        bail!(
+            "Can't split {} extra mature utxos off a coinbase with {} blocks",
+            setup.extra_mature_utxos,
+            setup.num_blocks,
+        );
+    }
     let mut bitcoind = BitcoindInstance::setup(bitcoind_conf)?;
     bitcoind.wait_for_ready()?;
     let script_hash = redeem_script.to_p2sh();
     let address = bitcoind.cmd_json("decodescript", &[&redeem_script.hex()])?;
-    let address = address["p2sh"].as_str().unwrap();
-    let utxos = setup_bitcoind_coins(
+    let address = address["p2sh"].as_str().unwrap().to_string();
+    let mut utxos = generate_coins(
         bitcoind.cli(),
         network,
-        num_generated_utxos,
-        address,
+        setup.num_blocks,
+        setup.coinbase_maturity,
+        &address,
         &script_hash.hex(),
     )?;
-    Ok((bitcoind, utxos))
+    if setup.extra_mature_utxos > 0 {
+        let (outpoint, value) = utxos.remove(0);
+        let output_value = (value - 10_000) / setup.extra_mature_utxos as i64;
+        let tx = build_tx(
+            outpoint,
+            redeem_script,
+            vec![
+                TxOutput {
+                    value: output_value,
+                    script: script_hash,
+                };
+                setup.extra_mature_utxos
+            ],
+        );
+        bitcoind.cmd_string("sendrawtransaction", &[&tx.ser().hex()])?;
+        bitcoind.cmd_json("generatetoaddress", &["1", &address])?;
+        let txid = tx.txid();
+        utxos.extend((0..setup.extra_mature_utxos).map(|out_idx| {
+            let outpoint = OutPoint {
+                txid: txid.clone(),
+                out_idx: out_idx as u32,
+            };
+            (outpoint, output_value)
+        }));
+    }
+    Ok(TestChain {
+        bitcoind,
+        utxos,
+        mining_address: address,
+    })
 }
 
 pub fn build_tx(outpoint: OutPoint, redeem_script: &Script, outputs: Vec<TxOutput>) -> UnhashedTx {
@@ -99,3 +248,103 @@ pub fn build_tx(outpoint: OutPoint, redeem_script: &Script, outputs: Vec<TxOutpu
         lock_time: 0,
     }
 }
+
+/// Sends `value` sats to `address` from a freshly mined coinbase and mines the tx into a block.
+/// Returns the outpoint of the funded output and its value.
+pub fn fund_address(
+    bitcoind: &BitcoindInstance,
+    network: Network,
+    address: &CashAddress,
+    value: i64,
+) -> Result<(OutPoint, i64)> {
+    // Coins are mined to an anyone-can-spend P2SH, change goes back to it
+    let redeem_script = Script::from_static_slice(&[0x51]);
+    let script_hash = redeem_script.to_p2sh();
+    let anyone_address = bitcoind.cmd_json("decodescript", &[&redeem_script.hex()])?;
+    let anyone_address = anyone_address["p2sh"].as_str().unwrap();
+    let (outpoint, coinbase_value) = setup_bitcoind_coins(
+        bitcoind.cli(),
+        network,
+        1,
+        anyone_address,
+        &script_hash.hex(),
+    )?
+    .remove(0);
+    let mut outputs = vec![TxOutput {
+        value,
+        script: address.to_script(),
+    }];
+    let change = coinbase_value - value - 10_000;
+    if change >= network.dust_amount() {
+        outputs.push(TxOutput {
+            value: change,
+            script: script_hash,
+        });
+    }
+    let tx = build_tx(outpoint, &redeem_script, outputs);
+    bitcoind.cmd_string("sendrawtransaction", &[&tx.ser().hex()])?;
+    bitcoind.cmd_json("generatetoaddress", &["1", anyone_address])?;
+    Ok((
+        OutPoint {
+            txid: tx.txid(),
+            out_idx: 0,
+        },
+        value,
+    ))
+}
+
+/// Builds a tx with empty input scripts and the given sign data attached to the inputs, ready to
+/// be signed, e.g. using a `TxBuilder`.
+pub fn build_unsigned_tx(inputs: &[(OutPoint, SignData)], outputs: Vec<TxOutput>) -> UnhashedTx {
+    UnhashedTx {
+        version: 1,
+        inputs: inputs
+            .iter()
+            .map(|(outpoint, sign_data)| TxInput {
+                prev_out: outpoint.clone(),
+                script: Script::default(),
+                sequence: SequenceNo::finalized(),
+                sign_data: Some(sign_data.clone()),
+            })
+            .collect(),
+        outputs,
+        lock_time: 0,
+    }
+}
+
+/// Mines one block and returns its height, failing if it doesn't include the tx.
+pub fn confirm_tx(bitcoind: &BitcoindInstance, txid: &Sha256d) -> Result<i32> {
+    let redeem_script = Script::from_static_slice(&[0x51]);
+    let address = bitcoind.cmd_json("decodescript", &[&redeem_script.hex()])?;
+    let address = address["p2sh"].as_str().unwrap();
+    let hashes = bitcoind.cmd_json("generatetoaddress", &["1", address])?;
+    let block = bitcoind.cmd_json("getblock", &[hashes[0].as_str().unwrap()])?;
+    let txid_hex = txid.to_hex_be();
+    if !block["tx"]
+        .members()
+        .any(|tx| tx.as_str() == Some(txid_hex.as_str()))
+    {
+        bail!("Tx {} not included in mined block", txid_hex);
+    }
+    Ok(block["height"].as_i32().unwrap())
+}
+
+/// Polls the node until the tx is in its mempool, failing after `timeout`.
+pub async fn wait_for_mempool(
+    bitcoind: &BitcoindInstance,
+    txid: &Sha256d,
+    timeout: Duration,
+) -> Result<()> {
+    let txid_hex = txid.to_hex_be();
+    let start = Instant::now();
+    loop {
+        let output = bitcoind.cmd_output("getmempoolentry", &[&txid_hex])?;
+        if output.status.success() {
+            return Ok(());
+        }
+        if start.elapsed() > timeout {
+            bail!("Tx {} not in mempool after {:?}", txid_hex, timeout);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
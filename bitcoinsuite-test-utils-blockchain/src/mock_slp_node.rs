@@ -1,59 +1,602 @@
-use std::{collections::HashMap, pin::Pin, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
-use bitcoinsuite_core::{CashAddress, Hashed, Sha256d};
-use bitcoinsuite_slp::{SlpNodeInterface, SlpTx, SlpUtxo, TokenId, TokenMetadata};
+use bitcoinsuite_core::{
+    AddressType, BitcoinCode, Bytes, CashAddress, Hashed, Net, Network, OutPoint, Script,
+    ScriptVariant, Sha256d, UnhashedTx, Utxo,
+};
+use bitcoinsuite_error::Result;
+use bitcoinsuite_slp::{
+    parse_slp_tx, validate_slp_tx, AddressEvent, BlockEvent, BlockId, NodeStatus, SlpBlock,
+    SlpBurn, SlpError, SlpGenesisInfo, SlpNodeInterface, SlpSpentOutput, SlpToken, SlpTokenType,
+    SlpTx, SlpTxType, SlpUtxo, TokenId, TokenMetadata,
+};
 use futures::{Stream, StreamExt};
-use raipay_log::Result;
 use tokio::sync::{broadcast, Mutex};
 use tokio_stream::wrappers::BroadcastStream;
 
 pub struct MockSlpNode {
+    /// Unspent outputs by address. Outputs of submitted txs are added for their P2PKH or P2SH
+    /// address; outputs with other scripts can't be spent.
     pub utxos: Arc<Mutex<HashMap<CashAddress<'static>, Vec<SlpUtxo>>>>,
-    pub address_tx_sender: broadcast::Sender<SlpTx>,
-    pub address_tx_receiver: broadcast::Receiver<SlpTx>,
+    /// Outpoints spent by submitted txs, and the txid spending them.
+    pub spent_outpoints: Arc<Mutex<HashMap<OutPoint, Sha256d>>>,
+    /// Utxos spent by each submitted tx and the address they were at, to give them back if the
+    /// tx is evicted.
+    spent_utxos: Arc<Mutex<HashMap<Sha256d, Vec<(CashAddress<'static>, SlpUtxo)>>>>,
+    pub txs: Arc<Mutex<HashMap<Sha256d, SlpTx>>>,
+    /// Txids of submitted txs which haven't been mined yet, in the order they were submitted,
+    /// see [`MockSlpNode::mine_mempool`].
+    pub mempool: Arc<Mutex<Vec<Sha256d>>>,
+    /// Hash and txids of the mined blocks, the first one being at height 1.
+    pub blocks: Arc<Mutex<Vec<(Sha256d, Vec<Sha256d>)>>>,
+    pub token_metadata: Arc<Mutex<HashMap<TokenId, TokenMetadata>>>,
+    pub node_status: Arc<Mutex<NodeStatus>>,
+    address_event_sender: broadcast::Sender<(Vec<Script>, AddressEvent)>,
+    block_sender: broadcast::Sender<BlockEvent>,
+    network: Network,
+    net: Net,
 }
 
 impl MockSlpNode {
+    /// Mock node on XEC regtest.
     pub fn new() -> Self {
-        let (address_tx_sender, address_tx_receiver) = broadcast::channel(10);
+        MockSlpNode::with_net(Network::XEC, Net::Regtest)
+    }
+
+    /// Mock node on the given network, which only accepts addresses of that network.
+    pub fn with_net(network: Network, net: Net) -> Self {
+        let (address_event_sender, _) = broadcast::channel(10);
+        let (block_sender, _) = broadcast::channel(10);
         MockSlpNode {
             utxos: Arc::new(Mutex::new(HashMap::new())),
-            address_tx_sender,
-            address_tx_receiver,
+            spent_outpoints: Arc::new(Mutex::new(HashMap::new())),
+            spent_utxos: Arc::new(Mutex::new(HashMap::new())),
+            txs: Arc::new(Mutex::new(HashMap::new())),
+            mempool: Arc::new(Mutex::new(Vec::new())),
+            blocks: Arc::new(Mutex::new(Vec::new())),
+            token_metadata: Arc::new(Mutex::new(HashMap::new())),
+            node_status: Arc::new(Mutex::new(NodeStatus::default())),
+            address_event_sender,
+            block_sender,
+            network,
+            net,
+        }
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn net(&self) -> Net {
+        self.net
+    }
+
+    fn check_address_net(&self, address: &CashAddress) -> Result<()> {
+        let expected = self.network.params(self.net).address_prefix;
+        if address.prefix() != expected {
+            return Err(SlpError::WrongAddressNet {
+                expected: expected.to_string(),
+                actual: address.prefix().to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Address of `script` on the node's net, `None` if it is neither P2PKH nor P2SH.
+    fn script_address(&self, script: &Script) -> Option<CashAddress<'static>> {
+        let prefix = self.network.params(self.net).address_prefix;
+        match script.parse_variant() {
+            ScriptVariant::P2PKH(hash) => {
+                Some(CashAddress::from_hash(prefix, AddressType::P2PKH, hash))
+            }
+            ScriptVariant::P2SH(hash) => {
+                Some(CashAddress::from_hash(prefix, AddressType::P2SH, hash))
+            }
+            _ => None,
+        }
+    }
+
+    /// Utxos spent by the inputs of the tx `txid`, failing like the node if an input spends an
+    /// output that doesn't exist or that is already spent, by another tx or an earlier input.
+    fn find_spent_utxos(
+        txid: &Sha256d,
+        tx: &UnhashedTx,
+        utxos: &HashMap<CashAddress<'static>, Vec<SlpUtxo>>,
+        spent_outpoints: &HashMap<OutPoint, Sha256d>,
+    ) -> Result<Vec<SlpUtxo>> {
+        let mut spent = Vec::with_capacity(tx.inputs.len());
+        for (input_idx, input) in tx.inputs.iter().enumerate() {
+            let outpoint = &input.prev_out;
+            let spent_by = match spent_outpoints.get(outpoint) {
+                Some(spent_by) => Some(spent_by.clone()),
+                None if tx.inputs[..input_idx]
+                    .iter()
+                    .any(|prev_input| &prev_input.prev_out == outpoint) =>
+                {
+                    Some(txid.clone())
+                }
+                None => None,
+            };
+            if let Some(spent_by) = spent_by {
+                return Err(SlpError::InputAlreadySpent {
+                    input_idx,
+                    outpoint: outpoint.clone(),
+                    spent_by,
+                }
+                .into());
+            }
+            let utxo = utxos
+                .values()
+                .flatten()
+                .find(|utxo| &utxo.utxo.outpoint == outpoint)
+                .ok_or_else(|| SlpError::InputMissing {
+                    input_idx,
+                    outpoint: outpoint.clone(),
+                })?;
+            spent.push(utxo.clone());
+        }
+        Ok(spent)
+    }
+
+    pub async fn add_utxo(&self, address: CashAddress<'static>, utxo: SlpUtxo) {
+        self.utxos
+            .lock()
+            .await
+            .entry(address)
+            .or_default()
+            .push(utxo);
+    }
+
+    /// Add a tx the node knows about; without a block height, it is treated as unconfirmed,
+    /// e.g. by [`SlpNodeInterface::mempool_ancestors`].
+    pub async fn add_tx(&self, txid: Sha256d, tx: SlpTx) {
+        self.txs.lock().await.insert(txid, tx);
+    }
+
+    /// Confirm all txs in the mempool in a new block at the tip, returning its hash.
+    pub async fn mine_mempool(&self) -> Sha256d {
+        let mut blocks = self.blocks.lock().await;
+        let txids = std::mem::take(&mut *self.mempool.lock().await);
+        let mut txs = self.txs.lock().await;
+        let height = blocks.len() as i32 + 1;
+        let prev_hash = blocks
+            .last()
+            .map(|(hash, _)| hash.clone())
+            .unwrap_or_default();
+        let mut header = prev_hash.as_slice().to_vec();
+        for txid in &txids {
+            header.extend_from_slice(txid.as_slice());
+            if let Some(slp_tx) = txs.get_mut(txid) {
+                slp_tx.set_block_height(Some(height));
+            }
+        }
+        let hash = Sha256d::digest(header.into());
+        let block = Self::slp_block(height, &hash, &txids, &txs);
+        for slp_tx in &block.txs {
+            // No subscribers is not an error for the mock
+            let _ = self.address_event_sender.send((
+                Self::touched_scripts(slp_tx),
+                AddressEvent::Confirmed {
+                    tx: slp_tx.clone(),
+                    height,
+                },
+            ));
+        }
+        blocks.push((hash.clone(), txids));
+        let mut node_status = self.node_status.lock().await;
+        node_status.best_height = height;
+        node_status.best_block_hash = hash.clone();
+        // No subscribers is not an error for the mock
+        let _ = self.block_sender.send(BlockEvent::Connected(block));
+        hash
+    }
+
+    /// Remove the mempool tx `txid` and its descendants, like the node does when a conflicting
+    /// tx is mined, giving back the utxos they spent. Fails if `txid` isn't in the mempool.
+    pub async fn evict_mempool_tx(&self, txid: &Sha256d) -> Result<()> {
+        let mut mempool = self.mempool.lock().await;
+        if !mempool.contains(txid) {
+            return Err(SlpError::UnknownTx(txid.clone()).into());
+        }
+        let mut txs = self.txs.lock().await;
+        let mut evicted = HashSet::from([txid.clone()]);
+        // Children are always submitted after their parents
+        for mempool_txid in mempool.iter() {
+            let spends_evicted = txs.get(mempool_txid).map_or(false, |slp_tx| {
+                slp_tx
+                    .tx()
+                    .inputs
+                    .iter()
+                    .any(|input| evicted.contains(&input.prev_out.txid))
+            });
+            if spends_evicted {
+                evicted.insert(mempool_txid.clone());
+            }
+        }
+        let evicted_txids = mempool
+            .iter()
+            .filter(|mempool_txid| evicted.contains(mempool_txid))
+            .cloned()
+            .collect::<Vec<_>>();
+        mempool.retain(|mempool_txid| !evicted.contains(mempool_txid));
+        let mut utxos = self.utxos.lock().await;
+        let mut spent_outpoints = self.spent_outpoints.lock().await;
+        let mut spent_utxos = self.spent_utxos.lock().await;
+        // Children first, so their parent also removes the outputs given back to it
+        for evicted_txid in evicted_txids.iter().rev() {
+            for address_utxos in utxos.values_mut() {
+                address_utxos.retain(|utxo| &utxo.utxo.outpoint.txid != evicted_txid);
+            }
+            for (address, utxo) in spent_utxos.remove(evicted_txid).unwrap_or_default() {
+                spent_outpoints.remove(&utxo.utxo.outpoint);
+                utxos.entry(address).or_default().push(utxo);
+            }
+            if let Some(slp_tx) = txs.remove(evicted_txid) {
+                let _ = self.address_event_sender.send((
+                    Self::touched_scripts(&slp_tx),
+                    AddressEvent::Removed(evicted_txid.clone()),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Disconnect the `num_blocks` blocks at the tip, like a reorg does, putting their txs
+    /// back into the mempool.
+    pub async fn disconnect_blocks(&self, num_blocks: usize) {
+        let mut blocks = self.blocks.lock().await;
+        let mut mempool = self.mempool.lock().await;
+        let mut txs = self.txs.lock().await;
+        let mut hashes = Vec::new();
+        let mut txids = Vec::new();
+        for _ in 0..num_blocks {
+            let (hash, block_txids) = match blocks.pop() {
+                Some(block) => block,
+                None => break,
+            };
+            for txid in &block_txids {
+                if let Some(slp_tx) = txs.get_mut(txid) {
+                    slp_tx.set_block_height(None);
+                }
+            }
+            hashes.push(hash);
+            txids.splice(0..0, block_txids);
+        }
+        txids.append(&mut mempool);
+        *mempool = txids;
+        let mut node_status = self.node_status.lock().await;
+        node_status.best_height = blocks.len() as i32;
+        node_status.best_block_hash = blocks
+            .last()
+            .map(|(hash, _)| hash.clone())
+            .unwrap_or_default();
+        let _ = self.block_sender.send(BlockEvent::Disconnected(hashes));
+    }
+
+    /// Scripts of the outputs `slp_tx` spends and creates, whose addresses are notified about it.
+    fn touched_scripts(slp_tx: &SlpTx) -> Vec<Script> {
+        slp_tx
+            .input_prevouts()
+            .iter()
+            .flatten()
+            .map(|utxo| utxo.script.clone())
+            .chain(
+                slp_tx
+                    .tx()
+                    .outputs
+                    .iter()
+                    .map(|output| output.script.clone()),
+            )
+            .collect()
+    }
+
+    fn slp_block(
+        height: i32,
+        hash: &Sha256d,
+        txids: &[Sha256d],
+        txs: &HashMap<Sha256d, SlpTx>,
+    ) -> SlpBlock {
+        SlpBlock {
+            height,
+            hash: hash.clone(),
+            txs: txids.iter().map(|txid| txs[txid].clone()).collect(),
         }
     }
+
+    async fn spent_output(&self, utxo: &SlpUtxo) -> Option<SlpSpentOutput> {
+        let token_id = utxo.token_id.clone()?;
+        let txs = self.txs.lock().await;
+        let prev_slp = txs
+            .get(&utxo.utxo.outpoint.txid)
+            .and_then(|prev_tx| prev_tx.slp());
+        Some(SlpSpentOutput {
+            token_id,
+            token_type: prev_slp
+                .map(|slp| slp.slp_token_type)
+                .unwrap_or(SlpTokenType::Fungible),
+            token: utxo.token,
+            group_token_id: prev_slp.and_then(|slp| slp.group_token_id.clone()),
+        })
+    }
+
+    async fn build_slp_tx(
+        &self,
+        txid: &Sha256d,
+        tx: UnhashedTx,
+        spent: &[Option<SlpUtxo>],
+    ) -> SlpTx {
+        let mut spent_outputs = Vec::with_capacity(spent.len());
+        for utxo in spent {
+            spent_outputs.push(match utxo {
+                Some(utxo) => self.spent_output(utxo).await,
+                None => None,
+            });
+        }
+        let valid_data = parse_slp_tx(txid, &tx).and_then(|parse_data| {
+            validate_slp_tx(
+                parse_data,
+                &spent_outputs.iter().map(Option::as_ref).collect::<Vec<_>>(),
+            )
+        });
+        let mut slp_tx = match valid_data {
+            Ok(valid_data) => SlpTx::new(tx, Some(valid_data.slp_tx_data), valid_data.slp_burns),
+            Err(error) => {
+                let slp_burns = spent_outputs
+                    .into_iter()
+                    .map(|spent_output| {
+                        spent_output.map(|spent_output| {
+                            Box::new(SlpBurn {
+                                token: spent_output.token,
+                                token_id: spent_output.token_id,
+                                is_intended: false,
+                            })
+                        })
+                    })
+                    .collect();
+                SlpTx::new_invalid(tx, error, slp_burns)
+            }
+        };
+        slp_tx.set_input_prevouts(
+            spent
+                .iter()
+                .map(|utxo| utxo.as_ref().map(|utxo| utxo.utxo.clone()))
+                .collect(),
+        );
+        slp_tx
+    }
+
+    /// Accept the tx into the mempool, returning it as parsed while being accepted.
+    async fn submit(&self, raw_tx: Vec<u8>) -> Result<(Sha256d, SlpTx)> {
+        let mut raw_tx: Bytes = raw_tx.into();
+        let txid = Sha256d::digest(raw_tx.clone());
+        let tx = UnhashedTx::deser(&mut raw_tx)?;
+        let spent = {
+            let mut utxos = self.utxos.lock().await;
+            let mut spent_outpoints = self.spent_outpoints.lock().await;
+            let spent = Self::find_spent_utxos(&txid, &tx, &utxos, &spent_outpoints)?;
+            let mut spent_at_addresses = Vec::with_capacity(spent.len());
+            for utxo in &spent {
+                for (address, address_utxos) in utxos.iter_mut() {
+                    if let Some(pos) = address_utxos
+                        .iter()
+                        .position(|address_utxo| address_utxo.utxo == utxo.utxo)
+                    {
+                        spent_at_addresses.push((address.clone(), address_utxos.remove(pos)));
+                    }
+                }
+                spent_outpoints.insert(utxo.utxo.outpoint.clone(), txid.clone());
+            }
+            let mut spent_utxos = self.spent_utxos.lock().await;
+            spent_utxos.insert(txid.clone(), spent_at_addresses);
+            spent.into_iter().map(Some).collect::<Vec<_>>()
+        };
+        let slp_tx = self.build_slp_tx(&txid, tx, &spent).await;
+        {
+            let mut utxos = self.utxos.lock().await;
+            let output_tokens = slp_tx
+                .slp()
+                .map(|slp| slp.output_tokens.as_slice())
+                .unwrap_or_default();
+            for (out_idx, output) in slp_tx.tx().outputs.iter().enumerate() {
+                let address = match self.script_address(&output.script) {
+                    Some(address) => address,
+                    None => continue,
+                };
+                let token = output_tokens.get(out_idx).copied().unwrap_or_default();
+                utxos.entry(address).or_default().push(SlpUtxo {
+                    utxo: Utxo {
+                        outpoint: OutPoint {
+                            txid: txid.clone(),
+                            out_idx: out_idx as u32,
+                        },
+                        script: output.script.clone(),
+                        value: output.value,
+                    },
+                    token,
+                    token_id: match token == SlpToken::EMPTY {
+                        true => None,
+                        false => slp_tx.slp().map(|slp| slp.token_id.clone()),
+                    },
+                });
+            }
+        }
+        self.add_tx(txid.clone(), slp_tx.clone()).await;
+        self.mempool.lock().await.push(txid.clone());
+        // No subscribers is not an error for the mock
+        let _ = self.address_event_sender.send((
+            Self::touched_scripts(&slp_tx),
+            AddressEvent::Mempool(slp_tx.clone()),
+        ));
+        Ok((txid, slp_tx))
+    }
+}
+
+impl Default for MockSlpNode {
+    fn default() -> Self {
+        MockSlpNode::new()
+    }
 }
 
 #[async_trait]
 impl SlpNodeInterface for MockSlpNode {
     async fn submit_tx(&self, raw_tx: Vec<u8>) -> Result<Sha256d> {
-        Ok(Sha256d::digest(raw_tx.into()))
+        let (txid, _) = self.submit(raw_tx).await?;
+        Ok(txid)
+    }
+
+    async fn submit_and_parse(&self, raw_tx: Vec<u8>) -> Result<SlpTx> {
+        let (_, slp_tx) = self.submit(raw_tx).await?;
+        Ok(slp_tx)
+    }
+
+    async fn check_tx(&self, raw_tx: Vec<u8>) -> Result<SlpTx> {
+        let mut raw_tx: Bytes = raw_tx.into();
+        let txid = Sha256d::digest(raw_tx.clone());
+        let tx = UnhashedTx::deser(&mut raw_tx)?;
+        let spent = {
+            let utxos = self.utxos.lock().await;
+            let spent_outpoints = self.spent_outpoints.lock().await;
+            Self::find_spent_utxos(&txid, &tx, &utxos, &spent_outpoints)?
+        };
+        let spent = spent.into_iter().map(Some).collect::<Vec<_>>();
+        Ok(self.build_slp_tx(&txid, tx, &spent).await)
     }
 
     async fn get_token_metadata(
         &self,
-        _token_ids: &[TokenId],
+        token_ids: &[TokenId],
     ) -> Result<HashMap<TokenId, TokenMetadata>> {
-        Ok(HashMap::new())
+        let token_metadata = self.token_metadata.lock().await;
+        Ok(token_ids
+            .iter()
+            .filter_map(|token_id| {
+                let metadata = token_metadata.get(token_id)?;
+                Some((token_id.clone(), metadata.clone()))
+            })
+            .collect())
+    }
+
+    async fn token_genesis_info(&self, token_id: &TokenId) -> Result<SlpGenesisInfo> {
+        let txs = self.txs.lock().await;
+        let slp = txs.get(token_id.hash()).and_then(|tx| tx.slp());
+        match slp.map(|slp| &slp.slp_tx_type) {
+            Some(SlpTxType::Genesis(genesis_info)) => Ok(genesis_info.as_ref().clone()),
+            _ => Err(SlpError::UnknownToken(token_id.clone()).into()),
+        }
     }
 
     async fn address_tx_stream(
         &self,
-        _address: &CashAddress,
+        address: &CashAddress,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<SlpTx>> + Send>>> {
+        self.check_address_net(address)?;
+        let events = self.address_event_stream(address).await?;
+        Ok(Box::pin(events.filter_map(|event| {
+            let result = match event {
+                Ok(AddressEvent::Mempool(slp_tx)) => Some(Ok(slp_tx)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            };
+            futures::future::ready(result)
+        })))
+    }
+
+    async fn address_event_stream(
+        &self,
+        address: &CashAddress,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<AddressEvent>> + Send>>> {
+        self.check_address_net(address)?;
+        let script = address.to_script();
         Ok(Box::pin(
-            BroadcastStream::new(self.address_tx_sender.subscribe()).map(|tx| Ok(tx?)),
+            BroadcastStream::new(self.address_event_sender.subscribe()).filter_map(move |item| {
+                let result = match item {
+                    Ok((touched_scripts, event)) => match touched_scripts.contains(&script) {
+                        true => Some(Ok(event)),
+                        false => None,
+                    },
+                    Err(err) => Some(Err(err.into())),
+                };
+                futures::future::ready(result)
+            }),
         ))
     }
 
     async fn address_utxos(&self, address: &CashAddress) -> Result<Vec<SlpUtxo>> {
+        self.check_address_net(address)?;
         Ok(self
             .utxos
             .lock()
             .await
-            .get(address)
+            .get(&address.to_owned_address())
             .cloned()
             .unwrap_or_default())
     }
+
+    async fn address_history(&self, address: &CashAddress) -> Result<Vec<SlpTx>> {
+        self.check_address_net(address)?;
+        let script = address.to_script();
+        Ok(self
+            .txs
+            .lock()
+            .await
+            .values()
+            .filter(|slp_tx| Self::touched_scripts(slp_tx).contains(&script))
+            .cloned()
+            .collect())
+    }
+
+    async fn node_status(&self) -> Result<NodeStatus> {
+        Ok(self.node_status.lock().await.clone())
+    }
+
+    async fn txs_in_block(&self, block: &BlockId) -> Result<Vec<SlpTx>> {
+        let blocks = self.blocks.lock().await;
+        let txs = self.txs.lock().await;
+        let block_txids = match block {
+            BlockId::Height(height) if *height >= 1 => blocks.get(*height as usize - 1),
+            BlockId::Height(_) => None,
+            BlockId::Hash(hash) => blocks.iter().find(|(block_hash, _)| block_hash == hash),
+        };
+        match block_txids {
+            Some((_, txids)) => Ok(txids.iter().map(|txid| txs[txid].clone()).collect()),
+            None => Err(SlpError::UnknownBlock(block.clone()).into()),
+        }
+    }
+
+    async fn get_tx(&self, txid: &Sha256d) -> Result<SlpTx> {
+        match self.txs.lock().await.get(txid) {
+            Some(tx) => Ok(tx.clone()),
+            None => Err(SlpError::UnknownTx(txid.clone()).into()),
+        }
+    }
+
+    async fn confirmed_tx_stream(
+        &self,
+        from_height: i32,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<BlockEvent>> + Send>>> {
+        let blocks = self.blocks.lock().await;
+        // Subscribe while holding the lock, so no block is missed between replay and updates
+        let receiver = self.block_sender.subscribe();
+        let txs = self.txs.lock().await;
+        let replayed = blocks
+            .iter()
+            .enumerate()
+            .map(|(idx, (hash, txids))| (idx as i32 + 1, hash, txids))
+            .filter(|&(height, _, _)| height >= from_height)
+            .map(|(height, hash, txids)| {
+                Ok(BlockEvent::Connected(Self::slp_block(
+                    height, hash, txids, &txs,
+                )))
+            })
+            .collect::<Vec<_>>();
+        Ok(Box::pin(futures::stream::iter(replayed).chain(
+            BroadcastStream::new(receiver).map(|event| -> Result<BlockEvent> { Ok(event?) }),
+        )))
+    }
 }
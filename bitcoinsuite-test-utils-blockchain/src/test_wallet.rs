@@ -0,0 +1,158 @@
+use bitcoinsuite_core::{
+    ecc::{Ecc, PubKey, SecKey},
+    BitcoinCode, CashAddress, FeeRate, Network, OutPoint, P2PKHSignatory, Script, SequenceNo,
+    Sha256d, SigHashType, SignData, SignField, TxBuilder, TxBuilderInput, TxBuilderOutput, TxInput,
+    TxOutput, UnhashedTx, Utxo, ECREGTEST,
+};
+use bitcoinsuite_ecc_secp256k1::EccSecp256k1;
+use bitcoinsuite_error::Result;
+use bitcoinsuite_slp::{
+    consts::SLP_DUST_AMOUNT, genesis_opreturn, MintBaton, SlpGenesisInfo, SlpTokenType, TokenId,
+};
+
+use crate::{fund_address, setup_xec_chain_with, ChainSetup, TestChain};
+
+/// Sats sent to the wallet's address by [`setup_slp_test_wallet`].
+pub const TEST_WALLET_INITIAL_SATS: i64 = 10_000_000;
+
+/// XEC regtest chain together with a key and its funded P2PKH address, for integration tests
+/// that need to sign their own txs.
+pub struct TestWallet {
+    pub chain: TestChain,
+    pub ecc: EccSecp256k1,
+    pub seckey: SecKey,
+    pub pubkey: PubKey,
+    pub address: CashAddress<'static>,
+    /// Sats-only utxos of `address`, updated with the txs sent by the wallet.
+    pub utxos: Vec<Utxo>,
+}
+
+/// Sets up an XEC regtest chain and a wallet with [`TEST_WALLET_INITIAL_SATS`] confirmed in one
+/// utxo.
+pub async fn setup_slp_test_wallet() -> Result<TestWallet> {
+    let chain =
+        setup_xec_chain_with(ChainSetup::default(), &Script::from_static_slice(&[0x51])).await?;
+    let ecc = EccSecp256k1::default();
+    let seckey = ecc.seckey_from_array([1; 32])?;
+    let pubkey = ecc.derive_pubkey(&seckey);
+    let address = CashAddress::p2pkh_from_pubkey(ECREGTEST, &pubkey);
+    let mut wallet = TestWallet {
+        chain,
+        ecc,
+        seckey,
+        pubkey,
+        address,
+        utxos: Vec::new(),
+    };
+    wallet.fund(TEST_WALLET_INITIAL_SATS)?;
+    Ok(wallet)
+}
+
+impl TestWallet {
+    /// Sends `sats` from a freshly mined coinbase to the wallet's address and confirms it.
+    pub fn fund(&mut self, sats: i64) -> Result<Utxo> {
+        let (outpoint, value) =
+            fund_address(&self.chain.bitcoind, Network::XEC, &self.address, sats)?;
+        let utxo = Utxo {
+            outpoint,
+            script: self.address.to_script(),
+            value,
+        };
+        self.utxos.push(utxo.clone());
+        Ok(utxo)
+    }
+
+    /// Sends `sats` to `address`, spending all of the wallet's utxos, and broadcasts the tx.
+    /// Returns its txid; the tx stays in the mempool.
+    pub fn send_to(&mut self, address: &CashAddress, sats: i64) -> Result<Sha256d> {
+        self.send_outputs(vec![TxOutput {
+            value: sats,
+            script: address.to_script(),
+        }])
+    }
+
+    /// Creates a new token with a GENESIS tx, sending `initial_quantity` to the wallet's address
+    /// at output 1 and, if `mint_baton` is set, the mint baton to it as well. The tx is
+    /// broadcast but stays in the mempool; the token ID is its txid.
+    pub fn mint_token(
+        &mut self,
+        genesis_info: &SlpGenesisInfo,
+        initial_quantity: u64,
+        mint_baton: MintBaton,
+    ) -> Result<TokenId> {
+        let opreturn = genesis_opreturn(
+            genesis_info,
+            SlpTokenType::Fungible,
+            mint_baton,
+            initial_quantity,
+        )?;
+        let token_output = TxOutput {
+            value: SLP_DUST_AMOUNT,
+            script: self.address.to_script(),
+        };
+        let mut outputs = vec![
+            TxOutput {
+                value: 0,
+                script: opreturn,
+            },
+            token_output.clone(),
+        ];
+        if let Some(vout) = mint_baton.vout {
+            // Pad with dust outputs up to the mint baton
+            outputs.resize(vout as usize, token_output.clone());
+            outputs.push(token_output);
+        }
+        let txid = self.send_outputs(outputs)?;
+        Ok(TokenId::new(txid))
+    }
+
+    /// Signs a tx spending all of the wallet's utxos to `outputs`, with the change sent back to
+    /// the wallet's address, and broadcasts it.
+    fn send_outputs(&mut self, outputs: Vec<TxOutput>) -> Result<Sha256d> {
+        let mut tx_builder = TxBuilder::from_tx(UnhashedTx {
+            version: 1,
+            outputs,
+            ..Default::default()
+        });
+        for utxo in &self.utxos {
+            tx_builder.inputs.push(TxBuilderInput::new(
+                TxInput {
+                    prev_out: utxo.outpoint.clone(),
+                    script: Script::default(),
+                    sequence: SequenceNo::finalized(),
+                    sign_data: Some(SignData::new(vec![
+                        SignField::Value(utxo.value),
+                        SignField::OutputScript(utxo.script.clone()),
+                    ])),
+                },
+                Box::new(P2PKHSignatory {
+                    seckey: self.seckey.clone(),
+                    pubkey: self.pubkey,
+                    sig_hash_type: SigHashType::ALL_BIP143,
+                }),
+            ));
+        }
+        let num_outputs = tx_builder.outputs.len();
+        let change_script = self.address.to_script();
+        tx_builder
+            .outputs
+            .push(TxBuilderOutput::Leftover(change_script.clone()));
+        let tx = tx_builder.sign_for_network(&self.ecc, FeeRate::per_kb(1000), Network::XEC)?;
+        self.chain
+            .bitcoind
+            .cmd_string("sendrawtransaction", &[&tx.ser().hex()])?;
+        let txid = tx.txid();
+        self.utxos.clear();
+        if let Some(change) = tx.outputs.get(num_outputs) {
+            self.utxos.push(Utxo {
+                outpoint: OutPoint {
+                    txid: txid.clone(),
+                    out_idx: num_outputs as u32,
+                },
+                script: change_script,
+                value: change.value,
+            });
+        }
+        Ok(txid)
+    }
+}
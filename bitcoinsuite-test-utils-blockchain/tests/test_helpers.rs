@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use bitcoinsuite_core::{
+    AddressType, BitcoinCode, CashAddress, Network, OutPoint, Script, ShaRmd160, TxOutput,
+    ECREGTEST,
+};
+use bitcoinsuite_error::Result;
+use bitcoinsuite_test_utils_blockchain::{
+    build_tx, confirm_tx, fund_address, setup_xec_chain, wait_for_mempool,
+};
+
+#[tokio::test]
+async fn test_fund_address() -> Result<()> {
+    let redeem_script = Script::from_static_slice(&[0x51]);
+    let (bitcoind, _) = setup_xec_chain(1, &redeem_script).await?;
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let (outpoint, value) = fund_address(&bitcoind, Network::XEC, &address, 123_456)?;
+    assert_eq!(value, 123_456);
+    let OutPoint { txid, out_idx } = outpoint;
+    let tx_out = bitcoind.cmd_json("gettxout", &[&txid.to_hex_display(), &out_idx.to_string()])?;
+    assert_eq!(tx_out["confirmations"].as_i32(), Some(1));
+    assert_eq!(
+        tx_out["scriptPubKey"]["hex"].as_str(),
+        Some(address.to_script().hex().as_str()),
+    );
+    assert_eq!(tx_out["value"].as_fixed_point_i64(2), Some(123_456));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_confirm_tx() -> Result<()> {
+    let redeem_script = Script::from_static_slice(&[0x51]);
+    let (bitcoind, mut utxos) = setup_xec_chain(1, &redeem_script).await?;
+    let (outpoint, value) = utxos.remove(0);
+    let tx = build_tx(
+        outpoint,
+        &redeem_script,
+        vec![TxOutput {
+            value: value - 10_000,
+            script: redeem_script.to_p2sh(),
+        }],
+    );
+    bitcoind.cmd_string("sendrawtransaction", &[&tx.ser().hex()])?;
+    wait_for_mempool(&bitcoind, &tx.txid(), Duration::from_secs(1)).await?;
+    assert_eq!(confirm_tx(&bitcoind, &tx.txid())?, 102);
+    // Tx already mined
+    assert!(confirm_tx(&bitcoind, &tx.txid()).is_err());
+    assert!(
+        wait_for_mempool(&bitcoind, &tx.txid(), Duration::from_millis(100))
+            .await
+            .is_err()
+    );
+    Ok(())
+}
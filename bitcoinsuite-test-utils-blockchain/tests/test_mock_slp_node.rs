@@ -0,0 +1,1011 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use bitcoinsuite_core::{
+    AddressType, BitcoinCode, CashAddress, FeeRate, Hashed, Net, Network, OutPoint, Sha256d,
+    ShaRmd160, TxInput, TxOutput, UnhashedTx, Utxo, ECASH, ECREGTEST,
+};
+use bitcoinsuite_error::Result;
+use bitcoinsuite_slp::{
+    genesis_opreturn, mint_opreturn, send_opreturn, AddressBalance, AddressEvent, BlockEvent,
+    BlockId, MempoolPackageInfo, MintBaton, SlpAmount, SlpBlock, SlpBurn, SlpError, SlpGenesisInfo,
+    SlpNodeInterface, SlpToken, SlpTokenType, SlpTx, SlpTxTypeVariant, SlpUtxo, SlpValidity,
+    TokenId,
+};
+use bitcoinsuite_test_utils_blockchain::MockSlpNode;
+use futures::StreamExt;
+
+#[tokio::test]
+async fn test_mock_slp_node_submit_tx() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let other_address =
+        CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([2; 20]));
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx: 0,
+            },
+            script: address.to_script(),
+            value: 10_000,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo.clone()).await;
+    assert_eq!(node.address_utxos(&address).await?, vec![utxo.clone()]);
+    assert_eq!(node.address_utxos(&other_address).await?, vec![]);
+
+    let mut stream = node.address_tx_stream(&address).await?;
+    let mut event_stream = node.address_event_stream(&address).await?;
+    let tx = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: utxo.utxo.outpoint.clone(),
+            ..Default::default()
+        }],
+        outputs: vec![TxOutput {
+            value: 9_000,
+            script: address.to_script(),
+        }],
+        lock_time: 0,
+    };
+    let txid = node.submit_tx(tx.ser().to_vec()).await?;
+    assert_eq!(txid, Sha256d::digest(tx.ser()));
+    assert_eq!(
+        node.address_utxos(&address).await?,
+        vec![SlpUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint { txid, out_idx: 0 },
+                script: address.to_script(),
+                value: 9_000,
+            },
+            token: SlpToken::EMPTY,
+            token_id: None,
+        }],
+    );
+
+    let slp_tx = stream.next().await.unwrap()?;
+    assert_eq!(slp_tx.tx(), &tx);
+    assert_eq!(slp_tx.slp(), None);
+    assert_eq!(slp_tx.burns(), &[None]);
+    assert_eq!(slp_tx.validity(), &SlpValidity::NotSlp);
+    assert_eq!(slp_tx.input_prevouts(), &[Some(utxo.utxo)]);
+
+    match event_stream.next().await.unwrap()? {
+        AddressEvent::Mempool(event_tx) => assert_eq!(event_tx, slp_tx),
+        event => panic!("Unexpected event: {:?}", event),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_submit_tx_spends() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let new_address = CashAddress::from_hash(ECREGTEST, AddressType::P2SH, ShaRmd160::new([2; 20]));
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx: 0,
+            },
+            script: address.to_script(),
+            value: 10_000,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo.clone()).await;
+    let spend_tx = |prev_outs: Vec<OutPoint>, lock_time: u32| UnhashedTx {
+        version: 1,
+        inputs: prev_outs
+            .into_iter()
+            .map(|prev_out| TxInput {
+                prev_out,
+                ..Default::default()
+            })
+            .collect(),
+        outputs: vec![TxOutput {
+            value: 9_000,
+            script: new_address.to_script(),
+        }],
+        lock_time,
+    };
+
+    // Missing input
+    let missing_outpoint = OutPoint {
+        txid: Sha256d::new([3; 32]),
+        out_idx: 1,
+    };
+    let tx = spend_tx(
+        vec![utxo.utxo.outpoint.clone(), missing_outpoint.clone()],
+        0,
+    );
+    let err = node.submit_tx(tx.ser().to_vec()).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::InputMissing {
+            input_idx: 1,
+            outpoint: missing_outpoint,
+        }),
+    );
+    let err = node.check_tx(tx.ser().to_vec()).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<SlpError>(),
+        Some(SlpError::InputMissing { input_idx: 1, .. }),
+    ));
+
+    // Same input spent twice within the tx
+    let tx = spend_tx(vec![utxo.utxo.outpoint.clone(); 2], 0);
+    let err = node.submit_tx(tx.ser().to_vec()).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::InputAlreadySpent {
+            input_idx: 1,
+            outpoint: utxo.utxo.outpoint.clone(),
+            spent_by: tx.txid(),
+        }),
+    );
+    // Rejected txs leave the utxos untouched
+    assert_eq!(node.address_utxos(&address).await?, vec![utxo.clone()]);
+    assert!(node.mempool.lock().await.is_empty());
+
+    // Outputs are credited to addresses the node hasn't seen yet
+    let tx = spend_tx(vec![utxo.utxo.outpoint.clone()], 0);
+    let txid = node.submit_tx(tx.ser().to_vec()).await?;
+    assert_eq!(node.address_utxos(&address).await?, vec![]);
+    assert_eq!(
+        node.address_utxos(&new_address).await?,
+        vec![SlpUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: txid.clone(),
+                    out_idx: 0,
+                },
+                script: new_address.to_script(),
+                value: 9_000,
+            },
+            token: SlpToken::EMPTY,
+            token_id: None,
+        }],
+    );
+    // The emptied address keeps the spending tx in its history
+    let history = node.address_history(&address).await?;
+    assert_eq!(
+        history
+            .iter()
+            .map(|slp_tx| slp_tx.tx().txid())
+            .collect::<Vec<_>>(),
+        vec![txid.clone()],
+    );
+    assert_eq!(node.address_history(&new_address).await?, history);
+
+    // Double spend of the now spent utxo
+    let double_spend = spend_tx(vec![utxo.utxo.outpoint.clone()], 1);
+    let err = node
+        .submit_tx(double_spend.ser().to_vec())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::InputAlreadySpent {
+            input_idx: 0,
+            outpoint: utxo.utxo.outpoint.clone(),
+            spent_by: txid.clone(),
+        }),
+    );
+    assert!(node.check_tx(double_spend.ser().to_vec()).await.is_err());
+    assert_eq!(node.mempool.lock().await.as_slice(), &[txid]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_address_events() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx: 0,
+            },
+            script: address.to_script(),
+            value: 10_000,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo.clone()).await;
+    let spend_tx = |prev_out: OutPoint, value: i64| UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out,
+            ..Default::default()
+        }],
+        outputs: vec![TxOutput {
+            value,
+            script: address.to_script(),
+        }],
+        lock_time: 0,
+    };
+    let mut tx_stream = node.address_tx_stream(&address).await?;
+    let mut event_stream = node.address_event_stream(&address).await?;
+
+    // Mined txs are reported as confirmed
+    let txid = node
+        .submit_tx(spend_tx(utxo.utxo.outpoint.clone(), 9_000).ser().to_vec())
+        .await?;
+    let slp_tx = tx_stream.next().await.unwrap()?;
+    assert_eq!(
+        event_stream.next().await.unwrap()?,
+        AddressEvent::Mempool(slp_tx.clone()),
+    );
+    node.mine_mempool().await;
+    let mut confirmed_tx = slp_tx;
+    confirmed_tx.set_block_height(Some(1));
+    assert_eq!(
+        event_stream.next().await.unwrap()?,
+        AddressEvent::Confirmed {
+            tx: confirmed_tx,
+            height: 1,
+        },
+    );
+    let confirmed_utxos = node.address_utxos(&address).await?;
+
+    // Evicting a tx also evicts its children, and gives back the utxos they spent
+    let parent_txid = node
+        .submit_tx(
+            spend_tx(OutPoint { txid, out_idx: 0 }, 8_000)
+                .ser()
+                .to_vec(),
+        )
+        .await?;
+    let child_txid = node
+        .submit_tx(
+            spend_tx(
+                OutPoint {
+                    txid: parent_txid.clone(),
+                    out_idx: 0,
+                },
+                7_000,
+            )
+            .ser()
+            .to_vec(),
+        )
+        .await?;
+    assert!(node.get_tx(&child_txid).await.is_ok());
+    node.evict_mempool_tx(&parent_txid).await?;
+    for expected_txid in [&parent_txid, &child_txid] {
+        assert_eq!(tx_stream.next().await.unwrap()?.tx().txid(), *expected_txid,);
+        assert!(matches!(
+            event_stream.next().await.unwrap()?,
+            AddressEvent::Mempool(_),
+        ));
+    }
+    assert_eq!(
+        event_stream.next().await.unwrap()?,
+        AddressEvent::Removed(child_txid.clone()),
+    );
+    assert_eq!(
+        event_stream.next().await.unwrap()?,
+        AddressEvent::Removed(parent_txid.clone()),
+    );
+    assert!(node.mempool.lock().await.is_empty());
+    assert!(node
+        .spent_outpoints
+        .lock()
+        .await
+        .get(&OutPoint {
+            txid: parent_txid.clone(),
+            out_idx: 0
+        })
+        .is_none());
+    assert_eq!(node.address_utxos(&address).await?, confirmed_utxos);
+
+    // Only mempool txs can be evicted
+    let err = node.evict_mempool_tx(&parent_txid).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::UnknownTx(parent_txid)),
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_submit_and_parse() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let token_id = TokenId::new(Sha256d::new([4; 32]));
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx: 1,
+            },
+            script: address.to_script(),
+            value: 546,
+        },
+        token: SlpToken::amount(7),
+        token_id: Some(token_id.clone()),
+    };
+    node.add_utxo(address.clone(), utxo.clone()).await;
+    let mut stream = node.address_tx_stream(&address).await?;
+
+    // Spending the token without an SLP OP_RETURN burns it
+    let tx = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: utxo.utxo.outpoint.clone(),
+            ..Default::default()
+        }],
+        outputs: vec![TxOutput {
+            value: 500,
+            script: address.to_script(),
+        }],
+        lock_time: 0,
+    };
+    let slp_tx = node.submit_and_parse(tx.ser().to_vec()).await?;
+    assert_eq!(slp_tx.tx(), &tx);
+    assert_eq!(
+        slp_tx.burns(),
+        &[Some(Box::new(SlpBurn {
+            token: SlpToken::amount(7),
+            token_id,
+            is_intended: false,
+        }))],
+    );
+    assert_eq!(slp_tx.input_prevouts(), &[Some(utxo.utxo)]);
+    // Same tx as reported by the stream
+    assert_eq!(stream.next().await.unwrap()?, slp_tx);
+    assert_eq!(
+        node.txs.lock().await.get(&Sha256d::digest(tx.ser())),
+        Some(&slp_tx),
+    );
+
+    assert!(node.submit_and_parse(vec![1, 2, 3]).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_get_txs() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let mut txids = Vec::new();
+    for lock_time in 0..5 {
+        let outpoint = OutPoint {
+            txid: Sha256d::new([3; 32]),
+            out_idx: lock_time,
+        };
+        node.add_utxo(
+            address.clone(),
+            SlpUtxo {
+                utxo: Utxo {
+                    outpoint: outpoint.clone(),
+                    script: address.to_script(),
+                    value: 2_000,
+                },
+                token: SlpToken::EMPTY,
+                token_id: None,
+            },
+        )
+        .await;
+        let tx = UnhashedTx {
+            version: 1,
+            inputs: vec![TxInput {
+                prev_out: outpoint,
+                ..Default::default()
+            }],
+            outputs: vec![TxOutput {
+                value: 1_000,
+                script: address.to_script(),
+            }],
+            lock_time,
+        };
+        txids.push(node.submit_tx(tx.ser().to_vec()).await?);
+    }
+
+    // Output is in the order of the requested txids, for any concurrency
+    let requested = [&txids[3], &txids[0], &txids[4], &txids[0], &txids[1]]
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    let expected = {
+        let txs = node.txs.lock().await;
+        requested
+            .iter()
+            .map(|txid| txs[txid].clone())
+            .collect::<Vec<_>>()
+    };
+    for concurrency in [0, 1, 2, 10] {
+        assert_eq!(node.get_txs(&requested, concurrency).await?, expected);
+    }
+    assert_eq!(node.get_txs(&[], 2).await?, vec![]);
+    assert_eq!(node.get_tx(&txids[2]).await?.tx().lock_time, 2);
+
+    // Any unknown txid fails the whole batch
+    let unknown_txid = Sha256d::new([9; 32]);
+    let err = node
+        .get_txs(&[txids[0].clone(), unknown_txid.clone()], 2)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::UnknownTx(unknown_txid)),
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_check_tx() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let token_id = TokenId::new(Sha256d::new([4; 32]));
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx: 1,
+            },
+            script: address.to_script(),
+            value: 546,
+        },
+        token: SlpToken::amount(7),
+        token_id: Some(token_id.clone()),
+    };
+    node.add_utxo(address.clone(), utxo.clone()).await;
+
+    // Spending the token without an SLP OP_RETURN burns it
+    let tx = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: utxo.utxo.outpoint.clone(),
+            ..Default::default()
+        }],
+        outputs: vec![TxOutput {
+            value: 500,
+            script: address.to_script(),
+        }],
+        lock_time: 0,
+    };
+    let slp_tx = node.check_tx(tx.ser().to_vec()).await?;
+    assert_eq!(slp_tx.tx(), &tx);
+    assert_eq!(slp_tx.slp(), None);
+    assert_eq!(
+        slp_tx.burns(),
+        &[Some(Box::new(SlpBurn {
+            token: SlpToken::amount(7),
+            token_id: token_id.clone(),
+            is_intended: false,
+        }))],
+    );
+    assert_eq!(slp_tx.validity(), &SlpValidity::NotSlp);
+
+    // SEND of a different token is invalid SLP and burns the token as well
+    let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+    let invalid_tx = UnhashedTx {
+        outputs: vec![
+            TxOutput {
+                value: 0,
+                script: send_opreturn(
+                    &other_token_id,
+                    SlpTokenType::Fungible,
+                    &[SlpAmount::new(7)],
+                )?,
+            },
+            TxOutput {
+                value: 546,
+                script: address.to_script(),
+            },
+        ],
+        ..tx.clone()
+    };
+    let slp_tx = node.check_tx(invalid_tx.ser().to_vec()).await?;
+    assert_eq!(slp_tx.slp(), None);
+    assert!(matches!(slp_tx.validity(), SlpValidity::Invalid(_)));
+    assert_eq!(
+        slp_tx.burns(),
+        &[Some(Box::new(SlpBurn {
+            token: SlpToken::amount(7),
+            token_id: token_id.clone(),
+            is_intended: false,
+        }))],
+    );
+
+    // Nothing has been spent or added
+    assert_eq!(node.address_utxos(&address).await?, vec![utxo]);
+    assert!(node.txs.lock().await.is_empty());
+
+    // Malformed txs error like in submit_tx
+    assert!(node.check_tx(vec![1, 2, 3]).await.is_err());
+    assert!(node.submit_tx(vec![1, 2, 3]).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_address_balance() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let token_id = TokenId::new(Sha256d::new([4; 32]));
+    assert_eq!(
+        node.address_balance(&address).await?,
+        AddressBalance::default()
+    );
+
+    let utxo = |out_idx, value, token, token_id: Option<&TokenId>| SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx,
+            },
+            script: address.to_script(),
+            value,
+        },
+        token,
+        token_id: token_id.cloned(),
+    };
+    // 20 tokens and the mint baton, on dust utxos whose sats don't count towards `sats`, and one
+    // sats-only utxo
+    node.add_utxo(
+        address.clone(),
+        utxo(1, 546, SlpToken::amount(20), Some(&token_id)),
+    )
+    .await;
+    node.add_utxo(
+        address.clone(),
+        utxo(2, 546, SlpToken::MINT_BATON, Some(&token_id)),
+    )
+    .await;
+    node.add_utxo(address.clone(), utxo(3, 10_000, SlpToken::EMPTY, None))
+        .await;
+    assert_eq!(
+        node.address_balance(&address).await?,
+        AddressBalance {
+            sats: 10_000,
+            tokens: HashMap::from([(token_id.clone(), SlpAmount::new(20))]),
+            mint_batons: HashSet::from([token_id.clone()]),
+        },
+    );
+    assert_eq!(
+        node.address_token_ids(&address).await?,
+        vec![token_id.clone()]
+    );
+
+    // Token IDs are listed in the order their first utxo appears
+    let other_token_id = TokenId::new(Sha256d::new([5; 32]));
+    node.add_utxo(
+        address.clone(),
+        utxo(4, 546, SlpToken::amount(3), Some(&other_token_id)),
+    )
+    .await;
+    node.add_utxo(
+        address.clone(),
+        utxo(5, 546, SlpToken::amount(1), Some(&token_id)),
+    )
+    .await;
+    assert_eq!(
+        node.address_token_ids(&address).await?,
+        vec![token_id, other_token_id],
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_token_genesis_info() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx: 0,
+            },
+            script: address.to_script(),
+            value: 10_000,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo.clone()).await;
+    let genesis_info = SlpGenesisInfo {
+        token_ticker: b"TEST".as_ref().into(),
+        token_name: b"Test".as_ref().into(),
+        decimals: 0,
+        ..Default::default()
+    };
+    let tx = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: utxo.utxo.outpoint,
+            ..Default::default()
+        }],
+        outputs: vec![
+            TxOutput {
+                value: 0,
+                script: genesis_opreturn(
+                    &genesis_info,
+                    SlpTokenType::Fungible,
+                    MintBaton::at(2),
+                    100,
+                )?,
+            },
+            TxOutput {
+                value: 546,
+                script: address.to_script(),
+            },
+            TxOutput {
+                value: 546,
+                script: address.to_script(),
+            },
+        ],
+        lock_time: 0,
+    };
+    let txid = node.submit_tx(tx.ser().to_vec()).await?;
+    let token_id = TokenId::new(txid);
+    assert_eq!(node.token_genesis_info(&token_id).await?, genesis_info);
+
+    let unknown_token_id = TokenId::new(Sha256d::new([4; 32]));
+    let err = node
+        .token_genesis_info(&unknown_token_id)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::UnknownToken(unknown_token_id)),
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_ping() -> Result<()> {
+    let node = MockSlpNode::new();
+    let latency = node.ping().await?;
+    assert!(latency < Duration::from_secs(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_mempool_ancestors() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let outpoint = OutPoint {
+        txid: Sha256d::new([3; 32]),
+        out_idx: 0,
+    };
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: outpoint.clone(),
+            script: address.to_script(),
+            value: 10_000,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo).await;
+
+    // Txs not in the mempool have no package
+    let empty = MempoolPackageInfo::default();
+    assert_eq!(node.mempool_ancestors(&outpoint.txid).await?, empty);
+
+    let parent = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: outpoint,
+            ..Default::default()
+        }],
+        outputs: vec![TxOutput {
+            value: 9_900,
+            script: address.to_script(),
+        }],
+        lock_time: 0,
+    };
+    let parent_txid = node.submit_tx(parent.ser().to_vec()).await?;
+    let child = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: OutPoint {
+                txid: parent_txid.clone(),
+                out_idx: 0,
+            },
+            ..Default::default()
+        }],
+        outputs: vec![TxOutput {
+            value: 9_850,
+            script: address.to_script(),
+        }],
+        lock_time: 0,
+    };
+    let child_txid = node.submit_tx(child.ser().to_vec()).await?;
+
+    let parent_package = MempoolPackageInfo {
+        num_txs: 1,
+        size: parent.vsize(),
+        fees: 100,
+    };
+    let child_package = MempoolPackageInfo {
+        num_txs: 2,
+        size: parent.vsize() + child.vsize(),
+        fees: 150,
+    };
+    assert_eq!(node.mempool_ancestors(&parent_txid).await?, parent_package);
+    assert_eq!(node.mempool_ancestors(&child_txid).await?, child_package);
+
+    // Fee a new child needs so the whole package pays the fee rate
+    let fee_rate = FeeRate::per_vbyte(1);
+    let package_shortfall = (child_package.size + 100) as i64 - child_package.fees;
+    assert_eq!(child_package.child_fee(100, fee_rate), package_shortfall);
+    assert_eq!(child_package.child_fee(100, FeeRate::per_kb(0)), 0);
+
+    node.mine_mempool().await;
+    assert_eq!(node.mempool_ancestors(&child_txid).await?, empty);
+
+    // Fees can't be computed for txs with unknown prevouts
+    let unknown_prevout = OutPoint {
+        txid: Sha256d::new([5; 32]),
+        out_idx: 1,
+    };
+    let tx = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: unknown_prevout.clone(),
+            ..Default::default()
+        }],
+        outputs: vec![],
+        lock_time: 0,
+    };
+    let txid = tx.txid();
+    node.add_tx(txid.clone(), SlpTx::new(tx, None, vec![None]))
+        .await;
+    let err = node.mempool_ancestors(&txid).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::InputMissing {
+            input_idx: 0,
+            outpoint: unknown_prevout,
+        }),
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_net() -> Result<()> {
+    let node = MockSlpNode::new();
+    assert_eq!(node.network(), Network::XEC);
+    assert_eq!(node.net(), Net::Regtest);
+
+    let node = MockSlpNode::with_net(Network::XEC, Net::Mainnet);
+    assert_eq!(node.network(), Network::XEC);
+    assert_eq!(node.net(), Net::Mainnet);
+    let hash = ShaRmd160::new([1; 20]);
+    let address = CashAddress::from_hash(ECASH, AddressType::P2PKH, hash.clone());
+    assert_eq!(node.address_utxos(&address).await?, vec![]);
+
+    // Regtest addresses are rejected by a mainnet node
+    let regtest_address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, hash);
+    let err = node.address_utxos(&regtest_address).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SlpError>(),
+        Some(&SlpError::WrongAddressNet {
+            expected: ECASH.to_string(),
+            actual: ECREGTEST.to_string(),
+        }),
+    );
+    assert!(node.address_tx_stream(&regtest_address).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_address_tx_channel() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint {
+                txid: Sha256d::new([3; 32]),
+                out_idx: 0,
+            },
+            script: address.to_script(),
+            value: 10_000,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo.clone()).await;
+
+    // A zero buffer is rounded up to 1 instead of panicking
+    let (handle, mut receiver) = node.address_tx_channel(&address, 0).await?;
+    let tx = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: utxo.utxo.outpoint,
+            ..Default::default()
+        }],
+        outputs: vec![TxOutput {
+            value: 9_000,
+            script: address.to_script(),
+        }],
+        lock_time: 0,
+    };
+    let txid = node.submit_tx(tx.ser().to_vec()).await?;
+    let slp_tx = receiver.recv().await.unwrap()?;
+    assert_eq!(slp_tx.tx().txid(), txid);
+
+    // Dropping the receiver ends the task
+    drop(receiver);
+    tokio::time::timeout(Duration::from_secs(1), handle).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_slp_node_confirmed_txs() -> Result<()> {
+    let node = MockSlpNode::new();
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([1; 20]));
+    let outpoint = OutPoint {
+        txid: Sha256d::new([3; 32]),
+        out_idx: 0,
+    };
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: outpoint.clone(),
+            script: address.to_script(),
+            value: 10_000,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo).await;
+    let token_output = TxOutput {
+        value: 546,
+        script: address.to_script(),
+    };
+    let spend = |txid: &Sha256d, out_idx| TxInput {
+        prev_out: OutPoint {
+            txid: txid.clone(),
+            out_idx,
+        },
+        ..Default::default()
+    };
+
+    let genesis_tx = UnhashedTx {
+        version: 1,
+        inputs: vec![TxInput {
+            prev_out: outpoint,
+            ..Default::default()
+        }],
+        outputs: vec![
+            TxOutput {
+                value: 0,
+                script: genesis_opreturn(
+                    &SlpGenesisInfo::default(),
+                    SlpTokenType::Fungible,
+                    MintBaton::at(2),
+                    100,
+                )?,
+            },
+            token_output.clone(),
+            token_output.clone(),
+        ],
+        lock_time: 0,
+    };
+    let genesis_txid = node.submit_tx(genesis_tx.ser().to_vec()).await?;
+    let token_id = TokenId::new(genesis_txid.clone());
+    let mint_tx = UnhashedTx {
+        version: 1,
+        inputs: vec![spend(&genesis_txid, 2)],
+        outputs: vec![
+            TxOutput {
+                value: 0,
+                script: mint_opreturn(&token_id, SlpTokenType::Fungible, MintBaton::NONE, 50)?,
+            },
+            token_output.clone(),
+        ],
+        lock_time: 0,
+    };
+    node.submit_tx(mint_tx.ser().to_vec()).await?;
+    let send_tx = UnhashedTx {
+        version: 1,
+        inputs: vec![spend(&genesis_txid, 1)],
+        outputs: vec![
+            TxOutput {
+                value: 0,
+                script: send_opreturn(
+                    &token_id,
+                    SlpTokenType::Fungible,
+                    &[SlpAmount::new(60), SlpAmount::new(40)],
+                )?,
+            },
+            token_output.clone(),
+            token_output.clone(),
+        ],
+        lock_time: 0,
+    };
+    let send_txid = node.submit_tx(send_tx.ser().to_vec()).await?;
+
+    let block_hash = node.mine_mempool().await;
+    let block_txs = node.txs_in_block(&BlockId::Height(1)).await?;
+    assert_eq!(
+        node.txs_in_block(&BlockId::Hash(block_hash.clone()))
+            .await?,
+        block_txs
+    );
+    assert_eq!(
+        block_txs
+            .iter()
+            .map(|slp_tx| slp_tx.slp().unwrap().slp_tx_type.tx_type_variant())
+            .collect::<Vec<_>>(),
+        vec![
+            SlpTxTypeVariant::Genesis,
+            SlpTxTypeVariant::Mint,
+            SlpTxTypeVariant::Send,
+        ],
+    );
+    assert!(block_txs
+        .iter()
+        .all(|slp_tx| slp_tx.block_height() == Some(1)));
+    assert_eq!(block_txs[2].tx(), &send_tx);
+    assert_eq!(node.node_status().await?.best_height, 1);
+    assert_eq!(node.node_status().await?.best_block_hash, block_hash);
+    for block in [
+        BlockId::Height(0),
+        BlockId::Height(2),
+        BlockId::Hash(Sha256d::new([5; 32])),
+    ] {
+        let err = node.txs_in_block(&block).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<SlpError>(),
+            Some(&SlpError::UnknownBlock(block)),
+        );
+    }
+
+    // Stream replays the mined block, then reports new blocks and reorgs
+    let mut stream = node.confirmed_tx_stream(1).await?;
+    let expected_block = SlpBlock {
+        height: 1,
+        hash: block_hash.clone(),
+        txs: block_txs.clone(),
+    };
+    assert_eq!(
+        stream.next().await.unwrap()?,
+        BlockEvent::Connected(expected_block.clone()),
+    );
+    let empty_block_hash = node.mine_mempool().await;
+    assert_eq!(
+        stream.next().await.unwrap()?,
+        BlockEvent::Connected(SlpBlock {
+            height: 2,
+            hash: empty_block_hash.clone(),
+            txs: vec![],
+        }),
+    );
+    node.disconnect_blocks(2).await;
+    assert_eq!(
+        stream.next().await.unwrap()?,
+        BlockEvent::Disconnected(vec![empty_block_hash, block_hash]),
+    );
+    assert_eq!(node.node_status().await?.best_height, 0);
+    assert_eq!(node.mempool.lock().await.len(), 3);
+    let send_tx = node.txs.lock().await[&send_txid].clone();
+    assert_eq!(send_tx.block_height(), None);
+
+    // Mining again confirms the same txs in a new block
+    node.mine_mempool().await;
+    match stream.next().await.unwrap()? {
+        BlockEvent::Connected(block) => {
+            assert_eq!(block.height, 1);
+            assert_eq!(block.txs, block_txs);
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+
+    // Streams starting above the tip only report new blocks
+    let mut stream = node.confirmed_tx_stream(2).await?;
+    let block_hash = node.mine_mempool().await;
+    match stream.next().await.unwrap()? {
+        BlockEvent::Connected(block) => assert_eq!(block.hash, block_hash),
+        event => panic!("Unexpected event: {:?}", event),
+    }
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use bitcoinsuite_core::{
+    ecc::{Ecc, ExtendedPubKey},
+    CashAddress, Net, Network, OutPoint, Sha256d, TxOutput, UnhashedTx, Utxo, ECASH,
+};
+use bitcoinsuite_ecc_secp256k1::EccSecp256k1;
+use bitcoinsuite_error::Result;
+use bitcoinsuite_slp::{scan_addresses, AddressBalance, SlpToken, SlpTx, SlpUtxo};
+use bitcoinsuite_test_utils_blockchain::MockSlpNode;
+
+/// BIP32 test vector 1, chain m
+const XPUB: &str = "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB";
+
+fn address(ecc: &impl Ecc, xpub: &ExtendedPubKey, index: u32) -> Result<CashAddress<'static>> {
+    let child = xpub.derive_pub(ecc, index)?;
+    Ok(CashAddress::p2pkh_from_pubkey(ECASH, child.pubkey()))
+}
+
+/// Add tx `tx_num` sending `value` to `address`, and its output as a utxo unless `is_spent`.
+async fn send_to(
+    node: &MockSlpNode,
+    address: &CashAddress<'static>,
+    tx_num: u8,
+    value: i64,
+    is_spent: bool,
+) {
+    let txid = Sha256d::new([tx_num; 32]);
+    let output = TxOutput {
+        value,
+        script: address.to_script(),
+    };
+    let tx = UnhashedTx {
+        version: 1,
+        inputs: vec![],
+        outputs: vec![output.clone()],
+        lock_time: 0,
+    };
+    node.add_tx(txid.clone(), SlpTx::new(tx, None, vec![]))
+        .await;
+    if is_spent {
+        return;
+    }
+    let utxo = SlpUtxo {
+        utxo: Utxo {
+            outpoint: OutPoint { txid, out_idx: 0 },
+            script: output.script,
+            value: output.value,
+        },
+        token: SlpToken::EMPTY,
+        token_id: None,
+    };
+    node.add_utxo(address.clone(), utxo).await;
+}
+
+#[tokio::test]
+async fn test_scan_addresses() -> Result<()> {
+    let ecc = EccSecp256k1::default();
+    let xpub = ExtendedPubKey::from_str(&ecc, XPUB)?;
+    let node = MockSlpNode::with_net(Network::XEC, Net::Mainnet);
+    assert_eq!(scan_addresses(&node, &ecc, &xpub, 5).await?, vec![]);
+
+    // First and third addresses have utxos
+    let address0 = address(&ecc, &xpub, 0)?;
+    let address2 = address(&ecc, &xpub, 2)?;
+    send_to(&node, &address0, 1, 1_000, false).await;
+    send_to(&node, &address0, 2, 2_000, false).await;
+    send_to(&node, &address2, 3, 5_000, false).await;
+    let balance = |sats| AddressBalance {
+        sats,
+        ..Default::default()
+    };
+
+    // The unused second address resets the gap, so the third is found
+    assert_eq!(
+        scan_addresses(&node, &ecc, &xpub, 2).await?,
+        vec![(0, balance(3_000)), (2, balance(5_000))],
+    );
+    assert_eq!(
+        scan_addresses(&node, &ecc, &xpub, 20).await?,
+        vec![(0, balance(3_000)), (2, balance(5_000))],
+    );
+    // With a gap limit of 1, scanning stops at the second address
+    assert_eq!(
+        scan_addresses(&node, &ecc, &xpub, 1).await?,
+        vec![(0, balance(3_000))],
+    );
+    assert_eq!(scan_addresses(&node, &ecc, &xpub, 0).await?, vec![]);
+
+    // Address 6 is beyond the gap of 3 unused addresses after address 2
+    let address6 = address(&ecc, &xpub, 6)?;
+    send_to(&node, &address6, 4, 7_000, false).await;
+    assert_eq!(
+        scan_addresses(&node, &ecc, &xpub, 3).await?,
+        vec![(0, balance(3_000)), (2, balance(5_000))],
+    );
+    assert_eq!(
+        scan_addresses(&node, &ecc, &xpub, 4).await?,
+        vec![
+            (0, balance(3_000)),
+            (2, balance(5_000)),
+            (6, balance(7_000)),
+        ],
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scan_addresses_emptied() -> Result<()> {
+    let ecc = EccSecp256k1::default();
+    let xpub = ExtendedPubKey::from_str(&ecc, XPUB)?;
+    let node = MockSlpNode::with_net(Network::XEC, Net::Mainnet);
+
+    // First address has been emptied, third address has utxos
+    let address0 = address(&ecc, &xpub, 0)?;
+    let address2 = address(&ecc, &xpub, 2)?;
+    send_to(&node, &address0, 1, 1_000, true).await;
+    send_to(&node, &address2, 2, 5_000, false).await;
+
+    // The emptied address is still used, so the funded one is within the gap
+    assert_eq!(
+        scan_addresses(&node, &ecc, &xpub, 2).await?,
+        vec![
+            (0, AddressBalance::default()),
+            (
+                2,
+                AddressBalance {
+                    sats: 5_000,
+                    ..Default::default()
+                },
+            ),
+        ],
+    );
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use bitcoinsuite_core::Script;
 use bitcoinsuite_error::Result;
-use bitcoinsuite_test_utils_blockchain::{setup_bch_chain, setup_xec_chain};
+use bitcoinsuite_test_utils_blockchain::{
+    setup_bch_chain, setup_xec_chain, setup_xec_chain_with, ChainSetup,
+};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_setup_xec_chain() -> Result<()> {
@@ -19,3 +21,43 @@ async fn test_setup_bch_chain() -> Result<()> {
     assert_eq!(utxos.len(), 10);
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_setup_xec_chain_with() -> Result<()> {
+    let redeem_script = Script::from_slice(&[0x51]);
+    let chain = setup_xec_chain_with(
+        ChainSetup {
+            num_blocks: 2,
+            coinbase_maturity: 100,
+            extra_mature_utxos: 5,
+        },
+        &redeem_script,
+    )
+    .await?;
+    assert_eq!(chain.block_height()?, 103);
+    assert_eq!(chain.utxos.len(), 6);
+    let (split_outpoint, split_value) = &chain.utxos[1];
+    assert!(chain.utxos[1..]
+        .iter()
+        .all(|(outpoint, value)| outpoint.txid == split_outpoint.txid && value == split_value));
+    let hashes = chain.mine_blocks(3)?;
+    assert_eq!(hashes.len(), 3);
+    assert_eq!(chain.block_height()?, 106);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_setup_xec_chain_with_extra_utxos_no_blocks() -> Result<()> {
+    let redeem_script = Script::from_slice(&[0x51]);
+    let result = setup_xec_chain_with(
+        ChainSetup {
+            num_blocks: 0,
+            coinbase_maturity: 100,
+            extra_mature_utxos: 5,
+        },
+        &redeem_script,
+    )
+    .await;
+    assert!(result.is_err());
+    Ok(())
+}
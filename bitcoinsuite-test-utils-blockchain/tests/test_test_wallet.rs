@@ -0,0 +1,68 @@
+use bitcoinsuite_core::{AddressType, Bytes, CashAddress, Hashed, ShaRmd160, ECREGTEST};
+use bitcoinsuite_error::Result;
+use bitcoinsuite_slp::{genesis_opreturn, MintBaton, SlpGenesisInfo, SlpTokenType};
+use bitcoinsuite_test_utils_blockchain::{setup_slp_test_wallet, TEST_WALLET_INITIAL_SATS};
+
+#[tokio::test]
+async fn test_test_wallet_mint_token() -> Result<()> {
+    let mut wallet = setup_slp_test_wallet().await?;
+    let genesis_info = SlpGenesisInfo {
+        token_ticker: Bytes::from_slice(b"TEST"),
+        decimals: 2,
+        ..Default::default()
+    };
+    let token_id = wallet.mint_token(&genesis_info, 1_000, MintBaton::at(2))?;
+
+    // Still in the mempool, so no -txindex needed
+    let tx = wallet
+        .chain
+        .bitcoind
+        .cmd_json("getrawtransaction", &[&token_id.hash().to_hex_be(), "1"])?;
+    let opreturn = genesis_opreturn(
+        &genesis_info,
+        SlpTokenType::Fungible,
+        MintBaton::at(2),
+        1_000,
+    )?;
+    assert_eq!(
+        tx["vout"][0]["scriptPubKey"]["hex"].as_str(),
+        Some(opreturn.hex().as_str()),
+    );
+    let address_hex = wallet.address.to_script().hex();
+    for out_idx in [1, 2] {
+        let output = &tx["vout"][out_idx];
+        assert_eq!(output["value"].as_fixed_point_i64(2), Some(546));
+        assert_eq!(
+            output["scriptPubKey"]["hex"].as_str(),
+            Some(address_hex.as_str())
+        );
+    }
+    // Change output is the wallet's only utxo
+    assert_eq!(wallet.utxos.len(), 1);
+    assert_eq!(wallet.utxos[0].outpoint.out_idx, 3);
+    assert!(wallet.utxos[0].value < TEST_WALLET_INITIAL_SATS - 2 * 546);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_test_wallet_send_to() -> Result<()> {
+    let mut wallet = setup_slp_test_wallet().await?;
+    wallet.fund(50_000)?;
+    assert_eq!(wallet.utxos.len(), 2);
+    let address = CashAddress::from_hash(ECREGTEST, AddressType::P2PKH, ShaRmd160::new([2; 20]));
+    let txid = wallet.send_to(&address, 20_000)?;
+    wallet.chain.mine_blocks(1)?;
+    let tx_out = wallet
+        .chain
+        .bitcoind
+        .cmd_json("gettxout", &[&txid.to_hex_be(), "0"])?;
+    assert_eq!(tx_out["value"].as_fixed_point_i64(2), Some(20_000));
+    assert_eq!(
+        tx_out["scriptPubKey"]["hex"].as_str(),
+        Some(address.to_script().hex().as_str()),
+    );
+    // Both utxos spent, change is left
+    assert_eq!(wallet.utxos.len(), 1);
+    assert_eq!(wallet.utxos[0].outpoint.txid, txid);
+    Ok(())
+}